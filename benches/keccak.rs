@@ -0,0 +1,36 @@
+// Keccak256 of four public keys: four scalar sha3 hashes against one `keccak256_x4`,
+// which hashes them side by side with AVX2. The ratio of the two times is the speedup.
+//
+// Run with `cargo bench --features simd --bench keccak`
+
+// Its unit tests are left out of the benchmark, and so are the uses of their imports
+#[allow(dead_code, unused_imports)]
+#[path = "../src/keccak_avx2.rs"]
+mod keccak_avx2;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::RngCore;
+use sha3::{Digest, Keccak256};
+use std::hint::black_box;
+
+fn keccak_x4(c: &mut Criterion) {
+    let mut inputs = [[0u8; 64]; 4];
+    for input in &mut inputs {
+        rand::thread_rng().fill_bytes(input);
+    }
+    let mut group = c.benchmark_group("keccak256_of_4");
+    group.throughput(Throughput::Elements(4));
+
+    group.bench_function("sha3_scalar", |b| {
+        b.iter(|| black_box(black_box(inputs).map(|input| <[u8; 32]>::from(Keccak256::digest(input)))))
+    });
+
+    group.bench_function("keccak256_x4", |b| {
+        b.iter(|| black_box(keccak_avx2::keccak256_x4(black_box(inputs))))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, keccak_x4);
+criterion_main!(benches);
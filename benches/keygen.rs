@@ -0,0 +1,75 @@
+// Key generation throughput: an OsRng syscall per key against one ChaCha20Rng
+// seeded from the OS per worker thread, as used by the search loop, and the
+// batch size of `generate_addresses_batch`
+//
+// Run with `cargo bench --bench keygen`
+
+#[allow(dead_code)]
+#[path = "../src/keyrange.rs"]
+mod keyrange;
+// Its unit tests are left out of the benchmark, and so are the uses of their imports
+#[cfg(feature = "simd")]
+#[allow(dead_code, unused_imports)]
+#[path = "../src/keccak_avx2.rs"]
+mod keccak_avx2;
+#[cfg_attr(feature = "simd", allow(dead_code))]
+#[path = "../src/keygen.rs"]
+mod keygen;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::rngs::OsRng;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use std::hint::black_box;
+
+fn keygen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("keygen");
+
+    group.bench_function("osrng_per_call", |b| {
+        b.iter(|| black_box(SecretKey::new(&mut OsRng)))
+    });
+
+    let mut rng = ChaCha20Rng::from_entropy();
+    group.bench_function("chacha20_per_thread", |b| {
+        b.iter(|| black_box(SecretKey::new(&mut rng)))
+    });
+
+    group.finish();
+}
+
+// The same comparison including the public key derivation, i.e. a whole raw-mode attempt
+fn keygen_with_public_key(c: &mut Criterion) {
+    let secp = Secp256k1::new();
+    let mut group = c.benchmark_group("keygen_with_public_key");
+
+    group.bench_function("osrng_per_call", |b| {
+        b.iter(|| black_box(PublicKey::from_secret_key(&secp, &SecretKey::new(&mut OsRng))))
+    });
+
+    let mut rng = ChaCha20Rng::from_entropy();
+    group.bench_function("chacha20_per_thread", |b| {
+        b.iter(|| black_box(PublicKey::from_secret_key(&secp, &SecretKey::new(&mut rng))))
+    });
+
+    group.finish();
+}
+
+// Addresses per second for several batch sizes, against generating one address per call
+fn address_batches(c: &mut Criterion) {
+    let secp = Secp256k1::new();
+    let mut rng = ChaCha20Rng::from_entropy();
+    let mut group = c.benchmark_group("address_batch");
+
+    for batch in [1, 32, 64, 128, 256] {
+        group.throughput(Throughput::Elements(batch as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(batch), &batch, |b, &batch| {
+            b.iter(|| black_box(keygen::generate_addresses_batch(&secp, &mut rng, None, batch)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, keygen, keygen_with_public_key, address_batches);
+criterion_main!(benches);
@@ -0,0 +1,119 @@
+// Prefix and suffix matching of one random address: the hex string path (format the address,
+// then compare characters), NibblePattern on the unpacked address, and the CompiledPattern
+// byte comparison of the search loop. Each includes its per-address conversion.
+//
+// Run with `cargo bench --bench pattern`
+
+#[allow(dead_code)]
+#[path = "../src/error.rs"]
+mod error;
+// Its unit tests are left out of the benchmark, and so are the uses of their imports
+#[allow(dead_code, unused_imports)]
+#[path = "../src/pattern.rs"]
+mod pattern;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use pattern::{bytes_to_nibbles, matches_at_offset, matches_pattern, CompiledPattern, NibblePattern, OffsetPattern, PartialMatcher};
+use rand::RngCore;
+use std::hint::black_box;
+
+const PREFIX: &str = "deadbeef";
+
+fn prefix_match(c: &mut Criterion) {
+    let mut address = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut address);
+    let nibble_pattern = NibblePattern::from_hex(PREFIX).expect("a valid pattern");
+    let compiled = CompiledPattern::compile(Some(PREFIX), None, false).expect("a valid pattern");
+    let mut group = c.benchmark_group("prefix_match");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("hex_string", |b| {
+        b.iter(|| {
+            let address = format!("0x{}", hex::encode(black_box(address)));
+            black_box(matches_pattern(&address, Some(PREFIX), None, &[], false))
+        })
+    });
+
+    group.bench_function("nibble_pattern", |b| {
+        b.iter(|| black_box(nibble_pattern.matches_prefix(&bytes_to_nibbles(black_box(&address)))))
+    });
+
+    group.bench_function("compiled_pattern", |b| {
+        b.iter(|| black_box(compiled.matches(black_box(&address))))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, prefix_match, suffix_match, offset_match, near_miss);
+criterion_main!(benches);
+
+// Suffixes of even and odd length, whose first character shares a byte with the address
+fn suffix_match(c: &mut Criterion) {
+    let mut address = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut address);
+    let mut group = c.benchmark_group("suffix_match");
+    group.throughput(Throughput::Elements(1));
+
+    for suffix in ["beef", "cbeef"] {
+        let compiled = CompiledPattern::compile(None, Some(suffix), false).expect("a valid pattern");
+        group.bench_function(format!("hex_string_{}", suffix.len()), |b| {
+            b.iter(|| {
+                let address = format!("0x{}", hex::encode(black_box(address)));
+                black_box(matches_pattern(&address, None, Some(suffix), &[], false))
+            })
+        });
+        group.bench_function(format!("compiled_pattern_{}", suffix.len()), |b| {
+            b.iter(|| black_box(compiled.matches(black_box(&address))))
+        });
+    }
+
+    group.finish();
+}
+
+// --at-offset in the middle of the address, as a string and on the raw bytes
+fn offset_match(c: &mut Criterion) {
+    let mut address = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut address);
+    let compiled = OffsetPattern::compile("dead", 20, false).expect("a valid pattern");
+    let mut group = c.benchmark_group("offset_match");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("hex_string", |b| {
+        b.iter(|| {
+            let address = format!("0x{}", hex::encode(black_box(address)));
+            black_box(matches_at_offset(&address, "dead", 20, false))
+        })
+    });
+
+    group.bench_function("offset_pattern", |b| {
+        b.iter(|| black_box(compiled.matches(black_box(&address))))
+    });
+
+    group.finish();
+}
+
+// What --track-near-miss adds to the check of each candidate: counting how many characters of
+// the prefix and suffix it got right, next to the compiled match alone
+fn near_miss(c: &mut Criterion) {
+    let mut address = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut address);
+    let compiled = CompiledPattern::compile(Some(PREFIX), Some("cbeef"), false).expect("a valid pattern");
+    let prefix = PartialMatcher::new(PREFIX, false);
+    let suffix = PartialMatcher::new("cbeef", true);
+    let mut group = c.benchmark_group("near_miss");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("compiled_pattern", |b| {
+        b.iter(|| black_box(compiled.matches(black_box(&address))))
+    });
+
+    group.bench_function("compiled_pattern_tracked", |b| {
+        b.iter(|| {
+            let address = black_box(&address);
+            black_box((compiled.matches(address), prefix.matched(address), suffix.matched(address)))
+        })
+    });
+
+    group.finish();
+}
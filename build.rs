@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Use the bundled protoc, so building needs no system protobuf install
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_build::configure().build_client(false).compile_protos(&["proto/vanity.proto"], &["proto"])?;
+
+    println!("cargo:rustc-check-cfg=cfg(cuda_kernel)");
+    if std::env::var_os("CARGO_FEATURE_CUDA").is_some() {
+        compile_cuda_kernel()?;
+    }
+    Ok(())
+}
+
+// The cuda feature's kernel, as PTX for the driver to compile when the search starts. Without
+// nvcc the build still succeeds, with a warning, and its CUDA searches run on the CPU.
+fn compile_cuda_kernel() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=src/kernels/vanity.cu");
+    println!("cargo:rerun-if-changed=src/kernels/secp256k1.h");
+    println!("cargo:rerun-if-env-changed=PATH");
+    let ptx = PathBuf::from(std::env::var("OUT_DIR")?).join("vanity.ptx");
+    match Command::new("nvcc").args(["--ptx", "-O3", "-o"]).arg(&ptx).arg("src/kernels/vanity.cu").status() {
+        Ok(status) if status.success() => println!("cargo:rustc-cfg=cuda_kernel"),
+        Ok(status) => return Err(format!("nvcc failed to compile src/kernels/vanity.cu ({})", status).into()),
+        Err(_) => println!("cargo:warning=nvcc is not on PATH, so this cuda build has no kernel and searches on the CPU"),
+    }
+    Ok(())
+}
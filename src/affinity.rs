@@ -0,0 +1,116 @@
+// --pin-cores and --cores: CPU affinity for the worker threads, so the scheduler cannot
+// move them between cores (or between NUMA nodes and big.LITTLE clusters) mid-search
+
+// Parse a core list such as `0-7,16-23` into sorted, distinct core numbers
+pub fn parse_core_list(value: &str) -> Result<Box<[usize]>, String> {
+    let mut cores = Vec::new();
+    for part in value.split(',').map(str::trim) {
+        let number = |text: &str| text.trim().parse::<usize>().map_err(|_| format!("'{}' is not a core number or range like 0-7", part));
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (number(first)?, number(last)?);
+                if first > last {
+                    return Err(format!("range '{}' ends before it starts", part));
+                }
+                cores.extend(first..=last);
+            }
+            None => cores.push(number(part)?),
+        }
+    }
+    cores.sort_unstable();
+    cores.dedup();
+    Ok(cores.into_boxed_slice())
+}
+
+// The inverse of parse_core_list, with consecutive cores as ranges
+pub fn format_core_list(cores: &[usize]) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &core in cores {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == core => *last = core,
+            _ => ranges.push((core, core)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// The cores this process may run on
+#[cfg(target_os = "linux")]
+pub fn available_cores() -> Vec<usize> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) } == 0 {
+        let cores: Vec<usize> = (0..libc::CPU_SETSIZE as usize).filter(|&core| unsafe { libc::CPU_ISSET(core, &set) }).collect();
+        if !cores.is_empty() {
+            return cores;
+        }
+    }
+    (0..std::thread::available_parallelism().map_or(1, |n| n.get())).collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn available_cores() -> Vec<usize> {
+    (0..std::thread::available_parallelism().map_or(1, |n| n.get())).collect()
+}
+
+// The cores each of `threads` workers may run on. Pinned workers get one core each,
+// spread evenly over `cores` when there are fewer workers than cores and wrapping
+// around when there are more; unpinned workers share every core of the list.
+pub fn plan(threads: usize, cores: &[usize], pin: bool) -> Vec<Vec<usize>> {
+    (0..threads)
+        .map(|thread| {
+            if !pin {
+                cores.to_vec()
+            } else if threads < cores.len() {
+                vec![cores[thread * cores.len() / threads]]
+            } else {
+                vec![cores[thread % cores.len()]]
+            }
+        })
+        .collect()
+}
+
+// Restrict the calling thread to `cores`
+#[cfg(target_os = "linux")]
+pub fn set_current_thread(cores: &[usize]) -> Result<(), String> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &core in cores {
+        if core >= libc::CPU_SETSIZE as usize {
+            return Err(format!("core {} is beyond the {} cores an affinity mask can hold", core, libc::CPU_SETSIZE));
+        }
+        unsafe { libc::CPU_SET(core, &mut set) };
+    }
+    // pid 0 is the calling thread
+    if unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(windows)]
+pub fn set_current_thread(cores: &[usize]) -> Result<(), String> {
+    use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadAffinityMask};
+    
+    // Without processor groups a mask covers the first 64 cores
+    let mut mask = 0usize;
+    for &core in cores {
+        if core >= usize::BITS as usize {
+            return Err(format!("core {} is beyond the {} cores an affinity mask can hold", core, usize::BITS));
+        }
+        mask |= 1 << core;
+    }
+    if unsafe { SetThreadAffinityMask(GetCurrentThread(), mask) } != 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn set_current_thread(_cores: &[usize]) -> Result<(), String> {
+    Err("CPU affinity is not supported on this platform".to_string())
+}
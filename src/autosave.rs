@@ -0,0 +1,67 @@
+// --autosave and --save-dir: every found wallet in a file of its own, so a find survives a
+// closed terminal or lost scrollback. Files are written to a hidden temporary name and
+// renamed, so the directory never holds a partly written wallet.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_DIR: &str = "vanity-results";
+
+pub struct SaveDir {
+    dir: PathBuf,
+    // The search parameters stored with every wallet
+    search: serde_json::Value,
+}
+
+// `<timestamp>-<lowercase address>.json`, in UTC with dashes instead of colons like keystore names
+pub fn file_name(address: &str) -> String {
+    format!("{}-{}.json", chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S%.9fZ"), address.to_lowercase())
+}
+
+impl SaveDir {
+    // Creates `dir` (owner-only on Unix) and checks that files can be written to it, so an
+    // unusable directory is reported before the search starts rather than after a find
+    pub fn prepare(dir: &Path) -> std::io::Result<SaveDir> {
+        if !dir.is_dir() {
+            let mut builder = std::fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::DirBuilderExt;
+                builder.mode(0o700);
+            }
+            builder.create(dir)?;
+        }
+        let probe = dir.join(format!(".evm-vanity-probe-{}", std::process::id()));
+        crate::write_private_file(&probe, b"")?;
+        std::fs::remove_file(&probe)?;
+        Ok(SaveDir { dir: dir.to_path_buf(), search: serde_json::Value::Null })
+    }
+    
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    
+    pub fn set_search(&mut self, search: serde_json::Value) {
+        self.search = search;
+    }
+    
+    // Saves a --json result object with the search parameters and the attempts and time
+    // taken so far, returning the path of the new file
+    pub fn save(&self, wallet: &serde_json::Value, attempts: u64, elapsed: Duration) -> std::io::Result<PathBuf> {
+        let mut saved = wallet.clone();
+        saved["search"] = self.search.clone();
+        saved["stats"] = serde_json::json!({
+            "attempts": attempts,
+            "elapsed_secs": elapsed.as_secs_f64(),
+            "saved_at": chrono::Utc::now().to_rfc3339(),
+        });
+        let name = file_name(wallet["address"].as_str().unwrap_or_default());
+        let path = self.dir.join(&name);
+        let temporary = self.dir.join(format!(".{}.tmp", name));
+        let contents = zeroize::Zeroizing::new(serde_json::to_string_pretty(&saved).expect("JSON values serialize"));
+        crate::write_private_file(&temporary, contents.as_bytes())?;
+        std::fs::rename(&temporary, &path)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,83 @@
+// Key generation throughput without pattern matching, for `bench` and --threads auto-tune.
+// Workers run the raw-mode batch loop of the search on a rayon pool of their own, with
+// the same --pin-cores/--cores affinity as a search when a core plan is given.
+
+use crate::counters::AttemptCounters;
+use crate::keygen::generate_addresses_batch;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use secp256k1::Secp256k1;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+pub struct BenchReport {
+    pub total: u64,
+    pub elapsed: Duration,
+    // Addresses generated in each whole second of the run
+    pub per_second: Vec<u64>,
+}
+
+impl BenchReport {
+    pub fn rate(&self) -> f64 {
+        self.total as f64 / self.elapsed.as_secs_f64()
+    }
+    
+    // Nearest-rank percentile of the per-second rates
+    pub fn percentile(&self, percent: f64) -> u64 {
+        let mut rates = self.per_second.clone();
+        rates.sort_unstable();
+        let rank = ((percent / 100.0) * rates.len() as f64).ceil() as usize;
+        rates.get(rank.clamp(1, rates.len().max(1)) - 1).copied().unwrap_or(0)
+    }
+}
+
+pub fn run(threads: usize, duration: Duration, core_plan: Option<&[Vec<usize>]>) -> BenchReport {
+    let core_plan = core_plan.map(<[Vec<usize>]>::to_vec);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .start_handler(move |thread_index| {
+            if let Some(cores) = core_plan.as_ref().map(|plan| &plan[thread_index]) {
+                if let Err(error_msg) = crate::affinity::set_current_thread(cores) {
+                    if thread_index == 0 {
+                        warning!("⚠️  Failed to set the CPU affinity, continuing unpinned: {}", error_msg);
+                    }
+                }
+            }
+        })
+        .build()
+        .expect("benchmark thread pool");
+    let counters = AttemptCounters::new(threads);
+    let running = AtomicBool::new(true);
+    let secp = Secp256k1::new();
+    
+    let start = Instant::now();
+    let mut per_second = Vec::new();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            pool.broadcast(|context| {
+                let mut rng = ChaCha20Rng::from_entropy();
+                while running.load(Ordering::Relaxed) {
+                    let mut batch = generate_addresses_batch(&secp, &mut rng, None, crate::KEYGEN_BATCH_SIZE);
+                    for (_, private_key) in &mut batch {
+                        private_key.non_secure_erase();
+                    }
+                    counters.add(context.index(), batch.len() as u64);
+                }
+            })
+        });
+        
+        // Sample on whole-second marks; a fractional last second is only in the total
+        let mut last_total = 0;
+        for second in 1..=duration.as_secs() {
+            let mark = start + Duration::from_secs(second);
+            std::thread::sleep(mark.saturating_duration_since(Instant::now()));
+            let total = counters.total();
+            per_second.push(total - last_total);
+            last_total = total;
+        }
+        std::thread::sleep((start + duration).saturating_duration_since(Instant::now()));
+        running.store(false, Ordering::Relaxed);
+    });
+    
+    BenchReport { total: counters.total(), elapsed: start.elapsed(), per_second }
+}
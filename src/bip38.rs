@@ -0,0 +1,205 @@
+// BIP38 passphrase-protected private keys (non-EC-multiply mode)
+//
+// The encoding follows the BIP38 specification byte for byte: the address
+// hash is the first 4 bytes of SHA256(SHA256(address)), the key is derived
+// with scrypt (N=16384, r=8, p=8) and each 16-byte half of the private key is
+// XORed with the derived key and encrypted as a single AES-256 block. For EVM
+// wallets the "address" is the 0x-prefixed address string we print, so the
+// address hash lets the decryptor detect a wrong password.
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::Aes256;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+const PREFIX: [u8; 2] = [0x01, 0x42];
+const FLAG_UNCOMPRESSED: u8 = 0xc0;
+const FLAG_COMPRESSED: u8 = 0xe0;
+const PAYLOAD_LEN: usize = 39;
+
+pub struct Bip38Decrypted {
+    pub private_key: Zeroizing<[u8; 32]>,
+    pub address_hash: [u8; 4],
+    pub compressed: bool,
+}
+
+pub fn address_hash(address: &str) -> [u8; 4] {
+    let hash = Sha256::digest(Sha256::digest(address.as_bytes()));
+    let mut out = [0u8; 4];
+    out.copy_from_slice(&hash[..4]);
+    out
+}
+
+fn derive_key(password: &str, salt: &[u8; 4]) -> Zeroizing<[u8; 64]> {
+    // log2(16384) = 14
+    let params = scrypt::Params::new(14, 8, 8, 64).expect("valid scrypt parameters");
+    let mut derived = Zeroizing::new([0u8; 64]);
+    scrypt::scrypt(password.as_bytes(), salt, &params, derived.as_mut())
+        .expect("64 bytes is a valid scrypt output length");
+    derived
+}
+
+pub fn bip38_encrypt(private_key: &[u8; 32], address: &str, password: &str) -> String {
+    encrypt(private_key, address, password, FLAG_UNCOMPRESSED)
+}
+
+// EVM addresses come from the uncompressed public key, so only the tests use the compressed flag
+fn encrypt(private_key: &[u8; 32], address: &str, password: &str, flag: u8) -> String {
+    let salt = address_hash(address);
+    let derived = derive_key(password, &salt);
+    let (half1, half2) = derived.split_at(32);
+    let cipher = Aes256::new(GenericArray::from_slice(half2));
+
+    let mut payload = Vec::with_capacity(PAYLOAD_LEN);
+    payload.extend_from_slice(&PREFIX);
+    payload.push(flag);
+    payload.extend_from_slice(&salt);
+
+    for i in 0..2 {
+        let mut block = GenericArray::default();
+        for (j, byte) in block.iter_mut().enumerate() {
+            *byte = private_key[i * 16 + j] ^ half1[i * 16 + j];
+        }
+        cipher.encrypt_block(&mut block);
+        payload.extend_from_slice(&block);
+    }
+
+    bs58::encode(payload).with_check().into_string()
+}
+
+pub fn bip38_decrypt(encoded: &str, password: &str) -> Result<Bip38Decrypted, String> {
+    let payload = bs58::decode(encoded.trim())
+        .with_check(None)
+        .into_vec()
+        .map_err(|e| format!("Invalid base58check encoding: {}", e))?;
+
+    if payload.len() != PAYLOAD_LEN || payload[..2] != PREFIX {
+        return Err("Not a BIP38 encrypted key (expected a key starting with 6P)".to_string());
+    }
+
+    let compressed = match payload[2] {
+        FLAG_UNCOMPRESSED => false,
+        FLAG_COMPRESSED => true,
+        _ => return Err("Unsupported BIP38 flag byte (EC-multiply keys are not supported)".to_string()),
+    };
+
+    let mut salt = [0u8; 4];
+    salt.copy_from_slice(&payload[3..7]);
+
+    let derived = derive_key(password, &salt);
+    let (half1, half2) = derived.split_at(32);
+    let cipher = Aes256::new(GenericArray::from_slice(half2));
+
+    let mut private_key = Zeroizing::new([0u8; 32]);
+    for i in 0..2 {
+        let start = 7 + i * 16;
+        let mut block = GenericArray::clone_from_slice(&payload[start..start + 16]);
+        cipher.decrypt_block(&mut block);
+        for (j, byte) in block.iter().enumerate() {
+            private_key[i * 16 + j] = byte ^ half1[i * 16 + j];
+        }
+    }
+
+    Ok(Bip38Decrypted {
+        private_key,
+        address_hash: salt,
+        compressed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The non-EC-multiply test vectors of the BIP38 specification: the Bitcoin address the key
+    // hash was taken over, the passphrase, the encrypted key and the private key it holds
+    struct Vector {
+        address: &'static str,
+        password: &'static str,
+        encrypted: &'static str,
+        private_key: &'static str,
+        compressed: bool,
+    }
+
+    const VECTORS: [Vector; 4] = [
+        Vector {
+            address: "1Jq6MksXQVWzrznvZzxkV6oY57oWXD9TXB",
+            password: "TestingOneTwoThree",
+            encrypted: "6PRVWUbkzzsbcVac2qwfssoUJAN1Xhrg6bNk8J7Nzm5H7kxEbn2Nh2ZoGg",
+            private_key: "cbf4b9f70470856bb4f40f80b87edb90865997ffee6df315ab166d713af433a5",
+            compressed: false,
+        },
+        Vector {
+            address: "1AvKt49sui9zfzGeo8EyL8ypvAhtR2KwbL",
+            password: "Satoshi",
+            encrypted: "6PRNFFkZc2NZ6dJqFfhRoFNMR9Lnyj7dYGrzdgXXVMXcxoKTePPX1dWByq",
+            private_key: "09c2686880095b1a4c249ee3ac4eea8a014f11e6f986d0b5025ac1f39afbd9ae",
+            compressed: false,
+        },
+        Vector {
+            address: "164MQi977u9GUteHr4EPH27VkkdxmfCvGW",
+            password: "TestingOneTwoThree",
+            encrypted: "6PYNKZ1EAgYgmQfmNVamxyXVWHzK5s6DGhwP4J5o44cvXdoY7sRzhtpUeo",
+            private_key: "cbf4b9f70470856bb4f40f80b87edb90865997ffee6df315ab166d713af433a5",
+            compressed: true,
+        },
+        Vector {
+            address: "1HmPbwsvG5qJ3KJfxzsZRZWhbm1xBMuS8B",
+            password: "Satoshi",
+            encrypted: "6PYLtMnXvfG3oJde97zRyLYFZCYizPU5T3LwgdYJz1fRhh16bU7u6PPmY7",
+            private_key: "09c2686880095b1a4c249ee3ac4eea8a014f11e6f986d0b5025ac1f39afbd9ae",
+            compressed: true,
+        },
+    ];
+
+    fn private_key(vector: &Vector) -> [u8; 32] {
+        hex::decode(vector.private_key).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn encrypts_spec_vectors() {
+        for vector in &VECTORS {
+            let flag = if vector.compressed { FLAG_COMPRESSED } else { FLAG_UNCOMPRESSED };
+            assert_eq!(encrypt(&private_key(vector), vector.address, vector.password, flag), vector.encrypted);
+        }
+    }
+
+    #[test]
+    fn bip38_encrypt_uses_the_uncompressed_flag() {
+        let vector = &VECTORS[0];
+        assert_eq!(bip38_encrypt(&private_key(vector), vector.address, vector.password), vector.encrypted);
+    }
+
+    #[test]
+    fn decrypts_spec_vectors() {
+        for vector in &VECTORS {
+            let decrypted = bip38_decrypt(vector.encrypted, vector.password).unwrap();
+            assert_eq!(*decrypted.private_key, private_key(vector));
+            assert_eq!(decrypted.compressed, vector.compressed);
+        }
+    }
+
+    #[test]
+    fn address_hash_matches_the_encrypted_salt() {
+        for vector in &VECTORS {
+            let decrypted = bip38_decrypt(vector.encrypted, vector.password).unwrap();
+            assert_eq!(decrypted.address_hash, address_hash(vector.address));
+        }
+        // The same key under its other address (compressed vs uncompressed) fails the check
+        assert_ne!(address_hash(VECTORS[0].address), address_hash(VECTORS[2].address));
+    }
+
+    #[test]
+    fn wrong_password_gives_a_different_key() {
+        let vector = &VECTORS[0];
+        let decrypted = bip38_decrypt(vector.encrypted, "wrong").unwrap();
+        assert_ne!(*decrypted.private_key, private_key(vector));
+    }
+
+    #[test]
+    fn rejects_non_bip38_input() {
+        assert!(bip38_decrypt("not base58!", "x").is_err());
+        let wif = bs58::encode([0x80; 34]).with_check().into_string();
+        assert!(bip38_decrypt(&wif, "x").is_err());
+    }
+}
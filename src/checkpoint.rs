@@ -0,0 +1,131 @@
+// --start-key scans: worker i checks start + i·2^64, start + i·2^64 + 1, ... and
+// --checkpoint records how many keys each worker has checked, so --resume carries on
+// without checking a key twice or skipping one. The start key itself is never saved,
+// only a hash that tells whether a resume was given the same one.
+
+use crate::keyrange::{add_wide_offset, MAX_KEY};
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use zeroize::Zeroizing;
+
+const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    version: u32,
+    start_key_hash: String,
+    pattern: String,
+    threads: usize,
+    // Keys checked in each worker's sub-range, counted from its first key
+    offsets: Vec<u64>,
+    attempts: u64,
+}
+
+pub struct SequentialScan {
+    start_key: Zeroizing<[u8; 32]>,
+    pattern: String,
+    threads: usize,
+    offsets: Vec<AtomicU64>,
+}
+
+fn start_key_hash(start_key: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(Keccak256::digest(start_key)))
+}
+
+impl SequentialScan {
+    pub fn new(start_key: [u8; 32], pattern: String, threads: usize) -> SequentialScan {
+        SequentialScan {
+            start_key: Zeroizing::new(start_key),
+            pattern,
+            threads,
+            offsets: (0..threads).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+    
+    // The scan saved at `path`. A different start key is always refused; a different pattern
+    // or thread count only without `force`. Sub-ranges of workers this run does not have are
+    // kept in the checkpoint for a later run with more threads.
+    pub fn resume(start_key: [u8; 32], pattern: String, threads: usize, path: &Path, force: bool) -> Result<SequentialScan, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| format!("Failed to read checkpoint {}: {}", path.display(), e))?;
+        let checkpoint: Checkpoint = serde_json::from_str(&contents).map_err(|e| format!("{} is not a checkpoint: {}", path.display(), e))?;
+        if checkpoint.version != VERSION {
+            return Err(format!("{} is a version {} checkpoint, this build reads version {}", path.display(), checkpoint.version, VERSION));
+        }
+        if checkpoint.start_key_hash != start_key_hash(&start_key) {
+            return Err(format!("{} was written for another --start-key", path.display()));
+        }
+        if checkpoint.pattern != pattern {
+            if !force {
+                return Err(format!("{} was written for pattern {}, not {} (--force resumes anyway)", path.display(), checkpoint.pattern, pattern));
+            }
+            warning!("⚠️  Resuming a scan for pattern {} with pattern {}", checkpoint.pattern, pattern);
+        }
+        if checkpoint.threads != threads {
+            if !force {
+                return Err(format!("{} was written by {} threads, not {} (--force resumes anyway)", path.display(), checkpoint.threads, threads));
+            }
+            if checkpoint.offsets.len() > threads {
+                warning!("⚠️  Only the first {} of {} sub-ranges are scanned; a later run with {} threads continues the rest",
+                         threads, checkpoint.offsets.len(), checkpoint.offsets.len());
+            }
+        }
+        let mut offsets = checkpoint.offsets;
+        if offsets.len() < threads {
+            offsets.resize(threads, 0);
+        }
+        Ok(SequentialScan {
+            start_key: Zeroizing::new(start_key),
+            pattern,
+            threads,
+            offsets: offsets.into_iter().map(AtomicU64::new).collect(),
+        })
+    }
+    
+    // Key number `offset` of `worker`'s sub-range, or None past the last valid key
+    pub fn key_at(&self, worker: usize, offset: u64) -> Option<SecretKey> {
+        let key = Zeroizing::new(add_wide_offset(&self.start_key, worker as u64, offset));
+        if *key < *self.start_key || *key > MAX_KEY {
+            return None;
+        }
+        SecretKey::from_slice(key.as_ref()).ok()
+    }
+    
+    // Keys `worker` has checked so far; only that worker moves its offset
+    pub fn offset(&self, worker: usize) -> u64 {
+        self.offsets[worker].load(Ordering::SeqCst)
+    }
+    
+    pub fn advance(&self, worker: usize, offset: u64) {
+        self.offsets[worker].store(offset, Ordering::SeqCst);
+    }
+    
+    // Moves `worker` past the match it stopped at, once that match has been delivered
+    pub fn delivered(&self, worker: usize) {
+        self.offsets[worker].fetch_add(1, Ordering::SeqCst);
+    }
+    
+    // Keys checked over all runs of this scan
+    pub fn checked(&self) -> u64 {
+        self.offsets.iter().map(|offset| offset.load(Ordering::SeqCst)).sum()
+    }
+    
+    // Written to a temporary file first, so a crash mid-write leaves the last checkpoint intact
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let offsets: Vec<u64> = self.offsets.iter().map(|offset| offset.load(Ordering::SeqCst)).collect();
+        let checkpoint = Checkpoint {
+            version: VERSION,
+            start_key_hash: start_key_hash(&self.start_key),
+            pattern: self.pattern.clone(),
+            threads: self.threads,
+            attempts: offsets.iter().sum(),
+            offsets,
+        };
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, serde_json::to_string_pretty(&checkpoint).expect("JSON values serialize"))?;
+        std::fs::rename(&temporary, path)
+    }
+}
@@ -0,0 +1,203 @@
+// Persistent defaults from a TOML file (--config, ~/.config/evm-vanity/config.toml or
+// ~/.evm-vanity.toml). Any search option can be set by its name, with underscores or dashes:
+//
+// prefix = "dead"
+// threads = 8
+// case_sensitive = true
+// output_format = "json"
+// output_file = "found.json"
+// time_limit = 3600
+// alt = ["dead", "beef"]
+//
+// Values only fill in options that were neither given on the command line nor
+// set through their EVM_VANITY_* environment variable. They are turned into the
+// command line arguments they stand for, so clap checks them like typed ones.
+
+use crate::error::VanityError;
+use clap::builder::ValueParser;
+use clap::ArgAction;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml::{Spanned, Value};
+
+// Options that make no sense in a config file
+const NOT_CONFIGURABLE: [&str; 4] = ["config", "print_config", "help", "version"];
+
+// Shown as "<redacted>" by --print-config
+const SECRET_ARGS: [&str; 10] = [
+    "start_key", "seed", "extra_entropy", "from_mnemonic", "passphrase", "keystore_password", "telegram_token", "discord_webhook", "api_token", "verify_rpc",
+];
+
+pub struct Config {
+    path: PathBuf,
+    contents: String,
+    values: BTreeMap<Spanned<String>, Spanned<Value>>,
+}
+
+impl Config {
+    pub fn from_file(path: &Path) -> Result<Config, VanityError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| VanityError::Io { path: path.to_path_buf(), source })?;
+        let values = toml::from_str(&contents).map_err(|e| VanityError::InvalidConfig { path: path.to_path_buf(), message: e.to_string() })?;
+        Ok(Config { path: path.to_path_buf(), contents, values })
+    }
+    
+    fn invalid(&self, start: usize, message: String) -> VanityError {
+        let line = self.contents[..start].matches('\n').count() + 1;
+        VanityError::InvalidConfig { path: self.path.clone(), message: format!("line {}: {}", line, message) }
+    }
+    
+    // The arguments standing for the settings of options `wanted` accepts, and the ids of
+    // those options. `command` holds the search options.
+    pub fn arguments(&self, command: &clap::Command, wanted: impl Fn(&str) -> bool) -> Result<(Vec<String>, Vec<String>), VanityError> {
+        let mut arguments = Vec::new();
+        let mut applied = Vec::new();
+        for (key, value) in &self.values {
+            let name = key.get_ref().replace('-', "_");
+            let Some(arg) = command.get_arguments().find(|arg| named(arg, &name) && !NOT_CONFIGURABLE.contains(&arg.get_id().as_str())) else {
+                return Err(self.invalid(key.span().start, format!("unknown option '{}'", key.get_ref())));
+            };
+            if !wanted(arg.get_id().as_str()) {
+                continue;
+            }
+            let flag = arg.get_long().map(|long| format!("--{}", long));
+            let mismatch = |expected: &str| self.invalid(value.span().start, format!("'{}' expects {}", key.get_ref(), expected));
+            match (arg.get_action(), value.get_ref()) {
+                (ArgAction::SetTrue, Value::Boolean(set)) | (ArgAction::SetFalse, Value::Boolean(set)) => {
+                    if *set == matches!(arg.get_action(), ArgAction::SetTrue) {
+                        arguments.extend(flag);
+                    }
+                }
+                (ArgAction::SetTrue | ArgAction::SetFalse, _) => return Err(mismatch("true or false")),
+                (ArgAction::Count, Value::Integer(count)) if *count >= 0 => {
+                    arguments.extend(flag.iter().cycle().take(*count as usize).cloned());
+                }
+                (ArgAction::Count, _) => return Err(mismatch("a count")),
+                (_, value) => {
+                    let items = match value {
+                        Value::Array(items) => items.iter().collect(),
+                        value => vec![value],
+                    };
+                    for item in items {
+                        let text = match item {
+                            Value::String(text) => text.clone(),
+                            Value::Integer(_) | Value::Float(_) | Value::Boolean(_) => item.to_string(),
+                            _ => return Err(mismatch("a string, number or boolean")),
+                        };
+                        // Positional options take the bare value
+                        arguments.push(match &flag {
+                            Some(flag) => format!("{}={}", flag, text),
+                            None => text,
+                        });
+                    }
+                }
+            }
+            applied.push(arg.get_id().to_string());
+        }
+        Ok((arguments, applied))
+    }
+    
+    // Prefixes a clap error about an option from this file with the line that set it
+    pub fn parse_error(&self, command: &clap::Command, error: &clap::Error) -> VanityError {
+        let message = error.to_string();
+        let message = message.lines().next().unwrap_or_default().trim_start_matches("error: ").to_string();
+        let option = match error.get(clap::error::ContextKind::InvalidArg) {
+            Some(clap::error::ContextValue::String(option)) => option.clone(),
+            _ => String::new(),
+        };
+        let key = self.values.keys().find(|key| {
+            let name = key.get_ref().replace('-', "_");
+            command.get_arguments().any(|arg| {
+                // Options with a value are named with a placeholder, e.g. `--threads <N>`
+                named(arg, &name) && arg.get_long().is_some_and(|long| option == format!("--{}", long) || option.starts_with(&format!("--{} ", long)))
+            })
+        });
+        match key {
+            Some(key) => self.invalid(key.span().start, message),
+            None => VanityError::InvalidConfig { path: self.path.clone(), message },
+        }
+    }
+}
+
+// Options are named by their id or long flag, with dashes as underscores
+fn named(arg: &clap::Arg, name: &str) -> bool {
+    arg.get_id() == name || arg.get_long().is_some_and(|long| long.replace('-', "_") == name)
+}
+
+// The first of ~/.config/evm-vanity/config.toml ($XDG_CONFIG_HOME if set) and
+// ~/.evm-vanity.toml that exists
+pub fn default_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| Path::new(home).join(".config")));
+    let candidates = [
+        config_dir.map(|dir| dir.join("evm-vanity").join("config.toml")),
+        home.map(|home| Path::new(&home).join(".evm-vanity.toml")),
+    ];
+    candidates.into_iter().flatten().find(|path| path.is_file())
+}
+
+// --print-config: every option with a value as TOML, with where the value came from.
+// Defaults are commented out and flags left off by default are omitted.
+pub fn print_effective(command: &clap::Command, matches: &clap::ArgMatches, from_config: &[String]) {
+    use clap::parser::ValueSource;
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        if NOT_CONFIGURABLE.contains(&id) || arg.is_hide_set() {
+            continue;
+        }
+        let (Some(raw), Some(source)) = (matches.get_raw(id), matches.value_source(id)) else {
+            continue;
+        };
+        let raw: Vec<String> = raw.map(|value| value.to_string_lossy().into_owned()).collect();
+        let source = if from_config.iter().any(|applied| applied == id) {
+            "config file"
+        } else {
+            match source {
+                ValueSource::CommandLine => "command line",
+                ValueSource::EnvVariable => "environment",
+                _ => "default",
+            }
+        };
+        let is_flag = matches!(arg.get_action(), ArgAction::SetTrue | ArgAction::SetFalse);
+        if is_flag && source == "default" && raw == ["false"] {
+            continue;
+        }
+        let value = if SECRET_ARGS.contains(&id) {
+            Value::String("<redacted>".to_string())
+        } else if is_flag {
+            Value::Boolean(raw == ["true"])
+        } else {
+            let parser = arg.get_value_parser().type_id();
+            let numeric = [
+                ValueParser::from(clap::value_parser!(u64)).type_id(),
+                ValueParser::from(clap::value_parser!(u32)).type_id(),
+                ValueParser::from(clap::value_parser!(u16)).type_id(),
+                ValueParser::from(clap::value_parser!(u8)).type_id(),
+                ValueParser::from(clap::value_parser!(usize)).type_id(),
+            ]
+            .contains(&parser);
+            let mut items: Vec<Value> = raw.iter().map(|text| toml_value(text, numeric)).collect();
+            if items.len() == 1 && !matches!(arg.get_action(), ArgAction::Append) {
+                items.remove(0)
+            } else {
+                Value::Array(items)
+            }
+        };
+        // Defaults are commented out, so the output can be saved as a config file
+        if source == "default" {
+            println!("# {} = {}  # default", id, value);
+        } else {
+            println!("{} = {}  # {}", id, value, source);
+        }
+    }
+}
+
+// Numeric options as TOML integers where they fit, everything else as strings
+fn toml_value(text: &str, numeric: bool) -> Value {
+    match text.parse::<i64>() {
+        Ok(number) if numeric => Value::Integer(number),
+        _ => Value::String(text.to_string()),
+    }
+}
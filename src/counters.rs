@@ -0,0 +1,34 @@
+// Attempt counters, one per worker thread
+//
+// A single shared counter bounces its cache line between every core on each
+// update. Each thread instead writes only its own counter, padded to a cache
+// line of its own, and readers sum them.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[repr(align(64))]
+pub struct PaddedCounter(pub AtomicU64);
+
+pub struct AttemptCounters {
+    counters: Vec<PaddedCounter>,
+}
+
+impl AttemptCounters {
+    pub fn new(threads: usize) -> AttemptCounters {
+        AttemptCounters {
+            counters: (0..threads).map(|_| PaddedCounter(AtomicU64::new(0))).collect(),
+        }
+    }
+    
+    pub fn add(&self, thread: usize, attempts: u64) {
+        self.counters[thread].0.fetch_add(attempts, Ordering::Relaxed);
+    }
+    
+    pub fn total(&self) -> u64 {
+        self.counters.iter().map(|c| c.0.load(Ordering::Relaxed)).sum()
+    }
+    
+    pub fn per_thread(&self) -> Vec<u64> {
+        self.counters.iter().map(|c| c.0.load(Ordering::Relaxed)).collect()
+    }
+}
@@ -0,0 +1,312 @@
+// The cuda feature: raw-mode search on an NVIDIA GPU through the CUDA driver API. build.rs
+// compiles kernels/vanity.cu to PTX with nvcc, and the driver's libcuda, loaded when the search
+// starts, compiles it for the device.
+//
+// The kernel derives its keys from a ChaCha20 stream like the OpenCL one, but checks their
+// addresses against the bits every match has (pattern::CompiledSet::common_bits) itself, and
+// only the keys that pass come back. The host derives their addresses again, so a key is only
+// handed on for the address the pattern matched.
+
+use crate::device::{Library, Searcher};
+use crate::keygen::address_bytes_from_secret_key;
+use crate::pattern::BitMask;
+use rand::RngCore;
+use secp256k1::{All, Secp256k1, SecretKey};
+use std::ffi::{c_char, c_void, CString};
+use std::ptr;
+use zeroize::Zeroizing;
+
+#[cfg(cuda_kernel)]
+const PTX: Option<&str> = Some(include_str!(concat!(env!("OUT_DIR"), "/vanity.ptx")));
+#[cfg(not(cuda_kernel))]
+const PTX: Option<&str> = None;
+
+// Threads per block of a launch
+const BLOCK_SIZE: u32 = 256;
+
+type Handle = *mut c_void;
+// A device address
+type DevicePtr = u64;
+
+const CUDA_SUCCESS: i32 = 0;
+
+// The CUDA driver API entry points the search uses
+struct Cuda {
+    init: unsafe extern "C" fn(u32) -> i32,
+    device_get_count: unsafe extern "C" fn(*mut i32) -> i32,
+    device_get: unsafe extern "C" fn(*mut i32, i32) -> i32,
+    device_get_name: unsafe extern "C" fn(*mut c_char, i32, i32) -> i32,
+    ctx_create: unsafe extern "C" fn(*mut Handle, u32, i32) -> i32,
+    ctx_set_current: unsafe extern "C" fn(Handle) -> i32,
+    ctx_synchronize: unsafe extern "C" fn() -> i32,
+    ctx_destroy: unsafe extern "C" fn(Handle) -> i32,
+    module_load_data: unsafe extern "C" fn(*mut Handle, *const c_void) -> i32,
+    module_get_function: unsafe extern "C" fn(*mut Handle, Handle, *const c_char) -> i32,
+    module_unload: unsafe extern "C" fn(Handle) -> i32,
+    mem_alloc: unsafe extern "C" fn(*mut DevicePtr, usize) -> i32,
+    mem_free: unsafe extern "C" fn(DevicePtr) -> i32,
+    memcpy_htod: unsafe extern "C" fn(DevicePtr, *const c_void, usize) -> i32,
+    memcpy_dtoh: unsafe extern "C" fn(*mut c_void, DevicePtr, usize) -> i32,
+    memset_d8: unsafe extern "C" fn(DevicePtr, u8, usize) -> i32,
+    #[allow(clippy::type_complexity)]
+    launch_kernel: unsafe extern "C" fn(Handle, u32, u32, u32, u32, u32, u32, u32, Handle, *mut *mut c_void, *mut *mut c_void) -> i32,
+}
+
+impl Cuda {
+    fn load() -> Result<Cuda, String> {
+        let library = Library::open(&["libcuda.so.1", "libcuda.so"])?;
+        // The types are those of the declarations in cuda.h, and the _v2 entry points are
+        // the ones its macros select
+        unsafe {
+            Ok(Cuda {
+                init: library.symbol("cuInit")?,
+                device_get_count: library.symbol("cuDeviceGetCount")?,
+                device_get: library.symbol("cuDeviceGet")?,
+                device_get_name: library.symbol("cuDeviceGetName")?,
+                ctx_create: library.symbol("cuCtxCreate_v2")?,
+                ctx_set_current: library.symbol("cuCtxSetCurrent")?,
+                ctx_synchronize: library.symbol("cuCtxSynchronize")?,
+                ctx_destroy: library.symbol("cuCtxDestroy_v2")?,
+                module_load_data: library.symbol("cuModuleLoadData")?,
+                module_get_function: library.symbol("cuModuleGetFunction")?,
+                module_unload: library.symbol("cuModuleUnload")?,
+                mem_alloc: library.symbol("cuMemAlloc_v2")?,
+                mem_free: library.symbol("cuMemFree_v2")?,
+                memcpy_htod: library.symbol("cuMemcpyHtoD_v2")?,
+                memcpy_dtoh: library.symbol("cuMemcpyDtoH_v2")?,
+                memset_d8: library.symbol("cuMemsetD8_v2")?,
+                launch_kernel: library.symbol("cuLaunchKernel")?,
+            })
+        }
+    }
+
+    fn alloc(&self, size: usize) -> Result<DevicePtr, String> {
+        let mut buffer = 0;
+        check(unsafe { (self.mem_alloc)(&mut buffer, size) }, "cuMemAlloc")?;
+        Ok(buffer)
+    }
+}
+
+fn check(status: i32, call: &str) -> Result<(), String> {
+    if status == CUDA_SUCCESS {
+        Ok(())
+    } else {
+        Err(format!("{} failed with error {}", call, status))
+    }
+}
+
+// Key slots per launch: four times the candidates expected to pass the filter, and at least
+// 256, so a launch that keeps more than fits is vanishingly rare. Its surplus keys are dropped.
+fn capacity(batch_size: u32, filter: &BitMask) -> u32 {
+    let expected = batch_size as f64 / filter.difficulty();
+    ((4.0 * expected) as u32).saturating_add(256).min(batch_size)
+}
+
+// A launch parameter, as cuLaunchKernel takes it
+fn parameter<T>(value: &mut T) -> *mut c_void {
+    (value as *mut T).cast()
+}
+
+pub struct CudaSearcher {
+    cuda: Cuda,
+    name: String,
+    context: Handle,
+    module: Handle,
+    function: Handle,
+    seed_buffer: DevicePtr,
+    filter_buffer: DevicePtr,
+    found_buffer: DevicePtr,
+    keys_buffer: DevicePtr,
+    filter: BitMask,
+    batch_size: u32,
+    capacity: u32,
+    // The keystream block of the next batch's first candidate
+    next_block: u64,
+    secp: Secp256k1<All>,
+}
+
+// The context is made current on the thread running each batch, and CUDA objects may move
+// between threads
+unsafe impl Send for CudaSearcher {}
+
+impl CudaSearcher {
+    // CUDA device `device_id` with the kernel loaded, checking every candidate until
+    // `configure` narrows it down
+    pub fn new(device_id: u32) -> Result<CudaSearcher, String> {
+        let ptx = PTX.ok_or("this build has no CUDA kernel, as nvcc was not on PATH when it was built")?;
+        let cuda = Cuda::load()?;
+        let mut count = 0;
+        check(unsafe { (cuda.init)(0) }, "cuInit")?;
+        check(unsafe { (cuda.device_get_count)(&mut count) }, "cuDeviceGetCount")?;
+        if device_id as i32 >= count {
+            return Err(match count {
+                0 => "no CUDA device".to_string(),
+                1 => format!("there is no CUDA device {}, only device 0", device_id),
+                count => format!("there is no CUDA device {}, only devices 0 to {}", device_id, count - 1),
+            });
+        }
+        let mut device = 0;
+        check(unsafe { (cuda.device_get)(&mut device, device_id as i32) }, "cuDeviceGet")?;
+        let mut name = [0u8; 256];
+        check(unsafe { (cuda.device_get_name)(name.as_mut_ptr().cast(), name.len() as i32, device) }, "cuDeviceGetName")?;
+        let name = String::from_utf8_lossy(&name).trim_end_matches('\0').trim().to_string();
+
+        // Created empty, so whatever was made before a failure is released by Drop
+        let mut searcher = CudaSearcher {
+            cuda,
+            name,
+            context: ptr::null_mut(),
+            module: ptr::null_mut(),
+            function: ptr::null_mut(),
+            seed_buffer: 0,
+            filter_buffer: 0,
+            found_buffer: 0,
+            keys_buffer: 0,
+            filter: BitMask::new([0; 20], [0; 20]),
+            batch_size: 0,
+            capacity: 0,
+            next_block: 0,
+            secp: Secp256k1::new(),
+        };
+        searcher.load(device, ptx)?;
+        searcher.configure(crate::device::DEFAULT_BATCH_SIZE, &BitMask::new([0; 20], [0; 20]))?;
+        Ok(searcher)
+    }
+
+    fn load(&mut self, device: i32, ptx: &str) -> Result<(), String> {
+        let cuda = &self.cuda;
+        unsafe {
+            check((cuda.ctx_create)(&mut self.context, 0, device), "cuCtxCreate")?;
+            let ptx = CString::new(ptx).map_err(|_| "the PTX has a NUL byte".to_string())?;
+            check((cuda.module_load_data)(&mut self.module, ptx.as_ptr().cast()), "cuModuleLoadData")?;
+            let function_name = CString::new("vanity_search").expect("no NUL");
+            check((cuda.module_get_function)(&mut self.function, self.module, function_name.as_ptr()), "cuModuleGetFunction")?;
+        }
+
+        // Every key of the search follows from the seed, so its only copy is on the device
+        let mut seed = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(seed.as_mut());
+        self.seed_buffer = cuda.alloc(seed.len())?;
+        check(unsafe { (cuda.memcpy_htod)(self.seed_buffer, seed.as_ptr().cast(), seed.len()) }, "cuMemcpyHtoD")?;
+        self.filter_buffer = cuda.alloc(40)?;
+        self.found_buffer = cuda.alloc(4)?;
+        Ok(())
+    }
+
+    // Sets the candidates per launch, and the bits a candidate needs for its key to come back
+    pub fn configure(&mut self, batch_size: u32, filter: &BitMask) -> Result<(), String> {
+        let cuda = &self.cuda;
+        check(unsafe { (cuda.ctx_set_current)(self.context) }, "cuCtxSetCurrent")?;
+        let bits: Vec<u8> = filter.mask.iter().chain(&filter.value).copied().collect();
+        check(unsafe { (cuda.memcpy_htod)(self.filter_buffer, bits.as_ptr().cast(), bits.len()) }, "cuMemcpyHtoD")?;
+        if self.keys_buffer != 0 {
+            check(unsafe { (cuda.mem_free)(self.keys_buffer) }, "cuMemFree")?;
+            self.keys_buffer = 0;
+        }
+        self.capacity = capacity(batch_size, filter);
+        self.keys_buffer = cuda.alloc(self.capacity as usize * 32)?;
+        self.batch_size = batch_size;
+        self.filter = *filter;
+        Ok(())
+    }
+}
+
+impl Searcher for CudaSearcher {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn search_batch(&mut self, find: &mut dyn FnMut(&[u8; 20]) -> bool) -> Result<(u64, Vec<SecretKey>), String> {
+        let first_block = self.next_block;
+        self.next_block += self.batch_size as u64;
+        let cuda = &self.cuda;
+        let mut found = 0u32;
+        let mut key_bytes = Zeroizing::new(Vec::new());
+        unsafe {
+            // The context is current on the thread that created it, not necessarily this one
+            check((cuda.ctx_set_current)(self.context), "cuCtxSetCurrent")?;
+            check((cuda.memset_d8)(self.found_buffer, 0, 4), "cuMemsetD8")?;
+            let (mut seed, mut first_block, mut count, mut filter, mut capacity, mut found_buffer, mut keys) =
+                (self.seed_buffer, first_block, self.batch_size, self.filter_buffer, self.capacity, self.found_buffer, self.keys_buffer);
+            let mut parameters = [
+                parameter(&mut seed),
+                parameter(&mut first_block),
+                parameter(&mut count),
+                parameter(&mut filter),
+                parameter(&mut capacity),
+                parameter(&mut found_buffer),
+                parameter(&mut keys),
+            ];
+            let blocks = self.batch_size.div_ceil(BLOCK_SIZE);
+            check((cuda.launch_kernel)(self.function, blocks, 1, 1, BLOCK_SIZE, 1, 1, 0, ptr::null_mut(), parameters.as_mut_ptr(), ptr::null_mut()), "cuLaunchKernel")?;
+            check((cuda.ctx_synchronize)(), "cuCtxSynchronize")?;
+
+            check((cuda.memcpy_dtoh)(parameter(&mut found), self.found_buffer, 4), "cuMemcpyDtoH")?;
+            let kept = found.min(self.capacity) as usize * 32;
+            key_bytes.resize(kept, 0);
+            check((cuda.memcpy_dtoh)(key_bytes.as_mut_ptr().cast(), self.keys_buffer, kept), "cuMemcpyDtoH")?;
+            // The keys only stay on the device until they are read
+            check((cuda.memset_d8)(self.keys_buffer, 0, kept), "cuMemsetD8")?;
+        }
+
+        let mut keys = Vec::new();
+        for key in key_bytes.chunks_exact(32) {
+            // A keystream block that is no valid key (about 1 in 2^128) is passed over
+            let Ok(mut key) = SecretKey::from_slice(key) else {
+                continue;
+            };
+            let address = address_bytes_from_secret_key(&self.secp, &key);
+            // A driver that miscompiles the kernel must not hand out keys that never passed its filter
+            if !self.filter.matches(&address) {
+                key.non_secure_erase();
+                return Err("the device kept a key whose address does not pass the filter".to_string());
+            }
+            if find(&address) {
+                keys.push(key);
+            } else {
+                key.non_secure_erase();
+            }
+        }
+        Ok((self.batch_size as u64, keys))
+    }
+}
+
+impl Drop for CudaSearcher {
+    fn drop(&mut self) {
+        let cuda = &self.cuda;
+        if self.context.is_null() {
+            return;
+        }
+        unsafe {
+            (cuda.ctx_set_current)(self.context);
+            for buffer in [self.keys_buffer, self.found_buffer, self.filter_buffer, self.seed_buffer] {
+                if buffer != 0 {
+                    (cuda.mem_free)(buffer);
+                }
+            }
+            if !self.module.is_null() {
+                (cuda.module_unload)(self.module);
+            }
+            (cuda.ctx_destroy)(self.context);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launches_keep_room_for_the_expected_keys() {
+        let batch_size = 1 << 20;
+        // Nothing filtered: every candidate comes back
+        assert_eq!(capacity(batch_size, &BitMask::new([0; 20], [0; 20])), batch_size);
+        // A 4-character prefix passes 16 candidates of the batch
+        let mut mask = [0; 20];
+        mask[..2].copy_from_slice(&[0xff, 0xff]);
+        assert_eq!(capacity(batch_size, &BitMask::new(mask, [0; 20])), 4 * 16 + 256);
+        // A long one still has the minimum
+        let mask = [0xff; 20];
+        assert_eq!(capacity(batch_size, &BitMask::new(mask, [0; 20])), 256);
+    }
+}
@@ -0,0 +1,93 @@
+// GPU search for the raw mode: a device turns random keys into candidate addresses a batch
+// at a time, and the host checks them against the pattern. The backends are Cargo features
+// that load the vendor library at runtime, so a build with them still starts, and searches on
+// the CPU, on a machine without a GPU.
+
+use crate::pattern::BitMask;
+use secp256k1::SecretKey;
+
+// Candidates per kernel launch unless --gpu-batch-size says otherwise
+pub const DEFAULT_BATCH_SIZE: u32 = 1 << 20;
+
+pub trait Searcher: Send {
+    // The device, as shown in the banner
+    fn name(&self) -> &str;
+
+    // Runs one batch and returns how many candidates it checked and the keys of those `find`
+    // accepted, each checked on the host to give the address `find` saw. `find` sees every
+    // candidate the device's filter let through.
+    fn search_batch(&mut self, find: &mut dyn FnMut(&[u8; 20]) -> bool) -> Result<(u64, Vec<SecretKey>), String>;
+}
+
+pub const UNSUPPORTED: &str = "this build has no GPU search; rebuild with --features gpu or --features cuda";
+
+// The searcher of device `index`, or None with a warning when there is no usable one. CUDA
+// is tried first, and only hands back the candidates that have the bits of `filter`; OpenCL
+// hands back every candidate.
+#[cfg(any(feature = "gpu", feature = "cuda"))]
+#[cfg_attr(not(feature = "cuda"), allow(unused_variables))]
+pub fn open(index: u32, batch_size: u32, filter: &BitMask) -> Option<Box<dyn Searcher>> {
+    let mut failures = Vec::new();
+    #[cfg(feature = "cuda")]
+    match crate::cuda::CudaSearcher::new(index).and_then(|mut searcher| searcher.configure(batch_size, filter).map(|()| searcher)) {
+        Ok(searcher) => return Some(Box::new(searcher)),
+        Err(error_msg) => failures.push(format!("CUDA: {}", error_msg)),
+    }
+    #[cfg(feature = "gpu")]
+    match crate::gpu::GpuSearcher::new(index, batch_size) {
+        Ok(searcher) => return Some(Box::new(searcher)),
+        Err(error_msg) => failures.push(format!("OpenCL: {}", error_msg)),
+    }
+    let error_msg = failures.join("; ");
+    warning!("⚠️  No usable GPU, searching on the CPU: {}", error_msg);
+    tracing::warn!(device = index, error = %error_msg, "opening the GPU failed");
+    None
+}
+
+#[cfg(not(any(feature = "gpu", feature = "cuda")))]
+pub fn open(_index: u32, _batch_size: u32, _filter: &BitMask) -> Option<Box<dyn Searcher>> {
+    None
+}
+
+// A vendor library opened with dlopen. It is never closed: the backends keep their device
+// for the whole run, and some drivers crash when unloaded.
+#[cfg(any(feature = "gpu", feature = "cuda"))]
+pub struct Library(*mut std::ffi::c_void);
+
+#[cfg(any(feature = "gpu", feature = "cuda"))]
+impl Library {
+    // The first of `names` that loads
+    #[cfg(unix)]
+    pub fn open(names: &[&str]) -> Result<Library, String> {
+        for name in names {
+            let path = std::ffi::CString::new(*name).expect("library names have no NUL");
+            let handle = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+            if !handle.is_null() {
+                return Ok(Library(handle));
+            }
+        }
+        Err(format!("{} not found", names[0]))
+    }
+
+    #[cfg(not(unix))]
+    pub fn open(names: &[&str]) -> Result<Library, String> {
+        Err(format!("{} cannot be loaded on this platform", names[0]))
+    }
+
+    // The function `name`, whose type the caller vouches for as `F`
+    #[cfg(unix)]
+    pub unsafe fn symbol<F: Copy>(&self, name: &str) -> Result<F, String> {
+        assert_eq!(std::mem::size_of::<F>(), std::mem::size_of::<*mut std::ffi::c_void>());
+        let symbol = std::ffi::CString::new(name).expect("symbol names have no NUL");
+        let address = libc::dlsym(self.0, symbol.as_ptr());
+        if address.is_null() {
+            return Err(format!("{} is missing from the library", name));
+        }
+        Ok(std::mem::transmute_copy(&address))
+    }
+
+    #[cfg(not(unix))]
+    pub unsafe fn symbol<F: Copy>(&self, name: &str) -> Result<F, String> {
+        Err(format!("{} is missing from the library", name))
+    }
+}
@@ -0,0 +1,418 @@
+// Distributed search: a work-unit server and the clients that search its units
+//
+// The server splits the private key space into units of consecutive keys, starting at a
+// random key so the searched keys cannot be guessed, and hands them to clients over TCP.
+// Every message is JSON preceded by its length as a 4-byte big-endian integer. A client
+// claims a unit, scans it on all its threads while reporting its attempts, reports the result
+// and claims the next. The server checks a reported key itself before printing it; once it
+// has `count` matches it tells every client to stop and exits when they have all disconnected.
+
+use crate::error::VanityError;
+use crate::keygen::address_bytes_from_secret_key;
+use crate::keyrange::{self, KeyRange};
+use crate::pattern::{pattern_difficulty, to_checksum_address, CompiledPattern};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use secp256k1::{Secp256k1, SecretKey};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+// Keys per unit: seconds to minutes of work for one machine, so a lost client costs little
+pub const WORK_UNIT_KEYS: u64 = 1 << 24;
+
+// Longest message accepted; real ones are a few hundred bytes
+const MAX_MESSAGE_LEN: u32 = 64 * 1024;
+
+// Offsets a client thread takes from the shared cursor at a time
+const CLAIM_CHUNK: u64 = crate::KEYGEN_BATCH_SIZE as u64;
+
+// How often clients report their attempts and the server prints the combined progress
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct WorkUnit {
+    pub range_start: [u8; 32],
+    pub range_end: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WorkResult {
+    pub found: bool,
+    pub key: Option<[u8; 32]>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SearchPattern {
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    pub case_sensitive: bool,
+}
+
+impl SearchPattern {
+    fn compile(&self) -> Result<CompiledPattern, VanityError> {
+        CompiledPattern::compile(self.prefix.as_deref(), self.suffix.as_deref(), self.case_sensitive)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Claim,
+    // Keys scanned since the previous progress message
+    Progress { attempts: u64 },
+    Report(WorkResult),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Assignment { pattern: SearchPattern, unit: WorkUnit },
+    Stop,
+}
+
+fn send<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let json = serde_json::to_vec(message).expect("messages serialize");
+    stream.write_all(&(json.len() as u32).to_be_bytes())?;
+    stream.write_all(&json)
+}
+
+fn receive<T: DeserializeOwned>(stream: &mut TcpStream) -> io::Result<T> {
+    let mut length = [0u8; 4];
+    stream.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length);
+    if length > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("message of {} bytes is too long", length)));
+    }
+    let mut json = vec![0u8; length as usize];
+    stream.read_exact(&mut json)?;
+    serde_json::from_slice(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+struct Coordinator {
+    pattern: SearchPattern,
+    next_start: [u8; 32],
+    next_id: u64,
+    // Units of clients that went away before reporting, handed out again first
+    abandoned: Vec<(u64, WorkUnit)>,
+    units_searched: u64,
+    // Keys scanned by all clients, from their progress messages
+    attempts: u64,
+    count: u64,
+    matches: u64,
+    // Write halves of the connected clients, to tell them to stop; every write happens
+    // under the coordinator lock, so messages never interleave
+    clients: HashMap<SocketAddr, TcpStream>,
+}
+
+impl Coordinator {
+    fn next_unit(&mut self) -> (u64, WorkUnit) {
+        if let Some(abandoned) = self.abandoned.pop() {
+            return abandoned;
+        }
+        let range_start = self.next_start;
+        let mut range_end = keyrange::add_offset(&range_start, WORK_UNIT_KEYS - 1);
+        // Wrap around to the smallest key at the end of the key space
+        if range_end > keyrange::MAX_KEY || range_end < range_start {
+            range_end = keyrange::MAX_KEY;
+            self.next_start = keyrange::MIN_KEY;
+        } else {
+            self.next_start = keyrange::add_offset(&range_end, 1);
+        }
+        self.next_id += 1;
+        (self.next_id, WorkUnit { range_start, range_end })
+    }
+    
+    fn done(&self) -> bool {
+        self.matches >= self.count
+    }
+}
+
+fn random_start() -> [u8; 32] {
+    loop {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        if (keyrange::MIN_KEY..=keyrange::MAX_KEY).contains(&key) {
+            return key;
+        }
+    }
+}
+
+pub fn run_server(bind: SocketAddr, pattern: SearchPattern, count: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind(bind)?;
+    let difficulty = pattern_difficulty(pattern.prefix.as_deref(), pattern.suffix.as_deref(), pattern.case_sensitive);
+    println!("🌐 Work-unit server listening on {}", listener.local_addr()?);
+    println!("Units: {} keys each, about {} per match", crate::format_with_commas(WORK_UNIT_KEYS), crate::format_difficulty((difficulty / WORK_UNIT_KEYS as f64).max(1.0)));
+    if count > 1 {
+        println!("Target: {} matches", count);
+    }
+    println!("Clients join with: evm-vanity --connect <this host>:{}", listener.local_addr()?.port());
+    warning!("⚠️  The match travels to this server unencrypted; only use a trusted network.");
+    
+    let coordinator = Arc::new(Mutex::new(Coordinator {
+        pattern,
+        next_start: random_start(),
+        next_id: 0,
+        abandoned: Vec::new(),
+        units_searched: 0,
+        attempts: 0,
+        count,
+        matches: 0,
+        clients: HashMap::new(),
+    }));
+    
+    let progress = coordinator.clone();
+    std::thread::spawn(move || report_progress(&progress));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                error!("❌ Failed to accept a client: {}", e);
+                continue;
+            }
+        };
+        let coordinator = coordinator.clone();
+        std::thread::spawn(move || serve_client(stream, &coordinator));
+    }
+    Ok(())
+}
+
+// The attempts and rate of all clients together
+fn report_progress(coordinator: &Mutex<Coordinator>) {
+    let start_time = Instant::now();
+    let mut last_attempts = 0;
+    loop {
+        std::thread::sleep(PROGRESS_INTERVAL);
+        let coordinator = coordinator.lock().unwrap();
+        if coordinator.done() {
+            return;
+        }
+        let rate = (coordinator.attempts - last_attempts) as f64 / PROGRESS_INTERVAL.as_secs_f64();
+        last_attempts = coordinator.attempts;
+        progress!("⏳ Attempts: {} | Rate: {} | Clients: {} | Units: {} | Elapsed: {}",
+                  coordinator.attempts, crate::output::rate(rate), coordinator.clients.len(), coordinator.units_searched,
+                  crate::output::elapsed(start_time.elapsed()));
+    }
+}
+
+fn serve_client(mut stream: TcpStream, coordinator: &Mutex<Coordinator>) {
+    let Ok(peer) = stream.peer_addr() else { return };
+    let Ok(writer) = stream.try_clone() else { return };
+    coordinator.lock().unwrap().clients.insert(peer, writer);
+    println!("🔌 {} connected", peer);
+    
+    let mut assigned = None;
+    let error = loop {
+        let message = match receive::<ClientMessage>(&mut stream) {
+            Ok(message) => message,
+            Err(e) => break e,
+        };
+        let mut coordinator = coordinator.lock().unwrap();
+        match message {
+            ClientMessage::Claim if coordinator.done() => {
+                if let Err(e) = send(&mut stream, &ServerMessage::Stop) {
+                    break e;
+                }
+            }
+            ClientMessage::Claim => {
+                let (id, unit) = coordinator.next_unit();
+                assigned = Some((id, unit));
+                let assignment = ServerMessage::Assignment { pattern: coordinator.pattern.clone(), unit };
+                if let Err(e) = send(&mut stream, &assignment) {
+                    break e;
+                }
+                println!("📦 Unit #{} → {}", id, peer);
+            }
+            ClientMessage::Progress { attempts } => coordinator.attempts += attempts,
+            ClientMessage::Report(result) => {
+                let Some((id, unit)) = assigned.take() else {
+                    break io::Error::new(io::ErrorKind::InvalidData, "report without a claimed unit");
+                };
+                if coordinator.done() {
+                    continue;
+                }
+                coordinator.units_searched += 1;
+                match result.key.filter(|_| result.found) {
+                    Some(key) => match verified_match(&coordinator.pattern, &unit, &key) {
+                        Some(private_key) => finish(&mut coordinator, private_key, id, peer),
+                        None => warning!("⚠️  {} reported a key for unit #{} that does not match; ignoring it", peer, id),
+                    },
+                    None => println!("✔️  {} searched unit #{} ({} units done)", peer, id, coordinator.units_searched),
+                }
+            }
+        }
+    };
+    
+    let mut coordinator = coordinator.lock().unwrap();
+    coordinator.clients.remove(&peer);
+    if coordinator.done() {
+        println!("🔌 {} stopped", peer);
+        if coordinator.clients.is_empty() {
+            std::process::exit(0);
+        }
+    } else if let Some((id, unit)) = assigned {
+        coordinator.abandoned.push((id, unit));
+        warning!("⚠️  {} disconnected ({}); unit #{} will be handed out again", peer, error, id);
+    } else {
+        println!("🔌 {} disconnected", peer);
+    }
+}
+
+// The private key of a reported match, if it lies in the unit and its address matches
+fn verified_match(pattern: &SearchPattern, unit: &WorkUnit, key: &[u8; 32]) -> Option<SecretKey> {
+    if !(unit.range_start..=unit.range_end).contains(key) {
+        return None;
+    }
+    let private_key = SecretKey::from_slice(key).ok()?;
+    let address = address_bytes_from_secret_key(&Secp256k1::new(), &private_key);
+    pattern.compile().ok()?.matches(&address).then_some(private_key)
+}
+
+fn finish(coordinator: &mut Coordinator, private_key: SecretKey, id: u64, peer: SocketAddr) {
+    let wallet = crate::generate_wallet_info(private_key);
+    coordinator.matches += 1;
+    let of_count = if coordinator.count > 1 { format!(" {} of {}", coordinator.matches, coordinator.count) } else { String::new() };
+    success!("\n🎉 Found vanity address{} in unit #{} from {} ({} units, {} attempts searched)",
+             of_count, id, peer, coordinator.units_searched, crate::format_with_commas(coordinator.attempts));
+    println!("📍 Address: {}", wallet.address);
+    println!("🔠 Checksummed: {}", to_checksum_address(&wallet.address));
+    println!("🔐 Private Key: {}", wallet.private_key.as_str());
+    if !coordinator.done() {
+        return;
+    }
+    println!("🛑 Stopping {} clients", coordinator.clients.len());
+    for client in coordinator.clients.values_mut() {
+        let _ = send(client, &ServerMessage::Stop);
+    }
+}
+
+pub fn run_client(server_url: &str, threads: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stream = match TcpStream::connect(server_url) {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("❌ Failed to connect to {}: {}", server_url, e);
+            std::process::exit(1);
+        }
+    };
+    println!("🔌 Connected to {}, searching with {} threads", server_url, threads);
+    
+    // One reader, so a Stop arriving mid-unit is seen right away
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+    let mut reader = stream.try_clone()?;
+    let reader_stop = stop.clone();
+    std::thread::spawn(move || loop {
+        let message = receive::<ServerMessage>(&mut reader);
+        if matches!(message, Ok(ServerMessage::Stop) | Err(_)) {
+            reader_stop.store(true, Ordering::Relaxed);
+        }
+        if message.is_err() || sender.send(message).is_err() {
+            break;
+        }
+    });
+    
+    loop {
+        let message = send(&mut stream, &ClientMessage::Claim).and_then(|()| {
+            receiver.recv().unwrap_or_else(|_| Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed")))
+        });
+        let (pattern, unit) = match message {
+            Ok(ServerMessage::Assignment { pattern, unit }) => (pattern, unit),
+            Ok(ServerMessage::Stop) => break,
+            Err(e) => {
+                error!("❌ Lost the connection to the server: {}", e);
+                std::process::exit(1);
+            }
+        };
+        
+        println!("📦 Searching 0x{}..0x{}", hex::encode(unit.range_start), hex::encode(unit.range_end));
+        let start = Instant::now();
+        let Some(key) = search_unit(&pattern, &unit, threads, &stop, &mut stream) else {
+            // Stopped before the unit was done; it is not reported as searched
+            break;
+        };
+        let elapsed = start.elapsed().as_secs_f64();
+        match key {
+            Some(key) => {
+                let address = address_bytes_from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&key)?);
+                success!("🎉 Found {} after {:.1}s; reporting it to the server", crate::format_address(&address), elapsed);
+            }
+            None => println!("✔️  No match in {:.1}s ({} keys/sec)", elapsed, crate::format_with_commas((WORK_UNIT_KEYS as f64 / elapsed) as u64)),
+        }
+        if let Err(e) = send(&mut stream, &ClientMessage::Report(WorkResult { found: key.is_some(), key })) {
+            error!("❌ Lost the connection to the server: {}", e);
+            std::process::exit(1);
+        }
+    }
+    
+    println!("🏁 The server has its matches; stopping");
+    Ok(())
+}
+
+// Scans the unit on `threads` threads sharing one cursor, sending the keys scanned to the
+// server as it goes. Returns None when stopped before the unit was exhausted, otherwise the
+// matching key, if any.
+fn search_unit(pattern: &SearchPattern, unit: &WorkUnit, threads: usize, stop: &AtomicBool, stream: &mut TcpStream) -> Option<Option<[u8; 32]>> {
+    let Ok(range) = KeyRange::new(unit.range_start, unit.range_end) else {
+        warning!("⚠️  The server sent an invalid unit; skipping it");
+        return Some(None);
+    };
+    let pattern = match pattern.compile() {
+        Ok(pattern) => pattern,
+        Err(e) => {
+            warning!("⚠️  The server sent an invalid pattern; skipping the unit: {}", e);
+            return Some(None);
+        }
+    };
+    let total = range.sequential_len().expect("units are far below 2^32 keys");
+    let secp = Secp256k1::new();
+    let cursor = AtomicU64::new(0);
+    let found = OnceLock::new();
+    
+    let mut reported = 0;
+    let mut report = |stream: &mut TcpStream| {
+        let scanned = cursor.load(Ordering::Relaxed).min(total);
+        // A lost connection shows up when the unit is reported
+        let _ = send(stream, &ClientMessage::Progress { attempts: scanned - reported });
+        reported = scanned;
+    };
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads).map(|_| {
+            scope.spawn(|| {
+                while !stop.load(Ordering::Relaxed) && found.get().is_none() {
+                    let first = cursor.fetch_add(CLAIM_CHUNK, Ordering::Relaxed);
+                    if first >= total {
+                        break;
+                    }
+                    for offset in first..(first + CLAIM_CHUNK).min(total) {
+                        let mut private_key = range.key_at(offset);
+                        if pattern.matches(&address_bytes_from_secret_key(&secp, &private_key)) {
+                            let _ = found.set(private_key.secret_bytes());
+                        }
+                        private_key.non_secure_erase();
+                    }
+                }
+            })
+        }).collect();
+        
+        let mut next_report = Instant::now() + PROGRESS_INTERVAL;
+        while !workers.iter().all(|worker| worker.is_finished()) {
+            std::thread::sleep(Duration::from_millis(100));
+            if Instant::now() >= next_report {
+                report(stream);
+                next_report += PROGRESS_INTERVAL;
+            }
+        }
+    });
+    report(stream);
+    
+    match found.into_inner() {
+        Some(key) => Some(Some(key)),
+        None if stop.load(Ordering::Relaxed) => None,
+        None => Some(None),
+    }
+}
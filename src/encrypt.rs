@@ -0,0 +1,84 @@
+// --encrypt-to: results files encrypted to age recipients, so found keys never reach
+// the disk in plain text. The JSON is encrypted in memory and written once.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// An X25519 recipient (`age1...`) or an SSH public key (`ssh-ed25519 ...`, `ssh-rsa ...`)
+fn parse_recipient(value: &str) -> Result<Box<dyn age::Recipient>, String> {
+    if let Ok(recipient) = value.parse::<age::x25519::Recipient>() {
+        return Ok(Box::new(recipient));
+    }
+    match value.parse::<age::ssh::Recipient>() {
+        Ok(recipient) => Ok(Box::new(recipient)),
+        Err(_) => Err(format!("'{}' is not an age recipient (age1...) or a supported SSH public key", value)),
+    }
+}
+
+// clap value parser: keeps the text once it parses as a recipient
+pub fn check_recipient(value: &str) -> Result<String, String> {
+    parse_recipient(value).map(|_| value.to_string())
+}
+
+// Encrypts `plaintext` so that any one of the recipients can decrypt it
+pub fn encrypt(plaintext: &[u8], recipients: &[String]) -> Result<Vec<u8>, String> {
+    let recipients = recipients.iter().map(|value| parse_recipient(value)).collect::<Result<Vec<_>, _>>()?;
+    let encryptor = age::Encryptor::with_recipients(recipients.iter().map(|recipient| recipient.as_ref()))
+        .map_err(|e| format!("Failed to encrypt the results: {}", e))?;
+    
+    let mut ciphertext = Vec::new();
+    let mut writer = encryptor.wrap_output(&mut ciphertext).map_err(|e| format!("Failed to encrypt the results: {}", e))?;
+    writer
+        .write_all(plaintext)
+        .and_then(|()| writer.finish())
+        .map_err(|e| format!("Failed to encrypt the results: {}", e))?;
+    Ok(ciphertext)
+}
+
+// The path with `.age` appended, unless it already ends in `.age`
+pub fn age_path(path: &Path) -> PathBuf {
+    if path.extension().is_some_and(|extension| extension == "age") {
+        return path.to_path_buf();
+    }
+    let mut path = path.as_os_str().to_owned();
+    path.push(".age");
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+    
+    const RESULTS: &str = r#"[{"address":"0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"}]"#;
+    
+    #[test]
+    fn every_recipient_can_decrypt() {
+        let identities = [age::x25519::Identity::generate(), age::x25519::Identity::generate()];
+        let recipients: Vec<String> = identities.iter().map(|identity| identity.to_public().to_string()).collect();
+        let ciphertext = encrypt(RESULTS.as_bytes(), &recipients).unwrap();
+        assert!(!ciphertext.windows(RESULTS.len()).any(|window| window == RESULTS.as_bytes()));
+        
+        for identity in &identities {
+            // Round-trips through the identity file format, as `age -d -i` reads it
+            let identity: age::x25519::Identity = identity.to_string().expose_secret().parse().unwrap();
+            assert_eq!(age::decrypt(&identity, &ciphertext).unwrap(), RESULTS.as_bytes());
+        }
+        assert!(age::decrypt(&age::x25519::Identity::generate(), &ciphertext).is_err());
+    }
+    
+    #[test]
+    fn recipients_are_checked() {
+        let recipient = age::x25519::Identity::generate().to_public().to_string();
+        assert_eq!(check_recipient(&recipient).unwrap(), recipient);
+        assert!(check_recipient("age1notarecipient").is_err());
+        assert!(encrypt(RESULTS.as_bytes(), &["ssh-ed25519 AAAA".to_string()]).unwrap_err().contains("is not an age recipient"));
+    }
+    
+    #[test]
+    fn age_path_appends_the_extension_once() {
+        assert_eq!(age_path(Path::new("found.json")), Path::new("found.json.age"));
+        assert_eq!(age_path(Path::new("found.age")), Path::new("found.age"));
+        assert_eq!(age_path(Path::new("results")), Path::new("results.age"));
+    }
+}
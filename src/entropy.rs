@@ -0,0 +1,136 @@
+// --extra-entropy and --entropy-prompt: user entropy mixed into the seed of every worker's
+// RNG. It only ever adds to the OS random generator, never replaces it: each seed is
+// keccak256(OsRng bytes || user entropy || counter), unpredictable while either is secret.
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use rand::rngs::OsRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha3::{Digest, Keccak256};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use zeroize::Zeroizing;
+
+const KEYSTROKES: usize = 100;
+
+// Numbers each seed, so no two are derived from the same inputs
+static SEEDS: AtomicU64 = AtomicU64::new(0);
+
+// Hex (with or without 0x), or the path of a file whose bytes are used
+pub fn read_extra_entropy(value: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+    let path = std::path::Path::new(value);
+    let bytes = if path.is_file() {
+        Zeroizing::new(std::fs::read(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?)
+    } else {
+        let digits = value.strip_prefix("0x").unwrap_or(value);
+        Zeroizing::new(hex::decode(digits).map_err(|_| format!("'{}' is neither a file nor hex", value))?)
+    };
+    if bytes.is_empty() {
+        return Err(format!("{} holds no entropy", value));
+    }
+    Ok(bytes)
+}
+
+// Reads KEYSTROKES key presses in raw mode, keeping each key and the nanoseconds since the
+// prompt; the timings carry most of the entropy
+pub fn prompt_keystrokes() -> Result<Zeroizing<Vec<u8>>, String> {
+    if !std::io::stdin().is_terminal() {
+        return Err("--entropy-prompt needs a terminal to read keystrokes from".to_string());
+    }
+    eprintln!("⌨️  Mash the keyboard: {} random keys (Ctrl+C to abort)", KEYSTROKES);
+    let mut collected = Zeroizing::new(Vec::new());
+    let start = Instant::now();
+    crossterm::terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    let mut keystrokes = 0;
+    let result = loop {
+        if keystrokes == KEYSTROKES {
+            break Ok(());
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    break Err("entropy prompt aborted".to_string());
+                }
+                collected.extend_from_slice(format!("{:?}", key.code).as_bytes());
+                collected.extend_from_slice(&start.elapsed().as_nanos().to_le_bytes());
+                keystrokes += 1;
+                eprint!("\r   {}/{}", keystrokes, KEYSTROKES);
+            }
+            Ok(_) => {}
+            Err(e) => break Err(e.to_string()),
+        }
+    };
+    let _ = crossterm::terminal::disable_raw_mode();
+    eprintln!();
+    result.map(|()| collected)
+}
+
+// The seed for `os_bytes`, `user_entropy` and a counter
+pub fn mix(os_bytes: &[u8; 32], user_entropy: &[u8], counter: u64) -> Zeroizing<[u8; 32]> {
+    let mut hasher = Keccak256::new();
+    hasher.update(os_bytes);
+    hasher.update(user_entropy);
+    hasher.update(counter.to_le_bytes());
+    Zeroizing::new(hasher.finalize().into())
+}
+
+// A worker RNG seeded from fresh OS randomness mixed with `user_entropy`
+pub fn seeded_rng(user_entropy: &[u8]) -> ChaCha20Rng {
+    mixed_rng(&mut OsRng, user_entropy, SEEDS.fetch_add(1, Ordering::Relaxed))
+}
+
+fn mixed_rng<R: RngCore>(os_rng: &mut R, user_entropy: &[u8], counter: u64) -> ChaCha20Rng {
+    let mut os_bytes = Zeroizing::new([0u8; 32]);
+    os_rng.fill_bytes(os_bytes.as_mut());
+    ChaCha20Rng::from_seed(*mix(&os_bytes, user_entropy, counter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+
+    // The first private keys a worker RNG would try
+    fn keys(mut rng: ChaCha20Rng) -> Vec<[u8; 32]> {
+        (0..4)
+            .map(|_| {
+                let mut key = [0u8; 32];
+                rng.fill_bytes(&mut key);
+                key
+            })
+            .collect()
+    }
+
+    #[test]
+    fn user_entropy_changes_the_keys_of_an_identical_os_rng() {
+        let stream = |user_entropy: &[u8], counter| keys(mixed_rng(&mut StepRng::new(7, 0), user_entropy, counter));
+        assert_eq!(stream(b"secret", 0), stream(b"secret", 0));
+        assert_ne!(stream(b"secret", 0), stream(b"secrets", 0));
+        assert_ne!(stream(b"secret", 0), stream(b"secret", 1));
+        for (a, b) in stream(b"secret", 0).iter().zip(stream(b"other secret", 0)) {
+            assert_ne!(*a, b);
+        }
+    }
+
+    #[test]
+    fn os_randomness_still_changes_the_keys() {
+        let stream = |os_rng: &mut StepRng| keys(mixed_rng(os_rng, b"secret", 0));
+        assert_ne!(stream(&mut StepRng::new(7, 0)), stream(&mut StepRng::new(8, 0)));
+        assert_ne!(keys(seeded_rng(b"secret")), keys(seeded_rng(b"secret")));
+    }
+
+    #[test]
+    fn extra_entropy_is_hex_or_a_file() {
+        assert_eq!(*read_extra_entropy("0xdeadbeef").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(*read_extra_entropy("00ff").unwrap(), [0, 0xff]);
+        assert_eq!(read_extra_entropy("not hex").unwrap_err(), "'not hex' is neither a file nor hex");
+        assert_eq!(read_extra_entropy("0x").unwrap_err(), "0x holds no entropy");
+
+        let path = std::env::temp_dir().join(format!("evm-vanity-entropy-{}", std::process::id()));
+        std::fs::write(&path, b"dice rolls: 3 1 4 1 5 9").unwrap();
+        let bytes = read_extra_entropy(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(*bytes.unwrap(), b"dice rolls: 3 1 4 1 5 9");
+    }
+}
@@ -0,0 +1,34 @@
+// Errors reported while setting up a search
+
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum VanityError {
+    Io { path: PathBuf, source: std::io::Error },
+    InvalidPattern { line: usize, message: String },
+    EmptyPatternSet { path: PathBuf },
+    BadPattern { message: String },
+    InvalidConfig { path: PathBuf, message: String },
+}
+
+impl fmt::Display for VanityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VanityError::Io { path, source } => write!(f, "Failed to read {}: {}", path.display(), source),
+            VanityError::InvalidPattern { line, message } => write!(f, "Line {}: {}", line, message),
+            VanityError::EmptyPatternSet { path } => write!(f, "{} contains no patterns", path.display()),
+            VanityError::BadPattern { message } => write!(f, "{}", message),
+            VanityError::InvalidConfig { path, message } => write!(f, "{}: {}", path.display(), message),
+        }
+    }
+}
+
+impl std::error::Error for VanityError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VanityError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,337 @@
+// The gpu feature: candidate addresses from an OpenCL 1.2 GPU. The driver's OpenCL library
+// is loaded when the search starts, so the feature needs no SDK to build.
+//
+// The kernel (kernels/vanity.cl) derives key i of a batch from block i of the ChaCha20
+// keystream of a seed drawn from the OS, multiplies it by the generator and hashes the public
+// key, all on the device. Only the addresses come back: the host recreates the key of a match
+// from the seed, and checks its address before handing it on.
+
+use crate::device::{Library, Searcher};
+use crate::keygen::address_bytes_from_secret_key;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use secp256k1::{All, Secp256k1, SecretKey};
+use std::ffi::{c_char, c_void, CString};
+use std::ptr;
+use zeroize::Zeroizing;
+
+// Built as one program, the header first
+const SOURCE: [&str; 2] = [include_str!("kernels/secp256k1.h"), include_str!("kernels/vanity.cl")];
+
+type Handle = *mut c_void;
+
+const CL_SUCCESS: i32 = 0;
+const CL_TRUE: u32 = 1;
+const CL_DEVICE_TYPE_GPU: u64 = 1 << 2;
+const CL_DEVICE_NAME: u32 = 0x102B;
+const CL_DEVICE_VERSION: u32 = 0x102F;
+const CL_PROGRAM_BUILD_LOG: u32 = 0x1183;
+const CL_MEM_WRITE_ONLY: u64 = 1 << 1;
+const CL_MEM_READ_ONLY: u64 = 1 << 2;
+const CL_MEM_COPY_HOST_PTR: u64 = 1 << 5;
+
+// The OpenCL 1.2 entry points the search uses
+struct OpenCl {
+    get_platform_ids: unsafe extern "C" fn(u32, *mut Handle, *mut u32) -> i32,
+    get_device_ids: unsafe extern "C" fn(Handle, u64, u32, *mut Handle, *mut u32) -> i32,
+    get_device_info: unsafe extern "C" fn(Handle, u32, usize, *mut c_void, *mut usize) -> i32,
+    create_context: unsafe extern "C" fn(*const isize, u32, *const Handle, *const c_void, *mut c_void, *mut i32) -> Handle,
+    create_command_queue: unsafe extern "C" fn(Handle, Handle, u64, *mut i32) -> Handle,
+    create_program_with_source: unsafe extern "C" fn(Handle, u32, *const *const c_char, *const usize, *mut i32) -> Handle,
+    build_program: unsafe extern "C" fn(Handle, u32, *const Handle, *const c_char, *const c_void, *mut c_void) -> i32,
+    get_program_build_info: unsafe extern "C" fn(Handle, Handle, u32, usize, *mut c_void, *mut usize) -> i32,
+    create_kernel: unsafe extern "C" fn(Handle, *const c_char, *mut i32) -> Handle,
+    create_buffer: unsafe extern "C" fn(Handle, u64, usize, *mut c_void, *mut i32) -> Handle,
+    set_kernel_arg: unsafe extern "C" fn(Handle, u32, usize, *const c_void) -> i32,
+    enqueue_nd_range_kernel: unsafe extern "C" fn(Handle, Handle, u32, *const usize, *const usize, *const usize, u32, *const Handle, *mut Handle) -> i32,
+    enqueue_read_buffer: unsafe extern "C" fn(Handle, Handle, u32, usize, usize, *mut c_void, u32, *const Handle, *mut Handle) -> i32,
+    release_mem_object: unsafe extern "C" fn(Handle) -> i32,
+    release_kernel: unsafe extern "C" fn(Handle) -> i32,
+    release_program: unsafe extern "C" fn(Handle) -> i32,
+    release_command_queue: unsafe extern "C" fn(Handle) -> i32,
+    release_context: unsafe extern "C" fn(Handle) -> i32,
+}
+
+impl OpenCl {
+    fn load() -> Result<OpenCl, String> {
+        let library = Library::open(&["libOpenCL.so.1", "libOpenCL.so", "/System/Library/Frameworks/OpenCL.framework/OpenCL"])?;
+        // The types are those of the declarations in CL/cl.h
+        unsafe {
+            Ok(OpenCl {
+                get_platform_ids: library.symbol("clGetPlatformIDs")?,
+                get_device_ids: library.symbol("clGetDeviceIDs")?,
+                get_device_info: library.symbol("clGetDeviceInfo")?,
+                create_context: library.symbol("clCreateContext")?,
+                create_command_queue: library.symbol("clCreateCommandQueue")?,
+                create_program_with_source: library.symbol("clCreateProgramWithSource")?,
+                build_program: library.symbol("clBuildProgram")?,
+                get_program_build_info: library.symbol("clGetProgramBuildInfo")?,
+                create_kernel: library.symbol("clCreateKernel")?,
+                create_buffer: library.symbol("clCreateBuffer")?,
+                set_kernel_arg: library.symbol("clSetKernelArg")?,
+                enqueue_nd_range_kernel: library.symbol("clEnqueueNDRangeKernel")?,
+                enqueue_read_buffer: library.symbol("clEnqueueReadBuffer")?,
+                release_mem_object: library.symbol("clReleaseMemObject")?,
+                release_kernel: library.symbol("clReleaseKernel")?,
+                release_program: library.symbol("clReleaseProgram")?,
+                release_command_queue: library.symbol("clReleaseCommandQueue")?,
+                release_context: library.symbol("clReleaseContext")?,
+            })
+        }
+    }
+
+    // The GPUs of every platform that support OpenCL 1.2, in platform order; --gpu-device
+    // indexes this list
+    fn devices(&self) -> Result<Vec<Handle>, String> {
+        let mut count = 0;
+        if unsafe { (self.get_platform_ids)(0, ptr::null_mut(), &mut count) } != CL_SUCCESS || count == 0 {
+            return Err("no OpenCL platform is installed".to_string());
+        }
+        let mut platforms = vec![ptr::null_mut(); count as usize];
+        check(unsafe { (self.get_platform_ids)(count, platforms.as_mut_ptr(), ptr::null_mut()) }, "clGetPlatformIDs")?;
+
+        let mut devices = Vec::new();
+        for platform in platforms {
+            // A platform without GPUs answers CL_DEVICE_NOT_FOUND
+            let mut count = 0;
+            if unsafe { (self.get_device_ids)(platform, CL_DEVICE_TYPE_GPU, 0, ptr::null_mut(), &mut count) } != CL_SUCCESS {
+                continue;
+            }
+            let mut platform_devices = vec![ptr::null_mut(); count as usize];
+            check(unsafe { (self.get_device_ids)(platform, CL_DEVICE_TYPE_GPU, count, platform_devices.as_mut_ptr(), ptr::null_mut()) }, "clGetDeviceIDs")?;
+            devices.extend(platform_devices.into_iter().filter(|&device| {
+                self.device_info(device, CL_DEVICE_VERSION).is_ok_and(|version| supports_opencl_1_2(&version))
+            }));
+        }
+        Ok(devices)
+    }
+
+    fn device_info(&self, device: Handle, param: u32) -> Result<String, String> {
+        let mut size = 0;
+        check(unsafe { (self.get_device_info)(device, param, 0, ptr::null_mut(), &mut size) }, "clGetDeviceInfo")?;
+        let mut value = vec![0u8; size];
+        check(unsafe { (self.get_device_info)(device, param, size, value.as_mut_ptr().cast(), ptr::null_mut()) }, "clGetDeviceInfo")?;
+        Ok(String::from_utf8_lossy(&value).trim_end_matches('\0').trim().to_string())
+    }
+}
+
+fn check(status: i32, call: &str) -> Result<(), String> {
+    if status == CL_SUCCESS {
+        Ok(())
+    } else {
+        Err(format!("{} failed with error {}", call, status))
+    }
+}
+
+// CL_DEVICE_VERSION reads "OpenCL <major>.<minor> <vendor information>"
+fn supports_opencl_1_2(version: &str) -> bool {
+    let number = version.strip_prefix("OpenCL ").and_then(|rest| rest.split_whitespace().next());
+    let Some((major, minor)) = number.and_then(|number| number.split_once('.')) else {
+        return false;
+    };
+    matches!((major.parse::<u32>(), minor.parse::<u32>()), (Ok(major), Ok(minor)) if (major, minor) >= (1, 2))
+}
+
+// The key of candidate `block`: that block of the keystream of `seed`, read big-endian as the
+// kernel does. None for the odd block (about 1 in 2^128) that is no valid key.
+fn recreate_key(seed: &[u8; 32], block: u64) -> Option<SecretKey> {
+    let mut rng = ChaCha20Rng::from_seed(*seed);
+    rng.set_word_pos(block as u128 * 16);
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    rng.fill_bytes(bytes.as_mut());
+    SecretKey::from_slice(bytes.as_ref()).ok()
+}
+
+pub struct GpuSearcher {
+    cl: OpenCl,
+    name: String,
+    device: Handle,
+    context: Handle,
+    queue: Handle,
+    program: Handle,
+    kernel: Handle,
+    seed_buffer: Handle,
+    addresses_buffer: Handle,
+    // Every key of the search follows from the seed, so it is as secret as they are
+    seed: Zeroizing<[u8; 32]>,
+    // The keystream block of the next batch's first candidate
+    next_block: u64,
+    batch_size: usize,
+    addresses: Vec<u8>,
+    secp: Secp256k1<All>,
+}
+
+// The handles are only used by the thread holding the searcher, and OpenCL objects may move
+// between threads
+unsafe impl Send for GpuSearcher {}
+
+impl GpuSearcher {
+    // GPU `index` of those that support OpenCL 1.2, with the kernel built for batches of
+    // `batch_size` candidates
+    pub fn new(index: u32, batch_size: u32) -> Result<GpuSearcher, String> {
+        let cl = OpenCl::load()?;
+        let devices = cl.devices()?;
+        let Some(&device) = devices.get(index as usize) else {
+            return Err(match devices.len() {
+                0 => "no GPU supports OpenCL 1.2".to_string(),
+                1 => format!("there is no GPU {}, only GPU 0", index),
+                count => format!("there is no GPU {}, only GPUs 0 to {}", index, count - 1),
+            });
+        };
+        let name = cl.device_info(device, CL_DEVICE_NAME)?;
+        let mut seed = Zeroizing::new([0u8; 32]);
+        rand::rngs::OsRng.fill_bytes(seed.as_mut());
+
+        // Created empty, so the handles made before a failure are released by Drop
+        let mut searcher = GpuSearcher {
+            cl,
+            name,
+            device,
+            context: ptr::null_mut(),
+            queue: ptr::null_mut(),
+            program: ptr::null_mut(),
+            kernel: ptr::null_mut(),
+            seed_buffer: ptr::null_mut(),
+            addresses_buffer: ptr::null_mut(),
+            seed,
+            next_block: 0,
+            batch_size: batch_size as usize,
+            addresses: vec![0; batch_size as usize * 20],
+            secp: Secp256k1::new(),
+        };
+        searcher.build()?;
+        Ok(searcher)
+    }
+
+    fn build(&mut self) -> Result<(), String> {
+        let cl = &self.cl;
+        let mut status = CL_SUCCESS;
+        unsafe {
+            self.context = (cl.create_context)(ptr::null(), 1, &self.device, ptr::null(), ptr::null_mut(), &mut status);
+            check(status, "clCreateContext")?;
+            self.queue = (cl.create_command_queue)(self.context, self.device, 0, &mut status);
+            check(status, "clCreateCommandQueue")?;
+
+            let sources = SOURCE.map(|source| source.as_ptr().cast::<c_char>());
+            let lengths = SOURCE.map(str::len);
+            self.program = (cl.create_program_with_source)(self.context, SOURCE.len() as u32, sources.as_ptr(), lengths.as_ptr(), &mut status);
+            check(status, "clCreateProgramWithSource")?;
+            let options = CString::new("-cl-std=CL1.2").expect("no NUL");
+            if (cl.build_program)(self.program, 1, &self.device, options.as_ptr(), ptr::null(), ptr::null_mut()) != CL_SUCCESS {
+                return Err(format!("the kernel failed to build:\n{}", self.build_log()));
+            }
+            let kernel_name = CString::new("vanity_addresses").expect("no NUL");
+            self.kernel = (cl.create_kernel)(self.program, kernel_name.as_ptr(), &mut status);
+            check(status, "clCreateKernel")?;
+
+            // The seed is written once; each batch only moves the block counter
+            self.seed_buffer = (cl.create_buffer)(self.context, CL_MEM_READ_ONLY | CL_MEM_COPY_HOST_PTR, self.seed.len(), self.seed.as_mut_ptr().cast(), &mut status);
+            check(status, "clCreateBuffer")?;
+            self.addresses_buffer = (cl.create_buffer)(self.context, CL_MEM_WRITE_ONLY, self.addresses.len(), ptr::null_mut(), &mut status);
+            check(status, "clCreateBuffer")?;
+            let handle_size = std::mem::size_of::<Handle>();
+            check((cl.set_kernel_arg)(self.kernel, 0, handle_size, (&self.seed_buffer as *const Handle).cast()), "clSetKernelArg")?;
+            check((cl.set_kernel_arg)(self.kernel, 2, handle_size, (&self.addresses_buffer as *const Handle).cast()), "clSetKernelArg")?;
+        }
+        Ok(())
+    }
+
+    fn build_log(&self) -> String {
+        let mut size = 0;
+        let status = unsafe { (self.cl.get_program_build_info)(self.program, self.device, CL_PROGRAM_BUILD_LOG, 0, ptr::null_mut(), &mut size) };
+        if status != CL_SUCCESS {
+            return "no build log".to_string();
+        }
+        let mut log = vec![0u8; size];
+        unsafe { (self.cl.get_program_build_info)(self.program, self.device, CL_PROGRAM_BUILD_LOG, size, log.as_mut_ptr().cast(), ptr::null_mut()) };
+        String::from_utf8_lossy(&log).trim_end_matches('\0').trim().to_string()
+    }
+}
+
+impl Searcher for GpuSearcher {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn search_batch(&mut self, find: &mut dyn FnMut(&[u8; 20]) -> bool) -> Result<(u64, Vec<SecretKey>), String> {
+        let first_block = self.next_block;
+        self.next_block += self.batch_size as u64;
+        let cl = &self.cl;
+        unsafe {
+            check((cl.set_kernel_arg)(self.kernel, 1, std::mem::size_of::<u64>(), (&first_block as *const u64).cast()), "clSetKernelArg")?;
+            check((cl.enqueue_nd_range_kernel)(self.queue, self.kernel, 1, ptr::null(), &self.batch_size, ptr::null(), 0, ptr::null(), ptr::null_mut()), "clEnqueueNDRangeKernel")?;
+            check((cl.enqueue_read_buffer)(self.queue, self.addresses_buffer, CL_TRUE, 0, self.addresses.len(), self.addresses.as_mut_ptr().cast(), 0, ptr::null(), ptr::null_mut()), "clEnqueueReadBuffer")?;
+        }
+
+        let mut keys = Vec::new();
+        for (item, address) in self.addresses.chunks_exact(20).enumerate() {
+            let address: &[u8; 20] = address.try_into().expect("chunks of 20 bytes");
+            if !find(address) {
+                continue;
+            }
+            let Some(mut key) = recreate_key(&self.seed, first_block + item as u64) else {
+                continue;
+            };
+            // A driver that miscompiles the kernel must not hand out a key for an address it is not
+            if address_bytes_from_secret_key(&self.secp, &key) != *address {
+                key.non_secure_erase();
+                return Err("the device computed a wrong address".to_string());
+            }
+            keys.push(key);
+        }
+        Ok((self.batch_size as u64, keys))
+    }
+}
+
+impl Drop for GpuSearcher {
+    fn drop(&mut self) {
+        let cl = &self.cl;
+        unsafe {
+            for buffer in [self.addresses_buffer, self.seed_buffer] {
+                if !buffer.is_null() {
+                    (cl.release_mem_object)(buffer);
+                }
+            }
+            if !self.kernel.is_null() {
+                (cl.release_kernel)(self.kernel);
+            }
+            if !self.program.is_null() {
+                (cl.release_program)(self.program);
+            }
+            if !self.queue.is_null() {
+                (cl.release_command_queue)(self.queue);
+            }
+            if !self.context.is_null() {
+                (cl.release_context)(self.context);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opencl_versions_from_1_2_are_supported() {
+        assert!(supports_opencl_1_2("OpenCL 1.2 CUDA"));
+        assert!(supports_opencl_1_2("OpenCL 3.0 "));
+        assert!(!supports_opencl_1_2("OpenCL 1.1 Mesa"));
+        assert!(!supports_opencl_1_2("1.2"));
+    }
+
+    // The seed words 0x01020304 * (i + 1), whose candidates were computed by the kernel
+    // header compiled as C
+    #[test]
+    fn recreated_keys_are_those_of_the_kernel() {
+        let mut seed = [0u8; 32];
+        for (i, word) in seed.chunks_exact_mut(4).enumerate() {
+            word.copy_from_slice(&0x01020304u32.wrapping_mul(i as u32 + 1).to_le_bytes());
+        }
+        let secp = Secp256k1::new();
+        for (block, address) in [(0, "0c766313245b8b612359b7b7019ab60b0ef75da4"), (2, "5674296354cd92e96a34f0eb7c1c3504a380dc4e")] {
+            let key = recreate_key(&seed, block).unwrap();
+            assert_eq!(hex::encode(address_bytes_from_secret_key(&secp, &key)), address);
+        }
+    }
+}
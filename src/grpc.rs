@@ -0,0 +1,129 @@
+// `grpc`: the search as a gRPC service (proto/vanity.proto)
+//
+// SearchVanity answers with the first match; SearchStream sends each of `count` matches as it
+// is found. A search stops when the client goes away. Results hold private keys, so the server
+// binds to localhost unless told otherwise.
+
+use crate::pattern::to_checksum_address;
+use crate::server::{check_search, search, Pattern};
+use proto::vanity_service_server::{VanityService, VanityServiceServer};
+use proto::{SearchRequest, SearchResponse, SearchResult};
+use secp256k1::SecretKey;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_util::sync::CancellationToken;
+use tonic::{Request, Response, Status};
+
+pub mod proto {
+    tonic::include_proto!("vanity");
+}
+
+// Matches buffered for a slow stream reader before the workers wait
+const STREAM_BUFFER: usize = 16;
+
+pub async fn serve(address: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🌐 Serving the gRPC search service on {}", address);
+    tonic::transport::Server::builder()
+        .add_service(VanityServiceServer::new(Vanity))
+        .serve(address)
+        .await?;
+    Ok(())
+}
+
+struct Vanity;
+
+// Empty strings stand for unset fields in proto3
+fn pattern(request: &SearchRequest) -> Pattern<'_> {
+    Pattern {
+        prefix: Some(request.prefix.as_str()).filter(|prefix| !prefix.is_empty()),
+        suffix: Some(request.suffix.as_str()).filter(|suffix| !suffix.is_empty()),
+        case_sensitive: request.case_sensitive,
+    }
+}
+
+fn checked_threads(request: &SearchRequest) -> Result<usize, String> {
+    let threads = Some(request.threads as usize).filter(|&threads| threads > 0);
+    check_search(&pattern(request), threads)
+}
+
+fn search_result(private_key: SecretKey, attempts: u64, start: Instant) -> SearchResult {
+    let wallet = crate::generate_wallet_info(private_key);
+    SearchResult {
+        checksum_address: to_checksum_address(&wallet.address),
+        private_key: wallet.private_key.to_string(),
+        mnemonic: wallet.mnemonic.as_ref().map(|mnemonic| mnemonic.to_string()).unwrap_or_default(),
+        address: wallet.address,
+        attempts,
+        elapsed_secs: start.elapsed().as_secs_f64(),
+    }
+}
+
+#[tonic::async_trait]
+impl VanityService for Vanity {
+    type SearchStreamStream = ReceiverStream<Result<SearchResult, Status>>;
+    
+    async fn search_vanity(&self, request: Request<SearchRequest>) -> Result<Response<SearchResponse>, Status> {
+        let request = request.into_inner();
+        let threads = checked_threads(&request).map_err(Status::invalid_argument)?;
+        let cancel = CancellationToken::new();
+        // A dropped call (the client went away) cancels the search
+        let _cancel_on_drop = cancel.clone().drop_guard();
+        
+        let result = tokio::task::spawn_blocking(move || {
+            let start = Instant::now();
+            let attempts = AtomicU64::new(0);
+            let found = std::sync::OnceLock::new();
+            search(&pattern(&request), threads, &cancel, &attempts, |private_key| {
+                let _ = found.set(search_result(private_key, attempts.load(Ordering::Relaxed), start));
+                false
+            });
+            found.into_inner()
+        })
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+        
+        match result {
+            Some(result) => Ok(Response::new(SearchResponse { result: Some(result) })),
+            None => Err(Status::cancelled("search cancelled")),
+        }
+    }
+    
+    async fn search_stream(&self, request: Request<SearchRequest>) -> Result<Response<Self::SearchStreamStream>, Status> {
+        let request = request.into_inner();
+        let threads = checked_threads(&request).map_err(Status::invalid_argument)?;
+        let count = u64::from(request.count.max(1));
+        let cancel = CancellationToken::new();
+        let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+        
+        // Stop searching once the client drops the stream; the watcher's sender must go
+        // when the search ends, or the stream would never close
+        let (watcher, search_cancel) = (sender.clone(), cancel.clone());
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = watcher.closed() => search_cancel.cancel(),
+                _ = search_cancel.cancelled() => {}
+            }
+        });
+        
+        tokio::task::spawn_blocking(move || {
+            let start = Instant::now();
+            let attempts = AtomicU64::new(0);
+            let sent = AtomicU64::new(0);
+            search(&pattern(&request), threads, &cancel, &attempts, |private_key| {
+                // Workers can match at the same time; only the first `count` are sent
+                let index = sent.fetch_add(1, Ordering::Relaxed);
+                if index >= count {
+                    return false;
+                }
+                let result = search_result(private_key, attempts.load(Ordering::Relaxed), start);
+                sender.blocking_send(Ok(result)).is_ok() && index + 1 < count
+            });
+            cancel.cancel();
+        });
+        
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+}
@@ -0,0 +1,345 @@
+// Minimal BIP32 hierarchical deterministic key derivation (private keys only),
+// plus the xprv/xpub serialization hardware wallets import
+
+use crate::keygen::address_bytes_from_secret_key;
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use secp256k1::hashes::{hash160, Hash};
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use sha2::Sha512;
+use std::fmt;
+use std::str::FromStr;
+use zeroize::{Zeroize, Zeroizing};
+
+type HmacSha512 = Hmac<Sha512>;
+
+pub const HARDENED: u32 = 0x8000_0000;
+
+// Mainnet version bytes of serialized extended keys
+const XPRV_VERSION: [u8; 4] = [0x04, 0x88, 0xad, 0xe4];
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+
+    pub fn child(&self, index: u32) -> DerivationPath {
+        let mut indices = self.0.clone();
+        indices.push(index);
+        DerivationPath(indices)
+    }
+
+    // The parent path and the last index, the inverse of `child`
+    pub fn split_last(&self) -> Option<(DerivationPath, u32)> {
+        let (&index, parents) = self.0.split_last()?;
+        Some((DerivationPath(parents.to_vec()), index))
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = String;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let mut parts = path.trim().split('/');
+        if parts.next() != Some("m") {
+            return Err(format!("Derivation path '{}' must start with 'm'", path));
+        }
+
+        let mut indices = Vec::new();
+        for part in parts {
+            let (number, hardened) = match part.strip_suffix('\'').or_else(|| part.strip_suffix('h')) {
+                Some(number) => (number, true),
+                None => (part, false),
+            };
+            let index: u32 = number
+                .parse()
+                .map_err(|_| format!("Invalid derivation path component '{}' in '{}'", part, path))?;
+            if index >= HARDENED {
+                return Err(format!("Derivation index {} is out of range in '{}'", index, path));
+            }
+            indices.push(if hardened { index | HARDENED } else { index });
+        }
+
+        Ok(DerivationPath(indices))
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for &index in &self.0 {
+            if index & HARDENED != 0 {
+                write!(f, "/{}'", index & !HARDENED)?;
+            } else {
+                write!(f, "/{}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct ExtendedPrivKey {
+    pub chain_code: [u8; 32],
+    pub secret_key: SecretKey,
+}
+
+impl Drop for ExtendedPrivKey {
+    fn drop(&mut self) {
+        self.chain_code.zeroize();
+        self.secret_key.non_secure_erase();
+    }
+}
+
+impl ExtendedPrivKey {
+    pub fn from_seed(seed: &[u8]) -> ExtendedPrivKey {
+        let mut mac = HmacSha512::new_from_slice(b"Bitcoin seed").expect("HMAC accepts any key length");
+        mac.update(seed);
+        let mut output = mac.finalize().into_bytes();
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&output[32..]);
+        let secret_key = SecretKey::from_slice(&output[..32]);
+        output.zeroize();
+
+        ExtendedPrivKey {
+            chain_code,
+            // An invalid master key has probability below 2^-127
+            secret_key: secret_key.expect("valid master key"),
+        }
+    }
+
+    pub fn derive_child<C: secp256k1::Signing>(&self, secp: &Secp256k1<C>, index: u32) -> ExtendedPrivKey {
+        let mut mac = HmacSha512::new_from_slice(&self.chain_code).expect("HMAC accepts any key length");
+        if index & HARDENED != 0 {
+            mac.update(&[0u8]);
+            mac.update(&self.secret_key.secret_bytes());
+        } else {
+            mac.update(&PublicKey::from_secret_key(secp, &self.secret_key).serialize());
+        }
+        mac.update(&index.to_be_bytes());
+        let mut output = mac.finalize().into_bytes();
+
+        let mut tweak_bytes = [0u8; 32];
+        tweak_bytes.copy_from_slice(&output[..32]);
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&output[32..]);
+        output.zeroize();
+
+        // BIP32 says to skip to the next index when IL >= n or the child key is zero;
+        // both happen with probability below 2^-127 so we treat them as unreachable
+        let tweak = Scalar::from_be_bytes(tweak_bytes).expect("IL below curve order");
+        tweak_bytes.zeroize();
+        let secret_key = self.secret_key.add_tweak(&tweak).expect("non-zero child key");
+
+        ExtendedPrivKey {
+            chain_code,
+            secret_key,
+        }
+    }
+
+    pub fn derive_path<C: secp256k1::Signing>(&self, secp: &Secp256k1<C>, path: &DerivationPath) -> ExtendedPrivKey {
+        path.indices()
+            .iter()
+            .fold(self.clone(), |key, &index| key.derive_child(secp, index))
+    }
+}
+
+// The first 4 bytes of HASH160 of the compressed public key, which identify a parent key
+pub fn fingerprint(public_key: &PublicKey) -> [u8; 4] {
+    let hash = hash160::Hash::hash(&public_key.serialize()).to_byte_array();
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+// BIP32 serialization: 78 bytes of version, depth, parent fingerprint, child index,
+// chain code and key data, base58check encoded
+fn serialize(version: [u8; 4], depth: u8, parent_fingerprint: [u8; 4], child_index: u32, chain_code: [u8; 32], key_data: &[u8; 33]) -> String {
+    let mut data = Vec::with_capacity(78);
+    data.extend_from_slice(&version);
+    data.push(depth);
+    data.extend_from_slice(&parent_fingerprint);
+    data.extend_from_slice(&child_index.to_be_bytes());
+    data.extend_from_slice(&chain_code);
+    data.extend_from_slice(key_data);
+    let encoded = bs58::encode(&data).with_check().into_string();
+    data.zeroize();
+    encoded
+}
+
+// The private key is serialized with a 0x00 byte in front
+pub fn serialize_xprv(depth: u8, parent_fingerprint: [u8; 4], child_index: u32, chain_code: [u8; 32], key: [u8; 32]) -> String {
+    let mut key_data = [0u8; 33];
+    key_data[1..].copy_from_slice(&key);
+    let encoded = serialize(XPRV_VERSION, depth, parent_fingerprint, child_index, chain_code, &key_data);
+    key_data.zeroize();
+    encoded
+}
+
+// `key` is the compressed public key
+pub fn serialize_xpub(depth: u8, parent_fingerprint: [u8; 4], child_index: u32, chain_code: [u8; 32], key: [u8; 33]) -> String {
+    serialize(XPUB_VERSION, depth, parent_fingerprint, child_index, chain_code, &key)
+}
+
+// The root xprv, and the xprv and xpub of the key at the end of a derivation path
+pub struct ExtendedKeys {
+    pub root_xprv: String,
+    pub xprv: String,
+    pub xpub: String,
+}
+
+impl Drop for ExtendedKeys {
+    fn drop(&mut self) {
+        self.root_xprv.zeroize();
+        self.xprv.zeroize();
+    }
+}
+
+pub fn extended_keys<C: secp256k1::Signing>(secp: &Secp256k1<C>, master: &ExtendedPrivKey, path: &DerivationPath) -> ExtendedKeys {
+    let root_xprv = serialize_xprv(0, [0; 4], 0, master.chain_code, master.secret_key.secret_bytes());
+
+    // The parent is the key one level up; the root has none
+    let (key, parent_fingerprint) = match path.indices().split_last() {
+        Some((&index, parents)) => {
+            let parent = master.derive_path(secp, &DerivationPath(parents.to_vec()));
+            (parent.derive_child(secp, index), fingerprint(&PublicKey::from_secret_key(secp, &parent.secret_key)))
+        }
+        None => (master.clone(), [0; 4]),
+    };
+    let depth = path.indices().len() as u8;
+    let child_index = path.indices().last().copied().unwrap_or(0);
+    let public_key = PublicKey::from_secret_key(secp, &key.secret_key);
+
+    ExtendedKeys {
+        root_xprv,
+        xprv: serialize_xprv(depth, parent_fingerprint, child_index, key.chain_code, key.secret_key.secret_bytes()),
+        xpub: serialize_xpub(depth, parent_fingerprint, child_index, key.chain_code, public_key.serialize()),
+    }
+}
+
+// Addresses at the `indices` of the chain `path_prefix` below `seed`, stopping short of the
+// hardened indices
+pub fn derive_addresses(seed: &[u8], path_prefix: &DerivationPath, indices: std::ops::Range<u32>) -> Vec<String> {
+    let secp = Secp256k1::new();
+    let chain = ExtendedPrivKey::from_seed(seed).derive_path(&secp, path_prefix);
+
+    (indices.start..indices.end.min(HARDENED))
+        .map(|index| crate::format_address(&address_bytes_from_secret_key(&secp, &chain.derive_child(&secp, index).secret_key)))
+        .collect()
+}
+
+// Addresses at the `count` indices after `matched_index` on the chain `path_prefix` of a
+// mnemonic without a passphrase
+pub fn derive_siblings(mnemonic: &Mnemonic, path_prefix: &DerivationPath, matched_index: u32, count: usize) -> Vec<String> {
+    let seed = Zeroizing::new(mnemonic.to_seed(""));
+    let start = matched_index.saturating_add(1);
+    derive_addresses(seed.as_ref(), path_prefix, start..start.saturating_add(count as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_wipes_the_extended_private_key() {
+        let mut key = std::mem::ManuallyDrop::new(ExtendedPrivKey::from_seed(&[7; 64]));
+        assert_ne!(key.chain_code, [0; 32]);
+        let ptr: *mut ExtendedPrivKey = &mut *key;
+        // The ManuallyDrop keeps the memory in place, so the fields can be read after dropping
+        let (chain_code, secret_key) = unsafe {
+            std::ptr::drop_in_place(ptr);
+            (std::ptr::read(std::ptr::addr_of!((*ptr).chain_code)), std::ptr::read(std::ptr::addr_of!((*ptr).secret_key)))
+        };
+        assert_eq!(chain_code, [0; 32]);
+        // non_secure_erase overwrites the key with the valid dummy key 1
+        assert_eq!(secret_key.secret_bytes(), [1; 32]);
+    }
+
+    #[test]
+    fn parses_derivation_paths() {
+        let path: DerivationPath = "m/44'/60'/0'/0".parse().unwrap();
+        assert_eq!(path.indices(), [44 | HARDENED, 60 | HARDENED, HARDENED, 0]);
+        assert_eq!("m/44h/60h/0h/0".parse::<DerivationPath>().unwrap(), path);
+        assert_eq!(path.to_string(), "m/44'/60'/0'/0");
+        assert_eq!(path.child(7).to_string(), "m/44'/60'/0'/0/7");
+        assert_eq!(path.child(7).split_last(), Some((path, 7)));
+        assert!("m".parse::<DerivationPath>().unwrap().indices().is_empty());
+    }
+
+    #[test]
+    fn rejects_bad_derivation_paths() {
+        for path in ["44'/60'", "m/x", "m/1/", "m/2147483648", "m/-1"] {
+            assert!(path.parse::<DerivationPath>().is_err(), "{}", path);
+        }
+        assert!("m/2147483647'".parse::<DerivationPath>().is_ok());
+    }
+
+    #[test]
+    fn serializes_bip32_test_vector_1() {
+        let secp = Secp256k1::new();
+        let master = ExtendedPrivKey::from_seed(&hex::decode("000102030405060708090a0b0c0d0e0f").unwrap());
+        for (path, xpub, xprv) in [
+            (
+                "m",
+                "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8",
+                "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+            ),
+            (
+                "m/0'",
+                "xpub68Gmy5EdvgibQVfPdqkBBCHxA5htiqg55crXYuXoQRKfDBFA1WEjWgP6LHhwBZeNK1VTsfTFUHCdrfp1bgwQ9xv5ski8PX9rL2dZXvgGDnw",
+                "xprv9uHRZZhk6KAJC1avXpDAp4MDc3sQKNxDiPvvkX8Br5ngLNv1TxvUxt4cV1rGL5hj6KCesnDYUhd7oWgT11eZG7XnxHrnYeSvkzY7d2bhkJ7",
+            ),
+            (
+                "m/0'/1",
+                "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ",
+                "xprv9wTYmMFdV23N2TdNG573QoEsfRrWKQgWeibmLntzniatZvR9BmLnvSxqu53Kw1UmYPxLgboyZQaXwTCg8MSY3H2EU4pWcQDnRnrVA1xe8fs",
+            ),
+            (
+                "m/0'/1/2'",
+                "xpub6D4BDPcP2GT577Vvch3R8wDkScZWzQzMMUm3PWbmWvVJrZwQY4VUNgqFJPMM3No2dFDFGTsxxpG5uJh7n7epu4trkrX7x7DogT5Uv6fcLW5",
+                "xprv9z4pot5VBttmtdRTWfWQmoH1taj2axGVzFqSb8C9xaxKymcFzXBDptWmT7FwuEzG3ryjH4ktypQSAewRiNMjANTtpgP4mLTj34bhnZX7UiM",
+            ),
+            (
+                "m/0'/1/2'/2",
+                "xpub6FHa3pjLCk84BayeJxFW2SP4XRrFd1JYnxeLeU8EqN3vDfZmbqBqaGJAyiLjTAwm6ZLRQUMv1ZACTj37sR62cfN7fe5JnJ7dh8zL4fiyLHV",
+                "xprvA2JDeKCSNNZky6uBCviVfJSKyQ1mDYahRjijr5idH2WwLsEd4Hsb2Tyh8RfQMuPh7f7RtyzTtdrbdqqsunu5Mm3wDvUAKRHSC34sJ7in334",
+            ),
+            (
+                "m/0'/1/2'/2/1000000000",
+                "xpub6H1LXWLaKsWFhvm6RVpEL9P4KfRZSW7abD2ttkWP3SSQvnyA8FSVqNTEcYFgJS2UaFcxupHiYkro49S8yGasTvXEYBVPamhGW6cFJodrTHy",
+                "xprvA41z7zogVVwxVSgdKUHDy1SKmdb533PjDz7J6N6mV6uS3ze1ai8FHa8kmHScGpWmj4WggLyQjgPie1rFSruoUihUZREPSL39UNdE3BBDu76",
+            ),
+        ] {
+            let keys = extended_keys(&secp, &master, &path.parse().unwrap());
+            assert_eq!(keys.xpub, xpub, "{}", path);
+            assert_eq!(keys.xprv, xprv, "{}", path);
+            assert_eq!(keys.root_xprv, "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi");
+        }
+    }
+
+    #[test]
+    fn derives_the_hardhat_accounts() {
+        let mnemonic = Mnemonic::parse("test test test test test test test test test test test junk").unwrap();
+        let seed = Zeroizing::new(mnemonic.to_seed(""));
+        let chain: DerivationPath = "m/44'/60'/0'/0".parse().unwrap();
+        // The first five of the accounts Hardhat publishes for its mnemonic
+        let accounts = [
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+            "0x70997970c51812dc3a010c7d01b50e0d17dc79c8",
+            "0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc",
+            "0x90f79bf6eb2c4f870365e785982e1f101e93b906",
+            "0x15d34aaf54267db7d7c367839aaf71a00a2c6a65",
+        ];
+        assert_eq!(derive_addresses(seed.as_ref(), &chain, 0..5), accounts);
+        assert_eq!(derive_addresses(seed.as_ref(), &chain, 2..4), accounts[2..4]);
+        assert!(derive_addresses(seed.as_ref(), &chain, 3..3).is_empty());
+        assert_eq!(derive_siblings(&mnemonic, &chain, 0, 1), accounts[1..2]);
+        assert_eq!(derive_siblings(&mnemonic, &chain, 1, 3), accounts[2..5]);
+
+        // Indices stop short of the hardened range
+        assert_eq!(derive_addresses(seed.as_ref(), &chain, HARDENED - 1..HARDENED + 5).len(), 1);
+    }
+}
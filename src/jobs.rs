@@ -0,0 +1,235 @@
+// --stdin-jobs: one long-running process fed searches as newline-delimited JSON on stdin
+//
+//   {"id":"a","prefix":"dead","suffix":"beef","case_sensitive":false,"count":2,"threads":4}
+//   {"cancel":"a"}
+//
+// Jobs run in the order they arrive, --concurrent-jobs at a time, on the search loop of the
+// REST and gRPC services. Each ends with one JSON line on stdout, tagged with its id:
+//
+//   {"id":"a","status":"done"|"cancelled"|"error","matches":[...],"attempts":N,"elapsed_secs":S}
+//
+// Jobs without an id take the number of their line. At the end of stdin the queued
+// and running jobs are finished before exiting.
+
+use crate::pattern::to_checksum_address;
+use crate::server::{check_search, search, Pattern};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobRequest {
+    id: Option<String>,
+    // A prefix, like -p on the command line
+    pattern: Option<String>,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    #[serde(default)]
+    case_sensitive: bool,
+    count: Option<u64>,
+    threads: Option<usize>,
+}
+
+struct Job {
+    // Line number; ids given by the jobs need not be unique
+    number: u64,
+    id: String,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    case_sensitive: bool,
+    count: u64,
+    threads: usize,
+}
+
+impl Job {
+    fn pattern(&self) -> Pattern<'_> {
+        Pattern { prefix: self.prefix.as_deref(), suffix: self.suffix.as_deref(), case_sensitive: self.case_sensitive }
+    }
+}
+
+enum Event {
+    Line(String),
+    EndOfInput,
+    // The job with this number wrote its result
+    Finished(u64),
+}
+
+// One line per result, written whole even when jobs finish at the same time
+fn emit(line: &Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
+
+fn failed(id: Option<&str>, message: String) -> Value {
+    json!({ "id": id, "status": "error", "error": message })
+}
+
+// The job a line asks for, checked like a REST API request. `threads` is the default share
+// of the cores.
+fn parse_job(request: Value, number: u64, threads: usize) -> Result<Job, Value> {
+    let id = request.get("id").and_then(Value::as_str).map(str::to_string);
+    let request: JobRequest = serde_json::from_value(request).map_err(|e| failed(id.as_deref(), e.to_string()))?;
+    let id = request.id.unwrap_or_else(|| number.to_string());
+    let invalid = |message: String| failed(Some(&id), message);
+    if request.pattern.is_some() && (request.prefix.is_some() || request.suffix.is_some()) {
+        return Err(invalid("give either pattern or prefix/suffix".to_string()));
+    }
+    if request.count == Some(0) {
+        return Err(invalid("count must be at least 1".to_string()));
+    }
+    let mut job = Job {
+        number,
+        id: id.clone(),
+        prefix: request.pattern.or(request.prefix),
+        suffix: request.suffix,
+        case_sensitive: request.case_sensitive,
+        count: request.count.unwrap_or(1),
+        threads,
+    };
+    job.threads = check_search(&job.pattern(), Some(request.threads.unwrap_or(threads))).map_err(invalid)?;
+    Ok(job)
+}
+
+// Searches until the job has its matches or is cancelled, and returns its result line
+fn run_job(job: &Job, cancel: &CancellationToken) -> Value {
+    let start = Instant::now();
+    let attempts = AtomicU64::new(0);
+    let found = Mutex::new(Vec::new());
+    search(&job.pattern(), job.threads, cancel, &attempts, |private_key| {
+        // Workers can match at the same time; only the first `count` are kept
+        let mut found = found.lock().unwrap();
+        if found.len() as u64 >= job.count {
+            return false;
+        }
+        let wallet = crate::generate_wallet_info(private_key);
+        found.push(json!({
+            "address": wallet.address,
+            "checksum_address": to_checksum_address(&wallet.address),
+            "private_key": wallet.private_key.as_str(),
+            "mnemonic": wallet.mnemonic.as_ref().map(|mnemonic| mnemonic.as_str()),
+        }));
+        (found.len() as u64) < job.count
+    });
+    let matches = found.into_inner().unwrap();
+    // The search cancels itself once it has every match
+    let status = if matches.len() as u64 >= job.count { "done" } else { "cancelled" };
+    json!({
+        "id": job.id,
+        "status": status,
+        "matches": matches,
+        "attempts": attempts.load(Ordering::Relaxed),
+        "elapsed_secs": start.elapsed().as_secs_f64(),
+    })
+}
+
+pub fn run(threads: usize, concurrent_jobs: usize) -> Result<(), Box<dyn std::error::Error>> {
+    // Each running job gets its share of the threads unless it asks for a number
+    let job_threads = (threads / concurrent_jobs).max(1);
+    info!("📥 Reading jobs from stdin, {} at a time on {} threads each", concurrent_jobs, job_threads);
+    
+    let (sender, events) = mpsc::channel();
+    let reader = sender.clone();
+    thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            match line {
+                Ok(line) => {
+                    if reader.send(Event::Line(line)).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    warning!("⚠️  Failed to read stdin, finishing the queued jobs: {}", e);
+                    break;
+                }
+            }
+        }
+        let _ = reader.send(Event::EndOfInput);
+    });
+    
+    let mut queue: VecDeque<Job> = VecDeque::new();
+    let mut running: HashMap<u64, (String, CancellationToken)> = HashMap::new();
+    let mut lines = 0;
+    let mut reading = true;
+    while reading || !queue.is_empty() || !running.is_empty() {
+        while running.len() < concurrent_jobs {
+            let Some(job) = queue.pop_front() else {
+                break;
+            };
+            let cancel = CancellationToken::new();
+            running.insert(job.number, (job.id.clone(), cancel.clone()));
+            tracing::info!(job = job.id.as_str(), threads = job.threads, "job started");
+            let finished = sender.clone();
+            thread::spawn(move || {
+                let result = run_job(&job, &cancel);
+                tracing::info!(job = job.id.as_str(), status = result["status"].as_str().unwrap_or_default(), "job finished");
+                emit(&result);
+                let _ = finished.send(Event::Finished(job.number));
+            });
+        }
+        
+        match events.recv().expect("the loop holds a sender") {
+            Event::Line(line) => {
+                lines += 1;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let request: Value = match serde_json::from_str(&line) {
+                    Ok(request) => request,
+                    Err(e) => {
+                        emit(&failed(None, format!("line {}: {}", lines, e)));
+                        continue;
+                    }
+                };
+                if let Some(cancel) = request.get("cancel") {
+                    let Some(id) = cancel.as_str() else {
+                        emit(&failed(None, format!("line {}: cancel expects a job id", lines)));
+                        continue;
+                    };
+                    cancel_jobs(id, &mut queue, &running);
+                    continue;
+                }
+                match parse_job(request, lines, job_threads) {
+                    Ok(job) => queue.push_back(job),
+                    Err(result) => emit(&result),
+                }
+            }
+            Event::EndOfInput => reading = false,
+            Event::Finished(number) => {
+                running.remove(&number);
+            }
+        }
+    }
+    Ok(())
+}
+
+// Drops the queued jobs with this id and stops the running ones, which report what they found
+fn cancel_jobs(id: &str, queue: &mut VecDeque<Job>, running: &HashMap<u64, (String, CancellationToken)>) {
+    let mut cancelled = false;
+    queue.retain(|job| {
+        if job.id != id {
+            return true;
+        }
+        emit(&json!({ "id": job.id, "status": "cancelled", "matches": [], "attempts": 0, "elapsed_secs": 0.0 }));
+        cancelled = true;
+        false
+    });
+    for (job_id, cancel) in running.values() {
+        if job_id == id {
+            cancel.cancel();
+            cancelled = true;
+        }
+    }
+    if cancelled {
+        tracing::info!(job = id, "job cancelled");
+    } else {
+        emit(&failed(Some(id), "no queued or running job has this id".to_string()));
+    }
+}
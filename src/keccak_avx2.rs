@@ -0,0 +1,164 @@
+// --features simd: four Keccak256 hashes of 64-byte inputs (uncompressed public keys
+// without the 0x04 prefix) at once. Each AVX2 register holds the same lane of four
+// independent states, so every step of Keccak-f[1600] runs on all four in one instruction.
+// A 64-byte input fits in one 136-byte block, so there is a single permutation per hash.
+// CPUs without AVX2, and other architectures, use sha3's scalar implementation.
+
+use sha3::{Digest, Keccak256};
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+// Rho rotation of lane x + 5y
+const ROTATIONS: [i32; 25] = [
+    0, 1, 62, 28, 27,
+    36, 44, 6, 55, 20,
+    3, 10, 43, 25, 39,
+    41, 45, 15, 21, 8,
+    18, 2, 61, 56, 14,
+];
+
+pub fn keccak256_x4(inputs: [[u8; 64]; 4]) -> [[u8; 32]; 4] {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx2") {
+        // Safety: the CPU was just checked for AVX2
+        return unsafe { avx2::keccak256_x4(&inputs) };
+    }
+    inputs.map(|input| Keccak256::digest(input).into())
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use super::{ROTATIONS, ROUND_CONSTANTS};
+    use std::arch::x86_64::*;
+    
+    #[target_feature(enable = "avx2")]
+    fn rotate(lane: __m256i, bits: i32) -> __m256i {
+        if bits == 0 {
+            return lane;
+        }
+        _mm256_or_si256(_mm256_sll_epi64(lane, _mm_cvtsi32_si128(bits)), _mm256_srl_epi64(lane, _mm_cvtsi32_si128(64 - bits)))
+    }
+    
+    #[target_feature(enable = "avx2")]
+    fn keccak_f(state: &mut [__m256i; 25]) {
+        for round_constant in ROUND_CONSTANTS {
+            // Theta
+            let mut parity = [_mm256_setzero_si256(); 5];
+            for (x, parity) in parity.iter_mut().enumerate() {
+                for y in 0..5 {
+                    *parity = _mm256_xor_si256(*parity, state[x + 5 * y]);
+                }
+            }
+            for x in 0..5 {
+                let d = _mm256_xor_si256(parity[(x + 4) % 5], rotate(parity[(x + 1) % 5], 1));
+                for y in 0..5 {
+                    state[x + 5 * y] = _mm256_xor_si256(state[x + 5 * y], d);
+                }
+            }
+            
+            // Rho and pi: lane (x, y) is rotated into (y, 2x + 3y)
+            let mut moved = [_mm256_setzero_si256(); 25];
+            for x in 0..5 {
+                for y in 0..5 {
+                    moved[y + 5 * ((2 * x + 3 * y) % 5)] = rotate(state[x + 5 * y], ROTATIONS[x + 5 * y]);
+                }
+            }
+            
+            // Chi
+            for y in 0..5 {
+                for x in 0..5 {
+                    let masked = _mm256_andnot_si256(moved[(x + 1) % 5 + 5 * y], moved[(x + 2) % 5 + 5 * y]);
+                    state[x + 5 * y] = _mm256_xor_si256(moved[x + 5 * y], masked);
+                }
+            }
+            
+            // Iota
+            state[0] = _mm256_xor_si256(state[0], _mm256_set1_epi64x(round_constant as i64));
+        }
+    }
+    
+    #[target_feature(enable = "avx2")]
+    pub fn keccak256_x4(inputs: &[[u8; 64]; 4]) -> [[u8; 32]; 4] {
+        let lane = |input: &[u8; 64], i: usize| i64::from_le_bytes(input[8 * i..8 * i + 8].try_into().expect("8 bytes"));
+        let mut state = [_mm256_setzero_si256(); 25];
+        for (i, lane_state) in state.iter_mut().take(8).enumerate() {
+            *lane_state = _mm256_set_epi64x(lane(&inputs[3], i), lane(&inputs[2], i), lane(&inputs[1], i), lane(&inputs[0], i));
+        }
+        // Keccak padding: 0x01 after the input and 0x80 in the last byte of the block
+        state[8] = _mm256_set1_epi64x(0x01);
+        state[16] = _mm256_set1_epi64x(i64::MIN);
+        keccak_f(&mut state);
+        
+        // The digest is the first four lanes of each state
+        let mut lanes = [[0u64; 4]; 4];
+        for (lane, lane_state) in lanes.iter_mut().zip(&state) {
+            // Safety: `lane` is 32 bytes and the store is unaligned
+            unsafe { _mm256_storeu_si256(lane.as_mut_ptr().cast(), *lane_state) };
+        }
+        let mut outputs = [[0u8; 32]; 4];
+        for (j, output) in outputs.iter_mut().enumerate() {
+            for (i, lane) in lanes.iter().enumerate() {
+                output[8 * i..8 * i + 8].copy_from_slice(&lane[j].to_le_bytes());
+            }
+        }
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+    
+    // Every lane of the four-way hash against sha3 for the same input
+    fn assert_lanes_match(inputs: [[u8; 64]; 4]) {
+        let expected: [[u8; 32]; 4] = inputs.map(|input| Keccak256::digest(input).into());
+        assert_eq!(keccak256_x4(inputs), expected, "{:02x?}", inputs);
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // Safety: the CPU was just checked for AVX2
+            assert_eq!(unsafe { avx2::keccak256_x4(&inputs) }, expected, "{:02x?}", inputs);
+        }
+    }
+    
+    #[test]
+    fn every_lane_matches_sha3() {
+        assert_lanes_match([[0; 64]; 4]);
+        assert_lanes_match([[0xff; 64]; 4]);
+        // A different input in each lane, so a lane mixed up with another is caught
+        assert_lanes_match([[0x00; 64], [0x01; 64], [0x80; 64], [0xff; 64]]);
+        let mut inputs = [[0u8; 64]; 4];
+        for (lane, input) in inputs.iter_mut().enumerate() {
+            for (i, byte) in input.iter_mut().enumerate() {
+                *byte = (lane * 64 + i) as u8;
+            }
+        }
+        assert_lanes_match(inputs);
+        for _ in 0..100 {
+            for input in &mut inputs {
+                rand::thread_rng().fill_bytes(input);
+            }
+            assert_lanes_match(inputs);
+        }
+    }
+    
+    #[test]
+    fn hashes_public_keys_into_addresses() {
+        // The uncompressed public key of the private key 1 hashes to 0x7e5f…5bdf
+        let generator = hex::decode(
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        )
+        .unwrap();
+        let mut inputs = [[0u8; 64]; 4];
+        inputs[2].copy_from_slice(&generator);
+        let hashes = keccak256_x4(inputs);
+        assert_eq!(hex::encode(&hashes[2][12..]), "7e5f4552091a69125d5dfcb7b8c2659029395bdf");
+    }
+}
@@ -0,0 +1,393 @@
+// Candidate keys and their addresses on a GPU, shared by vanity.cl (OpenCL C) and vanity.cu
+// (CUDA). It is plain C as well, so the arithmetic can be checked on the CPU.
+//
+// Item `block` of a batch takes its private key from block `block` of the ChaCha20 keystream of
+// `seed`, exactly as rand_chacha's ChaCha20Rng does at word position 16 * block, so the host
+// can recreate the key of a match without any key crossing the bus. The key is multiplied by
+// the generator in Jacobian coordinates, and the Keccak-256 hash of the public key gives the
+// address.
+//
+// Field elements and scalars are eight 32-bit limbs, least significant first.
+
+#ifndef EVM_VANITY_SECP256K1_H
+#define EVM_VANITY_SECP256K1_H
+
+#if defined(__OPENCL_VERSION__)
+#define DEVICE
+#define CONSTANT __constant
+typedef uchar u8;
+typedef uint u32;
+typedef ulong u64;
+#elif defined(__CUDACC__)
+#define DEVICE static __device__
+#define CONSTANT static __constant__ const
+typedef unsigned char u8;
+typedef unsigned int u32;
+typedef unsigned long long u64;
+#else
+#define DEVICE static
+#define CONSTANT static const
+typedef unsigned char u8;
+typedef unsigned int u32;
+typedef unsigned long long u64;
+#endif
+
+// ChaCha20 with a 64-bit block counter in words 12 and 13 and a zero stream id, keystream
+// words 0..7 of the block
+DEVICE void chacha20_key(const u32 seed[8], u64 block, u32 out[8]) {
+    u32 input[16] = {
+        0x61707865, 0x3320646e, 0x79622d32, 0x6b206574,
+        seed[0], seed[1], seed[2], seed[3], seed[4], seed[5], seed[6], seed[7],
+        (u32)block, (u32)(block >> 32), 0, 0,
+    };
+    u32 x[16];
+    for (int i = 0; i < 16; i++) {
+        x[i] = input[i];
+    }
+#define ROTL32(v, n) (((v) << (n)) | ((v) >> (32 - (n))))
+#define QUARTER(a, b, c, d) \
+    x[a] += x[b]; x[d] = ROTL32(x[d] ^ x[a], 16); \
+    x[c] += x[d]; x[b] = ROTL32(x[b] ^ x[c], 12); \
+    x[a] += x[b]; x[d] = ROTL32(x[d] ^ x[a], 8); \
+    x[c] += x[d]; x[b] = ROTL32(x[b] ^ x[c], 7);
+    for (int round = 0; round < 10; round++) {
+        QUARTER(0, 4, 8, 12) QUARTER(1, 5, 9, 13) QUARTER(2, 6, 10, 14) QUARTER(3, 7, 11, 15)
+        QUARTER(0, 5, 10, 15) QUARTER(1, 6, 11, 12) QUARTER(2, 7, 8, 13) QUARTER(3, 4, 9, 14)
+    }
+#undef QUARTER
+#undef ROTL32
+    for (int i = 0; i < 8; i++) {
+        out[i] = x[i] + input[i];
+    }
+}
+
+// The keystream bytes as a big-endian scalar, the way SecretKey::from_slice reads them
+DEVICE void scalar_from_keystream(const u32 words[8], u32 k[8]) {
+    for (int limb = 0; limb < 8; limb++) {
+        // Limb 0 holds bytes 28..31, the last four bytes of the keystream
+        u32 word = words[7 - limb];
+        k[limb] = (word << 24) | ((word & 0xff00) << 8) | ((word >> 8) & 0xff00) | (word >> 24);
+    }
+}
+
+// --- The field of secp256k1, p = 2^256 - 0x1000003d1 ---
+
+typedef struct {
+    u32 v[8];
+} fe;
+
+// r = a + 0x1000003d1 * c for c below 2^34, returning the carry out of 2^256
+DEVICE u32 fe_add_small(fe *r, const fe *a, u64 c) {
+    u64 low = c * 977;
+    u64 carry = (u64)a->v[0] + (low & 0xffffffff);
+    r->v[0] = (u32)carry;
+    carry >>= 32;
+    carry += (u64)a->v[1] + (low >> 32) + (c & 0xffffffff);
+    r->v[1] = (u32)carry;
+    carry >>= 32;
+    carry += (u64)a->v[2] + (c >> 32);
+    r->v[2] = (u32)carry;
+    carry >>= 32;
+    for (int i = 3; i < 8; i++) {
+        carry += a->v[i];
+        r->v[i] = (u32)carry;
+        carry >>= 32;
+    }
+    return (u32)carry;
+}
+
+// Brings a value below 2^256 under p
+DEVICE void fe_reduce_once(fe *r) {
+    fe t;
+    // r >= p exactly when r + 0x1000003d1 carries
+    if (fe_add_small(&t, r, 1)) {
+        *r = t;
+    }
+}
+
+DEVICE void fe_add(fe *r, const fe *a, const fe *b) {
+    u64 carry = 0;
+    for (int i = 0; i < 8; i++) {
+        carry += (u64)a->v[i] + b->v[i];
+        r->v[i] = (u32)carry;
+        carry >>= 32;
+    }
+    // Past 2^256, the sum is r + 2^256 = r + 0x1000003d1 modulo p, and below p after that
+    if (carry) {
+        fe_add_small(r, r, 1);
+    } else {
+        fe_reduce_once(r);
+    }
+}
+
+DEVICE void fe_sub(fe *r, const fe *a, const fe *b) {
+    u64 borrow = 0;
+    for (int i = 0; i < 8; i++) {
+        u64 d = (u64)a->v[i] - b->v[i] - borrow;
+        r->v[i] = (u32)d;
+        borrow = (d >> 32) & 1;
+    }
+    // Below zero, add p back: subtracting 0x1000003d1 is adding p modulo 2^256
+    if (borrow) {
+        u64 low = 0x3d1;
+        u64 d = (u64)r->v[0] - low;
+        r->v[0] = (u32)d;
+        borrow = (d >> 32) & 1;
+        d = (u64)r->v[1] - 1 - borrow;
+        r->v[1] = (u32)d;
+        borrow = (d >> 32) & 1;
+        for (int i = 2; i < 8; i++) {
+            d = (u64)r->v[i] - borrow;
+            r->v[i] = (u32)d;
+            borrow = (d >> 32) & 1;
+        }
+    }
+}
+
+DEVICE void fe_mul(fe *r, const fe *a, const fe *b) {
+    u32 t[16];
+    for (int i = 0; i < 16; i++) {
+        t[i] = 0;
+    }
+    for (int i = 0; i < 8; i++) {
+        u64 carry = 0;
+        for (int j = 0; j < 8; j++) {
+            carry += (u64)a->v[i] * b->v[j] + t[i + j];
+            t[i + j] = (u32)carry;
+            carry >>= 32;
+        }
+        t[i + 8] = (u32)carry;
+    }
+    // t = low + 2^256 * high = low + (2^32 + 977) * high modulo p
+    u64 carry = 0;
+    for (int i = 0; i < 8; i++) {
+        carry += (u64)t[i] + (u64)t[8 + i] * 977;
+        if (i > 0) {
+            carry += t[7 + i];
+        }
+        r->v[i] = (u32)carry;
+        carry >>= 32;
+    }
+    carry += t[15];
+    // carry < 2^34 is folded in the same way, and a last carry past 2^256 once more
+    if (fe_add_small(r, r, carry)) {
+        fe_add_small(r, r, 1);
+    }
+    fe_reduce_once(r);
+}
+
+DEVICE void fe_sqr(fe *r, const fe *a) {
+    fe_mul(r, a, a);
+}
+
+// a^(p - 2), the inverse of a non-zero a
+DEVICE void fe_inv(fe *r, const fe *a) {
+    // p - 2, most significant limb first
+    const u32 exponent[8] = { 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xffffffff, 0xfffffffe, 0xfffffc2d };
+    fe result = { { 1, 0, 0, 0, 0, 0, 0, 0 } };
+    for (int limb = 0; limb < 8; limb++) {
+        for (int bit = 31; bit >= 0; bit--) {
+            fe_sqr(&result, &result);
+            if ((exponent[limb] >> bit) & 1) {
+                fe_mul(&result, &result, a);
+            }
+        }
+    }
+    *r = result;
+}
+
+// --- Points on y^2 = x^3 + 7 ---
+
+typedef struct {
+    fe x, y, z;
+} point;
+
+// The generator, affine
+CONSTANT u32 GX[8] = { 0x16f81798, 0x59f2815b, 0x2dce28d9, 0x029bfcdb, 0xce870b07, 0x55a06295, 0xf9dcbbac, 0x79be667e };
+CONSTANT u32 GY[8] = { 0xfb10d4b8, 0x9c47d08f, 0xa6855419, 0xfd17b448, 0x0e1108a8, 0x5da4fbfc, 0x26a3c465, 0x483ada77 };
+
+// 2p, for a point that is not at infinity (dbl-2009-l)
+DEVICE void point_double(point *p) {
+    fe a, b, c, d, e, f, t;
+    fe_sqr(&a, &p->x);
+    fe_sqr(&b, &p->y);
+    fe_sqr(&c, &b);
+    fe_add(&t, &p->x, &b);
+    fe_sqr(&t, &t);
+    fe_sub(&t, &t, &a);
+    fe_sub(&t, &t, &c);
+    fe_add(&d, &t, &t);
+    fe_add(&e, &a, &a);
+    fe_add(&e, &e, &a);
+    fe_sqr(&f, &e);
+    // z3 = 2 y z, before y changes
+    fe_mul(&p->z, &p->y, &p->z);
+    fe_add(&p->z, &p->z, &p->z);
+    fe_sub(&p->x, &f, &d);
+    fe_sub(&p->x, &p->x, &d);
+    fe_sub(&t, &d, &p->x);
+    fe_mul(&t, &e, &t);
+    fe_add(&c, &c, &c);
+    fe_add(&c, &c, &c);
+    fe_add(&c, &c, &c);
+    fe_sub(&p->y, &t, &c);
+}
+
+// p + (qx, qy), for an affine q other than p and -p (madd-2007-bl)
+DEVICE void point_add_affine(point *p, const fe *qx, const fe *qy) {
+    fe z1z1, u2, s2, h, hh, i, j, r, v, t;
+    fe_sqr(&z1z1, &p->z);
+    fe_mul(&u2, qx, &z1z1);
+    fe_mul(&s2, qy, &p->z);
+    fe_mul(&s2, &s2, &z1z1);
+    fe_sub(&h, &u2, &p->x);
+    fe_sqr(&hh, &h);
+    fe_add(&i, &hh, &hh);
+    fe_add(&i, &i, &i);
+    fe_mul(&j, &h, &i);
+    fe_sub(&r, &s2, &p->y);
+    fe_add(&r, &r, &r);
+    fe_mul(&v, &p->x, &i);
+    // z3 = (z1 + h)^2 - z1z1 - hh
+    fe_add(&t, &p->z, &h);
+    fe_sqr(&t, &t);
+    fe_sub(&t, &t, &z1z1);
+    fe_sub(&p->z, &t, &hh);
+    // x3 = r^2 - j - 2 v
+    fe_sqr(&t, &r);
+    fe_sub(&t, &t, &j);
+    fe_sub(&t, &t, &v);
+    fe_sub(&t, &t, &v);
+    // y3 = r (v - x3) - 2 y1 j
+    fe_sub(&v, &v, &t);
+    fe_mul(&v, &r, &v);
+    fe_mul(&j, &p->y, &j);
+    fe_add(&j, &j, &j);
+    fe_sub(&p->y, &v, &j);
+    p->x = t;
+}
+
+// The affine public key k G of a non-zero k below the group order, as x || y big-endian
+DEVICE void public_key(const u32 k[8], u8 out[64]) {
+    fe gx, gy;
+    for (int i = 0; i < 8; i++) {
+        gx.v[i] = GX[i];
+        gy.v[i] = GY[i];
+    }
+    point p;
+    int started = 0;
+    // Double and add from the top bit; the running point is a multiple of G between 2 G and
+    // k G, so the addition never meets G or -G
+    for (int bit = 255; bit >= 0; bit--) {
+        if (started) {
+            point_double(&p);
+        }
+        if ((k[bit / 32] >> (bit % 32)) & 1) {
+            if (started) {
+                point_add_affine(&p, &gx, &gy);
+            } else {
+                p.x = gx;
+                p.y = gy;
+                p.z.v[0] = 1;
+                for (int i = 1; i < 8; i++) {
+                    p.z.v[i] = 0;
+                }
+                started = 1;
+            }
+        }
+    }
+    fe z_inv, z_inv2, x, y;
+    fe_inv(&z_inv, &p.z);
+    fe_sqr(&z_inv2, &z_inv);
+    fe_mul(&x, &p.x, &z_inv2);
+    fe_mul(&z_inv2, &z_inv2, &z_inv);
+    fe_mul(&y, &p.y, &z_inv2);
+    for (int limb = 0; limb < 8; limb++) {
+        for (int byte = 0; byte < 4; byte++) {
+            out[28 - 4 * limb + byte] = (u8)(x.v[limb] >> (24 - 8 * byte));
+            out[60 - 4 * limb + byte] = (u8)(y.v[limb] >> (24 - 8 * byte));
+        }
+    }
+}
+
+// --- Keccak-256 of a 64-byte public key ---
+
+CONSTANT u64 KECCAK_RC[24] = {
+    0x0000000000000001ULL, 0x0000000000008082ULL, 0x800000000000808aULL, 0x8000000080008000ULL,
+    0x000000000000808bULL, 0x0000000080000001ULL, 0x8000000080008081ULL, 0x8000000000008009ULL,
+    0x000000000000008aULL, 0x0000000000000088ULL, 0x0000000080008009ULL, 0x000000008000000aULL,
+    0x000000008000808bULL, 0x800000000000008bULL, 0x8000000000008089ULL, 0x8000000000008003ULL,
+    0x8000000000008002ULL, 0x8000000000000080ULL, 0x000000000000800aULL, 0x800000008000000aULL,
+    0x8000000080008081ULL, 0x8000000000008080ULL, 0x0000000080000001ULL, 0x8000000080008008ULL,
+};
+CONSTANT int KECCAK_ROTATION[24] = { 1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44 };
+CONSTANT int KECCAK_LANE[24] = { 10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1 };
+
+#define ROTL64(v, n) (((v) << (n)) | ((v) >> (64 - (n))))
+
+DEVICE void keccak_f(u64 st[25]) {
+    u64 bc[5];
+    for (int round = 0; round < 24; round++) {
+        for (int i = 0; i < 5; i++) {
+            bc[i] = st[i] ^ st[i + 5] ^ st[i + 10] ^ st[i + 15] ^ st[i + 20];
+        }
+        for (int i = 0; i < 5; i++) {
+            u64 t = bc[(i + 4) % 5] ^ ROTL64(bc[(i + 1) % 5], 1);
+            for (int j = 0; j < 25; j += 5) {
+                st[j + i] ^= t;
+            }
+        }
+        u64 t = st[1];
+        for (int i = 0; i < 24; i++) {
+            int lane = KECCAK_LANE[i];
+            u64 next = st[lane];
+            st[lane] = ROTL64(t, KECCAK_ROTATION[i]);
+            t = next;
+        }
+        for (int j = 0; j < 25; j += 5) {
+            for (int i = 0; i < 5; i++) {
+                bc[i] = st[j + i];
+            }
+            for (int i = 0; i < 5; i++) {
+                st[j + i] ^= (~bc[(i + 1) % 5]) & bc[(i + 2) % 5];
+            }
+        }
+        st[0] ^= KECCAK_RC[round];
+    }
+}
+
+#undef ROTL64
+
+// The last 20 bytes of the hash of the 64-byte public key
+DEVICE void address_of(const u8 public_key[64], u8 address[20]) {
+    u64 st[25];
+    for (int i = 0; i < 25; i++) {
+        st[i] = 0;
+    }
+    for (int i = 0; i < 8; i++) {
+        u64 lane = 0;
+        for (int byte = 0; byte < 8; byte++) {
+            lane |= (u64)public_key[8 * i + byte] << (8 * byte);
+        }
+        st[i] = lane;
+    }
+    // Keccak padding of a 64-byte message in a 136-byte block
+    st[8] ^= 0x01;
+    st[16] ^= 0x8000000000000000ULL;
+    keccak_f(st);
+    for (int i = 0; i < 20; i++) {
+        address[i] = (u8)(st[(12 + i) / 8] >> (8 * ((12 + i) % 8)));
+    }
+}
+
+// The address of item `block` of the keystream of `seed`, and its private key as a scalar
+DEVICE void candidate(const u32 seed[8], u64 block, u32 k[8], u8 address[20]) {
+    u32 words[8];
+    chacha20_key(seed, block, words);
+    scalar_from_keystream(words, k);
+    u8 public_key_bytes[64];
+    public_key(k, public_key_bytes);
+    address_of(public_key_bytes, address);
+}
+
+#endif
@@ -0,0 +1,19 @@
+// Kernel of the gpu feature: one candidate address per work item. gpu.rs puts secp256k1.h in
+// front of this source before building it.
+//
+// Item i takes block first_block + i of the ChaCha20 keystream of `seed` and writes its address
+// to addresses[20 * i..20 * i + 20]; the key never leaves the device.
+
+__kernel void vanity_addresses(__global const uint *seed, ulong first_block, __global uchar *addresses) {
+    size_t item = get_global_id(0);
+    u32 key_seed[8];
+    for (int i = 0; i < 8; i++) {
+        key_seed[i] = seed[i];
+    }
+    u32 k[8];
+    u8 address[20];
+    candidate(key_seed, first_block + item, k, address);
+    for (int i = 0; i < 20; i++) {
+        addresses[item * 20 + i] = address[i];
+    }
+}
@@ -0,0 +1,42 @@
+// Kernel of the cuda feature, compiled to PTX by build.rs when nvcc is on PATH.
+//
+// Item i of a launch takes block first_block + i of the ChaCha20 keystream of `seed`, as
+// vanity.cl does, and keeps its key when (address & filter[0..20]) == filter[20..40]. Kept keys
+// are written big-endian to keys[32 * slot..32 * slot + 32], the slots numbered by `found`;
+// keys past `capacity` are counted but dropped.
+
+#include "secp256k1.h"
+
+extern "C" __global__ void vanity_search(const u32 *seed, u64 first_block, u32 count, const u8 *filter,
+                                         u32 capacity, u32 *found, u8 *keys) {
+    u32 item = blockIdx.x * blockDim.x + threadIdx.x;
+    if (item >= count) {
+        return;
+    }
+    u32 key_seed[8];
+    for (int i = 0; i < 8; i++) {
+        key_seed[i] = seed[i];
+    }
+    u32 k[8];
+    u8 address[20];
+    candidate(key_seed, first_block + item, k, address);
+
+    u8 diff = 0;
+    for (int i = 0; i < 20; i++) {
+        diff |= (address[i] & filter[i]) ^ filter[20 + i];
+    }
+    if (diff != 0) {
+        return;
+    }
+    u32 slot = atomicAdd(found, 1);
+    if (slot >= capacity) {
+        return;
+    }
+    for (int i = 0; i < 8; i++) {
+        u32 limb = k[7 - i];
+        keys[32 * slot + 4 * i] = (u8)(limb >> 24);
+        keys[32 * slot + 4 * i + 1] = (u8)(limb >> 16);
+        keys[32 * slot + 4 * i + 2] = (u8)(limb >> 8);
+        keys[32 * slot + 4 * i + 3] = (u8)limb;
+    }
+}
@@ -0,0 +1,73 @@
+// Candidate key and address generation for the raw-mode search loop
+
+use crate::keyrange::KeyRange;
+use rand_chacha::ChaCha20Rng;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha3::{Digest, Keccak256};
+
+// Generate `batch` random private keys, within --key-min/--key-max if given, and their
+// address bytes. Drawing all keys first and then deriving all addresses keeps the loop
+// bodies small and independent, so the CPU can overlap the hashing of neighbours.
+//
+// Note that `SecretKey` is `Copy` and does not wipe itself on drop in the
+// `secp256k1` crate; callers erase non-matching keys with `non_secure_erase`.
+pub fn generate_addresses_batch(
+    secp: &Secp256k1<secp256k1::All>,
+    rng: &mut ChaCha20Rng,
+    key_range: Option<&KeyRange>,
+    batch: usize,
+) -> Vec<([u8; 20], SecretKey)> {
+    let private_keys: Vec<SecretKey> = (0..batch)
+        .map(|_| match key_range {
+            Some(key_range) => key_range.random_key(rng),
+            None => SecretKey::new(rng),
+        })
+        .collect();
+    
+    #[cfg(feature = "simd")]
+    return addresses_x4(secp, private_keys);
+    
+    #[cfg(not(feature = "simd"))]
+    private_keys
+        .into_iter()
+        .map(|private_key| (address_bytes_from_secret_key(secp, &private_key), private_key))
+        .collect()
+}
+
+// The public keys of four private keys at a time, hashed by one `keccak256_x4` call; a
+// short last chunk is padded with zeros and the extra hashes dropped
+#[cfg(feature = "simd")]
+fn addresses_x4(secp: &Secp256k1<secp256k1::All>, private_keys: Vec<SecretKey>) -> Vec<([u8; 20], SecretKey)> {
+    let mut addresses = Vec::with_capacity(private_keys.len());
+    for chunk in private_keys.chunks(4) {
+        let mut inputs = [[0u8; 64]; 4];
+        for (input, private_key) in inputs.iter_mut().zip(chunk) {
+            input.copy_from_slice(&PublicKey::from_secret_key(secp, private_key).serialize_uncompressed()[1..]);
+        }
+        let hashes = crate::keccak_avx2::keccak256_x4(inputs);
+        for (hash, private_key) in hashes.iter().zip(chunk) {
+            addresses.push((hash[12..].try_into().expect("20 bytes"), *private_key));
+        }
+    }
+    addresses
+}
+
+pub fn address_bytes_from_secret_key(secp: &Secp256k1<secp256k1::All>, private_key: &SecretKey) -> [u8; 20] {
+    address_bytes_from_public_key(&PublicKey::from_secret_key(secp, private_key))
+}
+
+pub fn address_bytes_from_public_key(public_key: &PublicKey) -> [u8; 20] {
+    // Get uncompressed public key bytes (remove the 0x04 prefix)
+    let public_key_bytes = public_key.serialize_uncompressed();
+    let public_key_hash = &public_key_bytes[1..]; // Remove first byte (0x04)
+    
+    // Hash with Keccak256
+    let mut hasher = Keccak256::new();
+    hasher.update(public_key_hash);
+    let hash = hasher.finalize();
+    
+    // Take last 20 bytes for address
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
@@ -0,0 +1,138 @@
+// Restricting private keys to [min, max] so machines can split the keyspace
+//
+// Keys are 32-byte big-endian integers, so comparing the byte arrays compares
+// the numbers. Valid secp256k1 keys lie in [1, n - 1] for the curve order n.
+
+use rand::RngCore;
+use secp256k1::SecretKey;
+use zeroize::Zeroizing;
+
+const CURVE_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+pub const MIN_KEY: [u8; 32] = {
+    let mut key = [0u8; 32];
+    key[31] = 1;
+    key
+};
+
+pub const MAX_KEY: [u8; 32] = {
+    let mut key = CURVE_ORDER;
+    key[31] -= 1;
+    key
+};
+
+#[derive(Clone, Debug)]
+pub struct KeyRange {
+    pub min: [u8; 32],
+    pub max: [u8; 32],
+}
+
+// clap value parser for a 64 character hex key (an optional 0x prefix is allowed)
+pub fn parse_key(value: &str) -> Result<[u8; 32], String> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.len() != 64 {
+        return Err(format!("expected 64 hex characters, got {}", digits.len()));
+    }
+    let mut key = [0u8; 32];
+    hex::decode_to_slice(digits, &mut key).map_err(|e| format!("invalid hex: {}", e))?;
+    Ok(key)
+}
+
+fn add(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut sum = [0u8; 32];
+    let mut carry = 0u16;
+    for i in (0..32).rev() {
+        let total = a[i] as u16 + b[i] as u16 + carry;
+        sum[i] = total as u8;
+        carry = total >> 8;
+    }
+    sum
+}
+
+fn sub(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut difference = [0u8; 32];
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let total = a[i] as i16 - b[i] as i16 - borrow;
+        difference[i] = total.rem_euclid(256) as u8;
+        borrow = (total < 0) as i16;
+    }
+    difference
+}
+
+// `key + offset`, wrapping around at 2^256
+pub fn add_offset(key: &[u8; 32], offset: u64) -> [u8; 32] {
+    let mut offset_bytes = [0u8; 32];
+    offset_bytes[24..].copy_from_slice(&offset.to_be_bytes());
+    add(key, &offset_bytes)
+}
+
+// `key + high·2^64 + low`, wrapping around at 2^256
+pub fn add_wide_offset(key: &[u8; 32], high: u64, low: u64) -> [u8; 32] {
+    let mut offset_bytes = [0u8; 32];
+    offset_bytes[16..24].copy_from_slice(&high.to_be_bytes());
+    offset_bytes[24..].copy_from_slice(&low.to_be_bytes());
+    add(key, &offset_bytes)
+}
+
+fn to_f64(value: &[u8; 32]) -> f64 {
+    value.iter().fold(0.0, |acc, &byte| acc * 256.0 + byte as f64)
+}
+
+impl KeyRange {
+    pub fn new(min: [u8; 32], max: [u8; 32]) -> Result<KeyRange, String> {
+        if [min, max].iter().any(|key| *key < MIN_KEY || *key > MAX_KEY) {
+            return Err(format!("Keys must lie between 0x{} and 0x{}", hex::encode(MIN_KEY), hex::encode(MAX_KEY)));
+        }
+        if min > max {
+            return Err("--key-min must not be greater than --key-max".to_string());
+        }
+        Ok(KeyRange { min, max })
+    }
+
+    // max - min, the largest offset from min
+    fn span(&self) -> [u8; 32] {
+        sub(&self.max, &self.min)
+    }
+
+    // Number of keys in the range when it is small enough to scan sequentially (at most 2^32)
+    pub fn sequential_len(&self) -> Option<u64> {
+        let span = self.span();
+        if span[..28].iter().any(|&byte| byte != 0) {
+            return None;
+        }
+        Some(u32::from_be_bytes([span[28], span[29], span[30], span[31]]) as u64 + 1)
+    }
+
+    // Share of all valid private keys covered by the range
+    pub fn keyspace_fraction(&self) -> f64 {
+        (to_f64(&self.span()) + 1.0) / to_f64(&MAX_KEY)
+    }
+
+    // The key `offset` places above min; the caller keeps offset within `sequential_len`
+    pub fn key_at(&self, offset: u64) -> SecretKey {
+        let key = Zeroizing::new(add_offset(&self.min, offset));
+        SecretKey::from_slice(key.as_ref()).expect("key within the validated range")
+    }
+
+    // A uniformly random key in the range: draw an offset with only as many bits as the
+    // span needs and reject the ones above it, which accepts at least half of the draws
+    pub fn random_key<R: RngCore>(&self, rng: &mut R) -> SecretKey {
+        let span = self.span();
+        let first = span.iter().position(|&byte| byte != 0).unwrap_or(31);
+        let mask = if span[first] == 0 { 0 } else { 0xff >> span[first].leading_zeros() };
+
+        let mut offset = Zeroizing::new([0u8; 32]);
+        loop {
+            rng.fill_bytes(&mut offset[first..]);
+            offset[first] &= mask;
+            if *offset <= span {
+                let key = Zeroizing::new(add(&self.min, &offset));
+                return SecretKey::from_slice(key.as_ref()).expect("key within the validated range");
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+// Ethereum keystore v3 ("UTC/JSON") files as written by Geth and read by MetaMask
+//
+// See https://ethereum.org/en/developers/docs/data-structures-and-encoding/web3-secret-storage/
+// The private key is encrypted with AES-128-CTR under the first half of the
+// KDF output, and the MAC is keccak256(derived_key[16..32] || ciphertext).
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Kdf {
+    Pbkdf2 { iterations: u32 },
+    Scrypt { log_n: u8, r: u32, p: u32 },
+}
+
+impl Default for Kdf {
+    fn default() -> Self {
+        Kdf::Pbkdf2 { iterations: 2048 }
+    }
+}
+
+impl Kdf {
+    fn derive(&self, password: &str, salt: &[u8]) -> Zeroizing<[u8; 32]> {
+        let mut derived = Zeroizing::new([0u8; 32]);
+        match *self {
+            Kdf::Pbkdf2 { iterations } => {
+                pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, derived.as_mut());
+            }
+            Kdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, 32).expect("valid scrypt parameters");
+                scrypt::scrypt(password.as_bytes(), salt, &params, derived.as_mut())
+                    .expect("32 bytes is a valid scrypt output length");
+            }
+        }
+        derived
+    }
+
+    fn params_json(&self, salt: &[u8]) -> (&'static str, Value) {
+        match *self {
+            Kdf::Pbkdf2 { iterations } => (
+                "pbkdf2",
+                json!({ "c": iterations, "dklen": 32, "prf": "hmac-sha256", "salt": hex::encode(salt) }),
+            ),
+            Kdf::Scrypt { log_n, r, p } => (
+                "scrypt",
+                json!({ "n": 1u64 << log_n, "r": r, "p": p, "dklen": 32, "salt": hex::encode(salt) }),
+            ),
+        }
+    }
+}
+
+pub fn create_keystore_v3(private_key: &[u8; 32], address: &str, password: &str, kdf: Kdf) -> Value {
+    let mut salt = [0u8; 32];
+    let mut iv = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut iv);
+
+    encrypt_keystore(private_key, address, password, kdf, &salt, &iv, uuid::Uuid::new_v4())
+}
+
+fn encrypt_keystore(
+    private_key: &[u8; 32],
+    address: &str,
+    password: &str,
+    kdf: Kdf,
+    salt: &[u8; 32],
+    iv: &[u8; 16],
+    id: uuid::Uuid,
+) -> Value {
+    let derived = kdf.derive(password, salt);
+
+    let mut ciphertext = *private_key;
+    let mut cipher = Aes128Ctr::new(derived[..16].into(), iv.into());
+    cipher.apply_keystream(&mut ciphertext);
+
+    let mut hasher = Keccak256::new();
+    hasher.update(&derived[16..32]);
+    hasher.update(ciphertext);
+    let mac = hasher.finalize();
+
+    let (kdf_name, kdf_params) = kdf.params_json(salt);
+
+    json!({
+        "address": address.strip_prefix("0x").unwrap_or(address).to_lowercase(),
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "ciphertext": hex::encode(ciphertext),
+            "cipherparams": { "iv": hex::encode(iv) },
+            "kdf": kdf_name,
+            "kdfparams": kdf_params,
+            "mac": hex::encode(mac),
+        },
+        "id": id.to_string(),
+        "version": 3,
+    })
+}
+
+/// Geth's file name: `UTC--<timestamp with dashes instead of colons>--<lowercase address>`
+pub fn keystore_file_name(address: &str) -> String {
+    let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S%.9fZ");
+    format!("UTC--{}--{}", timestamp, address.strip_prefix("0x").unwrap_or(address).to_lowercase())
+}
+
+pub fn write_keystore(dir: &Path, address: &str, keystore: &Value) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(keystore_file_name(address));
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    use std::io::Write;
+    let mut file = options.open(&path)?;
+    file.write_all(serde_json::to_string_pretty(keystore)?.as_bytes())?;
+    file.sync_all()?;
+
+    Ok(path)
+}
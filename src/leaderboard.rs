@@ -0,0 +1,172 @@
+// --leaderboard: the best --score-config addresses ever found on this machine, kept in a
+// JSON file so that runs build on each other
+//
+//   {"version":1,"entries":[{"address":"0x..","score":{"total":12.0,...},"found_at":"...","score_config":{...}}]}
+//
+// Entries are best first. Private keys are only stored BIP38-encrypted (with --bip38), and
+// otherwise left out. Every change rewrites the file under a temporary name and renames it.
+
+use crate::score::{Score, ScoreConfig, Scorer};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const VERSION: u64 = 1;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub address: String,
+    pub score: Score,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bip38_encrypted_key: Option<String>,
+    pub found_at: String,
+    pub score_config: ScoreConfig,
+}
+
+impl Entry {
+    pub fn new(address: &str, score: Score, bip38_encrypted_key: Option<String>, score_config: &ScoreConfig) -> Entry {
+        Entry {
+            address: address.to_string(),
+            score,
+            bip38_encrypted_key,
+            found_at: chrono::Utc::now().to_rfc3339(),
+            score_config: score_config.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LeaderboardFile {
+    version: u64,
+    entries: Vec<Entry>,
+}
+
+// The entries of a leaderboard file, best first
+pub fn load(path: &Path) -> Result<Vec<Entry>, String> {
+    let contents = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file: serde_json::Value = serde_json::from_slice(&contents).map_err(|e| format!("{} is not valid JSON: {}", path.display(), e))?;
+    match file["version"].as_u64() {
+        Some(VERSION) => {}
+        Some(version) => return Err(format!("{} is a version {} leaderboard, expected version {}", path.display(), version, VERSION)),
+        None => return Err(format!("{} has no leaderboard version", path.display())),
+    }
+    let file: LeaderboardFile = serde_json::from_value(file).map_err(|e| format!("{} is not a leaderboard: {}", path.display(), e))?;
+    let mut entries = file.entries;
+    entries.sort_by(|a, b| b.score.total.total_cmp(&a.score.total));
+    Ok(entries)
+}
+
+// The best `size` candidates across runs, shared by all workers. Like score::TopK, the score
+// to beat once the board is full is kept in an atomic, so most candidates cost one load.
+pub struct Leaderboard {
+    path: PathBuf,
+    size: usize,
+    threshold_bits: AtomicU64,
+    // Best first. The lock is held while the file is rewritten, so two improvements found at
+    // the same time are both written, one after the other.
+    entries: Mutex<Vec<Entry>>,
+}
+
+const NO_THRESHOLD: u64 = u64::MAX;
+
+impl Leaderboard {
+    // Loads the standings at `path`, rescoring entries found with another config so that all
+    // scores compare. A file that cannot be read as a leaderboard is moved aside and replaced.
+    pub fn open(path: &Path, size: usize, scorer: &Scorer) -> Leaderboard {
+        let mut entries = if path.exists() {
+            load(path).unwrap_or_else(|error_msg| {
+                let backup = backup_path(path);
+                match std::fs::rename(path, &backup) {
+                    Ok(()) => warning!("⚠️  {}; moved it to {} and starting a new leaderboard", error_msg, backup.display()),
+                    Err(e) => warning!("⚠️  {}; starting a new leaderboard (moving it to {} failed: {})", error_msg, backup.display(), e),
+                }
+                Vec::new()
+            })
+        } else {
+            Vec::new()
+        };
+        
+        let mut rescored = 0;
+        for entry in entries.iter_mut().filter(|entry| entry.score_config != scorer.config) {
+            let mut address = [0u8; 20];
+            if hex::decode_to_slice(entry.address.trim_start_matches("0x"), &mut address).is_ok() {
+                entry.score = scorer.breakdown(&address);
+                entry.score_config = scorer.config.clone();
+                rescored += 1;
+            }
+        }
+        if rescored > 0 {
+            warning!("⚠️  {} leaderboard entr{} scored with another config, rescored with this one", rescored, if rescored == 1 { "y was" } else { "ies were" });
+        }
+        entries.sort_by(|a, b| b.score.total.total_cmp(&a.score.total));
+        entries.truncate(size);
+        
+        let leaderboard = Leaderboard { path: path.to_path_buf(), size, threshold_bits: AtomicU64::new(NO_THRESHOLD), entries: Mutex::new(Vec::new()) };
+        leaderboard.update_threshold(&entries);
+        *leaderboard.entries.lock().unwrap() = entries;
+        leaderboard
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    pub fn champion(&self) -> Option<Entry> {
+        self.entries.lock().unwrap().first().cloned()
+    }
+    
+    // Enter the candidate if it makes the board; `candidate` is only built when it does.
+    // Beating the champion is reported as a new best, and every change is written out.
+    pub fn offer(&self, total: f64, candidate: impl FnOnce() -> Entry) {
+        let threshold = self.threshold_bits.load(Ordering::Relaxed);
+        if threshold != NO_THRESHOLD && total <= f64::from_bits(threshold) {
+            return;
+        }
+        
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.size && entries.last().is_some_and(|worst| total <= worst.score.total) {
+            return;
+        }
+        let entry = candidate();
+        if entries.iter().any(|existing| existing.address == entry.address) {
+            return;
+        }
+        let rank = entries.partition_point(|existing| existing.score.total >= total);
+        if rank == 0 {
+            info!("🏆 New best ever: score {}: {}", entry.score, entry.address);
+        }
+        tracing::info!(address = %entry.address, score = total, rank = rank + 1, "leaderboard entry");
+        entries.insert(rank, entry);
+        entries.truncate(self.size);
+        self.update_threshold(&entries);
+        if let Err(e) = self.write(&entries) {
+            warning!("⚠️  Failed to update leaderboard {}: {}", self.path.display(), e);
+            tracing::error!(path = %self.path.display(), error = %e, "leaderboard write failed");
+        }
+    }
+    
+    fn update_threshold(&self, entries: &[Entry]) {
+        if let (true, Some(worst)) = (entries.len() == self.size, entries.last()) {
+            self.threshold_bits.store(worst.score.total.to_bits(), Ordering::Relaxed);
+        }
+    }
+    
+    fn write(&self, entries: &[Entry]) -> std::io::Result<()> {
+        let file = LeaderboardFile { version: VERSION, entries: entries.to_vec() };
+        let contents = serde_json::to_string_pretty(&file).expect("JSON values serialize");
+        let name = self.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let temporary = self.path.with_file_name(format!(".{}.tmp", name));
+        crate::write_private_file(&temporary, contents.as_bytes())?;
+        std::fs::rename(&temporary, &self.path)
+    }
+}
+
+// `<path>.<timestamp>.bak`, so an earlier backup is never overwritten
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".{}.bak", chrono::Utc::now().format("%Y-%m-%dT%H-%M-%SZ")));
+    PathBuf::from(backup)
+}
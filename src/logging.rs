@@ -0,0 +1,159 @@
+// --log-file and --log-level: `tracing` events of a search (workers starting and stopping,
+// progress snapshots, signals, notifications and errors) written one per line with their
+// fields. Lines go through a channel to a writer thread, so logging never blocks a worker.
+// Only this crate's events are recorded, and no event carries a key, mnemonic or seed.
+
+use clap::ValueEnum;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+// How long exiting waits for queued lines to be written
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn level(self) -> Level {
+        match self {
+            LogLevel::Error => Level::ERROR,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Trace => Level::TRACE,
+        }
+    }
+}
+
+enum Message {
+    Line(String),
+    // Acknowledged once every earlier line is written and flushed
+    Flush(mpsc::Sender<()>),
+}
+
+struct LineSubscriber {
+    level: Level,
+    sender: mpsc::Sender<Message>,
+}
+
+// Writes the message first and the other fields after it as `name=value`
+#[derive(Default)]
+struct Fields {
+    message: String,
+    fields: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+    
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+impl Subscriber for LineSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.level && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+    }
+    
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.level))
+    }
+    
+    // Spans are not used; events are logged on their own
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+    
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+    
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+    
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {:<5} {}: {}{}",
+            chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            metadata.level(),
+            metadata.target(),
+            fields.message,
+            fields.fields
+        );
+        // The writer only hangs up if the process is exiting
+        let _ = self.sender.send(Message::Line(line));
+    }
+    
+    fn enter(&self, _span: &Id) {}
+    
+    fn exit(&self, _span: &Id) {}
+}
+
+// Flushes the log when dropped; `drop` it before `process::exit`
+pub struct LogWriter {
+    sender: mpsc::Sender<Message>,
+    _handle: JoinHandle<()>,
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        let (ack, acked) = mpsc::channel();
+        if self.sender.send(Message::Flush(ack)).is_ok() {
+            let _ = acked.recv_timeout(FLUSH_TIMEOUT);
+        }
+    }
+}
+
+// Records events at `level` and above in `path` (appended to), or on stderr without one
+pub fn init(level: LogLevel, path: Option<&Path>) -> std::io::Result<LogWriter> {
+    let mut out: Box<dyn Write + Send> = match path {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::OpenOptions::new().create(true).append(true).open(path)?)),
+        None => Box::new(std::io::stderr()),
+    };
+    let (sender, receiver) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        // Flushed whenever the queue runs dry, so a stalled run's log is up to date
+        while let Ok(message) = receiver.recv() {
+            for message in std::iter::once(message).chain(receiver.try_iter()) {
+                match message {
+                    Message::Line(line) => {
+                        let _ = writeln!(out, "{}", line);
+                    }
+                    Message::Flush(ack) => {
+                        let _ = out.flush();
+                        let _ = ack.send(());
+                    }
+                }
+            }
+            let _ = out.flush();
+        }
+    });
+    let subscriber = LineSubscriber { level: level.level(), sender: sender.clone() };
+    tracing::subscriber::set_global_default(subscriber).expect("the log is only initialized once");
+    Ok(LogWriter { sender, _handle: handle })
+}
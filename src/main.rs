@@ -1,354 +1,4763 @@
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
-use std::time::Instant;
+// Banners, progress and status lines, which move to stderr while stdout carries a --stream
+// or --quiet results
+static INFO_TO_STDERR: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+// --quiet drops everything but the results, progress and errors
+static QUIET: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::QUIET.load(std::sync::atomic::Ordering::Relaxed) {
+        } else if $crate::INFO_TO_STDERR.load(std::sync::atomic::Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+// Progress and status lines, which --quiet keeps on stderr
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if $crate::INFO_TO_STDERR.load(std::sync::atomic::Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+// Error and warning lines go to stderr, styled by the output module
+macro_rules! error {
+    ($($arg:tt)*) => {
+        eprintln!("{}", $crate::output::error(&format!($($arg)*)))
+    };
+}
+
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        eprintln!("{}", $crate::output::warning(&format!($($arg)*)))
+    };
+}
+
+macro_rules! success {
+    ($($arg:tt)*) => {
+        println!("{}", $crate::output::success(&format!($($arg)*)))
+    };
+}
+
+mod affinity;
+mod autosave;
+mod bench;
+mod bip38;
+mod checkpoint;
+mod config;
+mod counters;
+#[cfg(feature = "cuda")]
+mod cuda;
+mod device;
+mod distributed;
+mod encrypt;
+mod entropy;
+mod error;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod grpc;
+mod hd;
+mod jobs;
+#[cfg(feature = "simd")]
+mod keccak_avx2;
+mod keygen;
+mod keyrange;
+mod keystore;
+mod leaderboard;
+mod logging;
+mod metrics;
+mod nearmiss;
+mod notify;
+mod odds;
+mod output;
+mod pattern;
+mod qr;
+mod rpc;
+mod salt;
+mod score;
+mod server;
+mod shamir;
+mod signals;
+mod splitkey;
+mod stats;
+mod throttle;
+mod tui;
+mod tune;
+mod watchonly;
+mod word;
+
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::io::IsTerminal;
+use std::sync::{mpsc, Arc, Mutex};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 use std::thread;
-use clap::Parser;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use rayon::prelude::*;
 use secp256k1::{Secp256k1, SecretKey, PublicKey};
 use sha3::{Digest, Keccak256};
-use hex;
-use rand::rngs::OsRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 use bip39::Mnemonic;
+use zeroize::Zeroizing;
+use keygen::{address_bytes_from_secret_key, generate_addresses_batch};
+use pattern::{
+    bytes_to_nibbles, count_leading_char, first_nibble_ok, leading_run, parse_padded_bytes, matches_fuzzy, mirror_length, pattern_difficulty, pattern_len, second_nibble_ok, to_chain_checksum_address, to_checksum_address, validate_lengths, validate_pattern, ExcludedChars, Exclusion,
+    BitMask, NibblePattern, OffsetPattern, Palindrome, PatternEntry, PatternSet, Repeating,
+};
+
+// Candidates generated per raw-mode work item (see `cargo bench --bench keygen`)
+const KEYGEN_BATCH_SIZE: usize = 64;
+// Attempts between the sleeps of --cpu-limit
+const THROTTLE_INTERVAL: u64 = 1000;
+
+// Time spent on each candidate thread count by --threads auto-tune
+const CALIBRATION_SECS: u64 = 2;
+
+// Width of the fastest second's bar in the `bench` histogram
+const BENCH_BAR_WIDTH: u64 = 40;
 
+// Streamed addresses kept for the --tui dashboard
+const RECENT_FINDS: usize = 10;
+
+// Without a subcommand the search options are accepted directly, as before `search` existed
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
-struct Args {
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    
+    #[command(flatten)]
+    search: SearchArgs,
+    
+    /// Never color the output (also disabled by a non-empty NO_COLOR and when not on a terminal)
+    #[arg(long, global = true, env = "EVM_VANITY_NO_COLOR")]
+    no_color: bool,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group = clap::ArgGroup::new("shamir_split").args(["shamir", "shamir_shares"]))]
+#[command(group = clap::ArgGroup::new("server").args(["server_mode", "serve"]).conflicts_with_all(SERVER_CONFLICTS))]
+#[command(group = clap::ArgGroup::new("client").args(["client_mode", "connect"]).conflicts_with_all(CLIENT_CONFLICTS))]
+#[command(group = clap::ArgGroup::new("salt_target").args(["create2", "create3", "clone", "safe"]))]
+#[command(group = clap::ArgGroup::new("deployer_mode").args(["create2", "create3"]))]
+#[command(group = clap::ArgGroup::new("factory_mode").args(["clone", "safe"]))]
+#[command(group = clap::ArgGroup::new("safe_setup").args(["initializer", "owners"]))]
+struct SearchArgs {
     /// Target pattern to match (prefix or suffix)
-    #[arg(short, long)]
+    #[arg(short, long, env = "EVM_VANITY_PATTERN")]
     pattern: Option<String>,
     
     /// Prefix pattern to match
-    #[arg(long)]
+    #[arg(long, env = "EVM_VANITY_PREFIX")]
     prefix: Option<String>,
     
     /// Suffix pattern to match
-    #[arg(long)]
+    #[arg(long, env = "EVM_VANITY_SUFFIX")]
     suffix: Option<String>,
     
+    /// Also require the address to start with one of these patterns (repeatable, any one matches)
+    #[arg(long, value_name = "PATTERN", action = clap::ArgAction::Append, conflicts_with_all = OR_PATTERN_CONFLICTS, env = "EVM_VANITY_OR_PREFIX", value_delimiter = ',')]
+    or_prefix: Vec<String>,
+    
+    /// Also require the address to end with one of these patterns (repeatable, any one matches)
+    #[arg(long, value_name = "PATTERN", action = clap::ArgAction::Append, conflicts_with_all = OR_PATTERN_CONFLICTS, env = "EVM_VANITY_OR_SUFFIX", value_delimiter = ',')]
+    or_suffix: Vec<String>,
+    
+    /// Search several PREFIX:SUFFIX pairs at once, e.g. dead:beef (repeatable, either side may be empty, any pair matches)
+    #[arg(long, value_name = "PREFIX:SUFFIX", value_parser = parse_alternative, action = clap::ArgAction::Append, conflicts_with_all = ALT_CONFLICTS, env = "EVM_VANITY_ALT", value_delimiter = ',')]
+    alt: Vec<PatternEntry>,
+    
+    /// Reject addresses starting with this pattern even if they match (repeatable)
+    #[arg(long, value_name = "PATTERN", env = "EVM_VANITY_EXCLUDE_PREFIX", value_delimiter = ',')]
+    exclude_prefix: Vec<String>,
+    
+    /// Reject addresses ending with this pattern even if they match (repeatable)
+    #[arg(long, value_name = "PATTERN", env = "EVM_VANITY_EXCLUDE_SUFFIX", value_delimiter = ',')]
+    exclude_suffix: Vec<String>,
+    
+    /// Require the address to start with N zero bytes (2N '0' characters); every zero byte saves 12 gas whenever the address is passed in calldata
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=9), conflicts_with_all = ["server", "client"], env = "EVM_VANITY_LEADING_ZERO_BYTES")]
+    leading_zero_bytes: Option<u8>,
+    
+    /// Also require PATTERN at character N of the address (0-indexed, without 0x), e.g. --at-offset 20 dead
+    #[arg(long, num_args = 2, value_names = ["N", "PATTERN"], value_delimiter = ' ', conflicts_with_all = ["server", "client"], env = "EVM_VANITY_AT_OFFSET")]
+    at_offset: Vec<String>,
+    
+    /// Only match addresses where (address & MASK) == (--value & MASK), in up to 20 bytes of hex
+    #[arg(long, value_name = "HEX", requires = "value", conflicts_with_all = ["server", "client"], env = "EVM_VANITY_MASK")]
+    mask: Option<String>,
+    
+    /// The bits the --mask selects must equal these (up to 20 bytes of hex)
+    #[arg(long, value_name = "HEX", requires = "mask", env = "EVM_VANITY_VALUE")]
+    value: Option<String>,
+    
+    /// Which end of the address a --mask and --value shorter than 20 bytes apply to
+    #[arg(long, value_enum, default_value_t = MaskAlign::Start, requires = "mask", env = "EVM_VANITY_MASK_ALIGN")]
+    mask_align: MaskAlign,
+    
+    /// Reject addresses containing any of these hex characters anywhere (e.g. "0" or "abcdef")
+    #[arg(long, value_name = "CHARS", value_parser = ExcludedChars::parse, env = "EVM_VANITY_EXCLUDE")]
+    exclude: Option<ExcludedChars>,
+    
+    /// Require the same pattern as both prefix and suffix (shorthand for --prefix P --suffix P)
+    #[arg(long, value_name = "PATTERN", conflicts_with_all = ["pattern", "prefix", "suffix", "word", "pattern_file", "repeating", "palindrome"], env = "EVM_VANITY_WRAP")]
+    wrap: Option<String>,
+    
+    /// Spell a word in hex look-alikes (o->0, s->5, ...) and match any of its spellings as the prefix
+    #[arg(long, conflicts_with_all = ["pattern", "prefix"], env = "EVM_VANITY_WORD")]
+    word: Option<String>,
+    
+    /// Search every pattern in this file at once (one `prefix:<hex>`, `suffix:<hex>` or `both:<prefix>:<suffix>` per line)
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["pattern", "prefix", "suffix", "word", "repeating"], env = "EVM_VANITY_PATTERN_FILE")]
+    pattern_file: Option<std::path::PathBuf>,
+    
+    /// Instead of matching a pattern, keep the best-scoring address (weights from this TOML file) until stopped
+    #[arg(long, value_name = "PATH.toml", conflicts_with_all = ["pattern", "prefix", "suffix", "word", "wrap", "pattern_file", "repeating", "palindrome", "fuzzy_distance", "count", "from_mnemonic"], env = "EVM_VANITY_SCORE_CONFIG")]
+    score_config: Option<std::path::PathBuf>,
+    
+    /// Instead of matching a pattern, keep the address with the longest run of this hex character at the start until --time-limit or --max-attempts
+    #[arg(long, value_name = "CHAR", value_parser = parse_repeating_char, conflicts_with_all = ["pattern", "prefix", "suffix", "word", "wrap", "pattern_file", "score_config", "repeating", "palindrome", "fuzzy_distance", "count", "from_mnemonic", "stream", "server", "client"], env = "EVM_VANITY_MAXIMIZE")]
+    maximize: Option<u8>,
+    
+    /// Instead of matching a pattern, keep the --top-k best addresses by this criterion until --time-limit or --max-attempts
+    #[arg(long, value_name = "CRITERION", conflicts_with_all = ["pattern", "prefix", "suffix", "word", "wrap", "pattern_file", "score_config", "maximize", "repeating", "palindrome", "fuzzy_distance", "count", "from_mnemonic", "stream"], env = "EVM_VANITY_SCORE_BY")]
+    score_by: Option<ScoreBy>,
+    
+    /// How many of the best --score-by addresses to keep
+    #[arg(long, value_name = "K", default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..=1000), requires = "score_by", env = "EVM_VANITY_TOP_K")]
+    top_k: u64,
+    
+    /// Keep the best --score-config addresses of every run in this JSON file, and only report a new best that beats them
+    #[arg(long, value_name = "PATH", requires = "score_config", env = "EVM_VANITY_LEADERBOARD")]
+    leaderboard: Option<std::path::PathBuf>,
+    
+    /// How many addresses the --leaderboard file keeps
+    #[arg(long, value_name = "N", default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..=1000), requires = "leaderboard", env = "EVM_VANITY_LEADERBOARD_SIZE")]
+    leaderboard_size: u64,
+    
+    /// Keep the addresses closest to the --prefix and --suffix, to report when the search ends without a match
+    #[arg(long, conflicts_with_all = ["word", "pattern_file", "alt", "or_prefix", "or_suffix", "score_config", "score_by", "maximize", "palindrome", "repeating", "fuzzy_distance", "from_mnemonic", "stream"], env = "EVM_VANITY_TRACK_NEAR_MISS")]
+    track_near_miss: bool,
+    
+    /// Also print the private keys of the --track-near-miss addresses
+    #[arg(long, requires = "track_near_miss", env = "EVM_VANITY_SHOW_NEAR_MISS_KEY")]
+    show_near_miss_key: bool,
+    
+    /// Target of --score-by prefix_match_length, in hex
+    #[arg(long, value_name = "HEX", value_parser = parse_score_prefix, requires = "score_by", env = "EVM_VANITY_SCORE_PREFIX")]
+    score_prefix: Option<Box<[u8]>>,
+    
+    /// Keep searching until this many matching addresses have been found
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..), env = "EVM_VANITY_COUNT")]
+    count: u64,
+    
+    /// Accept addresses whose prefix and suffix differ from the pattern in at most N characters
+    #[arg(long, value_name = "N", conflicts_with = "repeating", value_parser = clap::value_parser!(u8).range(1..), env = "EVM_VANITY_FUZZY_DISTANCE")]
+    fuzzy_distance: Option<u8>,
+    
+    /// Match addresses whose first K hex characters mirror the last K (without K, the whole address is a palindrome)
+    #[arg(long, value_name = "K", num_args = 0..=1, default_missing_value = "40", conflicts_with_all = ["pattern", "prefix", "suffix", "word", "pattern_file", "repeating", "fuzzy_distance"], value_parser = clap::value_parser!(u8).range(1..=40), env = "EVM_VANITY_PALINDROME")]
+    palindrome: Option<u8>,
+    
+    /// Match addresses whose first N hex characters are all the same character (combines with --suffix)
+    #[arg(long, value_name = "N", conflicts_with_all = ["pattern", "prefix", "word"], value_parser = clap::value_parser!(u8).range(2..=40), env = "EVM_VANITY_REPEATING")]
+    repeating: Option<u8>,
+    
+    /// Require the --repeating run to consist of this hex character
+    #[arg(long, value_name = "CHAR", requires = "repeating", value_parser = parse_repeating_char, env = "EVM_VANITY_REPEATING_CHAR")]
+    repeating_char: Option<u8>,
+    
     /// Whether to match as suffix (default is prefix) - deprecated, use --prefix/--suffix instead
-    #[arg(short, long, default_value = "false")]
+    #[arg(short, long, default_value = "false", env = "EVM_VANITY_SUFFIX_MODE")]
     suffix_mode: bool,
     
     /// Whether to match case-sensitively (default is case-insensitive)
-    #[arg(short, long, default_value = "false")]
+    #[arg(short, long, default_value = "false", env = "EVM_VANITY_CASE_SENSITIVE")]
     case_sensitive: bool,
     
-    /// Number of threads to use (default is number of CPU cores)
-    #[arg(short, long)]
-    threads: Option<usize>,
+    /// EIP-1191 chain ID, e.g. 30 for RSK: also print that chain's checksummed address, and match --case-sensitive patterns against its casing
+    #[arg(long, value_name = "ID", value_parser = clap::value_parser!(u64).range(1..), env = "EVM_VANITY_CHAIN_ID")]
+    chain_id: Option<u64>,
+    
+    /// Smallest private key to search (64 hex characters, big-endian), for splitting the keyspace between machines
+    #[arg(long, value_name = "HEX", value_parser = keyrange::parse_key, conflicts_with = "from_mnemonic", env = "EVM_VANITY_KEY_MIN")]
+    key_min: Option<[u8; 32]>,
+    
+    /// Largest private key to search (64 hex characters, big-endian)
+    #[arg(long, value_name = "HEX", value_parser = keyrange::parse_key, conflicts_with = "from_mnemonic", env = "EVM_VANITY_KEY_MAX")]
+    key_max: Option<[u8; 32]>,
+    
+    /// Scan private keys in order from this one (64 hex characters, keep it secret): thread i checks start + i·2^64, start + i·2^64 + 1, ...
+    #[arg(long, value_name = "HEX", value_parser = keyrange::parse_key, conflicts_with_all = START_KEY_CONFLICTS, env = "EVM_VANITY_START_KEY")]
+    start_key: Option<[u8; 32]>,
+    
+    /// Save how far each thread of a --start-key scan got to this file every few seconds and when the search ends
+    #[arg(long, value_name = "PATH", requires = "start_key", env = "EVM_VANITY_CHECKPOINT")]
+    checkpoint: Option<std::path::PathBuf>,
+    
+    /// Continue the --start-key scan saved in this checkpoint, saving to it again unless --checkpoint is given
+    #[arg(long, value_name = "PATH", requires = "start_key", env = "EVM_VANITY_RESUME")]
+    resume: Option<std::path::PathBuf>,
+    
+    /// Resume a checkpoint written for another pattern or thread count
+    #[arg(long, requires = "resume", env = "EVM_VANITY_FORCE")]
+    force: bool,
+    
+    /// Number of threads to use, or "auto-tune" to time a few counts first (default is number of CPU cores)
+    #[arg(short, long, value_name = "N|auto-tune", value_parser = parse_threads, env = "EVM_VANITY_THREADS")]
+    threads: Option<Threads>,
+    
+    /// Calibrate again instead of using the cached --threads auto-tune result
+    #[arg(long, env = "EVM_VANITY_RETUNE")]
+    retune: bool,
+    
+    /// Derive candidate keys from this seed (plus the thread index) for reproducible runs; NEVER use the keys for funds
+    #[arg(long, value_name = "U64", conflicts_with = "from_mnemonic", env = "EVM_VANITY_SEED")]
+    seed: Option<u64>,
+    
+    /// Allow --seed together with --keystore-dir, --output-file or --autosave
+    #[arg(long = "i-understand-seeded-keys-are-insecure", requires = "seed", env = "EVM_VANITY_I_UNDERSTAND_SEEDED_KEYS_ARE_INSECURE")]
+    seeded_keys_acknowledged: bool,
+    
+    /// Mix this secret (hex, or a file to read) into every candidate key, on top of the OS random generator
+    #[arg(long, value_name = "HEX|PATH", conflicts_with_all = ENTROPY_CONFLICTS, env = "EVM_VANITY_EXTRA_ENTROPY", hide_env_values = true)]
+    extra_entropy: Option<String>,
+    
+    /// Ask for random keystrokes and mix them and their timings into every candidate key, on top of the OS random generator
+    #[arg(long, conflicts_with_all = ENTROPY_CONFLICTS, env = "EVM_VANITY_ENTROPY_PROMPT")]
+    entropy_prompt: bool,
+    
+    /// Hand out work units of the --prefix/--suffix search to --client-mode machines instead of searching
+    #[arg(long, requires = "server_bind", env = "EVM_VANITY_SERVER_MODE")]
+    server_mode: bool,
+    
+    /// Address and port the --server-mode server listens on (e.g. 0.0.0.0:7878)
+    #[arg(long, value_name = "ADDR:PORT", requires = "server_mode", env = "EVM_VANITY_SERVER_BIND")]
+    server_bind: Option<std::net::SocketAddr>,
+    
+    /// Coordinate a distributed search listening on ADDR:PORT (shorthand for --server-mode --server-bind ADDR:PORT)
+    #[arg(long, value_name = "ADDR:PORT", env = "EVM_VANITY_SERVE")]
+    serve: Option<std::net::SocketAddr>,
+    
+    /// Search work units from a --server-mode server; the pattern comes from the server
+    #[arg(long, requires = "server_url", env = "EVM_VANITY_CLIENT_MODE")]
+    client_mode: bool,
+    
+    /// Address and port of the --server-mode server (e.g. 192.168.1.10:7878)
+    #[arg(long, value_name = "ADDR:PORT", requires = "client_mode", env = "EVM_VANITY_SERVER_URL")]
+    server_url: Option<String>,
+    
+    /// Work for the distributed search coordinated at ADDR:PORT (shorthand for --client-mode --server-url ADDR:PORT)
+    #[arg(long, value_name = "ADDR:PORT", env = "EVM_VANITY_CONNECT")]
+    connect: Option<String>,
+    
+    /// Read search jobs as JSON lines from stdin and write one JSON result line per job to stdout
+    #[arg(long, conflicts_with_all = JOB_CONFLICTS, env = "EVM_VANITY_STDIN_JOBS")]
+    stdin_jobs: bool,
+    
+    /// Number of --stdin-jobs jobs searching at once, sharing the threads (default 1)
+    #[arg(long, value_name = "N", requires = "stdin_jobs", value_parser = clap::value_parser!(u64).range(1..), env = "EVM_VANITY_CONCURRENT_JOBS")]
+    concurrent_jobs: Option<u64>,
+    
+    /// Serve the REST job API of `serve` on ADDR:PORT instead of searching, optionally behind --api-token
+    #[arg(long, value_name = "ADDR:PORT", conflicts_with_all = JOB_CONFLICTS, conflicts_with = "stdin_jobs", env = "EVM_VANITY_API")]
+    api: Option<std::net::SocketAddr>,
+    
+    /// Bearer token every --api request but /healthz must send
+    #[arg(long, value_name = "TOKEN", requires = "api", hide_env_values = true, env = "EVM_VANITY_API_TOKEN")]
+    api_token: Option<String>,
+    
+    /// Mine a salt for init code deployed by --deployer with CREATE2 instead of a key
+    #[arg(long, requires_all = ["deployer", "initcode_hash"], conflicts_with_all = SALT_MINING_CONFLICTS, env = "EVM_VANITY_CREATE2")]
+    create2: bool,
+    
+    /// keccak256 of the --create2 init code (creation code followed by the ABI-encoded constructor arguments)
+    #[arg(long, value_name = "HEX", value_parser = keyrange::parse_key, requires = "create2", env = "EVM_VANITY_INITCODE_HASH")]
+    initcode_hash: Option<[u8; 32]>,
+    
+    /// Mine a salt for the --deployer CREATE3 factory instead of a key; the pattern applies to the deployed contract
+    #[arg(long, requires = "deployer", conflicts_with_all = SALT_MINING_CONFLICTS, env = "EVM_VANITY_CREATE3")]
+    create3: bool,
+    
+    /// CREATE2 deployer of --create2, or CREATE3 factory that deploys the proxy with CREATE2 (0x followed by 40 hex characters)
+    #[arg(long, value_name = "ADDRESS", value_parser = salt::parse_address, requires = "deployer_mode", env = "EVM_VANITY_DEPLOYER")]
+    deployer: Option<[u8; 20]>,
+    
+    /// keccak256 of the factory's proxy init code (default: the proxy of solmate, solady and 0xSequence)
+    #[arg(long, value_name = "HEX", value_parser = keyrange::parse_key, requires = "create3", env = "EVM_VANITY_PROXY_INITCODE_HASH")]
+    proxy_initcode_hash: Option<[u8; 32]>,
+    
+    /// Mine a salt for an EIP-1167 clone of --implementation deployed by --factory with CREATE2
+    #[arg(long, requires_all = ["factory", "implementation"], conflicts_with_all = SALT_MINING_CONFLICTS, env = "EVM_VANITY_CLONE")]
+    clone: bool,
+    
+    /// Factory that deploys the --clone or --safe with CREATE2 (--safe default: the canonical v1.3.0 SafeProxyFactory)
+    #[arg(long, value_name = "ADDRESS", value_parser = salt::parse_address, requires = "factory_mode", env = "EVM_VANITY_FACTORY")]
+    factory: Option<[u8; 20]>,
+    
+    /// Contract the --clone delegates to, embedded in its bytecode
+    #[arg(long, value_name = "ADDRESS", value_parser = salt::parse_address, requires = "clone", env = "EVM_VANITY_IMPLEMENTATION")]
+    implementation: Option<[u8; 20]>,
+    
+    /// Immutable args appended to the --clone's code (OpenZeppelin's cloneDeterministicWithImmutableArgs)
+    #[arg(long, value_name = "HEX", value_parser = salt::parse_extra_data, requires = "clone", env = "EVM_VANITY_EXTRA_DATA")]
+    extra_data: Option<Box<[u8]>>,
+    
+    /// Mine a saltNonce for a Safe deployed by the --factory SafeProxyFactory (createProxyWithNonce)
+    #[arg(long, requires_all = ["proxy_creation_code", "safe_setup"], conflicts_with_all = SALT_MINING_CONFLICTS, env = "EVM_VANITY_SAFE")]
+    safe: bool,
+    
+    /// Safe singleton (master copy) the --safe proxy delegates to (default: the canonical v1.3.0 GnosisSafe)
+    #[arg(long, value_name = "ADDRESS", value_parser = salt::parse_address, requires = "safe", env = "EVM_VANITY_SINGLETON")]
+    singleton: Option<[u8; 20]>,
+    
+    /// What the factory's proxyCreationCode() returns, in hex
+    #[arg(long, value_name = "HEX", value_parser = salt::parse_bytes, requires = "safe", env = "EVM_VANITY_PROXY_CREATION_CODE")]
+    proxy_creation_code: Option<Box<[u8]>>,
+    
+    /// Setup calldata the --safe proxy is initialized with, in hex (or build it with --owners)
+    #[arg(long, value_name = "HEX", value_parser = salt::parse_bytes, requires = "safe", env = "EVM_VANITY_INITIALIZER")]
+    initializer: Option<Box<[u8]>>,
+    
+    /// Owners of the --safe, for a Safe.setup initializer with --threshold and --fallback-handler
+    #[arg(long, value_name = "ADDRESS", value_parser = salt::parse_address, value_delimiter = ',', requires = "safe", env = "EVM_VANITY_OWNERS")]
+    owners: Vec<[u8; 20]>,
+    
+    /// Confirmations the --safe needs, out of its --owners
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..), requires = "owners", env = "EVM_VANITY_THRESHOLD")]
+    threshold: u64,
+    
+    /// Fallback handler set up with the --owners (default: none)
+    #[arg(long, value_name = "ADDRESS", value_parser = salt::parse_address, requires = "owners", env = "EVM_VANITY_FALLBACK_HANDLER")]
+    fallback_handler: Option<[u8; 20]>,
+    
+    /// Uniswap v4 hook permissions the mined address must encode in its low 14 bits (0x-prefixed hex, or names like BEFORE_SWAP,AFTER_SWAP)
+    #[arg(long, value_name = "FLAGS", value_parser = salt::parse_hook_flags, requires = "salt_target", env = "EVM_VANITY_HOOK_FLAGS")]
+    hook_flags: Option<u16>,
+    
+    /// Salt mining: start every salt with these bytes (hex, up to 24 bytes), e.g. the sender a factory requires
+    #[arg(long, value_name = "HEX", value_parser = salt::parse_salt_prefix, requires = "salt_target", conflicts_with = "safe", env = "EVM_VANITY_SALT_PREFIX")]
+    salt_prefix: Option<Box<[u8]>>,
+    
+    /// Salt mining: the first salt nonce to check, e.g. to resume an earlier run (the threads take turns: start, start+1, ...)
+    #[arg(long, value_name = "N", requires = "salt_target", env = "EVM_VANITY_NONCE_START")]
+    nonce_start: Option<u64>,
+    
+    /// Salt mining: the last salt nonce to check before giving up
+    #[arg(long, value_name = "M", requires = "salt_target", env = "EVM_VANITY_NONCE_END")]
+    nonce_end: Option<u64>,
+    
+    /// Search offsets k for which PUBKEY + k·G matches, for the owner of PUBKEY's private key to add with `combine` (uncompressed hex)
+    #[arg(long, value_name = "PUBKEY", value_parser = splitkey::parse_public_key, conflicts_with_all = SPLIT_KEY_CONFLICTS, env = "EVM_VANITY_SEARCH_FOR_PUBKEY")]
+    search_for_pubkey: Option<secp256k1::PublicKey>,
+    
+    /// Output only the address and compressed public key; each private key is wiped right after its public key is derived
+    #[arg(long, conflicts_with_all = WATCH_ONLY_CONFLICTS, env = "EVM_VANITY_WATCH_ONLY")]
+    watch_only: bool,
+    
+    /// Never stop on a match: write each one as a JSON line to stdout (or --output-file) and keep searching
+    #[arg(long, conflicts_with_all = ["count", "json", "score_config", "keystore_dir", "shamir", "shamir_shares", "qr", "qr_secret", "qr_file"], env = "EVM_VANITY_STREAM")]
+    stream: bool,
+    
+    /// Stop the search after about this many attempts
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..), env = "EVM_VANITY_MAX_ATTEMPTS")]
+    max_attempts: Option<u64>,
+    
+    /// Stop the search after this many seconds
+    #[arg(long, value_name = "SECS", env = "EVM_VANITY_TIME_LIMIT", value_parser = clap::value_parser!(u64).range(1..))]
+    time_limit: Option<u64>,
+    
+    /// Refuse to start a search expected to take longer than this (e.g. 12h, 30d) unless confirmed or --yes is given
+    #[arg(long, value_name = "DURATION", default_value = "30d", value_parser = parse_duration, env = "EVM_VANITY_MAX_EXPECTED_DURATION")]
+    max_expected_duration: u64,
+    
+    /// Start searches that exceed --max-expected-duration without asking
+    #[arg(short, long, env = "EVM_VANITY_YES")]
+    yes: bool,
+    
+    /// Print how many attempts each thread made, to expose load imbalance
+    #[arg(long, env = "EVM_VANITY_BENCHMARK_THREADING")]
+    benchmark_threading: bool,
+    
+    /// Run the workers at low scheduling priority so other programs stay responsive
+    #[arg(long, env = "EVM_VANITY_NICE")]
+    nice: bool,
+    
+    /// Keep each worker busy for only this percentage of the time by sleeping between batches of work (100 means no limit)
+    #[arg(long, visible_alias = "cpu-percent", value_name = "PERCENT", value_parser = clap::value_parser!(u8).range(1..=100), env = "EVM_VANITY_CPU_LIMIT")]
+    cpu_limit: Option<u8>,
+    
+    /// Pin each worker thread to a CPU core of its own, spread over the cores when there are fewer threads
+    #[arg(long, env = "EVM_VANITY_PIN_CORES")]
+    pin_cores: bool,
+    
+    /// Only run the workers on these cores, e.g. 0-7,16-23 (one thread per core unless --threads is given)
+    #[arg(long, value_name = "LIST", value_parser = affinity::parse_core_list, env = "EVM_VANITY_CORES")]
+    cores: Option<Box<[usize]>>,
+    
+    /// Search on this GPU: a CUDA device number, or with OpenCL, counting from 0 among the GPUs with OpenCL 1.2 (default 0; needs the gpu or cuda feature)
+    #[arg(long, value_name = "N", env = "EVM_VANITY_GPU_DEVICE")]
+    gpu_device: Option<u32>,
+    
+    /// Candidate keys per GPU kernel launch (default 1,048,576; needs the gpu or cuda feature)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=1 << 26), env = "EVM_VANITY_GPU_BATCH_SIZE")]
+    gpu_batch_size: Option<u32>,
+    
+    /// Restart a worker thread that panics instead of continuing without it
+    #[arg(long, conflicts_with_all = ["server", "client"], env = "EVM_VANITY_RESPAWN_WORKERS")]
+    respawn_workers: bool,
+    
+    /// Encrypt the found private key with this password (BIP38) instead of printing it in plain text
+    #[arg(long, value_name = "PASSWORD", env = "EVM_VANITY_BIP38", hide_env_values = true)]
+    bip38: Option<String>,
+    
+    /// How candidates are generated: raw private keys, or fresh BIP39 mnemonics derived along m/44'/60'/0'/0/i
+    #[arg(long, value_enum, default_value_t = SearchMode::Raw, env = "EVM_VANITY_MODE")]
+    mode: SearchMode,
+    
+    /// Number of external addresses (m/44'/60'/0'/0/0..K) checked per mnemonic in mnemonic mode
+    #[arg(long, value_name = "K", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..), env = "EVM_VANITY_SCAN_DEPTH")]
+    scan_depth: u32,
+    
+    /// Entropy generated per mnemonic in mnemonic mode (128 = 12 words, 160, 192, 224, 256 = 24 words)
+    #[arg(long, default_value_t = 128, value_parser = parse_entropy_bits, env = "EVM_VANITY_ENTROPY_BITS")]
+    entropy_bits: usize,
+    
+    /// Length of the generated mnemonic in mnemonic mode (12, 15, 18, 21 or 24 words)
+    #[arg(long, value_name = "N", value_parser = parse_words, conflicts_with_all = ["entropy_bits", "from_mnemonic"], env = "EVM_VANITY_WORDS")]
+    words: Option<usize>,
+    
+    /// Search your own mnemonic along m/44'/60'/a'/0/i instead of generating keys (the phrase itself, a FILE holding it, or prompted for if omitted)
+    #[arg(long, value_name = "PHRASE|FILE", num_args = 0..=1, default_missing_value = "", conflicts_with_all = ["mode", "bip38"], env = "EVM_VANITY_FROM_MNEMONIC")]
+    from_mnemonic: Option<String>,
+    
+    /// Prompt for the BIP39 passphrase ("25th word") used with --from-mnemonic
+    #[arg(long, requires = "from_mnemonic", env = "EVM_VANITY_PASSPHRASE")]
+    passphrase: bool,
+    
+    /// First address index checked with --from-mnemonic
+    #[arg(long, default_value_t = 0, requires = "from_mnemonic", env = "EVM_VANITY_START_INDEX")]
+    start_index: u32,
+    
+    /// Number of address indices checked per account with --from-mnemonic
+    #[arg(long, default_value_t = 1_000_000, requires = "from_mnemonic", value_parser = clap::value_parser!(u64).range(1..=u32::MAX as u64), env = "EVM_VANITY_INDEX_COUNT")]
+    index_count: u64,
+    
+    /// Number of accounts (m/44'/60'/0'..N') searched with --from-mnemonic
+    #[arg(long, default_value_t = 1, requires = "from_mnemonic", value_parser = clap::value_parser!(u32).range(1..hd::HARDENED as i64), env = "EVM_VANITY_ACCOUNTS")]
+    accounts: u32,
+    
+    /// Split the found private key into N Shamir secret shares, any K of which recover it (e.g. 2-of-3)
+    #[arg(long, value_name = "K-of-N", value_parser = parse_shamir, conflicts_with_all = ["shamir_threshold", "from_mnemonic"], env = "EVM_VANITY_SHAMIR")]
+    shamir: Option<(u8, u8)>,
+    
+    /// Print the private key in plain text as well as the Shamir shares
+    #[arg(long, requires = "shamir_split", env = "EVM_VANITY_SHOW_PRIVATE_KEY")]
+    show_private_key: bool,
+    
+    /// Split the found private key into N Shamir secret shares
+    #[arg(long, value_name = "N", requires = "shamir_threshold", conflicts_with = "from_mnemonic", value_parser = clap::value_parser!(u8).range(2..), env = "EVM_VANITY_SHAMIR_SHARES")]
+    shamir_shares: Option<u8>,
+    
+    /// Number of Shamir shares needed to reconstruct the private key
+    #[arg(long, value_name = "K", requires = "shamir_shares", value_parser = clap::value_parser!(u8).range(1..), env = "EVM_VANITY_SHAMIR_THRESHOLD")]
+    shamir_threshold: Option<u8>,
+    
+    /// Write the found wallet as an Ethereum keystore v3 (UTC/JSON) file into this directory
+    #[arg(long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, requires = "keystore_password", conflicts_with = "from_mnemonic", env = "EVM_VANITY_KEYSTORE_DIR")]
+    keystore_dir: Option<std::path::PathBuf>,
+    
+    /// Password protecting the keystore file
+    #[arg(long, value_name = "PASSWORD", requires = "keystore_dir", env = "EVM_VANITY_KEYSTORE_PASSWORD", hide_env_values = true)]
+    keystore_password: Option<String>,
+    
+    /// Key derivation function used for the keystore file
+    #[arg(long, value_enum, default_value_t = KeystoreKdf::Pbkdf2, env = "EVM_VANITY_KEYSTORE_KDF")]
+    keystore_kdf: KeystoreKdf,
+    
+    /// Save every found wallet as its own JSON file (mode 0600) in ./vanity-results, or --save-dir
+    #[arg(long, env = "EVM_VANITY_AUTOSAVE")]
+    autosave: bool,
+    
+    /// Directory --autosave writes <timestamp>-<address>.json files to (implies --autosave)
+    #[arg(long, value_name = "DIR", value_hint = clap::ValueHint::DirPath, conflicts_with = "encrypt_to", env = "EVM_VANITY_SAVE_DIR")]
+    save_dir: Option<std::path::PathBuf>,
+    
+    /// Print a QR code of the ethereum:<checksummed address> URI after the result (skipped when stdout is not a terminal)
+    #[arg(long, env = "EVM_VANITY_QR")]
+    qr: bool,
+    
+    /// Also print a QR code of the private key (implies --qr)
+    #[arg(long, conflicts_with_all = ["from_mnemonic", "bip38"], env = "EVM_VANITY_QR_SECRET")]
+    qr_secret: bool,
+    
+    /// Write a PNG QR code of the ethereum:<checksummed address> URI to this file
+    #[arg(long, value_name = "PATH.png", env = "EVM_VANITY_QR_FILE")]
+    qr_file: Option<std::path::PathBuf>,
+    
+    /// Also print the uncompressed and compressed public key of the found address
+    #[arg(long, env = "EVM_VANITY_SHOW_PUBLIC_KEY")]
+    show_public_key: bool,
+    
+    /// Also print the BIP32 root xprv and the xprv at the derivation path of a --mode mnemonic match
+    #[arg(long, conflicts_with = "from_mnemonic", env = "EVM_VANITY_OUTPUT_XPRV")]
+    output_xprv: bool,
+    
+    /// Also print the BIP32 xpub at the derivation path of a --mode mnemonic match
+    #[arg(long, conflicts_with = "from_mnemonic", env = "EVM_VANITY_OUTPUT_XPUB")]
+    output_xpub: bool,
+    
+    /// Also print the addresses at the N-1 indices after a --mode mnemonic match, to see the rest of its wallet
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(2..=1000), conflicts_with = "from_mnemonic", env = "EVM_VANITY_DERIVE_SIBLINGS")]
+    derive_siblings: Option<u32>,
+    
+    /// Also list the first N addresses (default 5) of a --mode mnemonic match's wallet, as MetaMask shows its accounts, marking the match
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "5", value_parser = clap::value_parser!(u32).range(1..=1000), conflicts_with = "from_mnemonic", env = "EVM_VANITY_SHOW_DERIVED")]
+    show_derived: Option<u32>,
+    
+    /// Format of the result: text, or a JSON array of the found wallets
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, env = "EVM_VANITY_OUTPUT_FORMAT")]
+    output_format: OutputFormat,
+    
+    /// Write every generated address to stderr as GEN: 0x... (rate limited) and every match as MATCH: 0x..., for auditing
+    #[arg(short, long, env = "EVM_VANITY_VERBOSE")]
+    verbose: bool,
+    
+    /// Most GEN: lines per second and thread with --verbose
+    #[arg(long, value_name = "LINES_PER_SEC", default_value_t = 200, requires = "verbose", value_parser = clap::value_parser!(u32).range(1..), env = "EVM_VANITY_VERBOSE_RATE")]
+    verbose_rate: u32,
+    
+    /// Print only the results: key=value lines, or one JSON object per line with --output-format json; progress goes to stderr
+    #[arg(short, long, conflicts_with_all = ["qr", "qr_secret"], env = "EVM_VANITY_QUIET")]
+    quiet: bool,
+    
+    /// Shorthand for --output-format json
+    #[arg(long, env = "EVM_VANITY_JSON")]
+    json: bool,
+    
+    /// Also write the found wallets as JSON to this file (readable only by you on Unix)
+    #[arg(long, value_name = "PATH", env = "EVM_VANITY_OUTPUT_FILE")]
+    output_file: Option<std::path::PathBuf>,
+    
+    /// Encrypt the --output-file results to this age recipient or SSH public key (repeatable) and write them as PATH.age
+    #[arg(long, value_name = "RECIPIENT", value_parser = encrypt::check_recipient, requires = "output_file", conflicts_with_all = ["json", "stream", "qr_secret", "shamir_split"], env = "EVM_VANITY_ENCRYPT_TO", value_delimiter = ',')]
+    encrypt_to: Vec<String>,
+    
+    /// Count the matches already in --output-file and only search for the rest of --count, appending them
+    #[arg(long, requires = "output_file", conflicts_with_all = ["stream", "encrypt_to", "score_config", "score_by", "maximize", "server", "client", "salt_target", "search_for_pubkey", "watch_only", "stdin_jobs", "api"], env = "EVM_VANITY_RESUME_COUNT")]
+    resume_count: bool,
+    
+    /// Read default options from this TOML file (default: ~/.config/evm-vanity/config.toml or ~/.evm-vanity.toml if it exists)
+    #[arg(long, value_name = "PATH", env = "EVM_VANITY_CONFIG")]
+    config: Option<std::path::PathBuf>,
+    
+    /// Print the effective options as TOML, with where each value came from, and exit
+    #[arg(long, env = "EVM_VANITY_PRINT_CONFIG")]
+    print_config: bool,
+    
+    /// Show a full-screen dashboard instead of progress lines (q quits, p pauses, s writes a --stats-file row); needs a terminal
+    #[arg(long, conflicts_with_all = ["quiet", "server", "client", "salt_target", "search_for_pubkey", "watch_only"], env = "EVM_VANITY_TUI")]
+    tui: bool,
+    
+    /// Append a CSV row of progress statistics (attempts, rate, smoothed rate, ...) to this file every --stats-interval
+    #[arg(long, value_name = "PATH.csv", env = "EVM_VANITY_STATS_FILE")]
+    stats_file: Option<std::path::PathBuf>,
+    
+    /// Seconds between rows written to --stats-file
+    #[arg(long, value_name = "SECS", default_value_t = 60, requires = "stats_file", value_parser = clap::value_parser!(u64).range(1..), env = "EVM_VANITY_STATS_INTERVAL")]
+    stats_interval: u64,
+    
+    /// Log diagnostic events (workers, progress, signals, notifications, errors) to this file; keys are never logged
+    #[arg(long, value_name = "PATH", env = "EVM_VANITY_LOG_FILE")]
+    log_file: Option<std::path::PathBuf>,
+    
+    /// Least severe events to log (default info); without --log-file they are logged to stderr
+    #[arg(long, value_enum, value_name = "LEVEL", env = "EVM_VANITY_LOG_LEVEL")]
+    log_level: Option<logging::LogLevel>,
+    
+    /// Serve Prometheus metrics (attempts, rate, matches, ...) on http://HOST:PORT/metrics while searching
+    #[arg(long, value_name = "HOST:PORT", conflicts_with_all = ["server", "client"], env = "EVM_VANITY_METRICS_ADDR")]
+    metrics_addr: Option<std::net::SocketAddr>,
+    
+    /// Send a message to this service on every match, never with the private key (repeatable)
+    #[arg(long, value_enum, value_name = "SERVICE", env = "EVM_VANITY_NOTIFY", value_delimiter = ',')]
+    notify: Vec<NotifyService>,
+    
+    /// Telegram bot token for --notify telegram
+    #[arg(long, value_name = "TOKEN", hide_env_values = true, env = "EVM_VANITY_TELEGRAM_TOKEN")]
+    telegram_token: Option<String>,
+    
+    /// Telegram chat ID for --notify telegram
+    #[arg(long, value_name = "ID", env = "EVM_VANITY_TELEGRAM_CHAT")]
+    telegram_chat: Option<String>,
+    
+    /// Discord webhook URL for --notify discord
+    #[arg(long, value_name = "URL", hide_env_values = true, env = "EVM_VANITY_DISCORD_WEBHOOK")]
+    discord_webhook: Option<String>,
+    
+    /// After a match, ask this Ethereum JSON-RPC endpoint whether the address already has transactions or a balance
+    #[arg(long, value_name = "URL", value_parser = parse_rpc_url, hide_env_values = true, env = "EVM_VANITY_VERIFY_RPC")]
+    verify_rpc: Option<String>,
+    
+    /// Show a desktop notification on every match and when the search gives up, never with the private key (needs the desktop-notify feature)
+    #[arg(long, env = "EVM_VANITY_NOTIFY_DESKTOP")]
+    notify_desktop: bool,
+    
+    /// Ring the terminal bell a few times when the search ends by itself
+    #[arg(long, env = "EVM_VANITY_BELL")]
+    bell: bool,
+    
+    /// Also notify every N attempts (default 100,000,000)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "100000000", value_parser = clap::value_parser!(u64).range(1..), env = "EVM_VANITY_NOTIFY_PROGRESS")]
+    notify_progress: Option<u64>,
+    
+    /// Send a test message to the --notify services (and the desktop with --notify-desktop) and exit
+    #[arg(long)]
+    notify_test: bool,
 }
 
-struct WalletInfo {
-    address: String,
-    private_key: String,
-    mnemonic: Option<String>,
+// Options that --create3 and --clone salt mining cannot honor, having no keys or a different search loop
+const SALT_MINING_CONFLICTS: [&str; 48] = [
+    "word", "pattern_file", "alt", "score_config", "score_by", "maximize", "palindrome", "repeating", "fuzzy_distance", "at_offset", "exclude", "exclude_prefix", "exclude_suffix",
+    "or_prefix", "or_suffix",
+    "from_mnemonic", "key_min", "key_max", "seed", "stream", "server", "client", "keystore_dir", "save_dir", "autosave", "bip38", "shamir_split",
+    "qr", "qr_secret", "qr_file", "output_file", "show_public_key", "output_xprv", "output_xpub", "derive_siblings", "show_derived", "metrics_addr", "notify", "notify_desktop", "bell", "verify_rpc",
+    "pin_cores", "cores", "gpu_device", "gpu_batch_size", "respawn_workers", "chain_id", "track_near_miss",
+];
+
+// Split-key searches have no private key and run their own search loop, like salt mining
+const SPLIT_KEY_CONFLICTS: [&str; 50] = [
+    "word", "pattern_file", "alt", "score_config", "score_by", "maximize", "palindrome", "repeating", "fuzzy_distance", "at_offset", "exclude", "exclude_prefix", "exclude_suffix",
+    "or_prefix", "or_suffix", "mode",
+    "from_mnemonic", "key_min", "key_max", "seed", "stream", "server", "client", "keystore_dir", "save_dir", "autosave", "bip38", "shamir_split",
+    "qr", "qr_secret", "qr_file", "output_file", "show_public_key", "output_xprv", "output_xpub", "derive_siblings", "show_derived", "metrics_addr", "notify", "notify_desktop", "bell", "verify_rpc", "salt_target",
+    "pin_cores", "cores", "gpu_device", "gpu_batch_size", "respawn_workers", "chain_id", "track_near_miss",
+];
+
+// Watch-only searches never hold a private key to export, and run their own search loop too
+const WATCH_ONLY_CONFLICTS: [&str; 52] = [
+    "word", "pattern_file", "alt", "score_config", "score_by", "maximize", "palindrome", "repeating", "fuzzy_distance", "at_offset", "exclude", "exclude_prefix", "exclude_suffix",
+    "or_prefix", "or_suffix", "mode",
+    "from_mnemonic", "key_min", "key_max", "seed", "stream", "server", "client", "keystore_dir", "save_dir", "autosave", "bip38", "shamir_split", "show_private_key",
+    "qr", "qr_secret", "qr_file", "output_file", "show_public_key", "output_xprv", "output_xpub", "derive_siblings", "show_derived", "metrics_addr", "notify", "notify_desktop", "bell", "verify_rpc", "salt_target",
+    "search_for_pubkey", "pin_cores", "cores", "gpu_device", "gpu_batch_size", "respawn_workers", "chain_id", "track_near_miss",
+];
+
+// --start-key scans walk fixed sub-ranges in their own branch of the search, with no RNG
+const START_KEY_CONFLICTS: [&str; 16] = [
+    "key_min", "key_max", "from_mnemonic", "seed", "extra_entropy", "entropy_prompt", "maximize", "score_config", "score_by",
+    "server", "client", "salt_target", "search_for_pubkey", "watch_only", "derive_siblings", "show_derived",
+];
+
+// The distributed server only searches plain prefixes and suffixes of random keys
+const SERVER_CONFLICTS: [&str; 17] = [
+    "word", "pattern_file", "alt", "score_config", "score_by", "palindrome", "repeating", "fuzzy_distance", "from_mnemonic", "key_min", "key_max", "seed", "stream", "chain_id",
+    "save_dir", "autosave", "track_near_miss",
+];
+// Clients take the pattern from the server
+const CLIENT_CONFLICTS: [&str; 15] = [
+    "server", "pattern", "prefix", "suffix", "wrap", "word", "pattern_file", "score_config", "score_by", "palindrome", "repeating", "chain_id", "save_dir", "autosave",
+    "track_near_miss",
+];
+
+// --stdin-jobs and --api take their patterns from the jobs and run them on the REST API's search loop
+const JOB_CONFLICTS: [&str; 34] = [
+    "pattern", "prefix", "suffix", "wrap", "word", "pattern_file", "alt", "or_prefix", "or_suffix", "score_config", "score_by", "maximize", "palindrome",
+    "repeating", "fuzzy_distance", "at_offset", "exclude", "exclude_prefix", "exclude_suffix", "from_mnemonic", "key_min", "key_max", "start_key", "seed", "stream",
+    "server", "client", "salt_target", "search_for_pubkey", "watch_only", "chain_id", "save_dir", "autosave", "track_near_miss",
+];
+
+// --or-prefix/--or-suffix only extend a plain --prefix/--suffix search
+const OR_PATTERN_CONFLICTS: [&str; 11] =
+    ["word", "pattern_file", "wrap", "score_config", "score_by", "maximize", "palindrome", "repeating", "fuzzy_distance", "server", "client"];
+
+// User entropy only goes into the keys of the main search loop
+const ENTROPY_CONFLICTS: [&str; 7] = ["seed", "from_mnemonic", "salt_target", "search_for_pubkey", "watch_only", "server", "client"];
+
+// --alt pairs replace every other way of giving the pattern
+const ALT_CONFLICTS: [&str; 15] = [
+    "pattern", "prefix", "suffix", "or_prefix", "or_suffix", "wrap", "word", "pattern_file", "score_config", "score_by", "maximize", "palindrome", "repeating",
+    "server", "client",
+];
+
+#[derive(ValueEnum, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum KeystoreKdf {
+    /// PBKDF2-HMAC-SHA256, 2048 iterations
+    Pbkdf2,
+    /// scrypt with Geth's standard parameters (n=262144, r=8, p=1)
+    Scrypt,
+}
+
+impl From<KeystoreKdf> for keystore::Kdf {
+    fn from(kdf: KeystoreKdf) -> Self {
+        match kdf {
+            KeystoreKdf::Pbkdf2 => keystore::Kdf::default(),
+            KeystoreKdf::Scrypt => keystore::Kdf::Scrypt { log_n: 18, r: 8, p: 1 },
+        }
+    }
+}
+
+fn parse_rpc_url(value: &str) -> Result<String, String> {
+    if value.starts_with("http://") || value.starts_with("https://") {
+        Ok(value.to_string())
+    } else {
+        Err("expected an http:// or https:// URL".to_string())
+    }
+}
+
+fn parse_entropy_bits(value: &str) -> Result<usize, String> {
+    match value {
+        "128" | "160" | "192" | "224" | "256" => Ok(value.parse().expect("matched digits")),
+        _ => Err(format!("'{}' is not supported, use 128, 160, 192, 224 or 256", value)),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Threads {
+    Count(usize),
+    AutoTune,
+}
+
+fn parse_threads(value: &str) -> Result<Threads, String> {
+    if value == "auto-tune" {
+        return Ok(Threads::AutoTune);
+    }
+    match value.parse() {
+        Ok(0) => Err("at least one thread is needed".to_string()),
+        Ok(threads) => Ok(Threads::Count(threads)),
+        Err(_) => Err(format!("'{}' is not a thread count or 'auto-tune'", value)),
+    }
+}
+
+// Seconds, with an optional s, m, h or d unit
+fn parse_duration(value: &str) -> Result<u64, String> {
+    let (number, unit) = match value.trim().find(|c: char| !c.is_ascii_digit()) {
+        Some(split) => value.trim().split_at(split),
+        None => (value.trim(), "s"),
+    };
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(format!("'{}' is not a duration like 90m, 12h or 30d", value)),
+    };
+    match number.parse::<u64>() {
+        Ok(0) => Err("the duration must be positive".to_string()),
+        Ok(number) => number.checked_mul(seconds).ok_or_else(|| format!("'{}' is too long", value)),
+        Err(_) => Err(format!("'{}' is not a duration like 90m, 12h or 30d", value)),
+    }
+}
+
+// `PREFIX:SUFFIX` for --alt, where either side may be empty
+fn parse_alternative(value: &str) -> Result<PatternEntry, String> {
+    let (prefix, suffix) = value.split_once(':').ok_or_else(|| format!("'{}' is not PREFIX:SUFFIX, e.g. dead:beef, dead: or :beef", value))?;
+    if prefix.is_empty() && suffix.is_empty() {
+        return Err("at least one of the prefix and suffix is needed".to_string());
+    }
+    for pattern in [prefix, suffix] {
+        validate_pattern(pattern).map_err(|error_msg| error_msg.trim_start_matches("❌ ").to_string())?;
+    }
+    validate_lengths(Some(prefix), Some(suffix))?;
+    let side = |pattern: &str| (!pattern.is_empty()).then(|| pattern.to_string());
+    Ok(PatternEntry::new(side(prefix), side(suffix)))
+}
+
+// The --alt pairs with the most specific first, so a match is reported by the longest pair
+// it matches, and each pair whose matches a broader pair already finds, with that pair
+fn alternative_set(alternatives: &[PatternEntry], case_sensitive: bool) -> (PatternSet, Vec<(PatternEntry, PatternEntry)>) {
+    let mut entries = alternatives.to_vec();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.fixed_len()));
+    let covered = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, entry)| entries[i + 1..].iter().find(|broader| broader.covers(entry, case_sensitive)).map(|broader| (entry.clone(), broader.clone())))
+        .collect();
+    (PatternSet::new(entries), covered)
+}
+
+// `K-of-N` for --shamir, as a (threshold, shares) pair
+fn parse_shamir(value: &str) -> Result<(u8, u8), String> {
+    let (threshold, shares) = value
+        .split_once("-of-")
+        .and_then(|(threshold, shares)| Some((threshold.parse::<u8>().ok()?, shares.parse::<u8>().ok()?)))
+        .ok_or_else(|| format!("'{}' is not K-of-N, e.g. 2-of-3", value))?;
+    if shares < 2 {
+        return Err("at least 2 shares are needed".to_string());
+    }
+    if threshold == 0 || threshold > shares {
+        return Err(format!("the threshold must be between 1 and {}", shares));
+    }
+    Ok((threshold, shares))
+}
+
+// The --score-prefix target as nibbles
+fn parse_score_prefix(value: &str) -> Result<Box<[u8]>, String> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if digits.is_empty() || digits.len() > 40 {
+        return Err(format!("expected 1 to 40 hex characters, got {}", digits.len()));
+    }
+    digits
+        .chars()
+        .map(|c| c.to_digit(16).map(|nibble| nibble as u8).ok_or_else(|| format!("'{}' is not a hex character", c)))
+        .collect()
+}
+
+// BIP39 phrases have 3 words per 32 bits of entropy
+fn parse_words(value: &str) -> Result<usize, String> {
+    match value {
+        "12" | "15" | "18" | "21" | "24" => Ok(value.parse().expect("matched digits")),
+        _ => Err(format!("'{}' is not supported, use 12, 15, 18, 21 or 24", value)),
+    }
+}
+
+fn parse_repeating_char(value: &str) -> Result<u8, String> {
+    let mut chars = value.chars();
+    match (chars.next().and_then(|c| c.to_digit(16)), chars.next()) {
+        (Some(nibble), None) => Ok(nibble as u8),
+        _ => Err(format!("'{}' is not a single hexadecimal character", value)),
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum SearchMode {
+    /// Random private keys (fastest)
+    Raw,
+    /// Standards-compliant HD wallets from fresh BIP39 mnemonics
+    Mnemonic,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "snake_case")]
+enum ScoreBy {
+    /// Leading '0' characters
+    LeadingZeros,
+    /// Longest run of one character anywhere in the address
+    MaxRepeat,
+    /// Leading characters mirrored by the trailing ones
+    PalindromeScore,
+    /// Leading characters equal to --score-prefix
+    PrefixMatchLength,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum MaskAlign {
+    /// From the first byte of the address, padding short inputs with zeros after them
+    Start,
+    /// Up to the last byte of the address, padding short inputs with zeros before them
+    End,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum NotifyService {
+    /// A Telegram bot message (--telegram-token, --telegram-chat)
+    Telegram,
+    /// A Discord webhook message (--discord-webhook)
+    Discord,
+}
+
+// The --notify services with their credentials, each checked to be present
+fn notify_channels(args: &SearchArgs) -> Vec<notify::Channel> {
+    let mut services = args.notify.clone();
+    services.dedup();
+    services
+        .into_iter()
+        .map(|service| match (service, &args.telegram_token, &args.telegram_chat, &args.discord_webhook) {
+            (NotifyService::Telegram, Some(token), Some(chat), _) => notify::Channel::Telegram { token: token.clone(), chat: chat.clone() },
+            (NotifyService::Discord, _, _, Some(webhook)) => notify::Channel::Discord { webhook: webhook.clone() },
+            (NotifyService::Telegram, ..) => Cli::command()
+                .error(clap::error::ErrorKind::MissingRequiredArgument, "--notify telegram needs --telegram-token and --telegram-chat")
+                .exit(),
+            (NotifyService::Discord, ..) => Cli::command()
+                .error(clap::error::ErrorKind::MissingRequiredArgument, "--notify discord needs --discord-webhook")
+                .exit(),
+        })
+        .chain(args.notify_desktop.then_some(notify::Channel::Desktop))
+        .collect()
+}
+
+// --notify-test: one hello message per service, failing if any was not delivered
+fn run_notify_test(channels: &[notify::Channel]) -> ! {
+    let agent = notify::agent();
+    let mut failed = false;
+    for channel in channels {
+        match channel.send(&agent, "👋 Hello from evm-vanity: notifications are working") {
+            Ok(()) => success!("✅ Sent a test message to {}", channel.name()),
+            Err(error_msg) => {
+                error!("❌ {} test message failed: {}", channel.name(), error_msg);
+                failed = true;
+            }
+        }
+    }
+    std::process::exit(if failed { 1 } else { 0 });
 }
 
-// Fast address generation without mnemonic for searching
-fn generate_address_fast(secp: &Secp256k1<secp256k1::All>) -> (String, SecretKey) {
-    // Generate random private key
-    let private_key = SecretKey::new(&mut OsRng);
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Search for a vanity address (the default when no subcommand is given)
+    Search(Box<SearchArgs>),
+    
+    /// Estimate how long a prefix/suffix search takes on this machine
+    Estimate(EstimateArgs),
+    
+    /// Decrypt a BIP38 encrypted private key produced with --bip38
+    Bip38Decrypt {
+        /// BIP38 encrypted key (starts with 6P)
+        #[arg(long)]
+        key: String,
+        
+        /// Password used for encryption (prompted for if omitted)
+        #[arg(long)]
+        password: Option<String>,
+    },
+    
+    /// Reconstruct a private key from Shamir shares produced with --shamir or --shamir-shares
+    #[command(alias = "recover")]
+    Reconstruct {
+        /// A share in hex (repeat for each share)
+        #[arg(long = "share", value_name = "HEX", required = true)]
+        shares: Vec<String>,
+    },
+    
+    /// Derive the address of an existing private key or mnemonic
+    Verify(VerifyArgs),
+    
+    /// Check a CSV of private keys against the addresses they should derive
+    VerifyBatch(VerifyBatchArgs),
+    
+    /// Add a partial key found with --search-for-pubkey to your private key
+    Combine {
+        /// Private key whose public key was searched (64 hex characters, 0x optional)
+        #[arg(long, value_name = "HEX")]
+        private_key: String,
+        
+        /// Partial key the searcher found (64 hex characters, 0x optional)
+        #[arg(long, value_name = "HEX")]
+        partial_key: String,
+        
+        /// Address the searcher reported; exit with code 1 unless the combined key has this address
+        #[arg(long, value_name = "0x...")]
+        address: Option<String>,
+    },
+    
+    /// Run a REST API that searches for prefixes and suffixes as background jobs
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        
+        /// Address to listen on; results include private keys, so expose it with care
+        #[arg(long, value_name = "IP", default_value = "127.0.0.1")]
+        bind: std::net::IpAddr,
+    },
+    
+    /// Run a gRPC service that searches for prefixes and suffixes (see proto/vanity.proto)
+    Grpc {
+        /// Port to listen on
+        #[arg(long, default_value_t = 50051)]
+        port: u16,
+        
+        /// Address to listen on; results include private keys, so expose it with care
+        #[arg(long, value_name = "IP", default_value = "127.0.0.1")]
+        bind: std::net::IpAddr,
+    },
+    
+    /// Measure key generation throughput without pattern matching
+    Bench(BenchArgs),
+    
+    /// Print the standings of a --leaderboard file
+    Leaderboard {
+        /// Leaderboard file written by --leaderboard
+        path: std::path::PathBuf,
+        
+        /// Print the entries as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Print a shell completion script to stdout
+    #[command(after_long_help = "Installation:
+  bash:        evm-vanity completions bash > ~/.local/share/bash-completion/completions/evm-vanity
+  zsh:         evm-vanity completions zsh > ~/.zfunc/_evm-vanity
+               (with `fpath+=~/.zfunc; autoload -Uz compinit; compinit` in ~/.zshrc)
+  fish:        evm-vanity completions fish > ~/.config/fish/completions/evm-vanity.fish
+  powershell:  evm-vanity completions powershell >> $PROFILE
+  elvish:      evm-vanity completions elvish >> ~/.config/elvish/rc.elv")]
+    Completions {
+        /// Shell to generate the script for
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group = clap::ArgGroup::new("pattern").required(true).args(["prefix", "suffix"]).multiple(true))]
+struct EstimateArgs {
+    /// Prefix pattern to match
+    #[arg(long)]
+    prefix: Option<String>,
+    
+    /// Suffix pattern to match
+    #[arg(long)]
+    suffix: Option<String>,
+    
+    /// Enable case-sensitive matching (EIP-55 checksum)
+    #[arg(short, long)]
+    case_sensitive: bool,
+    
+    /// Search rate in addresses per second (measured on all cores when omitted)
+    #[arg(long, value_name = "ADDR_PER_SEC")]
+    rate: Option<f64>,
+}
+
+#[derive(clap::Args, Debug)]
+#[command(group = clap::ArgGroup::new("key_material").required(true).args(["private_key", "mnemonic"]))]
+struct VerifyArgs {
+    /// Private key in hex (64 characters, 0x optional)
+    #[arg(long, value_name = "HEX")]
+    private_key: Option<String>,
+    
+    /// BIP39 mnemonic phrase
+    #[arg(long, value_name = "WORDS")]
+    mnemonic: Option<String>,
+    
+    /// Derivation path of the address with --mnemonic
+    #[arg(long, alias = "path", value_name = "PATH", default_value = "m/44'/60'/0'/0/0", value_parser = clap::value_parser!(hd::DerivationPath), requires = "mnemonic")]
+    derivation_path: hd::DerivationPath,
+    
+    /// Expected address; exit with code 1 unless the derived address is this one
+    #[arg(long, value_name = "0x...")]
+    address: Option<String>,
+    
+    /// Also print the BIP32 root xprv and the xprv at --derivation-path
+    #[arg(long, conflicts_with = "private_key")]
+    output_xprv: bool,
+    
+    /// Also print the BIP32 xpub at --derivation-path
+    #[arg(long, conflicts_with = "private_key")]
+    output_xpub: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct VerifyBatchArgs {
+    /// CSV file with a `private_key,expected_address` header
+    #[arg(long, value_name = "PATH")]
+    input: std::path::PathBuf,
+    
+    /// Also write the row, status and derived address of each row to this CSV file
+    #[arg(long, value_name = "PATH")]
+    output_csv: Option<std::path::PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct BenchArgs {
+    /// Number of threads to use (default is number of CPU cores)
+    #[arg(short, long, value_parser = clap::value_parser!(u64).range(1..))]
+    threads: Option<u64>,
+    
+    /// How long to run, in seconds
+    #[arg(long, value_name = "SECS", default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    duration: u64,
+    
+    /// Pin each thread to a CPU core of its own, as in a search
+    #[arg(long)]
+    pin_cores: bool,
+    
+    /// Only run on these cores, e.g. 0-7,16-23 (one thread per core unless --threads is given)
+    #[arg(long, value_name = "LIST", value_parser = affinity::parse_core_list)]
+    cores: Option<Box<[usize]>>,
+}
+
+// The prefix and suffix a search matches, resolved from the pattern options and validated
+#[derive(Debug, PartialEq)]
+struct SearchConfig {
+    prefix: Option<String>,
+    suffix: Option<String>,
+    prefix_alternatives: Vec<String>,
+    suffix_alternatives: Vec<String>,
+}
+
+// Why a SearchConfig could not be built from the search options
+#[derive(Debug, PartialEq)]
+enum SearchConfigError {
+    // Options that cannot be combined, reported by clap like its own usage errors
+    Usage(clap::error::ErrorKind, String),
+    // A pattern that no address can match
+    Invalid(String),
+}
+
+impl From<String> for SearchConfigError {
+    fn from(error_msg: String) -> SearchConfigError {
+        SearchConfigError::Invalid(error_msg)
+    }
+}
+
+// Combinations of options clap cannot check itself, as they depend on --mode or on each other's values
+fn check_option_combinations(args: &SearchArgs) -> Result<(), SearchConfigError> {
+    use clap::error::ErrorKind;
+    let usage = |kind, error_msg: &str| Err(SearchConfigError::Usage(kind, error_msg.to_string()));
+    
+    if (args.json || args.output_format == OutputFormat::Json) && (args.qr || args.qr_secret) {
+        return usage(ErrorKind::ArgumentConflict, "terminal QR codes cannot be combined with JSON output; use --qr-file");
+    }
+    // Anyone who knows the seed can regenerate every key, so persisting them needs an explicit opt-in
+    let saves_keys = args.keystore_dir.is_some() || args.output_file.is_some() || args.save_dir.is_some() || args.autosave;
+    if args.seed.is_some() && saves_keys && !args.seeded_keys_acknowledged {
+        return usage(
+            ErrorKind::ArgumentConflict,
+            "--seed makes every key predictable; pass --i-understand-seeded-keys-are-insecure to save them with --keystore-dir, --output-file or --autosave",
+        );
+    }
+    if let (Some(shares), Some(threshold)) = (args.shamir_shares, args.shamir_threshold) {
+        if threshold > shares {
+            return usage(ErrorKind::ValueValidation, &format!("--shamir-threshold ({}) cannot exceed --shamir-shares ({})", threshold, shares));
+        }
+    }
+    if (args.notify_progress.is_some() || args.notify_test) && args.notify.is_empty() && !args.notify_desktop {
+        return usage(ErrorKind::MissingRequiredArgument, "--notify-progress and --notify-test need --notify or --notify-desktop");
+    }
+    
+    // Raw keys are shown as the 24-word mnemonic encoding all 32 bytes of the key, which
+    // a shorter phrase could not hold
+    if args.words.is_some() && args.mode != SearchMode::Mnemonic {
+        return usage(
+            ErrorKind::ArgumentConflict,
+            "--words only applies to --mode mnemonic; raw private keys are always shown as 24-word mnemonics",
+        );
+    }
+    let mnemonic_only = [
+        (args.output_xprv || args.output_xpub, "--output-xprv/--output-xpub"),
+        (args.derive_siblings.is_some(), "--derive-siblings"),
+        (args.show_derived.is_some(), "--show-derived"),
+    ];
+    let raw_only = [
+        (args.score_config.is_some(), "--score-config"),
+        (args.maximize.is_some(), "--maximize"),
+        (args.score_by.is_some(), "--score-by"),
+        (args.track_near_miss, "--track-near-miss"),
+        (args.key_min.is_some() || args.key_max.is_some(), "--key-min/--key-max"),
+    ];
+    let (mode_only, other_mode) = match args.mode {
+        SearchMode::Raw => (mnemonic_only.as_slice(), "mnemonic"),
+        SearchMode::Mnemonic => (raw_only.as_slice(), "raw"),
+    };
+    if let Some((_, option)) = mode_only.iter().find(|(used, _)| *used) {
+        return usage(ErrorKind::ArgumentConflict, &format!("{} only works with --mode {}", option, other_mode));
+    }
+    
+    // Maximize and top-K modes keep going until a budget of time or attempts runs out
+    for (used, option) in [(args.maximize.is_some(), "--maximize"), (args.score_by.is_some(), "--score-by")] {
+        if used && args.time_limit.is_none() && args.max_attempts.is_none() {
+            return usage(ErrorKind::MissingRequiredArgument, &format!("{} needs --time-limit or --max-attempts to know when to stop", option));
+        }
+    }
+    Ok(())
+}
+
+impl TryFrom<&SearchArgs> for SearchConfig {
+    type Error = SearchConfigError;
+    
+    fn try_from(args: &SearchArgs) -> Result<SearchConfig, SearchConfigError> {
+        check_option_combinations(args)?;
+        
+        let (prefix, suffix) = match (&args.pattern, &args.prefix, &args.suffix) {
+            // --wrap uses the same pattern at both ends
+            (None, None, None) if args.wrap.is_some() => {
+                let wrap = args.wrap.as_deref().expect("guarded by the match arm");
+                validate_pattern(wrap).map_err(|error_msg| format!("Invalid wrap pattern:\n{}", error_msg))?;
+                if 2 * pattern_len(wrap) > 40 {
+                    return Err(format!("--wrap '{}' needs 2 × {} = {} characters but an address only has 40",
+                                       wrap, pattern_len(wrap), 2 * pattern_len(wrap)).into());
+                }
+                return Ok(SearchConfig {
+                    prefix: Some(wrap.to_string()),
+                    suffix: Some(wrap.to_string()),
+                    prefix_alternatives: Vec::new(),
+                    suffix_alternatives: Vec::new(),
+                });
+            }
+            
+            // New style: --prefix and/or --suffix
+            (None, prefix, suffix) if prefix.is_some() || suffix.is_some() => (prefix.clone(), suffix.clone()),
+            
+            // Legacy style: -p pattern with -s flag
+            (Some(pattern), None, None) => {
+                if args.suffix_mode {
+                    (None, Some(pattern.clone()))
+                } else {
+                    (Some(pattern.clone()), None)
+                }
+            }
+            
+            // Invalid combinations
+            (Some(_), _, _) => {
+                return Err("Cannot use -p/--pattern with --prefix/--suffix. Use either:\n  \
+                            • Legacy: -p <pattern> [-s]\n  \
+                            • New: --prefix <pattern> and/or --suffix <pattern>".to_string().into());
+            }
+            
+            (None, None, None)
+                if args.client_mode || args.stdin_jobs || args.api.is_some() || args.word.is_some() || args.repeating.is_some() || args.pattern_file.is_some() || args.palindrome.is_some()
+                    || args.score_config.is_some() || args.score_by.is_some() || args.maximize.is_some() || args.hook_flags.is_some() || args.mask.is_some()
+                    || args.leading_zero_bytes.is_some() || !args.at_offset.is_empty() || !args.or_prefix.is_empty() || !args.or_suffix.is_empty() || !args.alt.is_empty() =>
+            {
+                (None, None)
+            }
+            
+            (None, _, _) => {
+                return Err("Must specify at least one pattern:\n  \
+                            • --prefix <pattern>: Match prefix\n  \
+                            • --suffix <pattern>: Match suffix\n  \
+                            • --prefix <pattern> --suffix <pattern>: Match both\n  \
+                            • --or-prefix <pattern> (repeatable): Match any of several prefixes\n  \
+                            • --alt <prefix>:<suffix> (repeatable): Match any of several prefix and suffix pairs\n  \
+                            • --word <text>: Match a word spelled in hex\n  \
+                            • --repeating <n>: Match n identical leading characters\n  \
+                            • --pattern-file <path>: Match any pattern listed in a file\n  \
+                            • --palindrome [<k>]: Match addresses whose first k characters mirror the last k\n  \
+                            • --score-config <path.toml>: Keep the best-scoring address until stopped\n  \
+                            • --maximize <char> --time-limit <secs>: Keep the longest leading run of a character\n  \
+                            • --score-by <criterion> --time-limit <secs>: Keep the --top-k best addresses by one criterion\n  \
+                            • -p <pattern>: Legacy format".to_string().into());
+            }
+        };
+        
+        if let Some(prefix) = &prefix {
+            validate_pattern(prefix).map_err(|error_msg| format!("Invalid prefix pattern:\n{}", error_msg))?;
+        }
+        if let Some(suffix) = &suffix {
+            validate_pattern(suffix).map_err(|error_msg| format!("Invalid suffix pattern:\n{}", error_msg))?;
+        }
+        for (kind, pattern) in args.or_prefix.iter().map(|pattern| ("or-prefix", pattern)).chain(args.or_suffix.iter().map(|pattern| ("or-suffix", pattern))) {
+            validate_pattern(pattern).map_err(|error_msg| format!("Invalid {} pattern:\n{}", kind, error_msg))?;
+            if pattern_len(pattern) > 40 {
+                return Err(format!("{} '{}' is {} characters but an address only has 40", kind, pattern, pattern_len(pattern)).into());
+            }
+        }
+        validate_lengths(prefix.as_deref(), suffix.as_deref())?;
+        
+        // An alternative that contradicts the required pattern can never match; the search
+        // goes on with the others as long as one is left
+        let mut entry = PatternEntry {
+            prefix,
+            suffix,
+            prefix_alternatives: args.or_prefix.clone(),
+            suffix_alternatives: args.or_suffix.clone(),
+        };
+        let conflicts: Vec<String> = entry
+            .conflicts()
+            .into_iter()
+            .map(|(required, alternative)| {
+                warning!("⚠️  '{}' can never match together with '{}'; ignoring it", alternative, required);
+                alternative.to_string()
+            })
+            .collect();
+        if !conflicts.is_empty() {
+            let prefix_count = entry.prefix_alternatives.len();
+            entry.prefix_alternatives.retain(|alternative| !conflicts.contains(alternative));
+            if prefix_count > 0 && entry.prefix_alternatives.is_empty() {
+                return Err(format!("No --or-prefix can match together with --prefix '{}'", entry.prefix.unwrap_or_default()).into());
+            }
+            let suffix_count = entry.suffix_alternatives.len();
+            entry.suffix_alternatives.retain(|alternative| !conflicts.contains(alternative));
+            if suffix_count > 0 && entry.suffix_alternatives.is_empty() {
+                return Err(format!("No --or-suffix can match together with --suffix '{}'", entry.suffix.unwrap_or_default()).into());
+            }
+        }
+        Ok(SearchConfig {
+            prefix: entry.prefix,
+            suffix: entry.suffix,
+            prefix_alternatives: entry.prefix_alternatives,
+            suffix_alternatives: entry.suffix_alternatives,
+        })
+    }
+}
+
+// Secrets are wiped from the heap when the wallet info is dropped
+struct WalletInfo {
+    address: String,
+    private_key: Zeroizing<String>,
+    mnemonic: Option<Zeroizing<String>>,
+    derivation_path: Option<String>,
+    public_key_uncompressed: String,
+    public_key_compressed: String,
+}
+
+impl WalletInfo {
+    fn private_key_bytes(&self) -> Zeroizing<[u8; 32]> {
+        let mut key_bytes = Zeroizing::new([0u8; 32]);
+        hex::decode_to_slice(self.private_key.trim_start_matches("0x"), key_bytes.as_mut())
+            .expect("private key is 32 bytes of hex");
+        key_bytes
+    }
+}
+
+fn format_address(address: &[u8; 20]) -> String {
+    format!("0x{}", hex::encode(address))
+}
+
+fn address_from_secret_key(secp: &Secp256k1<secp256k1::All>, private_key: &SecretKey) -> String {
+    format_address(&address_bytes_from_secret_key(secp, private_key))
+}
+
+// 0x-prefixed hex of the uncompressed (0x04 || x || y) and compressed public key
+fn public_key_hex(secp: &Secp256k1<secp256k1::All>, private_key: &SecretKey) -> (String, String) {
+    let public_key = PublicKey::from_secret_key(secp, private_key);
+    (
+        format!("0x{}", hex::encode(public_key.serialize_uncompressed())),
+        format!("0x{}", hex::encode(public_key.serialize())),
+    )
+}
+
+// Read a user-supplied mnemonic: prompted for when `source` is empty (so it never appears in argv
+// or shell history), taken as is when it is a phrase, and otherwise read from the file it names
+fn read_mnemonic(source: &str) -> Result<Mnemonic, String> {
+    let phrase = Zeroizing::new(if source.is_empty() {
+        rpassword::prompt_password("📝 Mnemonic: ").map_err(|e| format!("Failed to read mnemonic: {}", e))?
+    } else if source.trim().contains(char::is_whitespace) && !std::path::Path::new(source).exists() {
+        warning!("⚠️  A mnemonic passed on the command line is visible to other users and kept in shell history;");
+        eprintln!("   prefer --from-mnemonic <FILE> or --from-mnemonic without a value to be prompted");
+        source.to_string()
+    } else {
+        std::fs::read_to_string(source).map_err(|e| format!("Failed to read mnemonic file '{}': {}", source, e))?
+    });
+    
+    Mnemonic::parse(phrase.trim()).map_err(|e| format!("Invalid mnemonic: {}", e))
+}
+
+fn format_with_commas(value: u64) -> String {
+    let digits = value.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(c);
+    }
+    formatted
+}
+
+// Entropy per generated mnemonic: --words, or else --entropy-bits
+fn mnemonic_entropy_bits(args: &SearchArgs) -> usize {
+    args.words.map_or(args.entropy_bits, |words| words / 3 * 32)
+}
+
+// Fresh BIP39 mnemonic with `entropy_bits` bits of entropy from the worker's RNG
+fn generate_mnemonic(rng: &mut ChaCha20Rng, entropy_bits: usize) -> Mnemonic {
+    let mut entropy = [0u8; 32];
+    let entropy = &mut entropy[..entropy_bits / 8];
+    rng.fill_bytes(entropy);
+    Mnemonic::from_entropy(entropy).expect("valid BIP39 entropy length")
+}
+
+// Generate full wallet info only when match is found
+fn generate_wallet_info(private_key: SecretKey) -> WalletInfo {
+    let secret_bytes = Zeroizing::new(private_key.secret_bytes());
+    let private_key_hex = Zeroizing::new(format!("0x{}", hex::encode(secret_bytes.as_ref())));
+    
+    // Generate mnemonic from private key entropy
+    let mnemonic = match Mnemonic::from_entropy(secret_bytes.as_ref()) {
+        Ok(m) => Some(Zeroizing::new(m.to_string())),
+        Err(_) => None,
+    };
+    
+    // Regenerate address for the wallet info
+    let secp = Secp256k1::new();
+    let public_key = PublicKey::from_secret_key(&secp, &private_key);
+    let public_key_bytes = public_key.serialize_uncompressed();
+    let public_key_hash = &public_key_bytes[1..];
+    let mut hasher = Keccak256::new();
+    hasher.update(public_key_hash);
+    let hash = hasher.finalize();
+    let address_bytes = &hash[12..];
+    let address = format!("0x{}", hex::encode(address_bytes));
+    let (public_key_uncompressed, public_key_compressed) = public_key_hex(&secp, &private_key);
+    
+    WalletInfo {
+        address,
+        private_key: private_key_hex,
+        mnemonic,
+        derivation_path: None,
+        public_key_uncompressed,
+        public_key_compressed,
+    }
+}
+
+// Wallet info for a key derived from a BIP39 mnemonic, importable into any HD wallet
+fn generate_mnemonic_wallet_info(mnemonic: &Mnemonic, path: &hd::DerivationPath, private_key: SecretKey) -> WalletInfo {
+    let secp = Secp256k1::new();
+    
+    let secret_bytes = Zeroizing::new(private_key.secret_bytes());
+    let (public_key_uncompressed, public_key_compressed) = public_key_hex(&secp, &private_key);
+    
+    WalletInfo {
+        address: address_from_secret_key(&secp, &private_key),
+        private_key: Zeroizing::new(format!("0x{}", hex::encode(secret_bytes.as_ref()))),
+        mnemonic: Some(Zeroizing::new(mnemonic.to_string())),
+        derivation_path: Some(path.to_string()),
+        public_key_uncompressed,
+        public_key_compressed,
+    }
+}
+
+thread_local! {
+    // The RNG of a pool thread, lent to each rayon job it runs so the stream continues across jobs
+    static WORKER_RNG: RefCell<Option<ChaCha20Rng>> = const { RefCell::new(None) };
+    // Likewise the --verbose line budget, so a new job cannot start with a full bucket
+    static WORKER_BUCKET: RefCell<Option<throttle::TokenBucket>> = const { RefCell::new(None) };
+}
+
+// State of a rayon job, which counts its attempts on the counter of the pool thread running it
+struct WorkerState {
+    // Seeded once per thread from the OS (a syscall per candidate key would dominate raw
+    // mode), mixed with any --extra-entropy/--entropy-prompt bytes, or from --seed plus
+    // the thread index for reproducible runs
+    rng: ChaCha20Rng,
+    thread: usize,
+    attempts: Arc<counters::AttemptCounters>,
+    cpu_limiter: Option<throttle::CpuLimiter>,
+    // Attempts since the limiter last slept
+    unthrottled: u64,
+    // GEN: lines this thread may still print with --verbose
+    verbose: Option<throttle::TokenBucket>,
+    // The closest candidates this thread has seen with --track-near-miss
+    near_miss: nearmiss::Records,
+}
+
+impl WorkerState {
+    fn new(attempts: Arc<counters::AttemptCounters>, cpu_limit: Option<u8>, seed: Option<u64>, user_entropy: Option<&[u8]>, verbose_rate: Option<u32>) -> WorkerState {
+        let thread = rayon::current_thread_index().expect("workers run on the rayon pool");
+        let rng = WORKER_RNG.with(|rng| rng.borrow_mut().take()).unwrap_or_else(|| match (seed, user_entropy) {
+            (Some(seed), _) => ChaCha20Rng::seed_from_u64(seed.wrapping_add(thread as u64)),
+            (None, Some(user_entropy)) => entropy::seeded_rng(user_entropy),
+            (None, None) => ChaCha20Rng::from_entropy(),
+        });
+        WorkerState {
+            rng,
+            thread,
+            attempts,
+            // Sleeping between attempts only slows the search; counters and results are unaffected
+            cpu_limiter: cpu_limit.map(throttle::CpuLimiter::new),
+            unthrottled: 0,
+            verbose: verbose_rate.map(|rate| WORKER_BUCKET.with(|bucket| bucket.borrow_mut().take()).unwrap_or_else(|| throttle::TokenBucket::new(rate))),
+            near_miss: nearmiss::Records::default(),
+        }
+    }
+    
+    fn attempt(&mut self) {
+        if let Some(cpu_limiter) = self.cpu_limiter.as_mut() {
+            self.unthrottled += 1;
+            if self.unthrottled == THROTTLE_INTERVAL {
+                cpu_limiter.throttle(self.unthrottled);
+                self.unthrottled = 0;
+            }
+        }
+        self.attempts.add(self.thread, 1);
+    }
+    
+    // --verbose: print each candidate while the thread has line budget left
+    fn generated(&mut self, address: &[u8; 20]) {
+        if self.verbose.as_mut().is_some_and(|bucket| bucket.try_take()) {
+            eprintln!("GEN: {}", format_address(address));
+        }
+    }
+}
+
+impl Drop for WorkerState {
+    fn drop(&mut self) {
+        WORKER_RNG.with(|rng| *rng.borrow_mut() = Some(self.rng.clone()));
+        if let Some(bucket) = self.verbose.take() {
+            WORKER_BUCKET.with(|slot| *slot.borrow_mut() = Some(bucket));
+        }
+    }
+}
+
+fn format_difficulty(difficulty: f64) -> String {
+    if difficulty < 1e15 {
+        format_with_commas(difficulty.round() as u64)
+    } else {
+        format!("{:.2e}", difficulty)
+    }
+}
+
+// Derive an address through the same functions as the search, to check key material
+fn run_verify(args: &VerifyArgs) -> Result<(), Box<dyn std::error::Error>> {
+    // Checked first so a typo in the address is not reported as a mismatch
+    let expected = args.address.as_deref().map(parse_expected_address);
+    let path = &args.derivation_path;
+    
+    let wallet = match (args.private_key.as_deref(), args.mnemonic.as_deref()) {
+        (Some(private_key), _) => generate_wallet_info(parse_private_key(private_key, "private key")),
+        (None, Some(phrase)) => {
+            let mnemonic = match Mnemonic::parse(phrase.trim()) {
+                Ok(mnemonic) => mnemonic,
+                Err(e) => {
+                    error!("❌ Invalid mnemonic: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            let secp = Secp256k1::new();
+            let master = hd::ExtendedPrivKey::from_seed(Zeroizing::new(mnemonic.to_seed("")).as_ref());
+            let child = master.derive_path(&secp, path);
+            generate_mnemonic_wallet_info(&mnemonic, path, child.secret_key)
+        }
+        (None, None) => unreachable!("clap requires --private-key or --mnemonic"),
+    };
+    
+    println!("📍 Address: {}", wallet.address);
+    println!("🔠 Checksummed: {}", to_checksum_address(&wallet.address));
+    println!("🔑 Public Key: {}", wallet.public_key_uncompressed);
+    if let Some(path) = &wallet.derivation_path {
+        println!("🧭 Derivation Path: {}", path);
+    }
+    if let Some(keys) = wallet_extended_keys(&wallet).filter(|_| args.output_xprv || args.output_xpub) {
+        if args.output_xprv {
+            println!("🌱 Root xprv: {}", keys.root_xprv);
+            println!("🗝️  xprv: {}", keys.xprv);
+        }
+        if args.output_xpub {
+            println!("👁️  xpub: {}", keys.xpub);
+        }
+    }
+    
+    if let Some(expected) = expected {
+        check_expected_address(&expected, &wallet.address);
+    }
+    
+    Ok(())
+}
+
+// Derives the address of every `private_key,expected_address` row of a CSV file in parallel and
+// compares it with the expected one; exits with code 1 if any row does not match
+fn run_verify_batch(args: &VerifyBatchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let input = args.input.display();
+    let mut reader = match csv::ReaderBuilder::new().trim(csv::Trim::All).from_path(&args.input) {
+        Ok(reader) => reader,
+        Err(e) => {
+            error!("❌ Failed to open {}: {}", input, e);
+            std::process::exit(1);
+        }
+    };
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            error!("❌ Failed to read {}: {}", input, e);
+            std::process::exit(1);
+        }
+    };
+    let column = |name: &str| headers.iter().position(|header| header == name);
+    let (Some(key_column), Some(address_column)) = (column("private_key"), column("expected_address")) else {
+        error!("❌ {} needs a private_key,expected_address header", input);
+        std::process::exit(1);
+    };
+    
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                error!("❌ Failed to read {}: {}", input, e);
+                std::process::exit(1);
+            }
+        };
+        let private_key = Zeroizing::new(record.get(key_column).unwrap_or_default().to_string());
+        rows.push((private_key, record.get(address_column).unwrap_or_default().to_string()));
+    }
+    if rows.is_empty() {
+        warning!("⚠️  {} has no rows to check", input);
+    }
+    
+    // Row numbers count from the first line after the header
+    let secp = Secp256k1::new();
+    let results: Vec<(bool, String)> = rows
+        .par_iter()
+        .enumerate()
+        .map(|(i, (private_key, expected))| match decode_private_key(private_key) {
+            Ok(private_key) => {
+                let derived = address_from_secret_key(&secp, &private_key);
+                let matches = derived[2..].eq_ignore_ascii_case(expected.strip_prefix("0x").unwrap_or(expected));
+                (matches, derived)
+            }
+            Err(error_msg) => {
+                warning!("⚠️  Row {}: invalid private key: {}", i + 1, error_msg);
+                (false, String::new())
+            }
+        })
+        .collect();
+    
+    let status = |matches: bool| if matches { "OK" } else { "MISMATCH" };
+    println!("{:>6}  {:<8}  derived_address", "row", "status");
+    for (i, (matches, derived)) in results.iter().enumerate() {
+        println!("{}", format!("{:>6}  {:<8}  {}", i + 1, status(*matches), derived).trim_end());
+    }
+    
+    if let Some(path) = &args.output_csv {
+        let written = csv::Writer::from_path(path).and_then(|mut writer| {
+            writer.write_record(["row", "status", "derived_address"])?;
+            for (i, (matches, derived)) in results.iter().enumerate() {
+                writer.write_record([(i + 1).to_string().as_str(), status(*matches), derived])?;
+            }
+            writer.flush().map_err(csv::Error::from)
+        });
+        if let Err(e) = written {
+            error!("❌ Failed to write {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+    
+    let mismatches = results.iter().filter(|(matches, _)| !matches).count();
+    if mismatches > 0 {
+        error!("❌ {} of {} rows do not match", mismatches, results.len());
+        std::process::exit(1);
+    }
+    success!("✅ All {} rows match", results.len());
+    Ok(())
+}
+
+// Adds the partial key of a split-key search to the private key whose public key was searched
+fn run_combine(private_key: &str, partial_key: &str, expected: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let expected = expected.map(parse_expected_address);
+    let private_key = parse_private_key(private_key, "private key");
+    let partial_key = parse_private_key(partial_key, "partial key");
+    let wallet = match splitkey::combine(&private_key, &partial_key) {
+        Ok(combined) => generate_wallet_info(combined),
+        Err(_) => {
+            error!("❌ The partial key is the negated private key, so their sum is zero");
+            std::process::exit(1);
+        }
+    };
+    
+    println!("📍 Address: {}", wallet.address);
+    println!("🔠 Checksummed: {}", to_checksum_address(&wallet.address));
+    println!("🔐 Private Key: {}", wallet.private_key.as_str());
+    
+    if let Some(expected) = expected {
+        check_expected_address(&expected, &wallet.address);
+    }
+    
+    Ok(())
+}
+
+// 64 hex characters, 0x optional, for a key between 1 and the curve order; exits otherwise
+fn parse_private_key(value: &str, name: &str) -> SecretKey {
+    match decode_private_key(value) {
+        Ok(private_key) => private_key,
+        Err(error_msg) => {
+            error!("❌ Invalid {}: {}", name, error_msg);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn decode_private_key(value: &str) -> Result<SecretKey, &'static str> {
+    let value = value.trim();
+    let mut key_bytes = Zeroizing::new([0u8; 32]);
+    if hex::decode_to_slice(value.strip_prefix("0x").unwrap_or(value), key_bytes.as_mut()).is_err() {
+        return Err("expected 64 hexadecimal characters");
+    }
+    SecretKey::from_slice(key_bytes.as_ref()).map_err(|_| "must be between 1 and the secp256k1 curve order")
+}
+
+// The 0x-prefixed address given to `verify --address` or `combine --address`; exits if malformed
+fn parse_expected_address(address: &str) -> String {
+    let address = address.trim();
+    let digits = address.strip_prefix("0x").unwrap_or(address);
+    if digits.len() != 40 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        error!("❌ Invalid address '{}': expected 40 hexadecimal characters", address);
+        std::process::exit(1);
+    }
+    format!("0x{}", digits)
+}
+
+// Exits with code 1 unless `address` is `expected`
+fn check_expected_address(expected: &str, address: &str) {
+    // Mixed case is an EIP-55 checksum; wrong casing hints at a mistyped address
+    let digits = &expected[2..];
+    let mixed_case = digits.chars().any(|c| c.is_ascii_uppercase()) && digits.chars().any(|c| c.is_ascii_lowercase());
+    if mixed_case && expected != to_checksum_address(&expected.to_lowercase()) {
+        warning!("⚠️  {} has mixed case but an invalid EIP-55 checksum", expected);
+    }
+    if expected.eq_ignore_ascii_case(address) {
+        success!("✅ Matches {}", expected);
+    } else {
+        error!("❌ Does not match {}", expected);
+        std::process::exit(1);
+    }
+}
+
+// Rough time to search a duration estimate, e.g. "3.2 hours"
+fn format_duration_estimate(secs: f64) -> String {
+    const UNITS: [(&str, f64); 5] = [("years", 365.25 * 86400.0), ("days", 86400.0), ("hours", 3600.0), ("minutes", 60.0), ("seconds", 1.0)];
+    if secs < 1.0 {
+        return "under a second".to_string();
+    }
+    let (unit, length) = UNITS.iter().copied().find(|&(_, length)| secs >= length).expect("secs is at least a second");
+    let value = secs / length;
+    if value >= 1e6 {
+        format!("{:.2e} {}", value, unit)
+    } else {
+        format!("{:.1} {}", value, unit)
+    }
+}
+
+// Warns about a search expected to take longer than --max-expected-duration and asks
+// whether to start it; without a terminal to ask on, only --yes lets it start
+fn confirm_expected_duration(args: &SearchArgs, expected_attempts: f64, threads: usize, core_plan: Option<&[Vec<usize>]>) {
+    // Faster than any CPU so far, so searches that fit the limit even at this rate skip the probe
+    const OPTIMISTIC_RATE_PER_THREAD: f64 = 5_000_000.0;
+    let limit = args.max_expected_duration as f64;
+    if expected_attempts / (OPTIMISTIC_RATE_PER_THREAD * threads as f64) <= limit {
+        return;
+    }
+    
+    info!("⏱️  Measuring the rate for {}s to estimate the search time", CALIBRATION_SECS);
+    // Raw keys are the fastest mode, so mnemonic searches take at least this long
+    let rate = bench::run(threads, Duration::from_secs(CALIBRATION_SECS), core_plan).rate() * args.cpu_limit.map_or(1.0, |percent| percent as f64 / 100.0);
+    let expected_secs = expected_attempts / rate;
+    if expected_secs <= limit {
+        return;
+    }
+    
+    warning!("⚠️  This search is expected to take {} ({} attempts at {} addr/sec)",
+             format_duration_estimate(expected_secs), format_difficulty(expected_attempts), format_with_commas(rate as u64));
+    warning!("⚠️  That is longer than --max-expected-duration ({}); even a 10% chance of a match takes {}",
+             format_duration_estimate(limit), format_duration_estimate(odds::attempts_for_chance(0.1, expected_secs)));
+    if args.yes {
+        return;
+    }
+    if !std::io::stdin().is_terminal() {
+        error!("❌ Refusing to start without confirmation; pass --yes to start it anyway, or shorten the pattern");
+        std::process::exit(1);
+    }
+    eprint!("Start anyway? [y/N] ");
+    let mut answer = String::new();
+    let _ = std::io::stdin().read_line(&mut answer);
+    if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+        info!("Search not started");
+        std::process::exit(1);
+    }
+}
+
+fn run_estimate(args: &EstimateArgs) -> Result<(), Box<dyn std::error::Error>> {
+    for (kind, pattern) in [("prefix", &args.prefix), ("suffix", &args.suffix)] {
+        if let Some(pattern) = pattern {
+            if let Err(error_msg) = validate_pattern(pattern) {
+                error!("❌ Invalid {} pattern:", kind);
+                eprintln!("{}", error_msg);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Err(error_msg) = validate_lengths(args.prefix.as_deref(), args.suffix.as_deref()) {
+        error!("❌ {}", error_msg);
+        std::process::exit(1);
+    }
+    let difficulty = pattern_difficulty(args.prefix.as_deref(), args.suffix.as_deref(), args.case_sensitive);
+    
+    let rate = match args.rate {
+        Some(rate) if rate > 0.0 => rate,
+        Some(_) => {
+            error!("❌ --rate must be positive");
+            std::process::exit(1);
+        }
+        None => {
+            let threads = thread::available_parallelism().unwrap().get();
+            println!("⏱️  Measuring the rate: {} threads for {}s", threads, CALIBRATION_SECS);
+            bench::run(threads, Duration::from_secs(CALIBRATION_SECS), None).rate()
+        }
+    };
+    
+    println!("Difficulty: 1 in {}", format_difficulty(difficulty));
+    println!("Rate: {} addr/sec", format_with_commas(rate as u64));
+    for (label, chance) in [("Expected", None), ("50% chance", Some(0.5f64)), ("90% chance", Some(0.9)), ("99% chance", Some(0.99))] {
+        let attempts = chance.map_or(difficulty, |chance| odds::attempts_for_chance(chance, difficulty));
+        println!("{}: {}", label, format_duration_estimate(attempts / rate));
+    }
+    
+    Ok(())
+}
+
+fn run_bench(args: &BenchArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let threads = match (args.threads, &args.cores) {
+        (Some(threads), _) => threads as usize,
+        (None, Some(cores)) => cores.len(),
+        (None, None) => thread::available_parallelism().unwrap().get(),
+    };
+    println!("⏱️  Benchmarking key generation: {} threads for {}s", threads, args.duration);
+    let core_plan = core_plan(threads, args.pin_cores, args.cores.as_deref());
+    if let Some(plan) = &core_plan {
+        println!("{}", describe_core_plan(plan, args.pin_cores));
+    }
+    let report = bench::run(threads, Duration::from_secs(args.duration), core_plan.as_deref());
+    
+    println!("Keys generated: {}", format_with_commas(report.total));
+    println!("Mean: {} addr/sec", format_with_commas(report.rate() as u64));
+    for (label, percent) in [("Median", 50.0), ("p95", 95.0), ("p99", 99.0)] {
+        println!("{}: {} addr/sec", label, format_with_commas(report.percentile(percent)));
+    }
+    
+    // One bar per second, scaled to the fastest second
+    println!("\nPer second:");
+    let fastest = report.per_second.iter().copied().max().unwrap_or(0).max(1);
+    for (second, &generated) in report.per_second.iter().enumerate() {
+        let bar = "█".repeat((generated * BENCH_BAR_WIDTH / fastest) as usize);
+        println!("{:>4}s │{:<width$} {}", second + 1, bar, format_with_commas(generated), width = BENCH_BAR_WIDTH as usize);
+    }
+    
+    Ok(())
+}
+
+// The cores of each worker under --pin-cores/--cores, or None to leave scheduling to the OS
+fn core_plan(threads: usize, pin_cores: bool, cores: Option<&[usize]>) -> Option<Vec<Vec<usize>>> {
+    if !pin_cores && cores.is_none() {
+        return None;
+    }
+    let cores = cores.map_or_else(affinity::available_cores, <[usize]>::to_vec);
+    Some(affinity::plan(threads, &cores, pin_cores))
+}
+
+fn describe_core_plan(plan: &[Vec<usize>], pinned: bool) -> String {
+    if pinned {
+        let cores: Vec<usize> = plan.iter().flatten().copied().collect();
+        format!("Cores: one per thread ({})", cores.iter().map(usize::to_string).collect::<Vec<_>>().join(", "))
+    } else {
+        format!("Cores: {}", affinity::format_core_list(plan.first().map_or(&[], Vec::as_slice)))
+    }
+}
+
+fn run_reconstruct(shares: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let key_bytes = match shamir::reconstruct_private_key(shares) {
+        Ok(key_bytes) => key_bytes,
+        Err(error_msg) => {
+            error!("❌ {}", error_msg);
+            std::process::exit(1);
+        }
+    };
+    
+    let private_key = match SecretKey::from_slice(key_bytes.as_ref()) {
+        Ok(private_key) => private_key,
+        Err(_) => {
+            error!("❌ Reconstructed value is not a valid private key; check the shares");
+            std::process::exit(1);
+        }
+    };
+    
+    let wallet = generate_wallet_info(private_key);
+    println!("📍 Address: {}", wallet.address);
+    println!("🔐 Private Key: {}", wallet.private_key.as_str());
+    
+    Ok(())
+}
+
+fn run_leaderboard(path: &std::path::Path, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let entries = match leaderboard::load(path) {
+        Ok(entries) => entries,
+        Err(error_msg) => {
+            error!("❌ {}", error_msg);
+            std::process::exit(1);
+        }
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    
+    println!("🏆 Leaderboard {} ({} entr{})", path.display(), entries.len(), if entries.len() == 1 { "y" } else { "ies" });
+    for (i, entry) in entries.iter().enumerate() {
+        println!("\n#{}  {}", i + 1, to_checksum_address(&entry.address));
+        println!("    Score: {}", entry.score);
+        println!("    Found: {}", entry.found_at);
+        if let Some(key) = &entry.bip38_encrypted_key {
+            println!("    BIP38 key: {}", key);
+        }
+    }
+    Ok(())
+}
+
+fn run_bip38_decrypt(key: &str, password: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let password = Zeroizing::new(match password {
+        Some(password) => password.to_string(),
+        None => rpassword::prompt_password("🔑 BIP38 password: ")?,
+    });
+    
+    let decrypted = match bip38::bip38_decrypt(key, &password) {
+        Ok(decrypted) => decrypted,
+        Err(error_msg) => {
+            error!("❌ {}", error_msg);
+            std::process::exit(1);
+        }
+    };
+    
+    let private_key = match SecretKey::from_slice(decrypted.private_key.as_ref()) {
+        Ok(private_key) => private_key,
+        Err(_) => {
+            error!("❌ Decryption failed: wrong password or corrupted key");
+            std::process::exit(1);
+        }
+    };
+    
+    // The address hash doubles as a password check
+    let wallet = generate_wallet_info(private_key);
+    if bip38::address_hash(&wallet.address) != decrypted.address_hash {
+        error!("❌ Decryption failed: wrong password or key was not encrypted for an EVM address");
+        std::process::exit(1);
+    }
+    
+    if decrypted.compressed {
+        warning!("⚠️  Key is flagged as compressed; EVM addresses always use the uncompressed public key");
+    }
+    println!("📍 Address: {}", wallet.address);
+    println!("🔐 Private Key: {}", wallet.private_key.as_str());
+    
+    Ok(())
+}
+
+// The BIP32 extended keys of a wallet derived from a mnemonic (without a passphrase)
+fn wallet_extended_keys(wallet: &WalletInfo) -> Option<hd::ExtendedKeys> {
+    let mnemonic = Mnemonic::parse(wallet.mnemonic.as_deref()?.as_str()).ok()?;
+    let path: hd::DerivationPath = wallet.derivation_path.as_deref()?.parse().ok()?;
+    let master = hd::ExtendedPrivKey::from_seed(Zeroizing::new(mnemonic.to_seed("")).as_ref());
+    Some(hd::extended_keys(&Secp256k1::new(), &master, &path))
+}
+
+// The (index, address) pairs after a mnemonic wallet's index, for --derive-siblings
+fn wallet_siblings(args: &SearchArgs, wallet: &WalletInfo) -> Vec<(u32, String)> {
+    let siblings = || {
+        let mnemonic = Mnemonic::parse(wallet.mnemonic.as_deref()?.as_str()).ok()?;
+        let path: hd::DerivationPath = wallet.derivation_path.as_deref()?.parse().ok()?;
+        let (chain, index) = path.split_last()?;
+        let addresses = hd::derive_siblings(&mnemonic, &chain, index, args.derive_siblings? as usize - 1);
+        Some((index + 1..).zip(addresses).collect())
+    };
+    siblings().unwrap_or_default()
+}
+
+// The (index, address) pairs at the first indices of a mnemonic wallet's chain, for --show-derived
+fn wallet_accounts(args: &SearchArgs, wallet: &WalletInfo) -> Vec<(u32, String)> {
+    let accounts = || {
+        let mnemonic = Mnemonic::parse(wallet.mnemonic.as_deref()?.as_str()).ok()?;
+        let path: hd::DerivationPath = wallet.derivation_path.as_deref()?.parse().ok()?;
+        let (chain, _) = path.split_last()?;
+        let seed = Zeroizing::new(mnemonic.to_seed(""));
+        let addresses = hd::derive_addresses(seed.as_ref(), &chain, 0..args.show_derived?);
+        Some((0..).zip(addresses).collect())
+    };
+    accounts().unwrap_or_default()
+}
+
+// JSON object for --json; secrets are withheld exactly as in the text output
+fn wallet_json(args: &SearchArgs, wallet: &WalletInfo) -> serde_json::Value {
+    let mut object = serde_json::json!({
+        "address": wallet.address,
+        "checksum_address": to_checksum_address(&wallet.address),
+        "public_key_uncompressed": wallet.public_key_uncompressed,
+        "public_key_compressed": wallet.public_key_compressed,
+    });
+    
+    if let Some(chain_id) = args.chain_id.filter(|&chain_id| chain_id != 1) {
+        object["chain_id"] = chain_id.into();
+        object["chain_checksum_address"] = to_chain_checksum_address(&wallet.address, chain_id).into();
+    }
+    if let Some(path) = &wallet.derivation_path {
+        if args.from_mnemonic.is_some() {
+            let index: u32 = path.rsplit('/').next().unwrap_or_default().parse().expect("non-hardened index");
+            object["index"] = index.into();
+        }
+        object["derivation_path"] = path.as_str().into();
+    }
+    let extended_keys = (args.output_xprv || args.output_xpub).then(|| wallet_extended_keys(wallet)).flatten();
+    if let Some(keys) = extended_keys.as_ref().filter(|_| args.output_xpub) {
+        object["xpub"] = keys.xpub.as_str().into();
+    }
+    let siblings = wallet_siblings(args, wallet);
+    if !siblings.is_empty() {
+        object["siblings"] = siblings.iter().map(|(index, address)| serde_json::json!({ "index": index, "address": address })).collect();
+    }
+    let accounts = wallet_accounts(args, wallet);
+    if !accounts.is_empty() {
+        object["accounts"] = accounts
+            .iter()
+            .map(|(index, address)| serde_json::json!({ "index": index, "address": address, "matched": *address == wallet.address }))
+            .collect();
+    }
+    
+    if args.from_mnemonic.is_some() {
+        // The key is derivable from the seed the user already holds
+    } else if args.shamir_shares.is_some() && !args.show_private_key {
+        // Only the shares leave the process
+    } else if let Some(password) = &args.bip38 {
+        let key_bytes = wallet.private_key_bytes();
+        object["bip38_encrypted_key"] = bip38::bip38_encrypt(&key_bytes, &wallet.address, password).into();
+    } else {
+        object["private_key"] = wallet.private_key.as_str().into();
+        if let Some(mnemonic) = &wallet.mnemonic {
+            object["mnemonic"] = mnemonic.as_str().into();
+        }
+        if let Some(keys) = extended_keys.as_ref().filter(|_| args.output_xprv) {
+            object["root_xprv"] = keys.root_xprv.as_str().into();
+            object["xprv"] = keys.xprv.as_str().into();
+        }
+    }
+    object
+}
+
+// --quiet text output: a key=value line per field of a result, named as in the JSON
+fn print_fields(args: &SearchArgs, object: &serde_json::Value) {
+    for key in [
+        "address", "chain_checksum_address", "matched", "index", "derivation_path", "xpub", "private_key", "mnemonic", "root_xprv", "xprv", "bip38_encrypted_key", "keystore",
+        "saved_to", "rank", "score", "score_by", "leading_run",
+    ] {
+        // With --encrypt-to the key only goes to the encrypted file
+        if !args.encrypt_to.is_empty() && matches!(key, "private_key" | "mnemonic" | "root_xprv" | "xprv") {
+            continue;
+        }
+        match &object[key] {
+            serde_json::Value::Null => {}
+            serde_json::Value::String(value) => println!("{}={}", key, value),
+            value => println!("{}={}", key, value),
+        }
+    }
+    for sibling in object["siblings"].as_array().into_iter().flatten() {
+        println!("sibling_{}={}", sibling["index"], sibling["address"].as_str().unwrap_or_default());
+    }
+    for account in object["accounts"].as_array().into_iter().flatten() {
+        println!("account_{}={}", account["index"], account["address"].as_str().unwrap_or_default());
+    }
+    for (i, share) in object["shamir_shares"].as_array().into_iter().flatten().enumerate() {
+        println!("shamir_share_{}={}", i + 1, share.as_str().unwrap_or_default());
+    }
+}
+
+// Create or replace a file holding secrets, readable only by the owner on Unix
+fn create_private_file(path: &std::path::Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+// Open a results file for --count or --stream matches to be appended to as they are found
+fn append_private_file(path: &std::path::Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+}
+
+// --resume-count: the matches an earlier run appended to the file, one JSON object per line.
+// A last line cut short by a crash is dropped, so the next match starts on a line of its own.
+fn count_saved_matches(path: &std::path::Path) -> Result<u64, String> {
+    let contents = match std::fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let complete = contents.iter().rposition(|&byte| byte == b'\n').map_or(0, |i| i + 1);
+    if complete < contents.len() {
+        warning!("⚠️  Dropping the incomplete last line of {}", path.display());
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_len(complete as u64))
+            .map_err(|e| format!("Failed to truncate {}: {}", path.display(), e))?;
+    }
+    let mut saved = 0;
+    for (i, line) in contents[..complete].split(|&byte| byte == b'\n').enumerate() {
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+        match serde_json::from_slice(line) {
+            Ok(serde_json::Value::Object(_)) => saved += 1,
+            _ => return Err(format!("Line {} of {} is not a JSON object; --resume-count needs a file of JSON lines", i + 1, path.display())),
+        }
+    }
+    Ok(saved)
+}
+
+fn write_private_file(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = create_private_file(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+// Write each streamed match as one JSON line as soon as it arrives, with the attempts
+// made since the previous one; returns how many were written
+fn stream_matches(
+    args: &SearchArgs,
+    receiver: mpsc::Receiver<WalletInfo>,
+    attempts: &counters::AttemptCounters,
+    start_time: Instant,
+    notifier: Option<&notify::Notifier>,
+    save_dir: Option<&autosave::SaveDir>,
+    recent_finds: &Mutex<VecDeque<String>>,
+) -> u64 {
+    use std::io::Write;
+    let (mut out, destination): (Box<dyn Write>, String) = match &args.output_file {
+        // A stream adds to the inventory of earlier runs rather than replacing it
+        Some(path) => match append_private_file(path) {
+            Ok(file) => (Box::new(std::io::BufWriter::new(file)), path.display().to_string()),
+            Err(e) => {
+                error!("❌ Failed to open {}: {}", path.display(), e);
+                return 0;
+            }
+        },
+        None => (Box::new(std::io::stdout().lock()), "stdout".to_string()),
+    };
+    
+    let mut streamed = 0;
+    let mut previous_attempts = 0;
+    for wallet in receiver {
+        let total_attempts = attempts.total();
+        let mut object = wallet_json(args, &wallet);
+        object["attempts"] = total_attempts.saturating_sub(previous_attempts).into();
+        object["total_attempts"] = total_attempts.into();
+        object["elapsed_secs"] = start_time.elapsed().as_secs_f64().into();
+        previous_attempts = total_attempts;
+        if let Some(path) = save_dir.and_then(|save_dir| save_wallet(save_dir, &object, total_attempts, start_time.elapsed())) {
+            object["saved_to"] = path.display().to_string().into();
+        }
+        
+        if let Err(e) = writeln!(out, "{}", object).and_then(|()| out.flush()) {
+            error!("❌ Failed to write to {}: {}", destination, e);
+            tracing::error!(destination = %destination, error = %e, "stream write failed");
+            break;
+        }
+        streamed += 1;
+        tracing::info!(address = %wallet.address, attempts = total_attempts, "match streamed");
+        if let Some(rpc_url) = &args.verify_rpc {
+            rpc::report(&wallet.address, rpc_url);
+        }
+        {
+            let mut recent_finds = recent_finds.lock().unwrap();
+            if recent_finds.len() == RECENT_FINDS {
+                recent_finds.pop_back();
+            }
+            recent_finds.push_front(wallet.address.clone());
+        }
+        if let Some(notifier) = notifier {
+            notifier.matched(&wallet.address, total_attempts, start_time.elapsed());
+        }
+    }
+    streamed
+}
+
+// --track-near-miss: the closest prefix and suffix candidates, as text, `best_partial` JSON
+// or, with --quiet, `best_partial_<end>_<field>=` lines
+fn report_near_misses(args: &SearchArgs, near_miss: &nearmiss::Tracker<WalletInfo>, json_output: bool) {
+    let (prefix, suffix) = near_miss.take();
+    if prefix.is_none() && suffix.is_none() {
+        info!("🎯 No candidate got a single character of the pattern right");
+        return;
+    }
+    
+    let mut best_partial = serde_json::Map::new();
+    for (end, closest) in [("prefix", &prefix), ("suffix", &suffix)] {
+        let Some(closest) = closest else {
+            continue;
+        };
+        let mut object = serde_json::json!({
+            "address": closest.item.address,
+            "matched": closest.matched,
+            "pattern_length": closest.pattern_len,
+        });
+        if args.show_near_miss_key {
+            object["private_key"] = closest.item.private_key.as_str().into();
+        }
+        best_partial.insert(end.to_string(), object);
+    }
+    let best_partial = serde_json::Value::Object(best_partial);
+    
+    if json_output && args.quiet {
+        println!("{}", Zeroizing::new(serde_json::json!({ "best_partial": best_partial }).to_string()).as_str());
+    } else if json_output {
+        println!("{}", Zeroizing::new(serde_json::to_string_pretty(&serde_json::json!({ "best_partial": best_partial })).expect("JSON values serialize")).as_str());
+    } else if args.quiet {
+        for (end, object) in best_partial.as_object().into_iter().flatten() {
+            for field in ["address", "matched", "private_key"] {
+                match &object[field] {
+                    serde_json::Value::Null => {}
+                    serde_json::Value::String(value) => println!("best_partial_{}_{}={}", end, field, value),
+                    value => println!("best_partial_{}_{}={}", end, field, value),
+                }
+            }
+        }
+    } else {
+        for (end, closest) in [("prefix", &prefix), ("suffix", &suffix)] {
+            let Some(closest) = closest else {
+                continue;
+            };
+            let (prefix_len, suffix_len) = if end == "prefix" { (closest.matched, 0) } else { (0, closest.matched) };
+            println!("🎯 Closest {}: {} ({} of {} characters)", end, output::address(&closest.item.address, prefix_len, suffix_len), closest.matched, closest.pattern_len);
+            if args.show_near_miss_key {
+                println!("🔐 Private Key: {}", closest.item.private_key.as_str());
+            }
+        }
+    }
+}
+
+// --autosave: a failed save is reported and the search goes on, as other matches may still save
+fn save_wallet(save_dir: &autosave::SaveDir, object: &serde_json::Value, attempts: u64, elapsed: Duration) -> Option<std::path::PathBuf> {
+    match save_dir.save(object, attempts, elapsed) {
+        Ok(path) => {
+            tracing::info!(path = %path.display(), "match saved");
+            Some(path)
+        }
+        Err(e) => {
+            error!("❌ Failed to save match to {}: {}", save_dir.dir().display(), e);
+            tracing::error!(dir = %save_dir.dir().display(), error = %e, "saving match failed");
+            None
+        }
+    }
+}
+
+fn print_qr_codes(args: &SearchArgs, wallet: &WalletInfo) {
+    let uri = qr::ethereum_uri(&to_checksum_address(&wallet.address));
+    
+    if let Some(path) = &args.qr_file {
+        match qr::save_png(&uri, path) {
+            Ok(()) => println!("🖼️  QR code saved to {}", path.display()),
+            Err(error_msg) => error!("❌ {}", error_msg),
+        }
+    }
+    
+    if !(args.qr || args.qr_secret) {
+        return;
+    }
+    if !std::io::stdout().is_terminal() {
+        warning!("⚠️  stdout is not a terminal, skipping QR code output");
+        return;
+    }
+    
+    match qr::render_terminal(&uri) {
+        Ok(code) => println!("\n📱 Address QR ({}):\n{}", uri, code),
+        Err(error_msg) => error!("❌ {}", error_msg),
+    }
+    if args.qr_secret {
+        match qr::render_terminal(wallet.private_key.as_str()) {
+            Ok(code) => println!("\n🔐 Private Key QR (keep this screen private!):\n{}", Zeroizing::new(code).as_str()),
+            Err(error_msg) => error!("❌ {}", error_msg),
+        }
+    }
+}
+
+// Options that choose what to search for; one source (command line, environment or config) supplies all of them
+const PATTERN_ARGS: [&str; 10] = ["pattern", "prefix", "suffix", "wrap", "word", "pattern_file", "alt", "repeating", "palindrome", "score_config"];
+
+// A pattern on the command line replaces one from EVM_VANITY_* variables instead of conflicting with it
+fn parse_command_line(argv: Vec<std::ffi::OsString>) -> Result<clap::ArgMatches, clap::Error> {
+    let without_pattern_env = PATTERN_ARGS.iter().fold(Cli::command(), |command, id| {
+        command
+            .mut_arg(*id, |arg| arg.env(None::<&str>))
+            .mut_subcommand("search", |search| search.mut_arg(*id, |arg| arg.env(None::<&str>)))
+    });
+    // Help and errors come from the second parse, which knows every variable
+    if let Ok(matches) = without_pattern_env.try_get_matches_from(&argv) {
+        let search_matches = matches.subcommand_matches("search").unwrap_or(&matches);
+        if PATTERN_ARGS.iter().any(|id| search_matches.value_source(id) == Some(ValueSource::CommandLine)) {
+            return Ok(matches);
+        }
+    }
+    Cli::command().try_get_matches_from(argv)
+}
+
+// Pick the fastest of a few thread counts, reusing an earlier calibration on this CPU
+fn auto_tune_threads(retune: bool) -> usize {
+    let cpu_key = tune::cpu_key();
+    let cache_path = tune::cache_path();
+    if !retune {
+        if let Some(threads) = cache_path.as_deref().and_then(|path| tune::cached(path, &cpu_key)) {
+            info!("🔧 Auto-tune: {} threads, calibrated earlier on {} (--retune to calibrate again)", threads, cpu_key);
+            return threads;
+        }
+    }
+    
+    let candidates = tune::candidates();
+    let threads = if let [threads] = candidates[..] {
+        info!("🔧 Auto-tune: {} is the only candidate thread count on {}", threads, cpu_key);
+        threads
+    } else {
+        info!("🔧 Auto-tune: timing {} thread counts for {}s each on {}", candidates.len(), CALIBRATION_SECS, cpu_key);
+        let mut fastest = (0, 0.0);
+        for &threads in &candidates {
+            let rate = bench::run(threads, Duration::from_secs(CALIBRATION_SECS), None).rate();
+            info!("  • {} threads: {:.0} addr/sec", threads, rate);
+            if rate > fastest.1 {
+                fastest = (threads, rate);
+            }
+        }
+        info!("🔧 Auto-tune: using {} threads", fastest.0);
+        fastest.0
+    };
+    
+    if let Some(path) = &cache_path {
+        if let Err(e) = tune::store(path, &cpu_key, threads) {
+            warning!("⚠️  Failed to cache the thread count in {}: {}", path.display(), e);
+        }
+    }
+    threads
+}
+
+async fn run_command(command: Command) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::Search(_) => unreachable!("searches run in main"),
+        Command::Estimate(args) => run_estimate(&args),
+        Command::Bip38Decrypt { key, password } => run_bip38_decrypt(&key, password.as_deref()),
+        Command::Reconstruct { shares } => run_reconstruct(&shares),
+        Command::Verify(args) => run_verify(&args),
+        Command::VerifyBatch(args) => run_verify_batch(&args),
+        Command::Combine { private_key, partial_key, address } => run_combine(&private_key, &partial_key, address.as_deref()),
+        Command::Bench(args) => run_bench(&args),
+        Command::Leaderboard { path, json } => run_leaderboard(&path, json),
+        Command::Serve { port, bind } => server::serve(std::net::SocketAddr::new(bind, port), None).await,
+        Command::Grpc { port, bind } => grpc::serve(std::net::SocketAddr::new(bind, port)).await,
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "evm-vanity", &mut std::io::stdout());
+            Ok(())
+        }
+    }
+}
+
+// Fill in options from the config file that were neither given on the command line nor set
+// through their environment variable, returning the search options (parsed again if any
+// were filled in) and the options used. Patterns in the file are a default job, so they
+// are ignored when any pattern option was given.
+fn apply_config(path: &std::path::Path, args: SearchArgs, matches: clap::ArgMatches, in_search: bool) -> Result<(SearchArgs, clap::ArgMatches, Vec<String>), error::VanityError> {
+    let config = config::Config::from_file(path)?;
+    let command = search_command();
+    let unset = |id: &str| matches.value_source(id).is_none_or(|source| source == ValueSource::DefaultValue);
+    let pattern_given = PATTERN_ARGS.iter().any(|id| !unset(id));
+    let (arguments, applied) = config.arguments(&command, |id| unset(id) && !(pattern_given && PATTERN_ARGS.contains(&id)))?;
+    if arguments.is_empty() {
+        return Ok((args, matches, applied));
+    }
+    
+    // Parsed again with the settings as arguments of their own, right after `search` if given
+    let mut argv: Vec<std::ffi::OsString> = std::env::args_os().collect();
+    let at = if in_search { argv.iter().position(|arg| arg == "search").map_or(1, |index| index + 1) } else { 1 };
+    argv.splice(at..at, arguments.into_iter().map(Into::into));
+    let (args, matches) = search_args(parse_command_line(argv).map_err(|e| config.parse_error(&command, &e))?);
+    Ok((args, matches, applied))
+}
+
+// The search options alone, for looking them up by name
+fn search_command() -> clap::Command {
+    <SearchArgs as clap::Args>::augment_args(clap::Command::new("search"))
+}
+
+// The search options and their matches, from the top level or the `search` subcommand
+fn search_args(matches: clap::ArgMatches) -> (SearchArgs, clap::ArgMatches) {
+    let Cli { command, search, .. } = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    match command {
+        Some(Command::Search(args)) => (*args, matches.subcommand_matches("search").expect("parsed as the search subcommand").clone()),
+        _ => (search, matches),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let matches = parse_command_line(std::env::args_os().collect()).unwrap_or_else(|e| e.exit());
+    let Cli { command, search, no_color } = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    output::init(no_color);
+    
+    // The search options come from `search` or, in the legacy invocation, the top level
+    let (args, matches, in_search) = match command {
+        None => (search, matches, false),
+        Some(Command::Search(args)) => {
+            let search_matches = matches.subcommand_matches("search").expect("parsed as the search subcommand").clone();
+            (*args, search_matches, true)
+        }
+        Some(command) => return run_command(command).await,
+    };
+    
+    // Command line flags win over EVM_VANITY_* variables, which win over the config file
+    let config_path = args.config.clone().or_else(config::default_path);
+    let (mut args, matches, config_applied) = match &config_path {
+        Some(path) => match apply_config(path, args, matches, in_search) {
+            Ok(applied) => applied,
+            Err(e) => {
+                error!("❌ Invalid config file: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => (args, matches, Vec::new()),
+    };
+    if args.print_config {
+        match &config_path {
+            Some(path) => println!("# Config file: {}", path.display()),
+            None => println!("# No config file"),
+        }
+        config::print_effective(&search_command(), &matches, &config_applied);
+        return Ok(());
+    }
+    // --shamir K-of-N is shorthand for --shamir-threshold K --shamir-shares N
+    if let Some((threshold, shares)) = args.shamir {
+        (args.shamir_threshold, args.shamir_shares) = (Some(threshold), Some(shares));
+    }
+    // --cpu-limit 100 is a full core, the same as no limit
+    args.cpu_limit = args.cpu_limit.filter(|&percent| percent < 100);
+    // --serve and --connect are shorthand for the server and client mode flags
+    if let Some(bind) = args.serve {
+        (args.server_mode, args.server_bind) = (true, Some(bind));
+    }
+    if let Some(url) = args.connect.take() {
+        (args.client_mode, args.server_url) = (true, Some(url));
+    }
+    INFO_TO_STDERR.store(args.stream || args.quiet || args.stdin_jobs, Ordering::Relaxed);
+    QUIET.store(args.quiet, Ordering::Relaxed);
+    // --resume-count searches only for the matches the output file is still missing
+    let resumed = match (&args.output_file, args.resume_count) {
+        (Some(path), true) => match count_saved_matches(path) {
+            Ok(saved) => saved,
+            Err(error_msg) => {
+                error!("❌ {}", error_msg);
+                std::process::exit(1);
+            }
+        },
+        _ => 0,
+    };
+    if resumed >= args.count && args.resume_count {
+        info!("🎉 {} already holds {} of {} matches", args.output_file.as_ref().expect("required by --resume-count").display(), resumed, args.count);
+        return Ok(());
+    }
+    args.count -= resumed;
+    let args = args;
+    let log_writer = if args.log_file.is_some() || args.log_level.is_some() {
+        match logging::init(args.log_level.unwrap_or(logging::LogLevel::Info), args.log_file.as_deref()) {
+            Ok(log_writer) => Some(log_writer),
+            Err(e) => {
+                error!("❌ Failed to open log file {}: {}", args.log_file.as_ref().map(|path| path.display().to_string()).unwrap_or_default(), e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "evm-vanity started");
+    let env_applied: Vec<String> = Cli::command()
+        .get_arguments()
+        .filter(|arg| matches.value_source(arg.get_id().as_str()) == Some(ValueSource::EnvVariable))
+        .filter_map(|arg| arg.get_env().map(|name| name.to_string_lossy().into_owned()))
+        .collect();
+    let json_output = args.json || args.output_format == OutputFormat::Json;
+    
+    // Check the options and determine the prefix and suffix patterns
+    let search_config = match SearchConfig::try_from(&args) {
+        Ok(search_config) => search_config,
+        Err(SearchConfigError::Usage(kind, error_msg)) => Cli::command().error(kind, error_msg).exit(),
+        Err(SearchConfigError::Invalid(error_msg)) => {
+            error!("❌ {}", error_msg);
+            std::process::exit(1);
+        }
+    };
+    
+    if args.notify_desktop && !cfg!(feature = "desktop-notify") {
+        error!("❌ --notify-desktop: {}", notify::DESKTOP_UNSUPPORTED);
+        std::process::exit(1);
+    }
+    let gpu_flags = [("--gpu-device", args.gpu_device.is_some()), ("--gpu-batch-size", args.gpu_batch_size.is_some())];
+    if let Some((flag, _)) = gpu_flags.iter().find(|&&(_, given)| given).filter(|_| !cfg!(any(feature = "gpu", feature = "cuda"))) {
+        error!("❌ {}: {}", flag, device::UNSUPPORTED);
+        std::process::exit(1);
+    }
+    let notify_channels = notify_channels(&args);
+    if args.notify_test {
+        run_notify_test(&notify_channels);
+    }
+    
+    let entropy_bits = mnemonic_entropy_bits(&args);
+    let (prefix_pattern, suffix_pattern) = (search_config.prefix.as_deref(), search_config.suffix.as_deref());
+    if args.pattern.is_some() {
+        warning!("⚠️  -p/--pattern and -s/--suffix-mode are deprecated; use --prefix and --suffix");
+    }
+    
+    let exclusions: Vec<Exclusion> = args.exclude_prefix.iter().cloned().map(Exclusion::Prefix)
+        .chain(args.exclude_suffix.iter().cloned().map(Exclusion::Suffix))
+        .collect();
+    for exclusion in &exclusions {
+        let (kind, pattern) = match exclusion {
+            Exclusion::Prefix(pattern) => ("prefix", pattern),
+            Exclusion::Suffix(pattern) => ("suffix", pattern),
+        };
+        if let Err(error_msg) = validate_pattern(pattern) {
+            error!("❌ Invalid exclude-{} pattern:", kind);
+            eprintln!("{}", error_msg);
+            std::process::exit(1);
+        }
+    }
+    
+    // Case-sensitive patterns match the casing of this chain's checksum
+    if let Some(chain_id) = args.chain_id {
+        pattern::set_checksum_chain_id(chain_id);
+    }
+    
+    // Every pattern that may match; --word expands into several spellings
+    let mut pattern_set = if let Some(path) = &args.pattern_file {
+        match PatternSet::from_file(path) {
+            Ok(pattern_set) => pattern_set,
+            Err(e) => {
+                error!("❌ Invalid pattern file: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else if let Some(word) = &args.word {
+        let candidates = word::leet_candidates(word).and_then(|candidates| {
+            // Every spelling of a word is as long as the word
+            validate_lengths(candidates.first().map(String::as_str), suffix_pattern).map_err(|error_msg| format!("❌ --word {}", error_msg))?;
+            Ok(candidates)
+        });
+        match candidates {
+            Ok(candidates) => PatternSet::new(
+                candidates
+                    .into_iter()
+                    .map(|candidate| PatternEntry::new(Some(candidate), suffix_pattern.map(String::from)))
+                    .collect(),
+            ),
+            Err(error_msg) => {
+                eprintln!("{}", error_msg);
+                std::process::exit(1);
+            }
+        }
+    } else if !args.alt.is_empty() {
+        let (pattern_set, covered) = alternative_set(&args.alt, args.case_sensitive);
+        for (entry, broader) in covered {
+            warning!("⚠️  Every address matching --alt {} also matches {}; it only changes which one is reported", entry, broader);
+        }
+        pattern_set
+    } else {
+        PatternSet::new(vec![PatternEntry {
+            prefix: prefix_pattern.map(String::from),
+            suffix: suffix_pattern.map(String::from),
+            prefix_alternatives: search_config.prefix_alternatives.clone(),
+            suffix_alternatives: search_config.suffix_alternatives.clone(),
+        }])
+    };
+    
+    // A pattern containing an excluded character could never match; word spellings that
+    // do are dropped as long as another spelling remains
+    if let Some(excluded) = args.exclude {
+        if args.word.is_some() {
+            pattern_set.entries.retain(|entry| entry.prefix.as_deref().is_none_or(|prefix| excluded.find_in(prefix).is_none()));
+            if pattern_set.entries.is_empty() {
+                error!("❌ Every spelling of '{}' contains one of the excluded characters {}", args.word.as_deref().unwrap_or_default(), excluded);
+                std::process::exit(1);
+            }
+        }
+        for entry in &pattern_set.entries {
+            for pattern in entry.patterns() {
+                if let Some(c) = excluded.find_in(pattern) {
+                    error!("❌ Pattern '{}' contains the excluded character '{}', so no address could match", pattern, c);
+                    std::process::exit(1);
+                }
+            }
+        }
+        if let Some(nibble) = args.repeating_char.filter(|&nibble| excluded.contains(nibble)) {
+            error!("❌ --repeating-char '{:x}' is one of the excluded characters", nibble);
+            std::process::exit(1);
+        }
+        if let Some(nibble) = args.maximize.filter(|&nibble| excluded.contains(nibble)) {
+            error!("❌ --maximize '{:x}' is one of the excluded characters", nibble);
+            std::process::exit(1);
+        }
+    }
+    
+    // Fuzzy matching compares characters one by one, so it has no notion of `~` escapes
+    let fuzzy_distance = args.fuzzy_distance.map_or(0, usize::from);
+    if fuzzy_distance > 0 {
+        for entry in &pattern_set.entries {
+            let patterns: Vec<&str> = entry.prefix.iter().chain(&entry.suffix).map(String::as_str).collect();
+            if patterns.iter().any(|pattern| pattern.contains('~')) {
+                error!("❌ '~' escapes cannot be combined with --fuzzy-distance ({})", entry);
+                std::process::exit(1);
+            }
+            let length: usize = patterns.iter().map(|pattern| pattern_len(pattern)).sum();
+            if fuzzy_distance >= length {
+                error!("❌ --fuzzy-distance {} would match every address for {} ({} characters)", fuzzy_distance, entry, length);
+                std::process::exit(1);
+            }
+        }
+    }
+    
+    let repeating = args.repeating.map(|length| Repeating {
+        length: length as usize,
+        nibble: args.repeating_char,
+    });
+    let palindrome = args.palindrome.map(|length| Palindrome { length: length as usize });
+    let leading_zero_bytes = args.leading_zero_bytes.map_or(0, usize::from);
+    let at_offset = match args.at_offset.as_slice() {
+        [offset, pattern] => {
+            let offset = match offset.parse::<usize>() {
+                Ok(offset) if offset < 40 => offset,
+                _ => Cli::command()
+                    .error(clap::error::ErrorKind::ValueValidation, format!("--at-offset: '{}' is not a character position from 0 to 39", offset))
+                    .exit(),
+            };
+            match OffsetPattern::compile(pattern, offset, args.case_sensitive) {
+                Ok(compiled) => Some((offset, pattern.as_str(), compiled)),
+                Err(e) => {
+                    error!("❌ --at-offset: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => None,
+    };
+    let bit_mask = match (&args.mask, &args.value) {
+        (Some(mask), Some(value)) => {
+            let at_end = args.mask_align == MaskAlign::End;
+            let (mask, value) = match (parse_padded_bytes(mask, at_end), parse_padded_bytes(value, at_end)) {
+                (Ok(mask), Ok(value)) => (mask, value),
+                (Err(error_msg), _) => Cli::command().error(clap::error::ErrorKind::ValueValidation, format!("--mask: {}", error_msg)).exit(),
+                (_, Err(error_msg)) => Cli::command().error(clap::error::ErrorKind::ValueValidation, format!("--value: {}", error_msg)).exit(),
+            };
+            let ignored: u32 = mask.iter().zip(&value).map(|(m, v)| (v & !m).count_ones()).sum();
+            if ignored > 0 {
+                warning!("⚠️  {} bits of --value are outside the --mask and are ignored", ignored);
+            }
+            Some(BitMask::new(mask, value))
+        }
+        _ => None,
+    };
+    
+    // Derive the account chains of a user-supplied mnemonic up front
+    let mnemonic_chains = match &args.from_mnemonic {
+        Some(path) => {
+            let mnemonic = match read_mnemonic(path) {
+                Ok(mnemonic) => mnemonic,
+                Err(error_msg) => {
+                    error!("❌ {}", error_msg);
+                    std::process::exit(1);
+                }
+            };
+            let passphrase = Zeroizing::new(if args.passphrase {
+                rpassword::prompt_password("🔑 BIP39 passphrase: ")?
+            } else {
+                String::new()
+            });
+            
+            let secp = Secp256k1::new();
+            let master = hd::ExtendedPrivKey::from_seed(Zeroizing::new(mnemonic.to_seed(passphrase.as_str())).as_ref());
+            let chains: Vec<(hd::DerivationPath, hd::ExtendedPrivKey)> = (0..args.accounts)
+                .map(|account| {
+                    let path: hd::DerivationPath = format!("m/44'/60'/{}'/0", account).parse().expect("valid derivation path");
+                    let chain = master.derive_path(&secp, &path);
+                    (path, chain)
+                })
+                .collect();
+            
+            let last_index = args.start_index as u64 + args.index_count - 1;
+            if last_index >= hd::HARDENED as u64 {
+                error!("❌ Index range {}..={} exceeds the largest non-hardened index {}", args.start_index, last_index, hd::HARDENED - 1);
+                std::process::exit(1);
+            }
+            
+            Some(Arc::new(chains))
+        }
+        None => None,
+    };
+    
+    // Scoring mode rates raw keys; every address passing the exclusions is a candidate
+    let scorer = match &args.score_config {
+        Some(path) => match score::Scorer::from_file(path) {
+            Ok(scorer) => Some(scorer),
+            Err(e) => {
+                error!("❌ Invalid score config: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let best = Arc::new(score::BestSoFar::<WalletInfo>::new());
+    let leaderboard = match (&scorer, &args.leaderboard) {
+        (Some(scorer), Some(path)) => Some(leaderboard::Leaderboard::open(path, args.leaderboard_size as usize, scorer)),
+        _ => None,
+    };
+    
+    // Maximize mode keeps the longest leading run for a fixed budget of time or attempts
+    let best_run_len = Arc::new(AtomicUsize::new(0));
+    let best_run = Mutex::new(None::<WalletInfo>);
+    
+    // Near misses are measured against the one prefix and suffix of a raw-mode search
+    let near_miss = if args.track_near_miss {
+        let entry = &pattern_set.entries[0];
+        if entry.prefix.is_none() && entry.suffix.is_none() {
+            Cli::command()
+                .error(clap::error::ErrorKind::MissingRequiredArgument, "--track-near-miss needs a --prefix or --suffix to measure candidates against")
+                .exit();
+        }
+        Some(nearmiss::Tracker::<WalletInfo>::new(entry.prefix.as_deref(), entry.suffix.as_deref()))
+    } else {
+        None
+    };
+    
+    // Top-K mode ranks every candidate by one criterion for a fixed budget, like maximize mode
+    let criterion = args.score_by.map(|score_by| match (score_by, &args.score_prefix) {
+        (ScoreBy::LeadingZeros, None) => score::Criterion::LeadingZeros,
+        (ScoreBy::MaxRepeat, None) => score::Criterion::MaxRepeat,
+        (ScoreBy::PalindromeScore, None) => score::Criterion::PalindromeScore,
+        (ScoreBy::PrefixMatchLength, Some(prefix)) => score::Criterion::PrefixMatchLength(prefix.to_vec()),
+        (ScoreBy::PrefixMatchLength, None) => Cli::command()
+            .error(clap::error::ErrorKind::MissingRequiredArgument, "--score-by prefix_match_length needs --score-prefix <HEX>")
+            .exit(),
+        (_, Some(_)) => Cli::command()
+            .error(clap::error::ErrorKind::ArgumentConflict, "--score-prefix only applies to --score-by prefix_match_length")
+            .exit(),
+    });
+    let top = Arc::new(score::TopK::<WalletInfo>::new(args.top_k as usize));
+    
+    let key_range = if args.key_min.is_some() || args.key_max.is_some() {
+        match keyrange::KeyRange::new(args.key_min.unwrap_or(keyrange::MIN_KEY), args.key_max.unwrap_or(keyrange::MAX_KEY)) {
+            Ok(key_range) => Some(key_range),
+            Err(error_msg) => {
+                error!("❌ {}", error_msg);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+    
+    // Bounded searches scan work items 0..total instead of drawing random keys:
+    // the indices of a user-supplied mnemonic, or a key range below 2^32 keys
+    let scan_total = match (&mnemonic_chains, &key_range) {
+        (Some(chains), _) => Some(chains.len() as u64 * args.index_count),
+        (None, Some(key_range)) => key_range.sequential_len(),
+        (None, None) => None,
+    };
+    
+    // clap waives `requires` when the required flag would conflict with another option given
+    if args.concurrent_jobs.is_some() && !args.stdin_jobs {
+        Cli::command()
+            .error(clap::error::ErrorKind::MissingRequiredArgument, "--concurrent-jobs only applies to --stdin-jobs")
+            .exit();
+    }
+    if args.leaderboard.is_some() && args.score_config.is_none() {
+        Cli::command()
+            .error(clap::error::ErrorKind::MissingRequiredArgument, "--leaderboard only applies to --score-config")
+            .exit();
+    }
+    if args.api_token.is_some() && args.api.is_none() {
+        Cli::command()
+            .error(clap::error::ErrorKind::MissingRequiredArgument, "--api-token only applies to --api")
+            .exit();
+    }
+    
+    if args.retune && args.threads != Some(Threads::AutoTune) {
+        Cli::command()
+            .error(clap::error::ErrorKind::ArgumentConflict, "--retune only applies to --threads auto-tune")
+            .exit();
+    }
+    
+    // Determine number of threads; calibration runs before the attempt counters exist
+    let num_threads = match args.threads {
+        Some(Threads::Count(threads)) => threads,
+        Some(Threads::AutoTune) => auto_tune_threads(args.retune),
+        None => args.cores.as_ref().map_or_else(|| thread::available_parallelism().unwrap().get(), |cores| cores.len()),
+    };
+    let core_plan = core_plan(num_threads, args.pin_cores, args.cores.as_deref());
+    
+    // Distributed searches run on work units instead of the search loop below
+    if args.server_mode {
+        let bind = args.server_bind.expect("required by --server-mode");
+        let pattern = distributed::SearchPattern {
+            prefix: prefix_pattern.map(str::to_string),
+            suffix: suffix_pattern.map(str::to_string),
+            case_sensitive: args.case_sensitive,
+        };
+        return distributed::run_server(bind, pattern, args.count);
+    }
+    if args.client_mode {
+        return distributed::run_client(args.server_url.as_deref().expect("required by --client-mode"), num_threads);
+    }
+    if args.stdin_jobs {
+        return jobs::run(num_threads, args.concurrent_jobs.unwrap_or(1) as usize);
+    }
+    if let Some(address) = args.api {
+        return server::serve(address, args.api_token.clone()).await;
+    }
+    // Salt mining searches contract addresses instead of keys
+    let salt_target = if args.create2 {
+        Some(salt::Target::Create2 {
+            deployer: args.deployer.expect("required by --create2"),
+            initcode_hash: args.initcode_hash.expect("required by --create2"),
+        })
+    } else if args.create3 {
+        Some(salt::Target::Create3 {
+            deployer: args.deployer.expect("required by --create3"),
+            proxy_initcode_hash: args.proxy_initcode_hash.unwrap_or_else(salt::default_proxy_initcode_hash),
+        })
+    } else if args.clone {
+        Some(salt::Target::clone_of(
+            args.factory.expect("required by --clone"),
+            args.implementation.expect("required by --clone"),
+            args.extra_data.as_deref().unwrap_or_default().to_vec(),
+        ))
+    } else if args.safe {
+        let initializer = match &args.initializer {
+            Some(initializer) => initializer.to_vec(),
+            None => {
+                if args.threshold > args.owners.len() as u64 {
+                    Cli::command()
+                        .error(
+                            clap::error::ErrorKind::ValueValidation,
+                            format!("--threshold ({}) cannot exceed the number of --owners ({})", args.threshold, args.owners.len()),
+                        )
+                        .exit();
+                }
+                salt::safe_setup_initializer(&args.owners, args.threshold, &args.fallback_handler.unwrap_or_default())
+            }
+        };
+        Some(salt::Target::safe(
+            args.factory.unwrap_or(salt::SAFE_PROXY_FACTORY),
+            args.singleton.unwrap_or(salt::SAFE_SINGLETON),
+            args.proxy_creation_code.as_deref().expect("required by --safe"),
+            &initializer,
+        ))
+    } else {
+        None
+    };
+    if let (Some(start), Some(end)) = (args.nonce_start, args.nonce_end) {
+        if end < start {
+            Cli::command()
+                .error(clap::error::ErrorKind::ValueValidation, format!("--nonce-end ({}) is below --nonce-start ({})", end, start))
+                .exit();
+        }
+    }
+    if let Some(target) = salt_target {
+        let search = salt::SaltSearch {
+            target,
+            prefix: prefix_pattern.map(str::to_string),
+            suffix: suffix_pattern.map(str::to_string),
+            case_sensitive: args.case_sensitive,
+            hook_flags: args.hook_flags,
+            bit_mask,
+            leading_zero_bytes,
+            nonce_start: args.nonce_start,
+            nonce_end: args.nonce_end,
+            salt_prefix: args.salt_prefix.as_deref().map(<[u8]>::to_vec).unwrap_or_default(),
+        };
+        return salt::run(&search, num_threads, args.count, json_output, args.quiet);
+    }
+    if let Some(public_key) = args.search_for_pubkey {
+        let search = splitkey::SplitKeySearch {
+            public_key,
+            prefix: prefix_pattern.map(str::to_string),
+            suffix: suffix_pattern.map(str::to_string),
+            case_sensitive: args.case_sensitive,
+            bit_mask,
+            leading_zero_bytes,
+        };
+        return splitkey::run(&search, num_threads, args.count, json_output, args.quiet);
+    }
+    if args.watch_only {
+        let search = watchonly::WatchOnlySearch {
+            prefix: prefix_pattern.map(str::to_string),
+            suffix: suffix_pattern.map(str::to_string),
+            case_sensitive: args.case_sensitive,
+            bit_mask,
+            leading_zero_bytes,
+        };
+        return watchonly::run(&search, num_threads, args.count, json_output, args.quiet);
+    }
+    
+    // A checkpoint only fits the pattern it was written for, so the pattern is saved with it
+    let sequential_scan = args.start_key.map(|start_key| {
+        if args.mode == SearchMode::Mnemonic {
+            Cli::command()
+                .error(clap::error::ErrorKind::ArgumentConflict, "--start-key only applies to --mode raw")
+                .exit();
+        }
+        if start_key < keyrange::MIN_KEY || start_key > keyrange::MAX_KEY {
+            error!("❌ --start-key must lie between 0x{} and 0x{}", hex::encode(keyrange::MIN_KEY), hex::encode(keyrange::MAX_KEY));
+            std::process::exit(1);
+        }
+        let mut pattern = pattern_set.entries.iter().map(PatternEntry::to_string).collect::<Vec<_>>().join(" or ");
+        if args.case_sensitive {
+            pattern.push_str(" (case sensitive)");
+        }
+        let scan = match &args.resume {
+            Some(path) => checkpoint::SequentialScan::resume(start_key, pattern, num_threads, path, args.force),
+            None => Ok(checkpoint::SequentialScan::new(start_key, pattern, num_threads)),
+        };
+        match scan {
+            Ok(scan) => Arc::new(scan),
+            Err(error_msg) => {
+                error!("❌ {}", error_msg);
+                std::process::exit(1);
+            }
+        }
+    });
+    let checkpoint_path = args.checkpoint.clone().or_else(|| args.resume.clone());
+    
+    // Every worker RNG is seeded from the OS mixed with these bytes
+    let user_entropy = if args.extra_entropy.is_some() || args.entropy_prompt {
+        let mut user_entropy = Zeroizing::new(Vec::new());
+        let sources = args.extra_entropy.as_deref().map(entropy::read_extra_entropy).into_iter()
+            .chain(args.entropy_prompt.then(entropy::prompt_keystrokes));
+        for source in sources {
+            match source {
+                Ok(bytes) => user_entropy.extend_from_slice(&bytes),
+                Err(error_msg) => {
+                    error!("❌ {}", error_msg);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(user_entropy)
+    } else {
+        None
+    };
+    
+    // Cleared by the signal handler for a graceful shutdown, which also records the signal
+    let running = Arc::new(AtomicBool::new(true));
+    let stop_signal = Arc::new(AtomicI32::new(0));
+    
+    let attempts = Arc::new(counters::AttemptCounters::new(num_threads));
+    info!("🔍 Searching for EVM vanity address...");
+    if let Some(seed) = args.seed {
+        warning!("⚠️  SEEDED RUN (--seed {}): every key below can be regenerated from the seed.", seed);
+        warning!("⚠️  These keys are for testing only. NEVER send real funds to them.");
+    }
+    if let Some(user_entropy) = &user_entropy {
+        info!("Extra entropy: {} bytes, mixed with the OS random generator", user_entropy.len());
+    }
+    if let Some(path) = &config_path {
+        if config_applied.is_empty() {
+            info!("Config: {} (every option overridden)", path.display());
+        } else {
+            info!("Config: {} ({})", path.display(), config_applied.join(", "));
+        }
+    }
+    if !env_applied.is_empty() {
+        info!("Environment: {}", env_applied.join(", "));
+    }
+    if let Some(word) = &args.word {
+        info!("Word '{}' spellings:", word);
+        for candidate in pattern_set.entries.iter().filter_map(|entry| entry.prefix.as_deref()) {
+            info!("  • {} (1 in {})", candidate, format_difficulty(pattern_difficulty(Some(candidate), None, args.case_sensitive)));
+        }
+        match suffix_pattern {
+            Some(suffix) => info!("Pattern: any of {} spellings AND suffix '{}'", pattern_set.len(), suffix),
+            None => info!("Pattern: any of {} spellings", pattern_set.len()),
+        }
+    } else if let Some(path) = &args.pattern_file {
+        info!("Pattern file: {} ({} patterns)", path.display(), pattern_set.len());
+        for entry in &pattern_set.entries {
+            info!("  • {} (1 in {})", entry,
+                  format_difficulty(pattern_difficulty(entry.prefix.as_deref(), entry.suffix.as_deref(), args.case_sensitive)));
+        }
+    } else if !args.alt.is_empty() {
+        info!("Pattern: any of {} alternatives", pattern_set.len());
+        for entry in &pattern_set.entries {
+            info!("  • {} (1 in {})", entry,
+                  format_difficulty(pattern_difficulty(entry.prefix.as_deref(), entry.suffix.as_deref(), args.case_sensitive)));
+        }
+    } else if let Some(wrap) = &args.wrap {
+        info!("Pattern: wraps '{}'", wrap);
+    } else if let Some(palindrome) = palindrome {
+        if palindrome.length == 40 {
+            info!("Pattern: palindrome (all 40 characters)");
+        } else {
+            info!("Pattern: palindrome, first {} characters mirror the last {}", palindrome.length, palindrome.length);
+        }
+    } else if let (Some(scorer), Some(path)) = (&scorer, &args.score_config) {
+        info!("Pattern: best score per {}", path.display());
+        let config = &scorer.config;
+        for (name, weight) in [
+            ("leading zeros", config.leading_zero_nibbles),
+            ("longest run", config.longest_repeat_run),
+            ("trailing zeros", config.trailing_zeros),
+        ] {
+            if weight > 0.0 {
+                info!("  • {} × {}", name, weight);
+            }
+        }
+        for (text, weight) in &config.contains {
+            info!("  • contains '{}': {}", text, weight);
+        }
+        if let Some(leaderboard) = &leaderboard {
+            match leaderboard.champion() {
+                Some(champion) => info!("Leaderboard: {}, best ever {} with score {}", leaderboard.path().display(), champion.address, champion.score.total),
+                None => info!("Leaderboard: {} (empty)", leaderboard.path().display()),
+            }
+        }
+    } else if let Some(nibble) = args.maximize {
+        info!("Pattern: longest run of leading '{:x}'", nibble);
+    } else if let Some(criterion) = &criterion {
+        match &args.score_prefix {
+            Some(prefix) => info!("Pattern: top {} by {} of '{}'", args.top_k, criterion.name(), prefix.iter().map(|nibble| format!("{:x}", nibble)).collect::<String>()),
+            None => info!("Pattern: top {} by {}", args.top_k, criterion.name()),
+        }
+    } else if let Some(repeating) = repeating {
+        let run = match repeating.nibble {
+            Some(nibble) => format!("{} repeating '{:x}'", repeating.length, nibble),
+            None => format!("{} repeating characters", repeating.length),
+        };
+        match suffix_pattern {
+            Some(suffix) => info!("Pattern: {} AND suffix '{}'", run, suffix),
+            None => info!("Pattern: {}", run),
+        }
+    } else {
+        info!("Pattern: {}", pattern_set.entries[0]);
+    }
+    if let (Some(total), Some(_)) = (scan_total, &mnemonic_chains) {
+        let accounts = if args.accounts == 1 { "0".to_string() } else { format!("0..{}", args.accounts - 1) };
+        info!("Mode: your mnemonic, m/44'/60'/{}'/0/{}..={} ({} addresses)",
+              accounts, args.start_index, args.start_index as u64 + args.index_count - 1, format_with_commas(total));
+    } else if let Some(key_range) = &key_range {
+        let fraction = key_range.keyspace_fraction() * 100.0;
+        let percentage = if fraction >= 0.01 { format!("{:.2}%", fraction) } else { format!("{:.2e}%", fraction) };
+        info!("Key range: 0x{} - 0x{} ({} of the keyspace)", hex::encode(key_range.min), hex::encode(key_range.max), percentage);
+        if let Some(total) = scan_total {
+            info!("Mode: sequential scan of {} keys", format_with_commas(total));
+        }
+    } else if let Some(scan) = &sequential_scan {
+        info!("Mode: sequential scan from --start-key, thread i in the sub-range starting at start + i·2^64");
+        if args.resume.is_some() {
+            info!("Resuming: {} keys already checked", format_with_commas(scan.checked()));
+        }
+        if let Some(path) = &checkpoint_path {
+            info!("Checkpoint: {}", path.display());
+        }
+        warning!("⚠️  Every key of this scan follows from --start-key: keep it as secret as a private key");
+    } else if args.mode == SearchMode::Mnemonic {
+        info!("Mode: mnemonic ({}-word phrases, m/44'/60'/0'/0/0..{})", entropy_bits / 32 * 3, args.scan_depth - 1);
+    }
+    for exclusion in &exclusions {
+        match exclusion {
+            Exclusion::Prefix(prefix) => info!("Excluding: prefix '{}'", prefix),
+            Exclusion::Suffix(suffix) => info!("Excluding: suffix '{}'", suffix),
+        }
+    }
+    if fuzzy_distance > 0 {
+        info!("Fuzzy: up to {} mismatched characters", fuzzy_distance);
+    }
+    info!("Case sensitive: {}", args.case_sensitive);
+    if let Some(chain_id) = args.chain_id.filter(|&chain_id| chain_id != 1) {
+        info!("Checksum: EIP-1191 for chain {}", chain_id);
+    }
+    if let Some(excluded) = args.exclude {
+        info!("Excluding characters: {} anywhere in the address", excluded);
+    }
+    if leading_zero_bytes > 0 {
+        info!("Leading zero bytes: {} (saves {} gas per use in calldata)", leading_zero_bytes, 12 * leading_zero_bytes);
+    }
+    if let Some((offset, pattern, _)) = at_offset {
+        info!("At offset {}: '{}'", offset, pattern);
+    }
+    if let Some(bit_mask) = &bit_mask {
+        info!("Mask: 0x{} == 0x{} ({} bits, 1 in {})",
+              hex::encode(bit_mask.mask), hex::encode(bit_mask.value), bit_mask.bits(), format_difficulty(bit_mask.difficulty()));
+    }
+    let difficulty = pattern_set.difficulty(fuzzy_distance, args.case_sensitive)
+        * repeating.map_or(1.0, |repeating| repeating.difficulty())
+        * palindrome.map_or(1.0, |palindrome| palindrome.difficulty())
+        * bit_mask.map_or(1.0, |bit_mask| bit_mask.difficulty())
+        * 256f64.powi(leading_zero_bytes as i32)
+        * at_offset.map_or(1.0, |(_, pattern, _)| pattern_difficulty(Some(pattern), None, args.case_sensitive));
+    
+    // Characters fixed by the pattern are already known not to be excluded; the estimate
+    // uses the longest pattern, which is the likeliest to be found first
+    let exclusion_factor = args.exclude.map_or(1.0, |excluded| {
+        let fixed = pattern_set.entries.iter()
+            .map(PatternEntry::fixed_len)
+            .max()
+            .unwrap_or(0)
+            + repeating.map_or(0, |repeating| repeating.difficulty().log(16.0).round() as usize)
+            + palindrome.map_or(0, |palindrome| palindrome.length.min(20));
+        excluded.difficulty(40usize.saturating_sub(fixed))
+    });
+    let difficulty = difficulty * exclusion_factor;
+    let best_effort = scorer.is_some() || args.maximize.is_some() || criterion.is_some();
+    if !best_effort {
+        info!("Difficulty: 1 in {}", format_difficulty(difficulty));
+    }
+    if args.exclude.is_some() && !best_effort && difficulty > 16f64.powi(10) {
+        warning!("⚠️  Excluding characters alone makes the search {}x harder", format_difficulty(exclusion_factor));
+    }
+    if let Some(wrap) = &args.wrap {
+        if difficulty > 16f64.powi(10) {
+            warning!("⚠️  Wrapping needs {} matching characters, as hard as a {}-character prefix",
+                  2 * pattern_len(wrap), 2 * pattern_len(wrap));
+        }
+    }
+    if args.stream {
+        match &args.output_file {
+            Some(path) => info!("Matches: streamed as JSON lines to {}", path.display()),
+            None => info!("Matches: streamed as JSON lines to stdout"),
+        }
+    } else if resumed > 0 {
+        info!("Matches wanted: {} more, {} already in {}", args.count, resumed, args.output_file.as_ref().expect("required by --resume-count").display());
+    } else if args.count > 1 {
+        info!("Matches wanted: {}", args.count);
+    }
+    // An unwritable directory fails here rather than when the first match is found
+    let save_dir = args.save_dir.clone().or_else(|| args.autosave.then(|| autosave::DEFAULT_DIR.into()));
+    let mut save_dir = save_dir.map(|dir: std::path::PathBuf| match autosave::SaveDir::prepare(&dir) {
+        Ok(save_dir) => {
+            info!("Saving matches to: {}", dir.display());
+            save_dir
+        }
+        Err(e) => {
+            error!("❌ Cannot save matches to {}: {}", dir.display(), e);
+            std::process::exit(1);
+        }
+    });
+    // With --count, matches are appended to --output-file as JSON lines as they are found,
+    // so a crash keeps every match already written
+    let results_file = args
+        .output_file
+        .as_ref()
+        .filter(|_| !args.stream && args.encrypt_to.is_empty() && (args.count > 1 || args.resume_count))
+        .map(|path| {
+            let file = if args.resume_count { append_private_file(path) } else { create_private_file(path) };
+            match file {
+                Ok(file) => Mutex::new(Some(std::io::BufWriter::with_capacity(4096, file))),
+                Err(e) => {
+                    error!("❌ Failed to open {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            }
+        });
+    if args.threads == Some(Threads::AutoTune) {
+        info!("Threads: {} (auto-tuned)", num_threads);
+    } else {
+        info!("Threads: {}", num_threads);
+    }
+    if let Some(plan) = &core_plan {
+        info!("{}", describe_core_plan(plan, args.pin_cores));
+    }
+    // A gpu or cuda build searches random raw-mode keys on the GPU, fed by pool thread 0.
+    // Other searches, and reproducible ones, need keys the kernels do not make.
+    let gpu_search = cfg!(any(feature = "gpu", feature = "cuda")) && args.mode == SearchMode::Raw && mnemonic_chains.is_none() && sequential_scan.is_none()
+        && key_range.is_none() && args.maximize.is_none() && criterion.is_none() && scorer.is_none() && near_miss.is_none()
+        && args.seed.is_none() && user_entropy.is_none();
+    if gpu_flags.iter().any(|&(_, given)| given) && !gpu_search {
+        warning!("⚠️  Only random raw-mode keys are searched on the GPU; this search runs on the CPU");
+    }
+    // The bits every match has, so CUDA only hands back the candidates that may match
+    let mut leading_zeros = [0; 20];
+    leading_zeros[..leading_zero_bytes].fill(0xff);
+    let device_filter = if fuzzy_distance == 0 { pattern_set.compile(&exclusions, args.case_sensitive).common_bits() } else { BitMask::new([0; 20], [0; 20]) }
+        .and(&BitMask::new(leading_zeros, [0; 20]))
+        .and(&bit_mask.unwrap_or(BitMask::new([0; 20], [0; 20])));
+    let gpu_batch_size = args.gpu_batch_size.unwrap_or(device::DEFAULT_BATCH_SIZE);
+    let device_searcher = gpu_search.then(|| device::open(args.gpu_device.unwrap_or(0), gpu_batch_size, &device_filter)).flatten().map(|searcher| {
+        info!("GPU: {}", searcher.name());
+        Mutex::new(Some(searcher))
+    });
+    if let Some(time_limit) = args.time_limit {
+        info!("Time limit: {}s", time_limit);
+    }
+    if let Some(max_attempts) = args.max_attempts {
+        info!("Attempt limit: {}", format_with_commas(max_attempts));
+    }
+    let throttle = match (args.cpu_limit, args.nice) {
+        (Some(percent), true) => Some(format!("{}% CPU, low priority", percent)),
+        (Some(percent), false) => Some(format!("{}% CPU", percent)),
+        (None, true) => Some("low priority".to_string()),
+        (None, false) => None,
+    };
+    if let Some(throttle) = &throttle {
+        info!("Throttled: {}", throttle);
+    }
+    let throttle_note = throttle.map(|throttle| format!(" | Throttled: {}", throttle)).unwrap_or_default();
+    // The dashboard owns the screen, so it needs a terminal no matches are streamed to
+    let tui_enabled = args.tui && if !std::io::stdout().is_terminal() {
+        warning!("⚠️  stdout is not a terminal, showing progress lines instead of --tui");
+        false
+    } else if args.stream && args.output_file.is_none() {
+        warning!("⚠️  Matches are streamed to stdout, showing progress lines instead of --tui");
+        false
+    } else {
+        true
+    };
+    // Bounded runs stop on their own, so only open-ended searches need confirming
+    if !best_effort && scan_total.is_none() && args.time_limit.is_none() && args.max_attempts.is_none() {
+        confirm_expected_duration(&args, difficulty * args.count as f64, num_threads, core_plan.as_deref());
+    }
+    
+    let start_time = Instant::now();
+    
+    let stats_csv = match &args.stats_file {
+        Some(path) => match stats::StatsCsv::open(path, num_threads, start_time) {
+            Ok(stats_csv) => Some(stats_csv),
+            Err(e) => {
+                error!("❌ Failed to open stats file {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+    let stats_interval = Duration::from_secs(args.stats_interval);
+    let matches_found = Arc::new(AtomicU64::new(0));
+    
+    // Scrapes read the search's counters; the server stops when the search ends
+    let metrics_server = match args.metrics_addr {
+        Some(address) => {
+            let source = metrics::Source::new(attempts.clone(), matches_found.clone(), start_time, num_threads);
+            match metrics::start(address, source).await {
+                Ok(server) => Some(server),
+                Err(e) => {
+                    error!("❌ Failed to serve metrics on {}: {}", address, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => None,
+    };
+    info!("Press Ctrl+C to stop\n");
+    
+    // Shared data between workers
+    let case_sensitive = args.case_sensitive;
+    let count = args.count;
+    let stream = args.stream;
+    let external_chain: hd::DerivationPath = "m/44'/60'/0'/0".parse().expect("valid derivation path");
+    
+    // Setup signal handling for graceful shutdown, with a status line on demand
+    let status_attempts = attempts.clone();
+    let status_matches = matches_found.clone();
+    let status = move || {
+        let attempts = status_attempts.total();
+        let elapsed = start_time.elapsed();
+        let rate = attempts as f64 / elapsed.as_secs_f64();
+        tracing::info!(attempts, rate, elapsed_secs = elapsed.as_secs_f64(), "status requested");
+        if stream {
+            progress!("📊 Status: Attempts: {} | Rate: {} | Elapsed: {} | Matches: {}",
+                  attempts, output::rate(rate), output::elapsed(elapsed), status_matches.load(Ordering::SeqCst));
+        } else if count > 1 {
+            progress!("📊 Status: Attempts: {} | Rate: {} | Elapsed: {} | Matches: {} of {}",
+                  attempts, output::rate(rate), output::elapsed(elapsed), status_matches.load(Ordering::SeqCst), count);
+        } else {
+            progress!("📊 Status: Attempts: {} | Rate: {} | Elapsed: {}", attempts, output::rate(rate), output::elapsed(elapsed));
+        }
+    };
+    tokio::spawn(signals::handle_signals(running.clone(), stop_signal.clone(), status));
+    
+    // Notifications and the dashboard describe the search in one line, like the banner's pattern
+    let label = if let Some(word) = &args.word {
+        format!("any of {} spellings of '{}'", pattern_set.len(), word)
+    } else if let Some(path) = &args.pattern_file {
+        format!("any of {} patterns in {}", pattern_set.len(), path.display())
+    } else if !args.alt.is_empty() {
+        format!("any of {} alternatives", pattern_set.len())
+    } else if let Some(wrap) = &args.wrap {
+        format!("wraps '{}'", wrap)
+    } else if let Some(palindrome) = palindrome {
+        format!("palindrome of {} characters", palindrome.length)
+    } else if scorer.is_some() {
+        "best score".to_string()
+    } else if let Some(nibble) = args.maximize {
+        format!("longest run of leading '{:x}'", nibble)
+    } else if let Some(criterion) = &criterion {
+        format!("top {} by {}", args.top_k, criterion.name())
+    } else if let Some(repeating) = repeating {
+        format!("{} repeating characters", repeating.length)
+    } else {
+        pattern_set.entries[0].to_string()
+    };
+    let (notifier, notify_dispatcher) = if notify_channels.is_empty() {
+        (None, None)
+    } else {
+        let (notifier, dispatcher) = notify::start(notify_channels, label.clone());
+        (Some(notifier), Some(dispatcher))
+    };
+    let progress_notifier = notifier.clone().zip(args.notify_progress);
+    tracing::info!(pattern = label.as_str(), threads = num_threads, difficulty, count, "search started");
+    if let Some(save_dir) = &mut save_dir {
+        save_dir.set_search(serde_json::json!({
+            "pattern": label,
+            "mode": args.mode.to_possible_value().expect("no skipped modes").get_name(),
+            "case_sensitive": args.case_sensitive,
+            "chain_id": args.chain_id,
+            "threads": num_threads,
+            "difficulty": (!best_effort).then_some(difficulty),
+            "count": (!stream).then_some(count),
+            "version": env!("CARGO_PKG_VERSION"),
+        }));
+    }
+    
+    let progress_running = running.clone();
+    let progress_attempts = attempts.clone();
+    let progress_best = scorer.as_ref().map(|_| best.clone());
+    let progress_best_run = args.maximize.map(|nibble| (nibble, best_run_len.clone()));
+    let progress_top = criterion.as_ref().map(|criterion| (criterion.name(), top.clone()));
+    let time_limit = args.time_limit.map(Duration::from_secs);
+    let time_limit_reached = Arc::new(AtomicBool::new(false));
+    let max_attempts_reached = AtomicBool::new(false);
+    let sequential_claimed = AtomicU64::new(0);
+    let stream_closed = AtomicBool::new(false);
+    let progress_time_limit_reached = time_limit_reached.clone();
+    let workers_done = Arc::new(AtomicBool::new(false));
+    let progress_workers_done = workers_done.clone();
+    let progress_checkpoint = sequential_scan.clone().zip(checkpoint_path.clone());
+    // The dashboard's `p` holds the workers between work items
+    let paused = Arc::new(AtomicBool::new(false));
+    let recent_finds = Arc::new(Mutex::new(VecDeque::new()));
+    let progress_paused = paused.clone();
+    let progress_recent_finds = stream.then(|| recent_finds.clone());
+    let progress_stop_signal = stop_signal.clone();
+    let progress_matches = matches_found.clone();
+    // Workers retired after a panic; the others keep searching
+    let failed_workers = Arc::new(AtomicUsize::new(0));
+    let progress_failed_workers = failed_workers.clone();
+    let stats_path = args.stats_file.clone();
+    let top_k = args.top_k;
+    // Progress reporting thread
+    let progress_handle = thread::spawn(move || {
+        let console_interval = Duration::from_secs(5);
+        let checkpoint_interval = Duration::from_secs(10);
+        let log_interval = Duration::from_secs(60);
+        let mut last_attempts = 0u64;
+        let mut last_time = Instant::now();
+        let mut next_console = last_time + console_interval;
+        let mut stats_csv = stats_csv;
+        let mut next_stats = last_time + stats_interval;
+        let mut next_checkpoint = last_time + checkpoint_interval;
+        let mut next_log = last_time + log_interval;
+        let mut last_logged = (last_time, 0u64);
+        let mut notified_milestones = 0u64;
+        let mut dashboard = if tui_enabled {
+            match tui::Dashboard::open(num_threads) {
+                Ok(dashboard) => Some(dashboard),
+                Err(e) => {
+                    warning!("⚠️  Failed to open the dashboard, showing progress lines: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let mut next_draw = last_time;
+        
+        // Tick often so the console and CSV intervals stay independent and shutdown is prompt
+        while progress_running.load(Ordering::SeqCst) && !progress_workers_done.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(100));
+            
+            let current_attempts = progress_attempts.total();
+            let current_time = Instant::now();
+            
+            // The workers wind down as if stopped by a signal, but without one recorded
+            if time_limit.is_some_and(|limit| current_time.duration_since(start_time) >= limit) {
+                progress_time_limit_reached.store(true, Ordering::SeqCst);
+                progress_running.store(false, Ordering::SeqCst);
+            }
+            
+            if let Some(csv) = stats_csv.as_mut() {
+                if current_time >= next_stats {
+                    if let Err(e) = csv.record(current_attempts, current_time, start_time) {
+                        warning!("⚠️  Failed to write stats file: {}", e);
+                        tracing::error!(error = %e, "stats file write failed");
+                    }
+                    next_stats += stats_interval;
+                }
+            }
+            
+            if let Some((scan, path)) = &progress_checkpoint {
+                if current_time >= next_checkpoint {
+                    if let Err(e) = scan.save(path) {
+                        warning!("⚠️  Failed to write checkpoint {}: {}", path.display(), e);
+                        tracing::error!(path = %path.display(), error = %e, "checkpoint write failed");
+                    }
+                    next_checkpoint += checkpoint_interval;
+                }
+            }
+            
+            // A snapshot a minute, so a stalled run shows in the log as a falling rate
+            if current_time >= next_log {
+                let rate = current_attempts.saturating_sub(last_logged.1) as f64 / current_time.duration_since(last_logged.0).as_secs_f64();
+                tracing::info!(attempts = current_attempts, rate, elapsed_secs = current_time.duration_since(start_time).as_secs_f64(),
+                               matches = progress_matches.load(Ordering::SeqCst), "progress");
+                last_logged = (current_time, current_attempts);
+                next_log += log_interval;
+            }
+            
+            if let Some((notifier, step)) = &progress_notifier {
+                let milestones = current_attempts / step;
+                if milestones > notified_milestones {
+                    let total_elapsed = current_time.duration_since(start_time);
+                    notifier.milestone(milestones * step, current_attempts as f64 / total_elapsed.as_secs_f64(), total_elapsed);
+                    notified_milestones = milestones;
+                }
+            }
+            
+            if let Some(dashboard) = dashboard.as_mut() {
+                for action in dashboard.actions() {
+                    match action {
+                        tui::Action::Quit => {
+                            // A second quit exits straight away, so the terminal is restored first
+                            if !progress_running.load(Ordering::SeqCst) {
+                                tui::restore();
+                            }
+                            signals::stop(&progress_running, &progress_stop_signal, signals::SIGINT);
+                        }
+                        tui::Action::Pause => {
+                            progress_paused.fetch_xor(true, Ordering::SeqCst);
+                        }
+                        tui::Action::Snapshot => {
+                            dashboard.message = Some(match (stats_csv.as_mut(), &stats_path) {
+                                (Some(csv), Some(path)) => match csv.record(current_attempts, current_time, start_time) {
+                                    Ok(()) => format!("Stats row written to {}", path.display()),
+                                    Err(e) => format!("Failed to write stats file: {}", e),
+                                },
+                                _ => "No --stats-file to write a snapshot to".to_string(),
+                            });
+                        }
+                    }
+                }
+                if current_time >= next_draw {
+                    next_draw = current_time + Duration::from_secs(1);
+                    let (finds_title, finds) = if let Some(best) = &progress_best {
+                        ("Best so far".to_string(), best.peek(|score, wallet| format!("{}  score {}", wallet.address, score)).into_iter().collect())
+                    } else if let Some((nibble, run)) = &progress_best_run {
+                        ("Best so far".to_string(), vec![format!("{} leading '{:x}'s", run.load(Ordering::Relaxed), nibble)])
+                    } else if let Some((name, top)) = &progress_top {
+                        (format!("Top {} by {}", top_k, name), top.peek(|score, wallet| format!("{}  {}", wallet.address, score)))
+                    } else if let Some(recent_finds) = &progress_recent_finds {
+                        ("Recent finds".to_string(), recent_finds.lock().unwrap().iter().cloned().collect())
+                    } else {
+                        (String::new(), Vec::new())
+                    };
+                    let frame = tui::Frame {
+                        label: &label,
+                        attempts: current_attempts,
+                        per_thread: &progress_attempts.per_thread(),
+                        elapsed: current_time.duration_since(start_time),
+                        difficulty: (!best_effort).then_some(difficulty),
+                        count: (!stream).then_some(count),
+                        matches: progress_matches.load(Ordering::SeqCst),
+                        paused: progress_paused.load(Ordering::SeqCst),
+                        finds_title: &finds_title,
+                        finds: &finds,
+                    };
+                    if let Err(e) = dashboard.draw(&frame) {
+                        dashboard.message = Some(format!("Failed to draw: {}", e));
+                    }
+                }
+                continue;
+            }
+            
+            if current_time < next_console {
+                continue;
+            }
+            next_console += console_interval;
+            
+            if current_attempts > last_attempts {
+                let elapsed = current_time.duration_since(last_time);
+                let rate = (current_attempts - last_attempts) as f64 / elapsed.as_secs_f64();
+                let total_elapsed = current_time.duration_since(start_time);
+                
+                if let Some(total) = scan_total {
+                    progress!("⏳ Index {} of {} | Rate: {} | Elapsed: {}{}",
+                          format_with_commas(current_attempts), format_with_commas(total), output::rate(rate), output::elapsed(total_elapsed), throttle_note);
+                } else if current_attempts % 500000 < last_attempts % 500000 || 
+                   current_attempts - last_attempts >= 500000 {
+                    let best_note = progress_best.as_ref()
+                        .and_then(|best| best.score())
+                        .map(|score| format!(" | Best: {}", score))
+                        .or_else(|| progress_best_run.as_ref().map(|(nibble, run)| {
+                            format!(" | Best: {} leading '{:x}'s", run.load(Ordering::Relaxed), nibble)
+                        }))
+                        .or_else(|| progress_top.as_ref().and_then(|(name, top)| {
+                            top.best_score().map(|score| format!(" | Best: {} {}", score, name))
+                        }))
+                        .unwrap_or_default();
+                    let workers_note = match progress_failed_workers.load(Ordering::SeqCst) {
+                        0 => String::new(),
+                        failed => format!(" | Workers: {} of {}", num_threads - failed, num_threads),
+                    };
+                    progress!("⏳ Attempts: {} | Rate: {} | Elapsed: {}{}{}{}",
+                          current_attempts, output::rate(rate), output::elapsed(total_elapsed), best_note, workers_note, throttle_note);
+                }
+                
+                last_attempts = current_attempts;
+                last_time = current_time;
+            }
+        }
+        
+        if let Some(dashboard) = dashboard {
+            dashboard.close();
+        }
+        
+        // Final row so the log ends with the totals of this run
+        if let Some(csv) = stats_csv.as_mut() {
+            if let Err(e) = csv.record(progress_attempts.total(), Instant::now(), start_time) {
+                warning!("⚠️  Failed to write stats file: {}", e);
+                tracing::error!(error = %e, "stats file write failed");
+            }
+        }
+    });
+    
+    // Rayon's pool runs the workers; --nice lowers the priority of each pool thread
+    // --pin-cores/--cores restrict each pool thread to its cores; unsupported platforms run unpinned
+    let nice = args.nice;
+    let pool_core_plan = core_plan.clone();
+    let affinity_failed = Arc::new(AtomicBool::new(false));
+    let pool_affinity_failed = affinity_failed.clone();
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .start_handler(move |thread_index| {
+            tracing::debug!(thread = thread_index, "worker started");
+            if nice {
+                if let Err(error_msg) = throttle::lower_thread_priority() {
+                    if thread_index == 0 {
+                        warning!("⚠️  Failed to lower the priority: {}", error_msg);
+                    }
+                    tracing::warn!(thread = thread_index, error = %error_msg, "lowering the priority failed");
+                }
+            }
+            if let Some(cores) = pool_core_plan.as_ref().map(|plan| &plan[thread_index]) {
+                if let Err(error_msg) = affinity::set_current_thread(cores) {
+                    pool_affinity_failed.store(true, Ordering::Relaxed);
+                    if thread_index == 0 {
+                        warning!("⚠️  Failed to set the CPU affinity, continuing unpinned: {}", error_msg);
+                    }
+                    tracing::warn!(thread = thread_index, error = %error_msg, "setting the CPU affinity failed");
+                }
+            }
+        })
+        .build_global()
+        .expect("the global thread pool is only configured once");
+    
+    let secp = Secp256k1::new();
+    
+    // Index of the matched pattern. Exact matching compares raw address bytes and never hex
+    // encodes a candidate; --fuzzy needs the hex address.
+    let compiled_set = (fuzzy_distance == 0).then(|| pattern_set.compile(&exclusions, case_sensitive));
+    let first_nibble = compiled_set.as_ref().and_then(|compiled_set| compiled_set.leading_nibble(0));
+    let second_nibble = compiled_set.as_ref().and_then(|compiled_set| compiled_set.leading_nibble(1));
+    // --fuzzy counts mismatched nibbles before formatting the address for the full check
+    let fuzzy_nibbles = (fuzzy_distance > 0).then(|| pattern_set.nibble_patterns()).flatten();
+    let find_match = |address: &[u8; 20]| {
+        if !first_nibble.is_none_or(|nibble| first_nibble_ok(address, nibble))
+            || !second_nibble.is_none_or(|nibble| second_nibble_ok(address, nibble))
+            || !address[..leading_zero_bytes].iter().all(|&byte| byte == 0)
+            || !at_offset.is_none_or(|(_, _, compiled)| compiled.matches(address))
+            || !repeating.is_none_or(|repeating| repeating.matches(address))
+            || !palindrome.is_none_or(|palindrome| palindrome.matches(address))
+            || !bit_mask.is_none_or(|bit_mask| bit_mask.matches(address))
+            || !args.exclude.is_none_or(|excluded| excluded.matches(address))
+        {
+            return None;
+        }
+        match &compiled_set {
+            Some(compiled_set) => compiled_set.find_match(address),
+            None => {
+                if let Some(entries) = &fuzzy_nibbles {
+                    let nibbles = bytes_to_nibbles(address);
+                    let distance = |(prefix, suffix): &(Option<NibblePattern>, Option<NibblePattern>)| {
+                        prefix.map_or(0, |prefix| prefix.prefix_mismatches(&nibbles)) + suffix.map_or(0, |suffix| suffix.suffix_mismatches(&nibbles))
+                    };
+                    if !entries.iter().any(|entry| distance(entry) <= fuzzy_distance) {
+                        return None;
+                    }
+                }
+                pattern_set.find_match(&format_address(address), fuzzy_distance, &exclusions, case_sensitive)
+            }
+        }
+    };
+    
+    // Test hook for tests/panics.rs: in debug builds, every work item of this thread panics
+    #[cfg(debug_assertions)]
+    let panic_thread = std::env::var("EVM_VANITY_TEST_PANIC_THREAD").ok().and_then(|thread| thread.parse::<usize>().ok());
+    
+    // Check one work item (a random candidate, or item `work` of a bounded scan) and
+    // return its matches; None once the search has been stopped, which ends the iterator
+    let search = |worker: &mut WorkerState, work: u64| -> Option<Vec<(usize, WalletInfo)>> {
+        if !running.load(Ordering::SeqCst) {
+            return None;
+        }
+        while paused.load(Ordering::SeqCst) && running.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(100));
+        }
+        // Checked per work item, so the limit is overshot by at most a batch per thread
+        if args.max_attempts.is_some_and(|max_attempts| attempts.total() >= max_attempts) {
+            max_attempts_reached.store(true, Ordering::SeqCst);
+            running.store(false, Ordering::SeqCst);
+            return None;
+        }
+        #[cfg(debug_assertions)]
+        if panic_thread == Some(worker.thread) {
+            panic!("injected panic");
+        }
+        let mut matches = Vec::new();
+        
+        if let Some(chains) = &mnemonic_chains {
+            worker.attempt();
+            let (chain_path, chain) = &chains[(work / args.index_count) as usize];
+            let index = args.start_index + (work % args.index_count) as u32;
+            let child = chain.derive_child(&secp, index);
+            let address = address_bytes_from_secret_key(&secp, &child.secret_key);
+            worker.generated(&address);
+            
+            if let Some(pattern_index) = find_match(&address) {
+                // The user already holds the seed, so only the path, address and public key are kept
+                let (public_key_uncompressed, public_key_compressed) = public_key_hex(&secp, &child.secret_key);
+                let wallet = WalletInfo {
+                    address: format_address(&address),
+                    private_key: Zeroizing::new(String::new()),
+                    mnemonic: None,
+                    derivation_path: Some(chain_path.child(index).to_string()),
+                    public_key_uncompressed,
+                    public_key_compressed,
+                };
+                matches.push((pattern_index, wallet));
+            }
+        } else if let Some(scan) = &sequential_scan {
+            // The offset only moves past checked keys, up to a match
+            let thread = rayon::current_thread_index().expect("workers run on the thread pool");
+            let first = scan.offset(thread);
+            let end = first + KEYGEN_BATCH_SIZE as u64;
+            let mut next = end;
+            for offset in first..end {
+                worker.attempt();
+                let Some(mut private_key) = scan.key_at(thread, offset) else {
+                    continue;
+                };
+                let address = address_bytes_from_secret_key(&secp, &private_key);
+                worker.generated(&address);
+                if let Some(near_miss) = &near_miss {
+                    near_miss.offer(&mut worker.near_miss, &address, || generate_wallet_info(private_key));
+                }
+                
+                if let Some(pattern_index) = find_match(&address) {
+                    // A match past `count` would be dropped, so its key is left for the resume
+                    if !stream && sequential_claimed.fetch_add(1, Ordering::SeqCst) >= count {
+                        private_key.non_secure_erase();
+                        scan.advance(thread, offset);
+                        return None;
+                    }
+                    // The offset only passes the match once it is delivered: stopping the
+                    // search discards matches still on their way, and the resume finds them again
+                    matches.push((pattern_index, generate_wallet_info(private_key)));
+                    private_key.non_secure_erase();
+                    next = offset;
+                    break;
+                }
+                private_key.non_secure_erase();
+            }
+            scan.advance(thread, next);
+        } else if let (Some(key_range), Some(_)) = (&key_range, scan_total) {
+            worker.attempt();
+            let mut private_key = key_range.key_at(work);
+            let address = address_bytes_from_secret_key(&secp, &private_key);
+            worker.generated(&address);
+            if let Some(near_miss) = &near_miss {
+                near_miss.offer(&mut worker.near_miss, &address, || generate_wallet_info(private_key));
+            }
+            
+            if let Some(pattern_index) = find_match(&address) {
+                matches.push((pattern_index, generate_wallet_info(private_key)));
+            }
+            private_key.non_secure_erase();
+        } else {
+            match args.mode {
+                SearchMode::Raw => 'raw: {
+                    // Pool thread 0 runs a GPU batch per work item while it has the device
+                    if let Some(device_searcher) = device_searcher.as_ref().filter(|_| worker.thread == 0) {
+                        let mut device_searcher = device_searcher.lock().unwrap();
+                        if let Some(searcher) = device_searcher.as_mut() {
+                            match searcher.search_batch(&mut |address| {
+                                worker.generated(address);
+                                find_match(address).is_some()
+                            }) {
+                                Ok((checked, keys)) => {
+                                    worker.attempts.add(worker.thread, checked);
+                                    for mut private_key in keys {
+                                        let address = address_bytes_from_secret_key(&secp, &private_key);
+                                        if let Some(pattern_index) = find_match(&address) {
+                                            matches.push((pattern_index, generate_wallet_info(private_key)));
+                                        }
+                                        private_key.non_secure_erase();
+                                    }
+                                }
+                                Err(error_msg) => {
+                                    error!("❌ GPU search failed, continuing on the CPU: {}", error_msg);
+                                    tracing::error!(error = %error_msg, "GPU search failed");
+                                    *device_searcher = None;
+                                }
+                            }
+                            break 'raw;
+                        }
+                    }
+                    
+                    // Generate a batch of addresses; the rest of a batch is discarded after a match
+                    let mut batch = generate_addresses_batch(&secp, &mut worker.rng, key_range.as_ref(), KEYGEN_BATCH_SIZE);
+                    
+                    for (address, private_key) in &batch {
+                        worker.attempt();
+                        worker.generated(address);
+                        
+                        // Maximize mode never matches either; the lock is only taken by a new best
+                        if let Some(nibble) = args.maximize {
+                            let run = count_leading_char(address, nibble);
+                            if run > best_run_len.load(Ordering::Relaxed) && find_match(address).is_some() {
+                                let mut best_wallet = best_run.lock().unwrap();
+                                if run > best_run_len.load(Ordering::Relaxed) {
+                                    best_run_len.store(run, Ordering::Relaxed);
+                                    let wallet = generate_wallet_info(*private_key);
+                                    info!("New best: {} leading '{:x}'s: {}", run, nibble, wallet.address);
+                                    *best_wallet = Some(wallet);
+                                }
+                            }
+                            continue;
+                        }
+                        
+                        // Top-K mode never matches either, it keeps the K best candidates
+                        if let Some(criterion) = &criterion {
+                            if find_match(address).is_some() {
+                                top.offer(score::score_address(address, criterion), || generate_wallet_info(*private_key));
+                            }
+                            continue;
+                        }
+                        
+                        // Scoring mode never matches, it only keeps the best candidate
+                        if let Some(scorer) = &scorer {
+                            if find_match(address).is_some() {
+                                let total = scorer.total(address);
+                                best.offer(total, || (scorer.breakdown(address), generate_wallet_info(*private_key)));
+                                if let Some(leaderboard) = &leaderboard {
+                                    leaderboard.offer(total, || {
+                                        let wallet = generate_wallet_info(*private_key);
+                                        let bip38_key = args.bip38.as_ref().map(|password| bip38::bip38_encrypt(&wallet.private_key_bytes(), &wallet.address, password));
+                                        leaderboard::Entry::new(&wallet.address, scorer.breakdown(address), bip38_key, &scorer.config)
+                                    });
+                                }
+                            }
+                            continue;
+                        }
+                        
+                        if let Some(near_miss) = &near_miss {
+                            near_miss.offer(&mut worker.near_miss, address, || generate_wallet_info(*private_key));
+                        }
+                        
+                        // Check if address matches pattern
+                        if let Some(pattern_index) = find_match(address) {
+                            // Found match - create full wallet info
+                            matches.push((pattern_index, generate_wallet_info(*private_key)));
+                            break;
+                        }
+                    }
+                    for (_, private_key) in &mut batch {
+                        private_key.non_secure_erase();
+                    }
+                }
+                SearchMode::Mnemonic => {
+                    // PBKDF2 in the seed derivation dominates, so amortize it over scan_depth addresses
+                    let mnemonic = generate_mnemonic(&mut worker.rng, entropy_bits);
+                    let master = hd::ExtendedPrivKey::from_seed(Zeroizing::new(mnemonic.to_seed("")).as_ref());
+                    let account = master.derive_path(&secp, &external_chain);
+                    
+                    for index in 0..args.scan_depth {
+                        worker.attempt();
+                        
+                        let child = account.derive_child(&secp, index);
+                        let address = address_bytes_from_secret_key(&secp, &child.secret_key);
+                        worker.generated(&address);
+                        
+                        if let Some(pattern_index) = find_match(&address) {
+                            let path = external_chain.child(index);
+                            matches.push((pattern_index, generate_mnemonic_wallet_info(&mnemonic, &path, child.secret_key)));
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Matches are never rate limited
+        if args.verbose {
+            for (_, wallet) in &matches {
+                eprintln!("MATCH: {}", wallet.address);
+            }
+        }
+        matches_found.fetch_add(matches.len() as u64, Ordering::SeqCst);
+        Some(matches)
+    };
+    let verbose_rate = args.verbose.then_some(args.verbose_rate);
+    let new_worker = || WorkerState::new(attempts.clone(), args.cpu_limit, args.seed, user_entropy.as_deref().map(Vec::as_slice), verbose_rate);
     
-    // Get public key
-    let public_key = PublicKey::from_secret_key(secp, &private_key);
+    // A panic loses its work item but never the search: the thread is retired, or with
+    // --respawn-workers given a fresh state. Bounded scans always restart it, as a retired
+    // thread would leave its share of the range unchecked.
+    let respawn = args.respawn_workers || scan_total.is_some() || sequential_scan.is_some();
+    let worker_panics: Vec<AtomicU64> = (0..num_threads).map(|_| AtomicU64::new(0)).collect();
+    let all_workers_failed = AtomicBool::new(false);
+    // Matches past the `take_any` below. Until a thread returns None, `take_any` only stops it
+    // at its next match, so every thread stops itself once `count` have been collected. A match
+    // is counted after `while_some` let it through, so stopping can no longer drop it.
+    let collected = AtomicU64::new(0);
+    let guarded_search = |worker: &mut WorkerState, work: u64| -> Option<Vec<(usize, WalletInfo)>> {
+        if !stream && collected.load(Ordering::SeqCst) >= count {
+            return None;
+        }
+        let thread = worker.thread;
+        // A retired thread idles until the search is over
+        if !respawn && worker_panics[thread].load(Ordering::SeqCst) > 0 {
+            thread::sleep(Duration::from_millis(100));
+            return running.load(Ordering::SeqCst).then(Vec::new);
+        }
+        let payload = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| search(worker, work))) {
+            Ok(matches) => return matches,
+            Err(payload) => payload,
+        };
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let panics = worker_panics[thread].fetch_add(1, Ordering::SeqCst) + 1;
+        tracing::error!(thread, panics, error = %message, "worker panicked");
+        if respawn {
+            // Only the first panic of a thread is shown, so a recurring one cannot flood the console
+            if panics == 1 {
+                error!("❌ Worker {} panicked: {}; restarting it", thread, message);
+            }
+            // A panic that recurs is retried ever more slowly (up to ~13s), leaving the CPU to the others
+            let restart = Instant::now() + Duration::from_millis(100 << (panics - 1).min(7));
+            while Instant::now() < restart && running.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(100));
+            }
+            *worker = new_worker();
+            // The replaced state hands its RNG back for the next job, which gets a fresh one instead
+            WORKER_RNG.with(|rng| rng.borrow_mut().take());
+            return Some(Vec::new());
+        }
+        let failed = failed_workers.fetch_add(1, Ordering::SeqCst) + 1;
+        if failed == num_threads {
+            error!("❌ Worker {} panicked: {}; no workers are left", thread, message);
+            all_workers_failed.store(true, Ordering::SeqCst);
+            running.store(false, Ordering::SeqCst);
+            return None;
+        }
+        error!("❌ Worker {} panicked: {}; continuing with {} of {} workers (--respawn-workers restarts them)",
+               thread, message, num_threads - failed, num_threads);
+        Some(Vec::new())
+    };
     
-    // Get uncompressed public key bytes (remove the 0x04 prefix)
-    let public_key_bytes = public_key.serialize_uncompressed();
-    let public_key_hash = &public_key_bytes[1..]; // Remove first byte (0x04)
+    // One JSON line per match kept by `take_any`, flushed before the search goes on
+    let append_match = |(pattern_index, wallet): &(usize, WalletInfo)| {
+        use std::io::Write;
+        let (Some(results_file), Some(path)) = (&results_file, &args.output_file) else {
+            return;
+        };
+        let mut object = wallet_json(&args, wallet);
+        if args.pattern_file.is_some() || !args.alt.is_empty() {
+            object["matched"] = pattern_set.entries[*pattern_index].to_string().into();
+        }
+        object["total_attempts"] = attempts.total().into();
+        object["elapsed_secs"] = start_time.elapsed().as_secs_f64().into();
+        let mut writer = results_file.lock().unwrap();
+        if let Some(out) = writer.as_mut() {
+            if let Err(e) = writeln!(out, "{}", object).and_then(|()| out.flush()) {
+                error!("❌ Failed to write to {}: {}; further matches are only printed", path.display(), e);
+                tracing::error!(path = %path.display(), error = %e, "appending match failed");
+                *writer = None;
+            }
+        }
+    };
     
-    // Hash with Keccak256
-    let mut hasher = Keccak256::new();
-    hasher.update(public_key_hash);
-    let hash = hasher.finalize();
+    // A match kept for the results, on the worker thread that found it; a --start-key
+    // worker's offset only moves past its match now
+    let deliver = || {
+        if let Some(scan) = &sequential_scan {
+            scan.delivered(rayon::current_thread_index().expect("workers run on the thread pool"));
+        }
+    };
     
-    // Take last 20 bytes for address
-    let address_bytes = &hash[12..];
-    let address = format!("0x{}", hex::encode(address_bytes));
+    // Bounded scans split the work items between threads; otherwise candidates are
+    // drawn until `count` matches are found or the search is stopped
+    let mut results: Vec<(usize, WalletInfo)> = if stream {
+        // Matches go through a channel to a single writer, so lines never interleave
+        let (sender, receiver) = mpsc::channel();
+        let streamed = thread::scope(|scope| {
+            let writer = scope.spawn(|| stream_matches(&args, receiver, &attempts, start_time, notifier.as_ref(), save_dir.as_ref(), &recent_finds));
+            let send = |sender: &mut mpsc::Sender<WalletInfo>, (_, wallet): (usize, WalletInfo)| {
+                // The writer only hangs up after a failed write
+                if sender.send(wallet).is_err() {
+                    stream_closed.store(true, Ordering::SeqCst);
+                    running.store(false, Ordering::SeqCst);
+                } else {
+                    deliver();
+                }
+            };
+            match scan_total {
+                Some(total) => (0..total)
+                    .into_par_iter()
+                    .map_init(new_worker, guarded_search)
+                    .while_some()
+                    .flat_map_iter(|matches| matches)
+                    .for_each_with(sender, send),
+                None => rayon::iter::repeat(0)
+                    .map_init(new_worker, guarded_search)
+                    .while_some()
+                    .flat_map_iter(|matches| matches)
+                    .for_each_with(sender, send),
+            }
+            // Matches already written stay written even if the writer failed afterwards
+            writer.join().unwrap_or_else(|_| {
+                error!("❌ The stream writer panicked");
+                stream_closed.store(true, Ordering::SeqCst);
+                0
+            })
+        });
+        info!("📡 Streamed {} matches", streamed);
+        Vec::new()
+    } else {
+        match scan_total {
+            Some(total) => (0..total)
+                .into_par_iter()
+                .map_init(new_worker, guarded_search)
+                .while_some()
+                .flat_map_iter(|matches| matches)
+                .inspect(|_| {
+                    collected.fetch_add(1, Ordering::SeqCst);
+                })
+                .take_any(count as usize)
+                .inspect(append_match)
+                .inspect(|_| deliver())
+                .collect(),
+            None => rayon::iter::repeat(0)
+                .map_init(new_worker, guarded_search)
+                .while_some()
+                .flat_map_iter(|matches| matches)
+                .inspect(|_| {
+                    collected.fetch_add(1, Ordering::SeqCst);
+                })
+                .take_any(count as usize)
+                .inspect(append_match)
+                .inspect(|_| deliver())
+                .collect(),
+        }
+    };
+    workers_done.store(true, Ordering::SeqCst);
+    let panics: u64 = worker_panics.iter().map(|panics| panics.load(Ordering::SeqCst)).sum();
+    // Each panic loses the work item it happened in; --start-key scans redo theirs
+    if panics > 0 && sequential_scan.is_none() {
+        let lost = if scan_total.is_some() { "; the keys they held were not checked" } else { "" };
+        warning!("⚠️  {} work item{} lost to worker panics{}", panics, if panics == 1 { " was" } else { "s were" }, lost);
+    }
+    tracing::info!(attempts = attempts.total(), matches = matches_found.load(Ordering::SeqCst), "workers stopped");
+    // Progress reporting is cosmetic, so its failure never costs the results
+    if progress_handle.join().is_err() {
+        tui::restore();
+        warning!("⚠️  The progress thread panicked");
+        tracing::error!("progress thread panicked");
+    }
+    // Every worker has finished its batch, so the final checkpoint is exact
+    if let (Some(scan), Some(path)) = (&sequential_scan, &checkpoint_path) {
+        match scan.save(path) {
+            Ok(()) => info!("💾 Checkpoint saved to {} ({} keys checked), continue with --resume {}",
+                            path.display(), format_with_commas(scan.checked()), path.display()),
+            Err(e) => {
+                warning!("⚠️  Failed to write checkpoint {}: {}", path.display(), e);
+                tracing::error!(path = %path.display(), error = %e, "checkpoint write failed");
+            }
+        }
+    }
+    if let Some(metrics_server) = metrics_server {
+        metrics_server.stop().await;
+    }
     
-    (address, private_key)
-}
-
-// Generate full wallet info only when match is found
-fn generate_wallet_info(private_key: SecretKey) -> WalletInfo {
-    let private_key_hex = format!("0x{}", hex::encode(private_key.secret_bytes()));
+    // Check results
+    let final_attempts = attempts.total();
+    let elapsed = start_time.elapsed();
     
-    // Generate mnemonic from private key entropy
-    let mnemonic = match Mnemonic::from_entropy(&private_key.secret_bytes()) {
-        Ok(m) => Some(m.to_string()),
-        Err(_) => None,
+    // Scoring mode reports its best candidate like a match
+    let best_score = match best.take() {
+        Some((score, wallet)) => {
+            results.push((0, wallet));
+            Some(score)
+        }
+        None => None,
     };
+    let best_run_len = best_run.lock().unwrap().take().map(|wallet| {
+        results.push((0, wallet));
+        best_run_len.load(Ordering::Relaxed)
+    });
+    // Top-K mode reports its candidates best first, with the score of each
+    let mut top_scores = Vec::new();
+    for scored in top.take() {
+        results.push((0, scored.item));
+        top_scores.push(scored.score);
+    }
     
-    // Regenerate address for the wallet info
-    let secp = Secp256k1::new();
-    let public_key = PublicKey::from_secret_key(&secp, &private_key);
-    let public_key_bytes = public_key.serialize_uncompressed();
-    let public_key_hash = &public_key_bytes[1..];
-    let mut hasher = Keccak256::new();
-    hasher.update(public_key_hash);
-    let hash = hasher.finalize();
-    let address_bytes = &hash[12..];
-    let address = format!("0x{}", hex::encode(address_bytes));
+    if !results.is_empty() {
+        if args.quiet {
+            // Only the results themselves
+        } else if let Some(score) = &best_score {
+            success!("🏆 Best address after {} attempts in {:.2?}: score {}", final_attempts, elapsed, score);
+        } else if let (Some(run), Some(nibble)) = (best_run_len, args.maximize) {
+            success!("🏆 Best address after {} attempts in {:.2?}: {} leading '{:x}'s", final_attempts, elapsed, run, nibble);
+        } else if let Some(criterion) = &criterion {
+            success!("🏆 Top {} addresses by {} after {} attempts in {:.2?}", results.len(), criterion.name(), final_attempts, elapsed);
+        } else if args.count == 1 {
+            success!("🎉 Found vanity address after {} attempts in {:.2?}!", final_attempts, elapsed);
+        } else {
+            success!("🎉 Found {} of {} vanity addresses after {} attempts in {:.2?}!", results.len(), args.count, final_attempts, elapsed);
+        }
+        // How lucky random draws were; scans of a mnemonic's indices are not random
+        let luck = (!best_effort && mnemonic_chains.is_none()).then(|| odds::Luck::new(final_attempts, difficulty, results.len() as u64));
+        if let (Some(luck), false) = (&luck, args.quiet) {
+            info!("🍀 Luck: {:.2}× the {} expected attempts, faster than {:.0}% of searches would be",
+                  luck.ratio, format_difficulty(luck.expected_attempts), luck.faster_than * 100.0);
+        }
+        
+        let mut json_results = Vec::new();
+        for (i, (pattern_index, wallet)) in results.iter().enumerate() {
+            tracing::info!(address = %wallet.address, attempts = final_attempts, "match found");
+            if let Some(notifier) = &notifier {
+                notifier.matched(&wallet.address, final_attempts, elapsed);
+            }
+            let mut object = wallet_json(&args, wallet);
+            if args.pattern_file.is_some() || !args.alt.is_empty() {
+                object["matched"] = pattern_set.entries[*pattern_index].to_string().into();
+            }
+            if let (Some(score), Some(scorer)) = (&best_score, &scorer) {
+                object["score"] = serde_json::json!(score);
+                object["score_config"] = serde_json::json!(scorer.config);
+            }
+            if let Some(run) = best_run_len {
+                object["leading_run"] = run.into();
+            }
+            if let Some(luck) = &luck {
+                object["attempts"] = final_attempts.into();
+                object["expected_attempts"] = luck.expected_attempts.round().into();
+                object["luck_ratio"] = luck.ratio.into();
+                object["faster_than_percent"] = (luck.faster_than * 100.0).into();
+            }
+            if let (Some(criterion), Some(&score)) = (&criterion, top_scores.get(i)) {
+                object["rank"] = (i + 1).into();
+                object["score"] = score.into();
+                object["score_by"] = criterion.name().into();
+            }
+            let saved_to = save_dir.as_ref().and_then(|save_dir| save_wallet(save_dir, &object, final_attempts, elapsed));
+            if json_output || args.quiet {
+                if let Some(path) = &saved_to {
+                    object["saved_to"] = path.display().to_string().into();
+                }
+                if let (Some(dir), Some(password)) = (&args.keystore_dir, &args.keystore_password) {
+                    let key_bytes = wallet.private_key_bytes();
+                    let keystore = keystore::create_keystore_v3(&key_bytes, &wallet.address, password, args.keystore_kdf.into());
+                    match keystore::write_keystore(dir, &wallet.address, &keystore) {
+                        Ok(path) => object["keystore"] = path.display().to_string().into(),
+                        Err(e) => error!("❌ Failed to write keystore file to {}: {}", dir.display(), e),
+                    }
+                }
+                if let (Some(shares), Some(threshold)) = (args.shamir_shares, args.shamir_threshold) {
+                    let key_bytes = wallet.private_key_bytes();
+                    object["shamir_threshold"] = threshold.into();
+                    object["shamir_shares"] = shamir::split_private_key(&key_bytes, threshold, shares).into();
+                }
+                if let Some(path) = &args.qr_file {
+                    if let Err(error_msg) = qr::save_png(&qr::ethereum_uri(&to_checksum_address(&wallet.address)), path) {
+                        error!("❌ {}", error_msg);
+                    }
+                }
+                json_results.push(object);
+                continue;
+            }
+            json_results.push(object);
+            
+            if let (Some(criterion), Some(score)) = (&criterion, top_scores.get(i)) {
+                println!("\n── #{} of {}: {} {} ──", i + 1, results.len(), criterion.name(), score);
+            } else if args.count > 1 {
+                println!("\n── Match {} of {} ──", i + 1, results.len());
+            }
+            let entry = &pattern_set.entries[*pattern_index];
+            let (prefix_len, suffix_len) = match (palindrome, repeating) {
+                (Some(palindrome), _) => (palindrome.length, palindrome.length),
+                (None, Some(_)) => {
+                    let mut address_bytes = [0u8; 20];
+                    hex::decode_to_slice(&wallet.address[2..], &mut address_bytes).expect("address is 20 bytes of hex");
+                    (leading_run(&address_bytes).1, entry.suffix.as_deref().map_or(0, pattern_len))
+                }
+                (None, None) if scorer.is_some() => (0, 0),
+                (None, None) if args.maximize.is_some() => (best_run_len.unwrap_or(0), 0),
+                (None, None) if criterion.is_some() => criterion.as_ref().zip(top_scores.get(i)).map_or((0, 0), |(criterion, &score)| criterion.highlight(score)),
+                (None, None) => {
+                    let (prefix_len, suffix_len) = entry.matched_lengths(&wallet.address, args.case_sensitive);
+                    (prefix_len.max(2 * leading_zero_bytes), suffix_len)
+                }
+            };
+            println!("📍 Address: {}", output::address(&wallet.address, prefix_len, suffix_len));
+            if args.case_sensitive || args.chain_id.is_some() {
+                println!("🔠 Checksummed: {}", to_checksum_address(&wallet.address));
+            }
+            if let Some(chain_id) = args.chain_id.filter(|&chain_id| chain_id != 1) {
+                println!("🔗 Chain {} (EIP-1191): {}", chain_id, to_chain_checksum_address(&wallet.address, chain_id));
+            }
+            if args.word.is_some() {
+                if let Some(spelling) = &entry.prefix {
+                    println!("🔤 Spelling: {}", spelling);
+                }
+            } else if args.pattern_file.is_some() || !args.alt.is_empty() {
+                println!("🎯 Matched: {}", entry);
+            }
+            
+            if fuzzy_distance > 0 {
+                let address = wallet.address.as_str();
+                let prefix_distance = entry.prefix.as_deref()
+                    .and_then(|prefix| matches_fuzzy(address, Some(prefix), None, usize::MAX, args.case_sensitive));
+                let suffix_distance = entry.suffix.as_deref()
+                    .and_then(|suffix| matches_fuzzy(address, None, Some(suffix), usize::MAX, args.case_sensitive));
+                let location = match (prefix_distance.unwrap_or(0), suffix_distance.unwrap_or(0)) {
+                    (0, 0) => None,
+                    (_, 0) => Some("prefix"),
+                    (0, _) => Some("suffix"),
+                    _ => Some("prefix and suffix"),
+                };
+                match location {
+                    Some(location) => println!("🔀 Matched with Hamming distance {} in {}",
+                                              prefix_distance.unwrap_or(0) + suffix_distance.unwrap_or(0), location),
+                    None => println!("🔀 Matched exactly (Hamming distance 0)"),
+                }
+            }
+            
+            if repeating.is_some() || palindrome.is_some() {
+                let mut address_bytes = [0u8; 20];
+                hex::decode_to_slice(&wallet.address[2..], &mut address_bytes).expect("address is 20 bytes of hex");
+                if repeating.is_some() {
+                    let (nibble, run) = leading_run(&address_bytes);
+                    println!("🔁 Repeating: '{:x}' × {}", nibble, run);
+                }
+                if let Some(palindrome) = palindrome {
+                    let reversed: String = wallet.address[2..].chars().rev().collect();
+                    println!("🔄 Reversed: {}", output::address(&format!("0x{}", reversed), palindrome.length, palindrome.length));
+                    println!("🪞 Palindrome: {} mirrored characters", mirror_length(&wallet.address));
+                }
+            }
+            
+            if let Some(path) = &wallet.derivation_path {
+                if args.from_mnemonic.is_some() {
+                    println!("🔢 Index: {}", path.rsplit('/').next().unwrap_or_default());
+                }
+                println!("🧭 Derivation Path: {}", path);
+            }
+            let extended_keys = (args.output_xprv || args.output_xpub).then(|| wallet_extended_keys(wallet)).flatten();
+            if let Some(keys) = extended_keys.as_ref().filter(|_| args.output_xpub) {
+                println!("👁️  xpub: {}", keys.xpub);
+            }
+            let siblings = wallet_siblings(&args, wallet);
+            if !siblings.is_empty() {
+                println!("🌳 Next addresses of this wallet:");
+                println!("   {:<7} Address", "Index");
+                for (index, address) in &siblings {
+                    println!("   {:<7} {}", index, address);
+                }
+            }
+            let accounts = wallet_accounts(&args, wallet);
+            if !accounts.is_empty() {
+                println!("🗂️  First addresses of this wallet:");
+                println!("   {:<7} Address", "Index");
+                for (index, address) in &accounts {
+                    if *address == wallet.address {
+                        println!("{}", output::success(&format!(" ▶ {:<7} {}  (match)", index, address)));
+                    } else {
+                        println!("   {:<7} {}", index, address);
+                    }
+                }
+            }
+            
+            if args.show_public_key {
+                println!("🔑 Public Key (uncompressed): {}", wallet.public_key_uncompressed);
+                println!("🔑 Public Key (compressed): {}", wallet.public_key_compressed);
+            }
+            
+            if args.from_mnemonic.is_some() {
+                // The key is derivable from the seed the user already holds
+            } else if !args.encrypt_to.is_empty() {
+                // Only the encrypted file holds the key
+            } else if args.shamir_shares.is_some() && !args.show_private_key {
+                // The mnemonic encodes the same entropy as the key, so it is withheld too
+                println!("🔐 Private Key: withheld, recover it from the Shamir shares below (--show-private-key prints it)");
+            } else if let Some(password) = &args.bip38 {
+                // The mnemonic encodes the same entropy as the key, so it is withheld too
+                let key_bytes = wallet.private_key_bytes();
+                let encrypted = bip38::bip38_encrypt(&key_bytes, &wallet.address, password);
+                println!("🔒 BIP38 Encrypted Key: {}", encrypted);
+            } else {
+                println!("🔐 Private Key: {}", wallet.private_key.as_str());
+                
+                if let Some(mnemonic) = &wallet.mnemonic {
+                    println!("📝 Mnemonic: {}", mnemonic.as_str());
+                }
+                if let Some(keys) = extended_keys.as_ref().filter(|_| args.output_xprv) {
+                    println!("🌱 Root xprv: {}", keys.root_xprv);
+                    println!("🗝️  xprv: {}", keys.xprv);
+                }
+            }
+            
+            if let (Some(dir), Some(password)) = (&args.keystore_dir, &args.keystore_password) {
+                let key_bytes = wallet.private_key_bytes();
+                let keystore = keystore::create_keystore_v3(&key_bytes, &wallet.address, password, args.keystore_kdf.into());
+                match keystore::write_keystore(dir, &wallet.address, &keystore) {
+                    Ok(path) => println!("💾 Keystore: {}", path.display()),
+                    Err(e) => error!("❌ Failed to write keystore file to {}: {}", dir.display(), e),
+                }
+            }
+            if let Some(path) = &saved_to {
+                println!("💾 Saved to {}", path.display());
+            }
+            
+            if let (Some(shares), Some(threshold)) = (args.shamir_shares, args.shamir_threshold) {
+                let key_bytes = wallet.private_key_bytes();
+                println!("🧩 Shamir shares ({} of {} needed to reconstruct):", threshold, shares);
+                for (i, share) in shamir::split_private_key(&key_bytes, threshold, shares).iter().enumerate() {
+                    println!("share_{}: {}", i + 1, share);
+                }
+            }
+            
+            print_qr_codes(&args, wallet);
+        }
+        
+        let json = Zeroizing::new(serde_json::to_string_pretty(&json_results).expect("JSON values serialize"));
+        if json_output && args.quiet {
+            // One compact object per line, for jq and read loops
+            for object in &json_results {
+                println!("{}", Zeroizing::new(object.to_string()).as_str());
+            }
+        } else if json_output {
+            println!("{}", json.as_str());
+        } else if args.quiet {
+            for (i, object) in json_results.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                print_fields(&args, object);
+            }
+        }
+        if let (Some(path), false) = (&args.output_file, args.encrypt_to.is_empty()) {
+            let path = encrypt::age_path(path);
+            match encrypt::encrypt(json.as_bytes(), &args.encrypt_to).and_then(|ciphertext| {
+                write_private_file(&path, &ciphertext).map_err(|e| format!("Failed to write results to {}: {}", path.display(), e))
+            }) {
+                Ok(()) if args.quiet => {}
+                Ok(()) => println!("🔒 Results encrypted to {} recipient{} and written to {}", args.encrypt_to.len(),
+                                   if args.encrypt_to.len() == 1 { "" } else { "s" }, path.display()),
+                Err(error_msg) => {
+                    error!("❌ {}", error_msg);
+                    tracing::error!(error = %error_msg, "writing encrypted results failed");
+                }
+            }
+        } else if let (Some(path), Some(results_file)) = (&args.output_file, &results_file) {
+            // Each match was appended when it was found
+            if results_file.lock().unwrap().is_some() && !json_output && !args.quiet {
+                println!("💾 Results appended to {} ({} in total)", path.display(), resumed + results.len() as u64);
+            }
+        } else if let Some(path) = &args.output_file {
+            match write_private_file(path, json.as_bytes()) {
+                Ok(()) if !json_output && !args.quiet => println!("💾 Results written to {}", path.display()),
+                Ok(()) => {}
+                Err(e) => {
+                    error!("❌ Failed to write results to {}: {}", path.display(), e);
+                    tracing::error!(path = %path.display(), error = %e, "writing results failed");
+                }
+            }
+        }
+    }
     
-    WalletInfo {
-        address,
-        private_key: private_key_hex,
-        mnemonic,
+    // Only once every wallet is out, so a slow node never holds up the results
+    if let Some(rpc_url) = &args.verify_rpc {
+        for (_, wallet) in &results {
+            rpc::report(&wallet.address, rpc_url);
+        }
     }
-}
-
-fn matches_pattern(address: &str, prefix_pattern: Option<&str>, suffix_pattern: Option<&str>, case_sensitive: bool) -> bool {
-    // Remove 0x prefix for matching
-    let address_without_prefix = address.strip_prefix("0x").unwrap_or(address);
     
-    let addr_to_check = if case_sensitive {
-        address_without_prefix.to_string()
+    // A search cut short by a limit says so, as its matches alone would not tell
+    let finished = results.len() as u64 >= args.count && !best_effort && !stream;
+    let gave_up = if finished || best_effort {
+        None
+    } else if scan_total.is_some() && running.load(Ordering::SeqCst) {
+        Some("range exhausted")
+    } else if time_limit_reached.load(Ordering::SeqCst) {
+        Some("time limit reached")
+    } else if max_attempts_reached.load(Ordering::SeqCst) {
+        Some("attempt limit reached")
     } else {
-        address_without_prefix.to_lowercase()
+        None
     };
-    
-    // Check prefix pattern
-    if let Some(prefix) = prefix_pattern {
-        let prefix_to_check = if case_sensitive {
-            prefix.to_string()
-        } else {
-            prefix.to_lowercase()
-        };
-        
-        if !addr_to_check.starts_with(&prefix_to_check) {
-            return false;
-        }
+    if let (Some(notifier), Some(reason)) = (&notifier, gave_up) {
+        notifier.gave_up(reason, matches_found.load(Ordering::SeqCst), final_attempts, elapsed);
+    }
+    // Queued messages go out before the summary, which may exit
+    if let (Some(notifier), Some(dispatcher)) = (notifier, notify_dispatcher) {
+        dispatcher.finish(notifier);
+    }
+    if args.bell && stop_signal.load(Ordering::SeqCst) == 0 {
+        output::bell();
     }
     
-    // Check suffix pattern
-    if let Some(suffix) = suffix_pattern {
-        let suffix_to_check = if case_sensitive {
-            suffix.to_string()
+    if finished {
+        // Every requested match was found
+    } else if let (Some(total), true) = (scan_total, running.load(Ordering::SeqCst)) {
+        if results.is_empty() {
+            info!("🏁 Range exhausted: checked all {} addresses without a match", format_with_commas(total));
         } else {
-            suffix.to_lowercase()
-        };
-        
-        if !addr_to_check.ends_with(&suffix_to_check) {
-            return false;
+            info!("🏁 Range exhausted: checked all {} addresses", format_with_commas(total));
+        }
+    } else if time_limit_reached.load(Ordering::SeqCst) {
+        info!("⏱️  Time limit of {}s reached after {} attempts", args.time_limit.unwrap_or_default(), final_attempts);
+    } else if stream_closed.load(Ordering::SeqCst) {
+        info!("📡 Stream closed after {} attempts", final_attempts);
+        drop(log_writer);
+        std::process::exit(1);
+    } else if all_workers_failed.load(Ordering::SeqCst) {
+        info!("💥 Every worker panicked after {} attempts", final_attempts);
+    } else if max_attempts_reached.load(Ordering::SeqCst) {
+        info!("🔢 Attempt limit of {} reached after {} attempts", args.max_attempts.unwrap_or_default(), final_attempts);
+    } else {
+        match stop_signal.load(Ordering::SeqCst) {
+            signals::SIGINT => info!("Search stopped by user after {} attempts", final_attempts),
+            signal => info!("Search stopped by {} after {} attempts", signals::signal_name(signal), final_attempts),
         }
     }
     
-    true
-}
-
-fn validate_pattern(pattern: &str) -> Result<(), String> {
-    let invalid_chars: Vec<char> = pattern
-        .chars()
-        .filter(|c| !c.is_ascii_hexdigit())
-        .collect();
+    // Without a match, the closest candidates are all the search has to show
+    if let (Some(near_miss), true) = (&near_miss, results.is_empty()) {
+        report_near_misses(&args, near_miss, json_output);
+    }
     
-    if !invalid_chars.is_empty() {
-        let mut error_msg = String::from("❌ Invalid characters found in pattern:\n");
-        
-        for &invalid_char in &invalid_chars {
-            error_msg.push_str(&format!("  • '{}' is not a valid hexadecimal character\n", invalid_char));
+    if args.benchmark_threading {
+        let per_thread = attempts.per_thread();
+        let (min, max) = (per_thread.iter().min().copied().unwrap_or(0), per_thread.iter().max().copied().unwrap_or(0));
+        info!("\n🧵 Attempts per thread (busiest/idlest: {:.2}):", max as f64 / min.max(1) as f64);
+        // Shows where each worker ran when it was pinned to a single core
+        let pinned_cores = core_plan.filter(|_| args.pin_cores && !affinity_failed.load(Ordering::Relaxed));
+        for (thread, count) in per_thread.iter().enumerate() {
+            let core = pinned_cores.as_ref().map(|plan| format!(" (core {})", plan[thread][0])).unwrap_or_default();
+            info!("  thread {}{}: {} ({:.1}%)", thread, core, format_with_commas(*count), *count as f64 * 100.0 / final_attempts.max(1) as f64);
         }
-        
-        error_msg.push_str("\n💡 EVM addresses only use hexadecimal characters: 0-9, a-f, A-F\n");
-        error_msg.push_str("   Valid examples: \"dead\", \"beef\", \"abc123\", \"DEF456\"");
-        
-        return Err(error_msg);
+    }
+    
+    // Exit with the conventional code of the signal that stopped the search
+    let signal = stop_signal.load(Ordering::SeqCst);
+    tracing::info!(attempts = final_attempts, matches = results.len(), elapsed_secs = elapsed.as_secs_f64(), signal, "search finished");
+    if signal != 0 {
+        drop(results);
+        drop(log_writer);
+        std::process::exit(signals::exit_code(signal));
+    }
+    // The results are out, but a search that lost every worker did not finish
+    if !finished && all_workers_failed.load(Ordering::SeqCst) {
+        drop(results);
+        drop(log_writer);
+        std::process::exit(1);
     }
     
     Ok(())
 }
-
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
-    
-    // Determine prefix and suffix patterns
-    let (prefix_pattern, suffix_pattern) = match (&args.pattern, &args.prefix, &args.suffix) {
-        // New style: --prefix and/or --suffix
-        (None, Some(prefix), Some(suffix)) => (Some(prefix.as_str()), Some(suffix.as_str())),
-        (None, Some(prefix), None) => (Some(prefix.as_str()), None),
-        (None, None, Some(suffix)) => (None, Some(suffix.as_str())),
-        
-        // Legacy style: -p pattern with -s flag
-        (Some(pattern), None, None) => {
-            if args.suffix_mode {
-                (None, Some(pattern.as_str()))
-            } else {
-                (Some(pattern.as_str()), None)
-            }
-        }
-        
-        // Invalid combinations
-        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
-            eprintln!("❌ Cannot use -p/--pattern with --prefix/--suffix. Use either:");
-            eprintln!("  • Legacy: -p <pattern> [-s]");
-            eprintln!("  • New: --prefix <pattern> and/or --suffix <pattern>");
-            std::process::exit(1);
-        }
-        
-        (None, None, None) => {
-            eprintln!("❌ Must specify at least one pattern:");
-            eprintln!("  • --prefix <pattern>: Match prefix");
-            eprintln!("  • --suffix <pattern>: Match suffix");
-            eprintln!("  • --prefix <pattern> --suffix <pattern>: Match both");
-            eprintln!("  • -p <pattern>: Legacy format");
-            std::process::exit(1);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    
+    const HARDHAT_MNEMONIC: &str = "test test test test test test test test test test test junk";
+    // Account 0 of HARDHAT_MNEMONIC, 0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266
+    const HARDHAT_KEY: [u8; 32] = [
+        0xac, 0x09, 0x74, 0xbe, 0xc3, 0x9a, 0x17, 0xe3, 0x6b, 0xa4, 0xa6, 0xb4, 0xd2, 0x38, 0xff, 0x94,
+        0x4b, 0xac, 0xb4, 0x78, 0xcb, 0xed, 0x5e, 0xfc, 0xae, 0x78, 0x4d, 0x7b, 0xf4, 0xf2, 0xff, 0x80,
+    ];
+    
+    const KEY_ONE: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+    
+    fn search_args(args: &[&str]) -> SearchArgs {
+        Cli::try_parse_from(std::iter::once("evm-vanity").chain(args.iter().copied())).unwrap_or_else(|e| panic!("{:?}: {}", args, e)).search
+    }
+    
+    fn search_config(args: &[&str]) -> Result<SearchConfig, SearchConfigError> {
+        SearchConfig::try_from(&search_args(args))
+    }
+    
+    fn patterns(prefix: Option<&str>, suffix: Option<&str>) -> SearchConfig {
+        SearchConfig {
+            prefix: prefix.map(String::from),
+            suffix: suffix.map(String::from),
+            prefix_alternatives: Vec::new(),
+            suffix_alternatives: Vec::new(),
         }
-    };
+    }
     
-    // Validate patterns
-    if let Some(prefix) = prefix_pattern {
-        if let Err(error_msg) = validate_pattern(prefix) {
-            eprintln!("❌ Invalid prefix pattern:");
-            eprintln!("{}", error_msg);
-            std::process::exit(1);
+    fn invalid(args: &[&str]) -> String {
+        match search_config(args) {
+            Err(SearchConfigError::Invalid(error_msg)) => error_msg,
+            other => panic!("{:?} gave {:?}", args, other),
         }
     }
     
-    if let Some(suffix) = suffix_pattern {
-        if let Err(error_msg) = validate_pattern(suffix) {
-            eprintln!("❌ Invalid suffix pattern:");
-            eprintln!("{}", error_msg);
-            std::process::exit(1);
+    #[test]
+    fn generates_mnemonics_of_each_length() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        for (entropy_bits, words) in [(128, 12), (160, 15), (192, 18), (224, 21), (256, 24)] {
+            let mnemonic = generate_mnemonic(&mut rng, entropy_bits);
+            assert_eq!(mnemonic.word_count(), words);
+            assert_eq!(Mnemonic::parse(mnemonic.to_string()).unwrap(), mnemonic);
         }
     }
     
-    // Determine number of threads
-    let num_threads = args.threads.unwrap_or_else(|| thread::available_parallelism().unwrap().get());
+    #[test]
+    fn mnemonic_wallet_info_derives_the_scanned_index() {
+        let mnemonic = Mnemonic::parse(HARDHAT_MNEMONIC).unwrap();
+        let secp = Secp256k1::new();
+        let external_chain: hd::DerivationPath = "m/44'/60'/0'/0".parse().unwrap();
+        let account = hd::ExtendedPrivKey::from_seed(&mnemonic.to_seed("")).derive_path(&secp, &external_chain);
+        
+        let path = external_chain.child(1);
+        let wallet = generate_mnemonic_wallet_info(&mnemonic, &path, account.derive_child(&secp, 1).secret_key);
+        assert_eq!(wallet.address, "0x70997970c51812dc3a010c7d01b50e0d17dc79c8");
+        assert_eq!(wallet.derivation_path.as_deref(), Some("m/44'/60'/0'/0/1"));
+        assert_eq!(wallet.mnemonic.as_deref().map(String::as_str), Some(HARDHAT_MNEMONIC));
+    }
     
-    // Setup signal handling for graceful shutdown
-    let running = Arc::new(AtomicBool::new(true));
-    let r = running.clone();
+    #[test]
+    fn show_derived_lists_the_first_accounts_and_marks_the_match() {
+        let mnemonic = Mnemonic::parse(HARDHAT_MNEMONIC).unwrap();
+        let secp = Secp256k1::new();
+        let external_chain: hd::DerivationPath = "m/44'/60'/0'/0".parse().unwrap();
+        let account = hd::ExtendedPrivKey::from_seed(&mnemonic.to_seed("")).derive_path(&secp, &external_chain);
+        let wallet = generate_mnemonic_wallet_info(&mnemonic, &external_chain.child(2), account.derive_child(&secp, 2).secret_key);
+        
+        let args = search_args(&["--mode", "mnemonic", "--prefix", "3c44", "--show-derived"]);
+        let accounts = wallet_accounts(&args, &wallet);
+        assert_eq!(accounts.iter().map(|(index, _)| *index).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+        assert_eq!(accounts[0].1, "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+        assert_eq!(accounts[2].1, wallet.address);
+        let json = wallet_json(&args, &wallet);
+        let matched: Vec<_> = json["accounts"].as_array().unwrap().iter().filter(|account| account["matched"] == true).collect();
+        assert_eq!(matched, [&serde_json::json!({ "index": 2, "address": wallet.address, "matched": true })]);
+        
+        // A list that stops before the match marks nothing
+        let args = search_args(&["--mode", "mnemonic", "--prefix", "3c44", "--show-derived", "2"]);
+        assert_eq!(wallet_accounts(&args, &wallet).len(), 2);
+        assert!(wallet_json(&args, &wallet)["accounts"].as_array().unwrap().iter().all(|account| account["matched"] == false));
+        
+        // Nothing without the flag, or without a mnemonic to derive from
+        let args = search_args(&["--mode", "mnemonic", "--prefix", "3c44"]);
+        assert!(wallet_accounts(&args, &wallet).is_empty());
+        assert!(wallet_json(&args, &wallet).get("accounts").is_none());
+        let args = search_args(&["--mode", "mnemonic", "--prefix", "f39f", "--show-derived"]);
+        assert!(wallet_accounts(&args, &generate_wallet_info(SecretKey::from_slice(&HARDHAT_KEY).unwrap())).is_empty());
+    }
     
-    tokio::spawn(async move {
-        tokio::signal::ctrl_c().await.expect("Failed to listen for ctrl+c");
-        println!("\nReceived Ctrl+C, shutting down...");
-        r.store(false, Ordering::SeqCst);
-    });
+    #[test]
+    fn wallet_info_public_keys_derive_the_address() {
+        let key = SecretKey::from_slice(&HARDHAT_KEY).unwrap();
+        let wallet = generate_wallet_info(key);
+        assert_eq!(wallet.address, "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+        
+        let uncompressed = hex::decode(wallet.public_key_uncompressed.strip_prefix("0x").unwrap()).unwrap();
+        assert_eq!((uncompressed.len(), uncompressed[0]), (65, 0x04));
+        assert_eq!(format!("0x{}", hex::encode(&Keccak256::digest(&uncompressed[1..])[12..])), wallet.address);
+        
+        let compressed = hex::decode(wallet.public_key_compressed.strip_prefix("0x").unwrap()).unwrap();
+        assert_eq!(compressed.len(), 33);
+        assert_eq!(PublicKey::from_slice(&compressed).unwrap().serialize_uncompressed().as_slice(), uncompressed.as_slice());
+        
+        // JSON output always carries both keys, with or without --show-public-key
+        let json = wallet_json(&search_args(&["--prefix", "f39f"]), &wallet);
+        assert_eq!(json["public_key_uncompressed"], wallet.public_key_uncompressed);
+        assert_eq!(json["public_key_compressed"], wallet.public_key_compressed);
+    }
     
-    let total_attempts = Arc::new(AtomicU64::new(0));
-    let start_time = Instant::now();
+    #[test]
+    fn words_choose_the_mnemonic_length() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        for words in ["12", "15", "18", "21", "24"] {
+            let args = search_args(&["--mode", "mnemonic", "--words", words]);
+            let mnemonic = generate_mnemonic(&mut rng, mnemonic_entropy_bits(&args));
+            assert_eq!(mnemonic.word_count().to_string(), words);
+            // Parsing checks the phrase's checksum word
+            assert_eq!(Mnemonic::parse(mnemonic.to_string()).unwrap(), mnemonic);
+        }
+        assert_eq!(mnemonic_entropy_bits(&search_args(&["--entropy-bits", "160"])), 160);
+        
+        for words in ["0", "13", "25", "twelve"] {
+            assert!(Cli::try_parse_from(["evm-vanity", "--words", words]).is_err(), "{}", words);
+        }
+        assert!(Cli::try_parse_from(["evm-vanity", "--words", "12", "--entropy-bits", "128"]).is_err());
+    }
     
-    println!("🔍 Searching for EVM vanity address...");
-    match (prefix_pattern, suffix_pattern) {
-        (Some(prefix), Some(suffix)) => println!("Pattern: prefix '{}' AND suffix '{}'", prefix, suffix),
-        (Some(prefix), None) => println!("Pattern: prefix '{}'", prefix),
-        (None, Some(suffix)) => println!("Pattern: suffix '{}'", suffix),
-        (None, None) => unreachable!(),
+    #[test]
+    fn streamed_matches_append_to_the_output_file() {
+        let path = std::env::temp_dir().join(format!("evm-vanity-stream-{}.jsonl", std::process::id()));
+        std::fs::write(&path, "{\"address\":\"0x0000000000000000000000000000000000000001\"}\n").unwrap();
+        let args = search_args(&["--prefix", "f39f", "--stream", "--output-file", path.to_str().unwrap()]);
+        
+        let key = SecretKey::from_slice(&HARDHAT_KEY).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        sender.send(generate_wallet_info(key)).unwrap();
+        drop(sender);
+        let streamed = stream_matches(&args, receiver, &counters::AttemptCounters::new(1), Instant::now(), None, None, &Mutex::default());
+        
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(streamed, 1);
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2, "{}", contents);
+        assert!(lines[0].contains("0x0000000000000000000000000000000000000001"));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(lines[1]).unwrap()["address"], "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
     }
-    println!("Case sensitive: {}", args.case_sensitive);
-    println!("Threads: {}", num_threads);
-    println!("Press Ctrl+C to stop\n");
     
-    // Shared data between threads
-    let prefix_pattern_arc = Arc::new(prefix_pattern.map(|s| s.to_string()));
-    let suffix_pattern_arc = Arc::new(suffix_pattern.map(|s| s.to_string()));
-    let case_sensitive = args.case_sensitive;
-    let found = Arc::new(AtomicBool::new(false));
-    let result = Arc::new(std::sync::Mutex::new(None::<WalletInfo>));
-    let winning_attempts = Arc::new(AtomicU64::new(0));
-    
-    // Spawn worker threads
-    let mut handles = Vec::new();
-    for _thread_id in 0..num_threads {
-        let running = running.clone();
-        let prefix_pattern_arc = prefix_pattern_arc.clone();
-        let suffix_pattern_arc = suffix_pattern_arc.clone();
-        let found = found.clone();
-        let result = result.clone();
-        let total_attempts = total_attempts.clone();
-        let winning_attempts = winning_attempts.clone();
-        
-        let handle = thread::spawn(move || {
-            let secp = Secp256k1::new();
-            let mut local_attempts = 0u64;
-            
-            while running.load(Ordering::SeqCst) && !found.load(Ordering::SeqCst) {
-                local_attempts += 1;
-                
-                // Generate new address
-                let (address, private_key) = generate_address_fast(&secp);
-                
-                // Check if address matches pattern
-                let prefix_ref = prefix_pattern_arc.as_ref().as_ref().map(|s| s.as_str());
-                let suffix_ref = suffix_pattern_arc.as_ref().as_ref().map(|s| s.as_str());
-                if matches_pattern(&address, prefix_ref, suffix_ref, case_sensitive) {
-                    // Found match - create full wallet info
-                    let wallet = generate_wallet_info(private_key);
-                    
-                    // Set found flag and store result
-                    found.store(true, Ordering::SeqCst);
-                    *result.lock().unwrap() = Some(wallet);
-                    winning_attempts.store(local_attempts, Ordering::SeqCst);
-                    break;
-                }
-                
-                // Update total attempts counter periodically
-                if local_attempts % 1000 == 0 {
-                    total_attempts.fetch_add(1000, Ordering::SeqCst);
-                }
-            }
-            
-            // Add remaining attempts
-            total_attempts.fetch_add(local_attempts % 1000, Ordering::SeqCst);
-        });
+    #[test]
+    fn search_config_resolves_the_pattern_options() {
+        assert_eq!(search_config(&["--prefix", "dead"]), Ok(patterns(Some("dead"), None)));
+        assert_eq!(search_config(&["--suffix", "beef"]), Ok(patterns(None, Some("beef"))));
+        assert_eq!(search_config(&["--prefix", "dead", "--suffix", "beef"]), Ok(patterns(Some("dead"), Some("beef"))));
+        assert_eq!(search_config(&["-p", "dead"]), Ok(patterns(Some("dead"), None)));
+        assert_eq!(search_config(&["-p", "beef", "-s"]), Ok(patterns(None, Some("beef"))));
+        assert_eq!(search_config(&["--wrap", "abc"]), Ok(patterns(Some("abc"), Some("abc"))));
+        // Modes with patterns of their own need no prefix or suffix
+        assert_eq!(search_config(&["--repeating", "6"]), Ok(patterns(None, None)));
         
-        handles.push(handle);
+        let alternatives = search_config(&["--prefix", "dead", "--or-suffix", "beef", "--or-suffix", "cafe"]).unwrap();
+        assert_eq!(alternatives.suffix_alternatives, ["beef", "cafe"]);
+        // An alternative that contradicts the prefix is dropped while another is left
+        let alternatives = search_config(&["--prefix", "dead", "--or-prefix", "be", "--or-prefix", "de"]).unwrap();
+        assert_eq!(alternatives.prefix_alternatives, ["de"]);
     }
     
-    // Progress reporting thread
-    let progress_running = running.clone();
-    let progress_attempts = total_attempts.clone();
-    let progress_found = found.clone();
-    let progress_handle = thread::spawn(move || {
-        let mut last_attempts = 0u64;
-        let mut last_time = Instant::now();
+    #[test]
+    fn search_config_rejects_unsearchable_patterns() {
+        assert!(invalid(&["-p", "dead", "--prefix", "beef"]).starts_with("Cannot use -p/--pattern with --prefix/--suffix"));
+        assert!(invalid(&["-p", "dead", "--suffix", "beef"]).starts_with("Cannot use -p/--pattern with --prefix/--suffix"));
+        assert!(invalid(&[]).starts_with("Must specify at least one pattern"));
+        assert!(invalid(&["--mode", "mnemonic"]).starts_with("Must specify at least one pattern"));
+        assert!(invalid(&["--prefix", "xyz"]).starts_with("Invalid prefix pattern"));
+        assert!(invalid(&["--suffix", "0xg"]).starts_with("Invalid suffix pattern"));
+        assert!(invalid(&["--prefix", "dead", "--or-suffix", "nope"]).starts_with("Invalid or-suffix pattern"));
+        assert!(invalid(&["--prefix", &"a".repeat(30), "--suffix", &"b".repeat(11)]).contains("40"));
+        assert_eq!(invalid(&["--wrap", &"a".repeat(21)]), format!("--wrap '{}' needs 2 × 21 = 42 characters but an address only has 40", "a".repeat(21)));
+        assert_eq!(invalid(&["--prefix", "dead", "--or-prefix", "be"]), "No --or-prefix can match together with --prefix 'dead'");
+    }
+    
+    #[test]
+    fn search_config_rejects_conflicting_options() {
+        use clap::error::ErrorKind;
+        let usage = |args: &[&str]| match search_config(args) {
+            Err(SearchConfigError::Usage(kind, error_msg)) => (kind, error_msg),
+            other => panic!("{:?} gave {:?}", args, other),
+        };
         
-        while progress_running.load(Ordering::SeqCst) && !progress_found.load(Ordering::SeqCst) {
-            thread::sleep(std::time::Duration::from_secs(5));
-            
-            let current_attempts = progress_attempts.load(Ordering::SeqCst);
-            let current_time = Instant::now();
-            
-            if current_attempts > last_attempts {
-                let elapsed = current_time.duration_since(last_time);
-                let rate = (current_attempts - last_attempts) as f64 / elapsed.as_secs_f64();
-                let total_elapsed = current_time.duration_since(start_time);
-                
-                if current_attempts % 500000 < last_attempts % 500000 || 
-                   current_attempts - last_attempts >= 500000 {
-                    println!("⏳ Attempts: {} | Rate: {:.0} addr/sec | Elapsed: {:.2?}", 
-                             current_attempts, rate, total_elapsed);
-                }
-                
-                last_attempts = current_attempts;
-                last_time = current_time;
-            }
+        for (args, option) in [
+            (["--prefix", "dead", "--words", "12"].as_slice(), "--words only applies to --mode mnemonic"),
+            (&["--prefix", "dead", "--output-xprv"], "--output-xprv/--output-xpub only works with --mode mnemonic"),
+            (&["--prefix", "dead", "--output-xpub"], "--output-xprv/--output-xpub only works with --mode mnemonic"),
+            (&["--prefix", "dead", "--derive-siblings", "3"], "--derive-siblings only works with --mode mnemonic"),
+            (&["--mode", "mnemonic", "--maximize", "0", "--time-limit", "5"], "--maximize only works with --mode raw"),
+            (&["--mode", "mnemonic", "--score-by", "leading_zeros", "--time-limit", "5"], "--score-by only works with --mode raw"),
+            (&["--mode", "mnemonic", "--prefix", "dead", "--track-near-miss"], "--track-near-miss only works with --mode raw"),
+            (&["--mode", "mnemonic", "--prefix", "dead", "--key-min", KEY_ONE], "--key-min/--key-max only works with --mode raw"),
+            (&["--prefix", "dead", "--json", "--qr"], "terminal QR codes cannot be combined with JSON output"),
+            (&["--prefix", "dead", "--seed", "1", "--output-file", "found.json"], "--seed makes every key predictable"),
+        ] {
+            let (kind, error_msg) = usage(args);
+            assert_eq!(kind, ErrorKind::ArgumentConflict, "{:?}", args);
+            assert!(error_msg.starts_with(option), "{:?}: {}", args, error_msg);
         }
-    });
+        
+        assert_eq!(usage(&["--maximize", "0"]).0, ErrorKind::MissingRequiredArgument);
+        assert_eq!(usage(&["--score-by", "max_repeat"]).0, ErrorKind::MissingRequiredArgument);
+        assert_eq!(usage(&["--prefix", "dead", "--notify-progress", "10"]).0, ErrorKind::MissingRequiredArgument);
+        assert_eq!(usage(&["--prefix", "dead", "--shamir-threshold", "4", "--shamir-shares", "3"]).0, ErrorKind::ValueValidation);
+    }
     
-    // Wait for all threads to complete
-    for handle in handles {
-        handle.join().unwrap();
+    #[test]
+    fn search_config_accepts_options_in_their_mode() {
+        for args in [
+            ["--prefix", "dead", "--mode", "mnemonic", "--words", "12"].as_slice(),
+            &["--prefix", "dead", "--mode", "mnemonic", "--output-xprv", "--output-xpub", "--derive-siblings", "3"],
+            &["--maximize", "0", "--max-attempts", "1000"],
+            &["--score-by", "max_repeat", "--time-limit", "5"],
+            &["--prefix", "dead", "--track-near-miss", "--key-min", KEY_ONE],
+            &["--prefix", "dead", "--json", "--qr-file", "found.png"],
+            &["--prefix", "dead", "--seed", "1"],
+            &["--prefix", "dead", "--seed", "1", "--output-file", "found.json", "--i-understand-seeded-keys-are-insecure"],
+            &["--prefix", "dead", "--shamir-threshold", "3", "--shamir-shares", "3"],
+        ] {
+            assert!(search_config(args).is_ok(), "{:?}: {:?}", args, search_config(args));
+        }
     }
-    progress_handle.join().unwrap();
     
-    // Check results
-    if found.load(Ordering::SeqCst) {
-        let final_attempts = total_attempts.load(Ordering::SeqCst);
-        let elapsed = start_time.elapsed();
+    #[test]
+    fn palindrome_length_defaults_to_the_whole_address() {
+        assert_eq!(search_args(&["--palindrome"]).palindrome, Some(40));
+        assert_eq!(search_args(&["--palindrome", "6"]).palindrome, Some(6));
+        for length in ["0", "41"] {
+            assert!(Cli::try_parse_from(["evm-vanity", "--palindrome", length]).is_err(), "{}", length);
+        }
+        assert_eq!(search_config(&["--palindrome"]), Ok(patterns(None, None)));
+    }
+    
+    #[test]
+    fn alt_pairs_report_the_most_specific_match() {
+        let args = search_args(&["--alt", "5a:", "--alt", "5aa:aed", "--alt", "5aae:beaed", "--alt", "cafe:babe"]);
+        let entry = |prefix: &str, suffix: &str| PatternEntry::new(Some(prefix.to_string()), (!suffix.is_empty()).then(|| suffix.to_string()));
+        let (set, covered) = alternative_set(&args.alt, false);
+        assert_eq!(set.entries, [entry("5aae", "beaed"), entry("cafe", "babe"), entry("5aa", "aed"), entry("5a", "")]);
+        assert_eq!(covered, [(entry("5aae", "beaed"), entry("5aa", "aed")), (entry("5aa", "aed"), entry("5a", ""))]);
         
-        if let Some(wallet) = result.lock().unwrap().as_ref() {
-            println!("🎉 Found vanity address after {} attempts in {:.2?}!", final_attempts, elapsed);
-            println!("📍 Address: {}", wallet.address);
-            println!("🔐 Private Key: {}", wallet.private_key);
-            
-            if let Some(mnemonic) = &wallet.mnemonic {
-                println!("📝 Mnemonic: {}", mnemonic);
-            }
+        // An address matching several pairs reports the longest of them
+        for (address, expected) in [
+            ("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", Some(0)),
+            ("0xcafe00000000000000000000000000000000babe", Some(1)),
+            ("0x5aa0000000000000000000000000000000000aed", Some(2)),
+            ("0x5aa0000000000000000000000000000000000000", Some(3)),
+            ("0x0000000000000000000000000000000000000aed", None),
+        ] {
+            assert_eq!(set.find_match(address, 0, &[], false), expected, "{}", address);
+            let bytes: [u8; 20] = hex::decode(&address[2..]).unwrap().try_into().unwrap();
+            assert_eq!(set.compile(&[], false).find_match(&bytes), expected, "{}", address);
         }
-    } else {
-        let final_attempts = total_attempts.load(Ordering::SeqCst);
-        println!("Search stopped by user after {} attempts", final_attempts);
+        
+        // The probabilities of the pairs add up
+        let probability = 16f64.powi(-9) + 16f64.powi(-8) + 16f64.powi(-6) + 16f64.powi(-2);
+        assert!((set.difficulty(0, false) * probability - 1.0).abs() < 1e-9);
     }
     
-    Ok(())
-}
\ No newline at end of file
+    #[test]
+    fn alt_pairs_are_covered_only_by_agreeing_case() {
+        let alternatives = search_args(&["--alt", "5Aa:", "--alt", "5a:"]).alt;
+        assert_eq!(alternative_set(&alternatives, false).1.len(), 1);
+        assert!(alternative_set(&alternatives, true).1.is_empty());
+        let alternatives = search_args(&["--alt", "5Aa:", "--alt", "5~a:"]).alt;
+        assert_eq!(alternative_set(&alternatives, true).1.len(), 1);
+    }
+    
+    #[test]
+    fn alt_pairs_are_validated() {
+        for (value, error_msg) in [
+            ("dead", "'dead' is not PREFIX:SUFFIX"),
+            (":", "at least one of the prefix and suffix is needed"),
+            ("xyz:", "Invalid"),
+            (":beeg", "Invalid"),
+        ] {
+            let error = Cli::try_parse_from(["evm-vanity", "--alt", value]).map(|_| ()).unwrap_err().to_string();
+            assert!(error.contains(error_msg), "{}: {}", value, error);
+        }
+        assert!(Cli::try_parse_from(["evm-vanity", "--alt", &format!("{}:{}", "a".repeat(30), "b".repeat(11))]).is_err());
+        assert!(Cli::try_parse_from(["evm-vanity", "--alt", "dead:beef", "--prefix", "cafe"]).is_err());
+    }
+}
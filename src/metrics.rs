@@ -0,0 +1,136 @@
+// --metrics-addr: a Prometheus /metrics endpoint for long searches. Every value is read
+// from the search's own atomics when scraped, so the workers never do extra work.
+
+use crate::counters::AttemptCounters;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+pub struct Source {
+    pub attempts: Arc<AttemptCounters>,
+    pub matches_found: Arc<AtomicU64>,
+    pub start_time: Instant,
+    pub threads: usize,
+    // Total attempts at the previous scrape, for the rate between scrapes
+    last_scrape: Mutex<Option<(Instant, u64)>>,
+}
+
+impl Source {
+    pub fn new(attempts: Arc<AttemptCounters>, matches_found: Arc<AtomicU64>, start_time: Instant, threads: usize) -> Source {
+        Source { attempts, matches_found, start_time, threads, last_scrape: Mutex::new(None) }
+    }
+}
+
+pub struct MetricsServer {
+    cancel: CancellationToken,
+    handle: JoinHandle<()>,
+}
+
+// Binds before the search starts, so a busy port is reported up front
+pub async fn start(address: SocketAddr, source: Source) -> std::io::Result<MetricsServer> {
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    info!("📈 Serving metrics on http://{}/metrics", listener.local_addr()?);
+    let app = Router::new().route("/metrics", get(metrics)).with_state(Arc::new(source));
+    let cancel = CancellationToken::new();
+    let shutdown = cancel.clone();
+    let handle = tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).with_graceful_shutdown(shutdown.cancelled_owned()).await {
+            warning!("⚠️  Metrics server failed: {}", e);
+        }
+    });
+    Ok(MetricsServer { cancel, handle })
+}
+
+impl MetricsServer {
+    pub async fn stop(self) {
+        self.cancel.cancel();
+        let _ = self.handle.await;
+    }
+}
+
+async fn metrics(State(source): State<Arc<Source>>) -> impl IntoResponse {
+    let now = Instant::now();
+    let per_thread = source.attempts.per_thread();
+    let total: u64 = per_thread.iter().sum();
+    let elapsed = now.duration_since(source.start_time).as_secs_f64();
+    
+    // The first scrape reports the average since the start
+    let (since, previous) = source.last_scrape.lock().unwrap().replace((now, total)).unwrap_or((source.start_time, 0));
+    let interval = now.duration_since(since).as_secs_f64();
+    let rate = if interval > 0.0 { total.saturating_sub(previous) as f64 / interval } else { 0.0 };
+    
+    let mut body = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+        let _ = write!(body, "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n");
+    };
+    metric("evm_vanity_attempts_total", "counter", "Candidate addresses checked.", total.to_string());
+    metric("evm_vanity_attempts_per_second", "gauge", "Attempts per second since the previous scrape.", rate.to_string());
+    metric("evm_vanity_matches_found_total", "counter", "Matching addresses found.", source.matches_found.load(Ordering::Relaxed).to_string());
+    metric("evm_vanity_elapsed_seconds", "gauge", "Seconds since the search started.", elapsed.to_string());
+    metric("evm_vanity_threads", "gauge", "Configured worker threads.", source.threads.to_string());
+    
+    body.push_str("# HELP evm_vanity_thread_attempts_total Candidate addresses checked by each worker thread.\n");
+    body.push_str("# TYPE evm_vanity_thread_attempts_total counter\n");
+    for (thread, attempts) in per_thread.iter().enumerate() {
+        let _ = writeln!(body, "evm_vanity_thread_attempts_total{{thread=\"{}\"}} {}", thread, attempts);
+    }
+    
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    async fn scrape(source: &Arc<Source>) -> String {
+        let body = metrics(State(source.clone())).await.into_response().into_body();
+        String::from_utf8(axum::body::to_bytes(body, usize::MAX).await.unwrap().to_vec()).unwrap()
+    }
+    
+    fn value<'a>(body: &'a str, name: &str) -> &'a str {
+        body.lines().find_map(|line| line.strip_prefix(name)?.strip_prefix(' ')).unwrap_or_else(|| panic!("no {} in\n{}", name, body))
+    }
+    
+    #[tokio::test]
+    async fn scrapes_read_the_search_counters() {
+        let attempts = Arc::new(AttemptCounters::new(2));
+        let matches_found = Arc::new(AtomicU64::new(0));
+        let source = Arc::new(Source::new(attempts.clone(), matches_found.clone(), Instant::now(), 2));
+        attempts.add(0, 300);
+        attempts.add(1, 200);
+        
+        let body = scrape(&source).await;
+        assert_eq!(value(&body, "evm_vanity_attempts_total"), "500");
+        assert_eq!(value(&body, "evm_vanity_matches_found_total"), "0");
+        assert_eq!(value(&body, "evm_vanity_threads"), "2");
+        assert_eq!(value(&body, "evm_vanity_thread_attempts_total{thread=\"0\"}"), "300");
+        assert_eq!(value(&body, "evm_vanity_thread_attempts_total{thread=\"1\"}"), "200");
+        assert!(body.contains("# TYPE evm_vanity_attempts_total counter\n"));
+        assert!(body.contains("# TYPE evm_vanity_attempts_per_second gauge\n"));
+        assert!(value(&body, "evm_vanity_elapsed_seconds").parse::<f64>().unwrap() >= 0.0);
+        
+        // The rate covers only the attempts since the previous scrape
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        matches_found.store(1, Ordering::Relaxed);
+        let body = scrape(&source).await;
+        assert_eq!(value(&body, "evm_vanity_matches_found_total"), "1");
+        assert_eq!(value(&body, "evm_vanity_attempts_per_second"), "0");
+    }
+    
+    #[tokio::test]
+    async fn server_stops_when_asked() {
+        let address: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let source = Source::new(Arc::new(AttemptCounters::new(1)), Arc::new(AtomicU64::new(0)), Instant::now(), 1);
+        let server = start(address, source).await.unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), server.stop()).await.expect("the metrics server did not stop");
+    }
+}
@@ -0,0 +1,87 @@
+// --track-near-miss: the candidates that came closest to the pattern, so that a search stopped
+// before its first match still has something to show. The prefix and the suffix are tracked
+// separately, each by how many of its characters a candidate got right.
+
+use crate::pattern::PartialMatcher;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// The best a worker has seen itself; it only looks at the shared records when it beats its own
+#[derive(Default)]
+pub struct Records {
+    prefix: usize,
+    suffix: usize,
+}
+
+// The closest candidate at one end of the address
+pub struct NearMiss<T> {
+    pub matched: usize,
+    pub pattern_len: usize,
+    pub item: T,
+}
+
+// Like score::BestSoFar, the length to beat is kept in an atomic, so the lock is only taken
+// by a candidate that beats every thread
+struct Closest<T> {
+    matcher: PartialMatcher,
+    matched: AtomicUsize,
+    best: Mutex<Option<(usize, T)>>,
+}
+
+impl<T> Closest<T> {
+    fn new(matcher: PartialMatcher) -> Closest<T> {
+        Closest { matcher, matched: AtomicUsize::new(0), best: Mutex::new(None) }
+    }
+    
+    fn offer(&self, record: &mut usize, address: &[u8; 20], candidate: impl FnOnce() -> T) {
+        let matched = self.matcher.matched(address);
+        if matched <= *record {
+            return;
+        }
+        *record = matched;
+        if matched <= self.matched.load(Ordering::Relaxed) {
+            return;
+        }
+        
+        let mut best = self.best.lock().unwrap();
+        if best.as_ref().is_some_and(|(best_matched, _)| matched <= *best_matched) {
+            return;
+        }
+        *best = Some((matched, candidate()));
+        self.matched.store(matched, Ordering::Relaxed);
+    }
+    
+    fn take(&self) -> Option<NearMiss<T>> {
+        let (matched, item) = self.best.lock().unwrap().take()?;
+        Some(NearMiss { matched, pattern_len: self.matcher.len(), item })
+    }
+}
+
+pub struct Tracker<T> {
+    prefix: Option<Closest<T>>,
+    suffix: Option<Closest<T>>,
+}
+
+impl<T> Tracker<T> {
+    pub fn new(prefix: Option<&str>, suffix: Option<&str>) -> Tracker<T> {
+        Tracker {
+            prefix: prefix.map(|prefix| Closest::new(PartialMatcher::new(prefix, false))),
+            suffix: suffix.map(|suffix| Closest::new(PartialMatcher::new(suffix, true))),
+        }
+    }
+    
+    // Keep the candidate if it comes closer at either end; `candidate` is only built when it does
+    pub fn offer(&self, records: &mut Records, address: &[u8; 20], candidate: impl Fn() -> T) {
+        if let Some(prefix) = &self.prefix {
+            prefix.offer(&mut records.prefix, address, &candidate);
+        }
+        if let Some(suffix) = &self.suffix {
+            suffix.offer(&mut records.suffix, address, &candidate);
+        }
+    }
+    
+    // The closest prefix and suffix candidates, if any got a character right
+    pub fn take(&self) -> (Option<NearMiss<T>>, Option<NearMiss<T>>) {
+        (self.prefix.as_ref().and_then(Closest::take), self.suffix.as_ref().and_then(Closest::take))
+    }
+}
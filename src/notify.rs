@@ -0,0 +1,156 @@
+// --notify and --notify-desktop: Telegram, Discord and desktop messages on matches, attempt
+// milestones and searches that give up. Messages are sent from a thread of their own, so a
+// slow or unreachable service never holds up the search; they carry the address and
+// pattern, never the key.
+
+use crate::pattern::to_checksum_address;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const TRIES: u32 = 3;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+pub enum Channel {
+    Telegram { token: String, chat: String },
+    Discord { webhook: String },
+    // A native notification, with the desktop-notify feature
+    Desktop,
+}
+
+impl Channel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Telegram { .. } => "Telegram",
+            Channel::Discord { .. } => "Discord",
+            Channel::Desktop => "Desktop",
+        }
+    }
+    
+    // The error never includes the URL, which holds the credentials
+    fn post(&self, agent: &ureq::Agent, text: &str) -> Result<(), String> {
+        let response = match self {
+            Channel::Desktop => return show_desktop(text),
+            Channel::Telegram { token, chat } => agent
+                .post(&format!("https://api.telegram.org/bot{}/sendMessage", token))
+                .send_json(serde_json::json!({ "chat_id": chat, "text": text, "disable_web_page_preview": true })),
+            Channel::Discord { webhook } => agent.post(webhook).send_json(serde_json::json!({ "content": text })),
+        };
+        match response {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(code, _)) => Err(format!("HTTP status {}", code)),
+            Err(ureq::Error::Transport(transport)) => Err(transport.kind().to_string()),
+        }
+    }
+    
+    // Tries a few times with a growing pause between tries
+    pub fn send(&self, agent: &ureq::Agent, text: &str) -> Result<(), String> {
+        // A notification daemon that is not running will not be there a second later either
+        let max_tries = if matches!(self, Channel::Desktop) { 1 } else { TRIES };
+        let mut tries = 0;
+        loop {
+            tries += 1;
+            match self.post(agent, text) {
+                Ok(()) => return Ok(()),
+                Err(error_msg) if max_tries == 1 => return Err(error_msg),
+                Err(error_msg) if tries == max_tries => return Err(format!("{} after {} tries", error_msg, max_tries)),
+                Err(error_msg) => {
+                    tracing::debug!(channel = self.name(), tries, error = %error_msg, "notification attempt failed, retrying");
+                    thread::sleep(Duration::from_secs(tries as u64));
+                }
+            }
+        }
+    }
+}
+
+// The first line of the message is the title, the rest its body
+#[cfg(feature = "desktop-notify")]
+fn show_desktop(text: &str) -> Result<(), String> {
+    let (summary, body) = text.split_once('\n').unwrap_or((text, ""));
+    notify_rust::Notification::new()
+        .appname("evm-vanity")
+        .summary(summary)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "desktop-notify"))]
+fn show_desktop(_text: &str) -> Result<(), String> {
+    Err(DESKTOP_UNSUPPORTED.to_string())
+}
+
+pub const DESKTOP_UNSUPPORTED: &str = "this build has no desktop notifications; rebuild with --features desktop-notify";
+
+pub fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(TIMEOUT).build()
+}
+
+// Cheap to clone into the threads that report matches and progress
+#[derive(Clone)]
+pub struct Notifier {
+    sender: mpsc::Sender<String>,
+    pattern: String,
+}
+
+pub struct Dispatcher {
+    handle: JoinHandle<()>,
+}
+
+// Starts the sending thread; `pattern` describes the search in every message
+pub fn start(channels: Vec<Channel>, pattern: String) -> (Notifier, Dispatcher) {
+    let (sender, receiver) = mpsc::channel::<String>();
+    let handle = thread::spawn(move || {
+        let agent = agent();
+        for text in receiver {
+            for channel in &channels {
+                match channel.send(&agent, &text) {
+                    Ok(()) => tracing::info!(channel = channel.name(), "notification sent"),
+                    Err(error_msg) => {
+                        warning!("⚠️  {} notification failed: {}", channel.name(), error_msg);
+                        tracing::error!(channel = channel.name(), error = %error_msg, "notification failed");
+                    }
+                }
+            }
+        }
+    });
+    (Notifier { sender, pattern }, Dispatcher { handle })
+}
+
+impl Notifier {
+    fn send(&self, text: String) {
+        // The thread only stops after every sender is gone
+        let _ = self.sender.send(text);
+    }
+    
+    pub fn matched(&self, address: &str, attempts: u64, elapsed: Duration) {
+        self.send(format!(
+            "🎉 evm-vanity found a match\nAddress: {}\nPattern: {}\nAttempts: {}\nElapsed: {:.2?}",
+            to_checksum_address(address), self.pattern, crate::format_with_commas(attempts), elapsed
+        ));
+    }
+    
+    // The search ended on a limit before finding what it was asked for
+    pub fn gave_up(&self, reason: &str, matches: u64, attempts: u64, elapsed: Duration) {
+        self.send(format!(
+            "⏹️ evm-vanity gave up: {}\nPattern: {}\nMatches: {}\nAttempts: {}\nElapsed: {:.2?}",
+            reason, self.pattern, matches, crate::format_with_commas(attempts), elapsed
+        ));
+    }
+    
+    pub fn milestone(&self, attempts: u64, rate: f64, elapsed: Duration) {
+        self.send(format!(
+            "⏳ evm-vanity passed {} attempts\nPattern: {}\nRate: {:.0} addr/sec\nElapsed: {:.2?}",
+            crate::format_with_commas(attempts), self.pattern, rate, elapsed
+        ));
+    }
+}
+
+impl Dispatcher {
+    // Waits for queued messages once `notifier`, the last sender, is dropped
+    pub fn finish(self, notifier: Notifier) {
+        drop(notifier);
+        let _ = self.handle.join();
+    }
+}
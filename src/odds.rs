@@ -0,0 +1,102 @@
+// The odds of a search, shared by `estimate`, --max-expected-duration and the luck line
+// after a find. Each attempt matches with probability 1/difficulty, so matches arrive like
+// a Poisson process: after n attempts the number found is Poisson with mean n/difficulty.
+
+// Chance that `matches` matches have turned up within `attempts` attempts
+pub fn chance_within(attempts: f64, difficulty: f64, matches: u64) -> f64 {
+    let mean = attempts / difficulty;
+    if mean <= 0.0 {
+        return 0.0;
+    }
+    if matches == 1 {
+        return -(-mean).exp_m1();
+    }
+    // 1 - P(fewer than `matches`), with each Poisson term in logs so large means don't underflow
+    let mut log_factorial = 0.0;
+    let mut fewer = 0.0;
+    for found in 0..matches {
+        if found > 0 {
+            log_factorial += (found as f64).ln();
+        }
+        fewer += (found as f64 * mean.ln() - mean - log_factorial).exp();
+    }
+    (1.0 - fewer).max(0.0)
+}
+
+// Attempts after which a single match has turned up with probability `chance`
+pub fn attempts_for_chance(chance: f64, difficulty: f64) -> f64 {
+    -(1.0 - chance).ln() * difficulty
+}
+
+pub struct Luck {
+    pub expected_attempts: f64,
+    // Actual attempts over expected ones; below 1 is lucky
+    pub ratio: f64,
+    // Share of searches that would have needed more attempts
+    pub faster_than: f64,
+}
+
+impl Luck {
+    pub fn new(attempts: u64, difficulty: f64, matches: u64) -> Luck {
+        let expected_attempts = difficulty * matches as f64;
+        Luck {
+            expected_attempts,
+            ratio: attempts as f64 / expected_attempts,
+            faster_than: 1.0 - chance_within(attempts as f64, difficulty, matches),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::{pattern_difficulty, PatternEntry, PatternSet};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn matches_follow_the_poisson_model() {
+        let difficulty = 4096.0;
+        assert!(close(chance_within(difficulty, difficulty, 1), 1.0 - (-1f64).exp()));
+        // Two matches within a mean of m: 1 - e^-m (1 + m)
+        let mean: f64 = 1.5;
+        assert!(close(chance_within(mean * difficulty, difficulty, 2), 1.0 - (-mean).exp() * (1.0 + mean)));
+        assert!((chance_within(1000.0 * difficulty, difficulty, 1000) - 0.5).abs() < 0.01);
+        assert_eq!(chance_within(0.0, difficulty, 1), 0.0);
+        for chance in [0.1, 0.5, 0.9] {
+            assert!(close(chance_within(attempts_for_chance(chance, difficulty), difficulty, 1), chance));
+        }
+    }
+
+    #[test]
+    fn luck_compares_attempts_with_the_expected_ones() {
+        let luck = Luck::new(2048, 4096.0, 1);
+        assert_eq!(luck.expected_attempts, 4096.0);
+        assert_eq!(luck.ratio, 0.5);
+        assert!(close(luck.faster_than, (-0.5f64).exp()));
+
+        let luck = Luck::new(8192, 4096.0, 4);
+        assert_eq!(luck.expected_attempts, 16384.0);
+        assert_eq!(luck.ratio, 0.5);
+        assert!(luck.faster_than > 0.8);
+    }
+
+    #[test]
+    fn expected_attempts_match_the_estimate() {
+        // The search takes its difficulty from the pattern set and `estimate` from the pattern alone
+        for (prefix, suffix, case_sensitive) in [
+            (Some("dead"), None, false),
+            (None, Some("beef"), false),
+            (Some("dead"), Some("beef"), false),
+            (Some("DeAd"), Some("b~eEf"), true),
+            (Some("0123"), Some("4567"), true),
+        ] {
+            let entry = PatternEntry::new(prefix.map(String::from), suffix.map(String::from));
+            let set = PatternSet::new(vec![entry]);
+            assert_eq!(set.difficulty(0, case_sensitive), pattern_difficulty(prefix, suffix, case_sensitive), "{:?} {:?}", prefix, suffix);
+        }
+        assert_eq!(pattern_difficulty(Some("DeAd"), Some("b~eEf"), true), 16.0 * 32f64.powi(7));
+    }
+}
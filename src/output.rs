@@ -0,0 +1,86 @@
+// Terminal styling of the text output. Colors are only used on a terminal, and never with
+// --no-color or when NO_COLOR is set (https://no-color.org). JSON output is built without
+// these helpers, so it never holds escape codes.
+
+use crossterm::style::Stylize;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+const BELLS: usize = 3;
+
+static STDOUT_COLOR: AtomicBool = AtomicBool::new(false);
+static STDERR_COLOR: AtomicBool = AtomicBool::new(false);
+
+pub fn init(no_color: bool) {
+    let allowed = !no_color && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+    STDOUT_COLOR.store(allowed && std::io::stdout().is_terminal(), Ordering::Relaxed);
+    STDERR_COLOR.store(allowed && std::io::stderr().is_terminal(), Ordering::Relaxed);
+}
+
+fn stdout_color() -> bool {
+    STDOUT_COLOR.load(Ordering::Relaxed)
+}
+
+fn stderr_color() -> bool {
+    STDERR_COLOR.load(Ordering::Relaxed)
+}
+
+// info! output goes to stderr while streaming
+fn info_color() -> bool {
+    if crate::INFO_TO_STDERR.load(Ordering::Relaxed) {
+        stderr_color()
+    } else {
+        stdout_color()
+    }
+}
+
+pub fn error(text: &str) -> String {
+    if stderr_color() { text.red().bold().to_string() } else { text.to_string() }
+}
+
+pub fn warning(text: &str) -> String {
+    if stderr_color() { text.yellow().bold().to_string() } else { text.to_string() }
+}
+
+pub fn success(text: &str) -> String {
+    if stdout_color() { text.green().bold().to_string() } else { text.to_string() }
+}
+
+pub fn rate(rate: f64) -> String {
+    let text = format!("{:.0} addr/sec", rate);
+    if info_color() { text.cyan().to_string() } else { text }
+}
+
+pub fn elapsed(elapsed: Duration) -> String {
+    let text = format!("{:.2?}", elapsed);
+    if info_color() { text.yellow().to_string() } else { text }
+}
+
+// A 0x-prefixed address with the matched first `prefix_len` hex characters in green and
+// the matched last `suffix_len` in blue
+pub fn address(address: &str, prefix_len: usize, suffix_len: usize) -> String {
+    if !stdout_color() {
+        return address.to_string();
+    }
+    let (head, hex) = address.split_at(2);
+    let (start, rest) = hex.split_at(prefix_len.min(hex.len()));
+    let (middle, end) = rest.split_at(rest.len() - suffix_len.min(rest.len()));
+    format!("{}{}{}{}", head, start.green().bold(), middle, end.blue().bold())
+}
+
+// --bell: a few terminal bells, so a search that ends in another window is heard. Only on a
+// terminal, as the control characters would end up in a log file.
+pub fn bell() {
+    use std::io::Write;
+    if !std::io::stderr().is_terminal() {
+        return;
+    }
+    for i in 0..BELLS {
+        if i > 0 {
+            std::thread::sleep(Duration::from_millis(300));
+        }
+        let mut stderr = std::io::stderr();
+        let _ = stderr.write_all(b"\x07").and_then(|()| stderr.flush());
+    }
+}
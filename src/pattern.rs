@@ -0,0 +1,1435 @@
+// Pattern validation, matching and difficulty estimation
+
+use crate::error::VanityError;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// A pattern that disqualifies an otherwise matching address
+#[derive(Clone, Debug)]
+pub enum Exclusion {
+    Prefix(String),
+    Suffix(String),
+}
+
+// Walk a pattern position by position, yielding the lowercase hex character and
+// the case it must have in the checksummed address (None = any case).
+// Uppercase letters must be uppercase, lowercase letters lowercase, and `~a`
+// accepts either case.
+pub fn pattern_positions(pattern: &str) -> impl Iterator<Item = (u8, Option<bool>)> + '_ {
+    let mut chars = pattern.bytes();
+    std::iter::from_fn(move || {
+        let c = chars.next()?;
+        if c == b'~' {
+            let c = chars.next()?;
+            Some((c.to_ascii_lowercase(), None))
+        } else if c.is_ascii_alphabetic() {
+            Some((c.to_ascii_lowercase(), Some(c.is_ascii_uppercase())))
+        } else {
+            Some((c, None))
+        }
+    })
+}
+
+// Number of address characters a pattern covers (escapes don't count)
+pub fn pattern_len(pattern: &str) -> usize {
+    pattern.bytes().filter(|&c| c != b'~').count()
+}
+
+pub fn has_case_constraints(pattern: &str) -> bool {
+    pattern_positions(pattern).any(|(_, upper)| upper.is_some())
+}
+
+pub fn value_matches_at(address: &[u8], pattern: &str, start: usize) -> bool {
+    start + pattern_len(pattern) <= address.len()
+        && pattern_positions(pattern)
+            .zip(&address[start..])
+            .all(|((c, _), &a)| c == a.to_ascii_lowercase())
+}
+
+pub fn case_matches_at(checksummed: &[u8], pattern: &str, start: usize) -> bool {
+    pattern_positions(pattern)
+        .zip(&checksummed[start..])
+        .all(|((_, upper), &a)| upper.is_none_or(|upper| a.is_ascii_uppercase() == upper))
+}
+
+// EIP-55 mixed-case checksum encoding of an address
+pub fn to_checksum_address(address: &str) -> String {
+    to_chain_checksum_address(address, 1)
+}
+
+// EIP-1191 checksum for `chain_id`, which hashes the chain ID and 0x before the address.
+// Chain 1 keeps plain EIP-55.
+pub fn to_chain_checksum_address(address: &str, chain_id: u64) -> String {
+    let lower = address.strip_prefix("0x").unwrap_or(address).to_lowercase();
+    let hash = if chain_id == 1 {
+        Keccak256::digest(lower.as_bytes())
+    } else {
+        Keccak256::digest(format!("{}0x{}", chain_id, lower).as_bytes())
+    };
+    
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> (if i.is_multiple_of(2) { 4 } else { 0 })) & 0x0f;
+        if c.is_ascii_alphabetic() && nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+    checksummed
+}
+
+// --chain-id: the chain whose checksum case-sensitive patterns are matched against
+static CHECKSUM_CHAIN_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn set_checksum_chain_id(chain_id: u64) {
+    CHECKSUM_CHAIN_ID.store(chain_id, Ordering::Relaxed);
+}
+
+fn matching_checksum_address(address: &str) -> String {
+    to_chain_checksum_address(address, CHECKSUM_CHAIN_ID.load(Ordering::Relaxed))
+}
+
+pub fn matches_pattern(
+    address: &str,
+    prefix_pattern: Option<&str>,
+    suffix_pattern: Option<&str>,
+    exclusions: &[Exclusion],
+    case_sensitive: bool,
+) -> bool {
+    // Remove 0x prefix for matching
+    let addr = address.strip_prefix("0x").unwrap_or(address).as_bytes();
+    let suffix_start = suffix_pattern.map(|suffix| addr.len().saturating_sub(pattern_len(suffix)));
+    
+    // Check prefix and suffix values first; this rejects almost every candidate cheaply
+    if let Some(prefix) = prefix_pattern {
+        if !value_matches_at(addr, prefix, 0) {
+            return false;
+        }
+    }
+    
+    if let (Some(suffix), Some(start)) = (suffix_pattern, suffix_start) {
+        if !value_matches_at(addr, suffix, start) {
+            return false;
+        }
+    }
+    
+    // Only hash for the checksum once the values already match
+    if case_sensitive
+        && (prefix_pattern.is_some_and(has_case_constraints) || suffix_pattern.is_some_and(has_case_constraints))
+    {
+        let checksummed = matching_checksum_address(address);
+        let checksummed = &checksummed.as_bytes()[2..];
+        
+        if let Some(prefix) = prefix_pattern {
+            if !case_matches_at(checksummed, prefix, 0) {
+                return false;
+            }
+        }
+        
+        if let (Some(suffix), Some(start)) = (suffix_pattern, suffix_start) {
+            if !case_matches_at(checksummed, suffix, start) {
+                return false;
+            }
+        }
+    }
+    
+    // Exclusions only need checking for the rare address that matched
+    !is_excluded(address, exclusions, case_sensitive)
+}
+
+pub fn is_excluded(address: &str, exclusions: &[Exclusion], case_sensitive: bool) -> bool {
+    exclusions.iter().any(|exclusion| match exclusion {
+        Exclusion::Prefix(prefix) => matches_pattern(address, Some(prefix), None, &[], case_sensitive),
+        Exclusion::Suffix(suffix) => matches_pattern(address, None, Some(suffix), &[], case_sensitive),
+    })
+}
+
+// Whether `pattern` appears at character `offset` of the address (0x optional). The search
+// checks OffsetPattern on the raw bytes instead, which benches/pattern.rs compares it with.
+#[allow(dead_code)]
+pub fn matches_at_offset(address: &str, pattern: &str, offset: usize, case_sensitive: bool) -> bool {
+    let addr = address.strip_prefix("0x").unwrap_or(address).as_bytes();
+    if !value_matches_at(addr, pattern, offset) {
+        return false;
+    }
+    !case_sensitive || !has_case_constraints(pattern) || case_matches_at(&matching_checksum_address(address).as_bytes()[2..], pattern, offset)
+}
+
+// Characters of `pattern` at `start` that the address gets wrong: a different value or, if
+// `case_sensitive`, a letter of the wrong case in the checksummed address. Characters past
+// the end of the address count as wrong.
+fn mismatches_at(address: &[u8], pattern: &str, start: usize, case_sensitive: bool) -> usize {
+    let wrong = pattern_positions(pattern)
+        .zip(address.get(start..).unwrap_or_default())
+        .filter(|&((c, upper), &a)| c != a.to_ascii_lowercase() || (case_sensitive && upper.is_some_and(|upper| a.is_ascii_uppercase() != upper)))
+        .count();
+    wrong + pattern_len(pattern).saturating_sub(address.len().saturating_sub(start))
+}
+
+// Hamming distance of the address's prefix and suffix to the patterns, if it is at most
+// `max_dist`. Patterns are compared character by character, so a `~` escape counts once.
+pub fn matches_fuzzy(
+    address: &str,
+    prefix: Option<&str>,
+    suffix: Option<&str>,
+    max_dist: usize,
+    case_sensitive: bool,
+) -> Option<usize> {
+    let distance = |addr: &[u8], case_sensitive: bool| {
+        prefix.map_or(0, |prefix| mismatches_at(addr, prefix, 0, case_sensitive))
+            + suffix.map_or(0, |suffix| mismatches_at(addr, suffix, addr.len().saturating_sub(pattern_len(suffix)), case_sensitive))
+    };
+    
+    // Case can only add mismatches, so the cheap case-insensitive distance rejects first
+    let addr = address.strip_prefix("0x").unwrap_or(address).as_bytes();
+    let value_distance = distance(addr, false);
+    if value_distance > max_dist {
+        return None;
+    }
+    if !case_sensitive {
+        return Some(value_distance);
+    }
+    
+    let checksummed = matching_checksum_address(address);
+    let case_distance = distance(&checksummed.as_bytes()[2..], true);
+    (case_distance <= max_dist).then_some(case_distance)
+}
+
+// Expected number of attempts to find a match: 16 per hex character, and a
+// further factor of 2 for every letter whose checksum case is pinned
+pub fn pattern_difficulty(prefix_pattern: Option<&str>, suffix_pattern: Option<&str>, case_sensitive: bool) -> f64 {
+    [prefix_pattern, suffix_pattern]
+        .into_iter()
+        .flatten()
+        .flat_map(pattern_positions)
+        .map(|(_, upper)| if case_sensitive && upper.is_some() { 32.0 } else { 16.0 })
+        .product()
+}
+
+// Expected attempts when up to `max_distance` characters may mismatch. Each
+// position matches independently with probability 1/16 (1/32 if its case is
+// pinned), so this sums the probability of 0..=max_distance mismatches.
+pub fn fuzzy_difficulty(prefix_pattern: Option<&str>, suffix_pattern: Option<&str>, max_distance: usize, case_sensitive: bool) -> f64 {
+    // mismatches[k] = probability of exactly k mismatches among the positions seen so far
+    let mut mismatches = vec![1.0f64];
+    for (_, upper) in [prefix_pattern, suffix_pattern].into_iter().flatten().flat_map(pattern_positions) {
+        let p = if case_sensitive && upper.is_some() { 1.0 / 32.0 } else { 1.0 / 16.0 };
+        let mut next = vec![0.0; mismatches.len() + 1];
+        for (k, &probability) in mismatches.iter().enumerate() {
+            next[k] += probability * p;
+            next[k + 1] += probability * (1.0 - p);
+        }
+        mismatches = next;
+    }
+    1.0 / mismatches.iter().take(max_distance + 1).sum::<f64>()
+}
+
+// One line of a pattern set: the prefix and/or suffix an address must have
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PatternEntry {
+    // Required (AND) patterns
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    // --or-prefix/--or-suffix: the address must also start (end) with one of these
+    pub prefix_alternatives: Vec<String>,
+    pub suffix_alternatives: Vec<String>,
+}
+
+impl PatternEntry {
+    pub fn new(prefix: Option<String>, suffix: Option<String>) -> PatternEntry {
+        PatternEntry { prefix, suffix, ..PatternEntry::default() }
+    }
+    
+    // Every pattern of the entry, required or alternative
+    pub fn patterns(&self) -> impl Iterator<Item = &String> {
+        self.prefix.iter().chain(&self.suffix).chain(&self.prefix_alternatives).chain(&self.suffix_alternatives)
+    }
+    
+    pub fn matches(&self, address: &str, exclusions: &[Exclusion], case_sensitive: bool) -> bool {
+        // The alternatives reject most candidates before the required patterns and exclusions are checked
+        (self.prefix_alternatives.is_empty()
+            || self.prefix_alternatives.iter().any(|prefix| matches_pattern(address, Some(prefix), None, &[], case_sensitive)))
+            && (self.suffix_alternatives.is_empty()
+                || self.suffix_alternatives.iter().any(|suffix| matches_pattern(address, None, Some(suffix), &[], case_sensitive)))
+            && matches_pattern(address, self.prefix.as_deref(), self.suffix.as_deref(), exclusions, case_sensitive)
+    }
+    
+    // The prefix and suffix patterns an address can match the entry with: one per combination
+    // of alternatives, each merged with the required pattern it must agree with
+    fn combinations(&self) -> Vec<(Option<&str>, Option<&str>)> {
+        fn choices<'a>(required: &'a Option<String>, alternatives: &'a [String]) -> Vec<Option<&'a str>> {
+            if alternatives.is_empty() {
+                return vec![required.as_deref()];
+            }
+            alternatives
+                .iter()
+                .map(|alternative| match required {
+                    Some(required) if pattern_len(required) > pattern_len(alternative) => Some(required.as_str()),
+                    _ => Some(alternative.as_str()),
+                })
+                .collect()
+        }
+        let suffixes = choices(&self.suffix, &self.suffix_alternatives);
+        choices(&self.prefix, &self.prefix_alternatives)
+            .into_iter()
+            .flat_map(|prefix| suffixes.iter().map(move |&suffix| (prefix, suffix)))
+            .collect()
+    }
+    
+    // One compiled pattern per combination of alternatives, each ANDed with the required
+    // pattern so the case of both is checked
+    fn compiled(&self, case_sensitive: bool) -> Vec<CompiledPattern> {
+        fn choices(required: &Option<String>, alternatives: &[String], at_end: bool) -> Vec<Option<ByteMatcher>> {
+            let required = required.as_deref().map(|pattern| ByteMatcher::new(pattern, at_end));
+            if alternatives.is_empty() {
+                return vec![required];
+            }
+            alternatives
+                .iter()
+                .map(|alternative| {
+                    let alternative = ByteMatcher::new(alternative, at_end);
+                    Some(required.map_or(alternative, |required| required.and(alternative)))
+                })
+                .collect()
+        }
+        let suffixes = choices(&self.suffix, &self.suffix_alternatives, true);
+        choices(&self.prefix, &self.prefix_alternatives, false)
+            .into_iter()
+            .flat_map(|prefix| suffixes.iter().map(move |&suffix| CompiledPattern { prefix, suffix, case_sensitive }))
+            .collect()
+    }
+    
+    // Alternatives that contradict the required pattern at the same end, which no address can match
+    pub fn conflicts(&self) -> Vec<(&str, &str)> {
+        let prefixes = self.prefix.iter().flat_map(|prefix| {
+            self.prefix_alternatives.iter().filter(|alternative| !agree(prefix, alternative, false)).map(move |alternative| (prefix.as_str(), alternative.as_str()))
+        });
+        let suffixes = self.suffix.iter().flat_map(|suffix| {
+            self.suffix_alternatives.iter().filter(|alternative| !agree(suffix, alternative, true)).map(move |alternative| (suffix.as_str(), alternative.as_str()))
+        });
+        prefixes.chain(suffixes).collect()
+    }
+    
+    // Expected attempts to match any combination; their probabilities add up
+    pub fn difficulty(&self, max_distance: usize, case_sensitive: bool) -> f64 {
+        let probability: f64 = self
+            .combinations()
+            .into_iter()
+            .map(|(prefix, suffix)| 1.0 / fuzzy_difficulty(prefix, suffix, max_distance, case_sensitive))
+            .sum();
+        1.0 / probability
+    }
+    
+    // Characters every match has fixed, from the easiest combination
+    pub fn fixed_len(&self) -> usize {
+        self.combinations()
+            .into_iter()
+            .map(|(prefix, suffix)| prefix.map_or(0, pattern_len) + suffix.map_or(0, pattern_len))
+            .min()
+            .unwrap_or(0)
+    }
+    
+    // Whether every address matching `other` also matches this entry, as `de:ef` does
+    // `dead:beef`; only meant for entries without alternatives
+    pub fn covers(&self, other: &PatternEntry, case_sensitive: bool) -> bool {
+        let covers_end = |shorter: &Option<String>, longer: &Option<String>, at_end: bool| match (shorter, longer) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(shorter), Some(longer)) => {
+                let (mut a, mut b): (Vec<_>, Vec<_>) = (pattern_positions(shorter).collect(), pattern_positions(longer).collect());
+                if at_end {
+                    a.reverse();
+                    b.reverse();
+                }
+                a.len() <= b.len()
+                    && a.iter().zip(&b).all(|(&(c, upper), &(d, other_upper))| {
+                        c == d && (!case_sensitive || upper.is_none_or(|upper| other_upper == Some(upper)))
+                    })
+            }
+        };
+        covers_end(&self.prefix, &other.prefix, false) && covers_end(&self.suffix, &other.suffix, true)
+    }
+    
+    // Lengths of the prefix and suffix an address matched, for highlighting
+    pub fn matched_lengths(&self, address: &str, case_sensitive: bool) -> (usize, usize) {
+        // Fuzzy matches may match no combination exactly; they have a single one
+        let combinations = self.combinations();
+        let (prefix, suffix) = combinations
+            .iter()
+            .find(|(prefix, suffix)| matches_pattern(address, *prefix, *suffix, &[], case_sensitive))
+            .unwrap_or(&combinations[0]);
+        (prefix.map_or(0, pattern_len), suffix.map_or(0, pattern_len))
+    }
+}
+
+// Whether two patterns anchored at the same end of the address can both match
+fn agree(a: &str, b: &str, at_end: bool) -> bool {
+    let (mut a, mut b): (Vec<u8>, Vec<u8>) = (pattern_positions(a).map(|(c, _)| c).collect(), pattern_positions(b).map(|(c, _)| c).collect());
+    if at_end {
+        a.reverse();
+        b.reverse();
+    }
+    a.iter().zip(&b).all(|(x, y)| x == y)
+}
+
+impl fmt::Display for PatternEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(prefix) = &self.prefix {
+            parts.push(format!("prefix '{}'", prefix));
+        }
+        if let Some(suffix) = &self.suffix {
+            parts.push(format!("suffix '{}'", suffix));
+        }
+        for (alternatives, anchor) in [(&self.prefix_alternatives, "starts with"), (&self.suffix_alternatives, "ends with")] {
+            let choices: Vec<String> = alternatives.iter().map(|pattern| format!("{} '{}'", anchor, pattern)).collect();
+            match choices.len() {
+                0 => {}
+                1 => parts.push(choices.join("")),
+                _ => parts.push(format!("({})", choices.join(" OR "))),
+            }
+        }
+        if parts.is_empty() {
+            return write!(f, "any address");
+        }
+        write!(f, "{}", parts.join(" AND "))
+    }
+}
+
+// A prefix or suffix decoded to the address bytes it covers. `mask` selects its nibbles, so an
+// odd-length pattern ends halfway through a byte; bit i of `upper` (`lower`) means address
+// character i must be uppercase (lowercase) in the checksummed address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ByteMatcher {
+    bytes: [u8; 20],
+    mask: [u8; 20],
+    upper: u64,
+    lower: u64,
+}
+
+impl ByteMatcher {
+    // A checked pattern anchored at the start or, `at_end`, the end of the address. A character
+    // that is not hex never matches the string check either, so any value will do for it.
+    fn new(pattern: &str, at_end: bool) -> ByteMatcher {
+        ByteMatcher::at(pattern, if at_end { 40 - pattern_len(pattern).min(40) } else { 0 })
+    }
+    
+    // A pattern starting at character `start`; characters past the end of the address are dropped
+    fn at(pattern: &str, start: usize) -> ByteMatcher {
+        let mut matcher = ByteMatcher::default();
+        for (i, (c, upper)) in (start..40).zip(pattern_positions(pattern)) {
+            let shift = if i.is_multiple_of(2) { 4 } else { 0 };
+            matcher.bytes[i / 2] |= ((c as char).to_digit(16).unwrap_or(0) as u8) << shift;
+            matcher.mask[i / 2] |= 0x0f << shift;
+            match upper {
+                Some(true) => matcher.upper |= 1 << i,
+                Some(false) => matcher.lower |= 1 << i,
+                None => {}
+            }
+        }
+        matcher
+    }
+    
+    // Matches the addresses both matchers do; they must agree where they overlap
+    fn and(self, other: ByteMatcher) -> ByteMatcher {
+        let mut merged = self;
+        for i in 0..20 {
+            merged.bytes[i] |= other.bytes[i];
+            merged.mask[i] |= other.mask[i];
+        }
+        merged.upper |= other.upper;
+        merged.lower |= other.lower;
+        merged
+    }
+    
+    // Whether the address has the pattern's values, ignoring case. Differences are ORed
+    // together rather than compared byte by byte, so the loop has no branches; the half byte
+    // of an odd-length pattern is compared through its mask like the rest. Comparing only the
+    // suffix's bytes as a slice, or the ends as masked u64 words, measured no faster (see
+    // benches/pattern.rs).
+    #[inline]
+    pub fn matches(&self, addr_bytes: &[u8; 20]) -> bool {
+        addr_bytes.iter().zip(&self.mask).zip(&self.bytes).fold(0, |diff, ((a, mask), b)| diff | ((a & mask) ^ b)) == 0
+    }
+    
+    // Nibble i (0 or 1) of the address, if the pattern fixes it
+    pub fn leading_nibble(&self, i: usize) -> Option<u8> {
+        let shift = if i == 0 { 4 } else { 0 };
+        (self.mask[0] >> shift & 0x0f == 0x0f).then_some(self.bytes[0] >> shift & 0x0f)
+    }
+}
+
+// A prefix and suffix compiled for the hot loop: candidates are compared as raw address bytes
+// without allocating, and only the rare one whose values match is hashed for its checksum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompiledPattern {
+    pub prefix: Option<ByteMatcher>,
+    pub suffix: Option<ByteMatcher>,
+    pub case_sensitive: bool,
+}
+
+impl CompiledPattern {
+    pub fn compile(prefix: Option<&str>, suffix: Option<&str>, case_sensitive: bool) -> Result<CompiledPattern, VanityError> {
+        let invalid = |message: String| VanityError::BadPattern { message: message.trim_start_matches("❌ ").to_string() };
+        for pattern in prefix.iter().chain(&suffix) {
+            validate_pattern(pattern).map_err(invalid)?;
+        }
+        validate_lengths(prefix, suffix).map_err(invalid)?;
+        Ok(CompiledPattern {
+            prefix: prefix.map(|prefix| ByteMatcher::new(prefix, false)),
+            suffix: suffix.map(|suffix| ByteMatcher::new(suffix, true)),
+            case_sensitive,
+        })
+    }
+    
+    #[inline]
+    pub fn matches(&self, addr_bytes: &[u8; 20]) -> bool {
+        self.prefix.is_none_or(|prefix| prefix.matches(addr_bytes))
+            && self.suffix.is_none_or(|suffix| suffix.matches(addr_bytes))
+            && self.case_matches(addr_bytes)
+    }
+    
+    fn case_matches(&self, addr_bytes: &[u8; 20]) -> bool {
+        let (upper, lower) = self.prefix.iter().chain(&self.suffix).fold((0, 0), |(upper, lower), matcher| (upper | matcher.upper, lower | matcher.lower));
+        !self.case_sensitive || case_bits_match(addr_bytes, upper, lower)
+    }
+    
+    // Nibble i (0 or 1) every match starts with, if the prefix fixes it
+    pub fn leading_nibble(&self, i: usize) -> Option<u8> {
+        self.prefix.and_then(|prefix| prefix.leading_nibble(i))
+    }
+}
+
+// Whether the characters set in `upper` (`lower`) are uppercase (lowercase) in the checksum;
+// only hashes when a character has a case to check
+fn case_bits_match(addr_bytes: &[u8; 20], upper: u64, lower: u64) -> bool {
+    if upper | lower == 0 {
+        return true;
+    }
+    let uppercase = checksum_uppercase(addr_bytes, CHECKSUM_CHAIN_ID.load(Ordering::Relaxed));
+    upper & !uppercase == 0 && lower & uppercase == 0
+}
+
+// --at-offset: a pattern at a fixed character position, compiled like a prefix or suffix
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OffsetPattern {
+    matcher: ByteMatcher,
+    case_sensitive: bool,
+}
+
+impl OffsetPattern {
+    pub fn compile(pattern: &str, offset: usize, case_sensitive: bool) -> Result<OffsetPattern, VanityError> {
+        validate_pattern(pattern).map_err(|message| VanityError::BadPattern { message: message.trim_start_matches("❌ ").to_string() })?;
+        if offset + pattern_len(pattern) > 40 {
+            return Err(VanityError::BadPattern {
+                message: format!("'{}' at offset {} needs {} characters but an address only has 40", pattern, offset, offset + pattern_len(pattern)),
+            });
+        }
+        Ok(OffsetPattern { matcher: ByteMatcher::at(pattern, offset), case_sensitive })
+    }
+    
+    #[inline]
+    pub fn matches(&self, addr_bytes: &[u8; 20]) -> bool {
+        self.matcher.matches(addr_bytes) && (!self.case_sensitive || case_bits_match(addr_bytes, self.matcher.upper, self.matcher.lower))
+    }
+}
+
+// Bit i is set when address character i is uppercase in the checksum for `chain_id`, the same
+// case to_chain_checksum_address gives it, worked out on the stack
+fn checksum_uppercase(addr_bytes: &[u8; 20], chain_id: u64) -> u64 {
+    let mut lower = [0u8; 40];
+    hex::encode_to_slice(addr_bytes, &mut lower).expect("40 hex characters for 20 bytes");
+    let mut hasher = Keccak256::new();
+    if chain_id != 1 {
+        // u64::MAX has 20 digits
+        let mut digits = [0u8; 20];
+        let mut rest = &mut digits[..];
+        std::io::Write::write_fmt(&mut rest, format_args!("{}", chain_id)).expect("a u64 fits in 20 digits");
+        let len = 20 - rest.len();
+        hasher.update(&digits[..len]);
+        hasher.update(b"0x");
+    }
+    hasher.update(lower);
+    let hash = hasher.finalize();
+    (0..40)
+        .filter(|&i| lower[i].is_ascii_alphabetic() && (hash[i / 2] >> (if i.is_multiple_of(2) { 4 } else { 0 })) & 0x0f >= 8)
+        .fold(0, |bits, i| bits | 1 << i)
+}
+
+// A pattern set compiled for exact matching: every combination of every entry, with the entry
+// it belongs to, and the exclusions
+#[derive(Clone, Debug)]
+pub struct CompiledSet {
+    combinations: Vec<(usize, CompiledPattern)>,
+    exclusions: Vec<CompiledPattern>,
+}
+
+impl CompiledSet {
+    // Index of the first entry the address matches, like PatternSet::find_match without --fuzzy
+    pub fn find_match(&self, addr_bytes: &[u8; 20]) -> Option<usize> {
+        let &(index, _) = self.combinations.iter().find(|(_, pattern)| pattern.matches(addr_bytes))?;
+        (!self.exclusions.iter().any(|exclusion| exclusion.matches(addr_bytes))).then_some(index)
+    }
+    
+    // Nibble i (0 or 1) that every match starts with, if all combinations agree on one
+    pub fn leading_nibble(&self, i: usize) -> Option<u8> {
+        let mut nibbles = self.combinations.iter().map(|(_, pattern)| pattern.leading_nibble(i));
+        let first = nibbles.next()??;
+        nibbles.all(|nibble| nibble == Some(first)).then_some(first)
+    }
+    
+    // The address bits every match has, whatever its case, for a GPU to filter candidates by
+    // before the full check
+    pub fn common_bits(&self) -> BitMask {
+        let mut matchers = self.combinations.iter().map(|(_, pattern)| pattern.prefix.unwrap_or_default().and(pattern.suffix.unwrap_or_default()));
+        let Some(first) = matchers.next() else {
+            return BitMask::new([0; 20], [0; 20]);
+        };
+        let mut mask = first.mask;
+        for other in matchers {
+            for ((mask, first), (other_mask, other)) in mask.iter_mut().zip(&first.bytes).zip(other.mask.iter().zip(&other.bytes)) {
+                *mask &= other_mask & !(first ^ other);
+            }
+        }
+        BitMask::new(mask, first.bytes)
+    }
+}
+
+// A hex pattern as one nibble per character, compared against an address unpacked once by
+// bytes_to_nibbles. Lives on the stack; `mask` is all ones where the character is fixed and
+// zero where any character will do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NibblePattern {
+    nibbles: [u8; 40],
+    mask: [u8; 40],
+    len: usize,
+}
+
+impl NibblePattern {
+    // Plain hex only: the case and '~' escapes of case-sensitive patterns are not represented
+    pub fn from_hex(s: &str) -> Result<NibblePattern, VanityError> {
+        if let Some(c) = s.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(VanityError::BadPattern { message: format!("'{}' in '{}' is not a hexadecimal character", c, s) });
+        }
+        validate_lengths(Some(s), None).map_err(|message| VanityError::BadPattern { message })?;
+        let mut pattern = NibblePattern { nibbles: [0; 40], mask: [0; 40], len: s.len() };
+        for (i, c) in s.bytes().enumerate() {
+            pattern.nibbles[i] = (c as char).to_digit(16).expect("checked to be hex") as u8;
+            pattern.mask[i] = 0xff;
+        }
+        Ok(pattern)
+    }
+    
+    // The nibble-wise equivalent of memcmp on the start of the address. Exact searches use
+    // CompiledPattern instead, which benches/pattern.rs compares it with.
+    #[allow(dead_code)]
+    pub fn matches_prefix(&self, addr_nibbles: &[u8; 40]) -> bool {
+        self.prefix_mismatches(addr_nibbles) == 0
+    }
+    
+    pub fn prefix_mismatches(&self, addr_nibbles: &[u8; 40]) -> usize {
+        self.mismatches(&addr_nibbles[..self.len])
+    }
+    
+    pub fn suffix_mismatches(&self, addr_nibbles: &[u8; 40]) -> usize {
+        self.mismatches(&addr_nibbles[40 - self.len..])
+    }
+    
+    fn mismatches(&self, addr_nibbles: &[u8]) -> usize {
+        addr_nibbles
+            .iter()
+            .zip(&self.mask)
+            .zip(&self.nibbles)
+            .filter(|&((a, mask), nibble)| a & mask != *nibble)
+            .count()
+    }
+}
+
+// How many characters of a pattern an address gets right before the first wrong one, counted
+// from the start of the address or, `at_end`, from its end. Only values are compared, so a
+// near miss may differ from the pattern in case too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialMatcher {
+    // In the order they are compared: the last character of a suffix comes first
+    nibbles: [u8; 40],
+    len: usize,
+    at_end: bool,
+}
+
+impl PartialMatcher {
+    pub fn new(pattern: &str, at_end: bool) -> PartialMatcher {
+        let mut matcher = PartialMatcher { nibbles: [0; 40], len: pattern_len(pattern).min(40), at_end };
+        let mut positions: Vec<u8> = pattern_positions(pattern).map(|(c, _)| c).collect();
+        if at_end {
+            positions.reverse();
+        }
+        for (nibble, c) in matcher.nibbles.iter_mut().zip(positions) {
+            // Never equal to an address nibble, like a character that is not hex never matches
+            *nibble = (c as char).to_digit(16).map_or(0xff, |digit| digit as u8);
+        }
+        matcher
+    }
+    
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    #[inline]
+    pub fn matched(&self, addr_bytes: &[u8; 20]) -> usize {
+        self.nibbles[..self.len]
+            .iter()
+            .enumerate()
+            .take_while(|&(i, &nibble)| nibble_at(addr_bytes, if self.at_end { 39 - i } else { i }) == nibble)
+            .count()
+    }
+}
+
+// Address character i as nibble i
+pub fn bytes_to_nibbles(bytes: &[u8; 20]) -> [u8; 40] {
+    let mut nibbles = [0u8; 40];
+    for (i, byte) in bytes.iter().enumerate() {
+        nibbles[2 * i] = byte >> 4;
+        nibbles[2 * i + 1] = byte & 0x0f;
+    }
+    nibbles
+}
+
+// Hash output is uniform, so a random address has a given first nibble with probability
+// 1/16: these one-instruction checks reject 15 of 16 candidates, and the second nibble 15 of
+// the 16 survivors, before any pattern is compared
+#[inline(always)]
+pub fn first_nibble_ok(addr_bytes: &[u8; 20], expected: u8) -> bool {
+    (addr_bytes[0] >> 4) == expected
+}
+
+#[inline(always)]
+pub fn second_nibble_ok(addr_bytes: &[u8; 20], expected: u8) -> bool {
+    (addr_bytes[0] & 0x0f) == expected
+}
+
+// Alternative patterns searched at once; an address matches when any entry does
+#[derive(Clone, Debug)]
+pub struct PatternSet {
+    pub entries: Vec<PatternEntry>,
+}
+
+impl PatternSet {
+    pub fn new(entries: Vec<PatternEntry>) -> PatternSet {
+        PatternSet { entries }
+    }
+    
+    // One pattern per line: `prefix:<hex>`, `suffix:<hex>` or `both:<prefix>:<suffix>`.
+    // Blank lines and lines starting with `#` are skipped.
+    pub fn from_file(path: &Path) -> Result<PatternSet, VanityError> {
+        let contents = std::fs::read_to_string(path).map_err(|source| VanityError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        
+        let mut entries = Vec::new();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            let invalid = |message: String| VanityError::InvalidPattern { line: i + 1, message };
+            let entry = match line.split(':').collect::<Vec<_>>().as_slice() {
+                ["prefix", prefix] => PatternEntry::new(Some(prefix.to_string()), None),
+                ["suffix", suffix] => PatternEntry::new(None, Some(suffix.to_string())),
+                ["both", prefix, suffix] => PatternEntry::new(Some(prefix.to_string()), Some(suffix.to_string())),
+                _ => {
+                    return Err(invalid(format!(
+                        "expected 'prefix:<hex>', 'suffix:<hex>' or 'both:<prefix>:<suffix>', found '{}'",
+                        line
+                    )))
+                }
+            };
+            
+            for pattern in entry.prefix.iter().chain(&entry.suffix) {
+                if pattern.is_empty() {
+                    return Err(invalid(format!("empty pattern in '{}'", line)));
+                }
+                validate_pattern(pattern).map_err(|e| invalid(e.trim_start_matches("❌ ").to_string()))?;
+            }
+            validate_lengths(entry.prefix.as_deref(), entry.suffix.as_deref()).map_err(invalid)?;
+            entries.push(entry);
+        }
+        
+        if entries.is_empty() {
+            return Err(VanityError::EmptyPatternSet { path: path.to_path_buf() });
+        }
+        Ok(PatternSet { entries })
+    }
+    
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    // Index of the first entry the address matches, allowing up to `max_distance`
+    // mismatched characters (see `matches_fuzzy`)
+    pub fn find_match(
+        &self,
+        address: &str,
+        max_distance: usize,
+        exclusions: &[Exclusion],
+        case_sensitive: bool,
+    ) -> Option<usize> {
+        if max_distance == 0 {
+            return self.entries.iter().position(|entry| entry.matches(address, exclusions, case_sensitive));
+        }
+        self.entries
+            .iter()
+            .position(|entry| {
+                matches_fuzzy(address, entry.prefix.as_deref(), entry.suffix.as_deref(), max_distance, case_sensitive)
+                    .is_some()
+            })
+            .filter(|_| !is_excluded(address, exclusions, case_sensitive))
+    }
+    
+    // The set for exact matching on raw address bytes; its patterns are already checked
+    pub fn compile(&self, exclusions: &[Exclusion], case_sensitive: bool) -> CompiledSet {
+        let combinations = self
+            .entries
+            .iter()
+            .enumerate()
+            .flat_map(|(index, entry)| entry.compiled(case_sensitive).into_iter().map(move |pattern| (index, pattern)))
+            .collect();
+        let exclusions = exclusions
+            .iter()
+            .map(|exclusion| match exclusion {
+                Exclusion::Prefix(prefix) => CompiledPattern { prefix: Some(ByteMatcher::new(prefix, false)), suffix: None, case_sensitive },
+                Exclusion::Suffix(suffix) => CompiledPattern { prefix: None, suffix: Some(ByteMatcher::new(suffix, true)), case_sensitive },
+            })
+            .collect();
+        CompiledSet { combinations, exclusions }
+    }
+    
+    // The entries as nibble patterns for --fuzzy, whose distances ignore alternatives; None
+    // when a pattern has '~' escapes, which nibbles cannot hold
+    pub fn nibble_patterns(&self) -> Option<Vec<(Option<NibblePattern>, Option<NibblePattern>)>> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let prefix = entry.prefix.as_deref().map(NibblePattern::from_hex).transpose().ok()?;
+                let suffix = entry.suffix.as_deref().map(NibblePattern::from_hex).transpose().ok()?;
+                Some((prefix, suffix))
+            })
+            .collect()
+    }
+    
+    // The match probabilities of the entries add up (exactly so for mutually exclusive
+    // patterns such as equal-length prefixes, and as a close estimate otherwise)
+    pub fn difficulty(&self, max_distance: usize, case_sensitive: bool) -> f64 {
+        let probability: f64 = self.entries.iter().map(|entry| 1.0 / entry.difficulty(max_distance, case_sensitive)).sum();
+        1.0 / probability
+    }
+}
+
+// A run of identical leading hex characters, optionally pinned to one character
+#[derive(Clone, Copy, Debug)]
+pub struct Repeating {
+    pub length: usize,
+    pub nibble: Option<u8>,
+}
+
+impl Repeating {
+    // Checked on the raw address bytes so non-matching candidates are never hex encoded
+    pub fn matches(&self, address: &[u8; 20]) -> bool {
+        let (nibble, run) = leading_run(address);
+        run >= self.length && self.nibble.is_none_or(|pinned| pinned == nibble)
+    }
+    
+    // Any of the 16 nibbles may start the run, so only the remaining n - 1 characters are constrained
+    pub fn difficulty(&self) -> f64 {
+        let constrained = if self.nibble.is_some() { self.length } else { self.length - 1 };
+        16f64.powi(constrained as i32)
+    }
+}
+
+// The first hex character of an address and how many times it repeats from the start
+pub fn leading_run(address: &[u8; 20]) -> (u8, usize) {
+    let first = address[0] >> 4;
+    let run = address
+        .iter()
+        .flat_map(|&byte| [byte >> 4, byte & 0x0f])
+        .take_while(|&nibble| nibble == first)
+        .count();
+    (first, run)
+}
+
+// How many times `nibble` repeats from the start of the address
+pub fn count_leading_char(address_bytes: &[u8], nibble: u8) -> usize {
+    address_bytes
+        .iter()
+        .flat_map(|&byte| [byte >> 4, byte & 0x0f])
+        .take_while(|&n| n == nibble)
+        .count()
+}
+
+// The first `length` hex characters mirror the last `length` (length 40 is a full palindrome)
+#[derive(Clone, Copy, Debug)]
+pub struct Palindrome {
+    pub length: usize,
+}
+
+impl Palindrome {
+    pub fn matches(&self, address: &[u8; 20]) -> bool {
+        (0..self.length.min(20)).all(|i| nibble_at(address, i) == nibble_at(address, 39 - i))
+    }
+    
+    // Character i and 39 - i form one constraint, and beyond 20 the pairs repeat
+    pub fn difficulty(&self) -> f64 {
+        16f64.powi(self.length.min(20) as i32)
+    }
+}
+
+fn nibble_at(address: &[u8; 20], i: usize) -> u8 {
+    if i.is_multiple_of(2) { address[i / 2] >> 4 } else { address[i / 2] & 0x0f }
+}
+
+// Whether the first `len` characters equal the last `len` reversed, ignoring case and 0x
+pub fn is_partial_palindrome(addr: &str, len: usize) -> bool {
+    let addr = addr.strip_prefix("0x").unwrap_or(addr).as_bytes();
+    len <= addr.len()
+        && addr[..len]
+            .iter()
+            .zip(addr[addr.len() - len..].iter().rev())
+            .all(|(a, b)| a.eq_ignore_ascii_case(b))
+}
+
+// How many leading characters mirror the trailing ones
+pub fn mirror_length(address: &str) -> usize {
+    match (1..=20).find(|&len| !is_partial_palindrome(address, len)) {
+        Some(len) => len - 1,
+        None => 40,
+    }
+}
+
+// --mask/--value: (address & mask) == (value & mask) on the raw address bytes
+#[derive(Clone, Copy, Debug)]
+pub struct BitMask {
+    pub mask: [u8; 20],
+    // Already masked, so the hot loop needs one AND per byte
+    pub value: [u8; 20],
+}
+
+impl BitMask {
+    pub fn new(mask: [u8; 20], value: [u8; 20]) -> BitMask {
+        let mut masked = value;
+        for (v, m) in masked.iter_mut().zip(&mask) {
+            *v &= m;
+        }
+        BitMask { mask, value: masked }
+    }
+    
+    pub fn matches(&self, address: &[u8; 20]) -> bool {
+        address.iter().zip(&self.mask).zip(&self.value).all(|((a, m), v)| a & m == *v)
+    }
+    
+    // Both masks' bits; where they overlap, `self` decides the value
+    pub fn and(&self, other: &BitMask) -> BitMask {
+        let mut combined = *self;
+        for i in 0..20 {
+            combined.value[i] |= other.value[i] & !self.mask[i];
+            combined.mask[i] |= other.mask[i];
+        }
+        combined
+    }
+    
+    pub fn bits(&self) -> u32 {
+        self.mask.iter().map(|m| m.count_ones()).sum()
+    }
+    
+    pub fn difficulty(&self) -> f64 {
+        2f64.powi(self.bits() as i32)
+    }
+}
+
+// Up to 20 bytes of hex, zero-padded to a full address after the input (`at_end` false) or before it
+pub fn parse_padded_bytes(value: &str, at_end: bool) -> Result<[u8; 20], String> {
+    let bytes = hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| format!("'{}' is not whole bytes of hex: {}", value, e))?;
+    if bytes.len() > 20 {
+        return Err(format!("'{}' is {} bytes but an address only has 20", value, bytes.len()));
+    }
+    let mut padded = [0u8; 20];
+    let start = if at_end { 20 - bytes.len() } else { 0 };
+    padded[start..start + bytes.len()].copy_from_slice(&bytes);
+    Ok(padded)
+}
+
+// Hex characters that may not appear anywhere in the address, as a bit per nibble value
+#[derive(Clone, Copy, Debug)]
+pub struct ExcludedChars {
+    mask: u16,
+}
+
+impl ExcludedChars {
+    // clap value parser for --exclude: hex characters in any case, e.g. "0" or "abcdef"
+    pub fn parse(chars: &str) -> Result<ExcludedChars, String> {
+        let mut mask = 0u16;
+        for c in chars.chars() {
+            let nibble = c.to_digit(16).ok_or_else(|| format!("'{}' is not a hexadecimal character", c))?;
+            mask |= 1 << nibble;
+        }
+        match mask {
+            0 => Err("expected at least one hexadecimal character".to_string()),
+            u16::MAX => Err("excluding all 16 hexadecimal characters leaves no possible address".to_string()),
+            _ => Ok(ExcludedChars { mask }),
+        }
+    }
+    
+    pub fn contains(&self, nibble: u8) -> bool {
+        self.mask >> nibble & 1 == 1
+    }
+    
+    // Checked on the raw address bytes so non-matching candidates are never hex encoded
+    pub fn matches(&self, address: &[u8; 20]) -> bool {
+        address.iter().all(|&byte| !self.contains(byte >> 4) && !self.contains(byte & 0x0f))
+    }
+    
+    // The first excluded character of a pattern, which then can never match
+    pub fn find_in(&self, pattern: &str) -> Option<char> {
+        pattern_positions(pattern)
+            .map(|(c, _)| c as char)
+            .find(|c| c.to_digit(16).is_some_and(|nibble| self.contains(nibble as u8)))
+    }
+    
+    // Each of the `free` characters not fixed by a pattern avoids the k excluded ones
+    // with probability (16 - k) / 16
+    pub fn difficulty(&self, free: usize) -> f64 {
+        let allowed = 16 - self.mask.count_ones();
+        (16.0 / allowed as f64).powi(free as i32)
+    }
+}
+
+impl fmt::Display for ExcludedChars {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chars: Vec<String> = (0..16u8).filter(|&nibble| self.contains(nibble)).map(|nibble| format!("'{:x}'", nibble)).collect();
+        write!(f, "{}", chars.join(", "))
+    }
+}
+
+pub fn validate_pattern(pattern: &str) -> Result<(), String> {
+    let mut invalid_chars = Vec::new();
+    let mut escape_errors = Vec::new();
+    
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '~' {
+            match chars.next() {
+                Some(next) if next.is_ascii_hexdigit() && next.is_ascii_alphabetic() => {}
+                Some(next) if next.is_ascii_digit() => {
+                    escape_errors.push(format!("  • '~{}': digits have no case, drop the '~'\n", next));
+                }
+                Some(next) => invalid_chars.push(next),
+                None => escape_errors.push("  • '~' at the end of the pattern must be followed by a letter a-f\n".to_string()),
+            }
+        } else if !c.is_ascii_hexdigit() {
+            invalid_chars.push(c);
+        }
+    }
+    
+    if !invalid_chars.is_empty() || !escape_errors.is_empty() {
+        let mut error_msg = String::from("❌ Invalid characters found in pattern:\n");
+        
+        for &invalid_char in &invalid_chars {
+            error_msg.push_str(&format!("  • '{}' is not a valid hexadecimal character\n", invalid_char));
+        }
+        for escape_error in &escape_errors {
+            error_msg.push_str(escape_error);
+        }
+        
+        error_msg.push_str("\n💡 EVM addresses only use hexadecimal characters: 0-9, a-f, A-F\n");
+        error_msg.push_str("   With --case-sensitive, prefix a letter with '~' to accept either case: \"~dEaD\"\n");
+        error_msg.push_str("   Valid examples: \"dead\", \"beef\", \"abc123\", \"DEF456\"");
+        
+        return Err(error_msg);
+    }
+    
+    Ok(())
+}
+
+// A prefix and suffix an address can hold: each at most 40 characters and together at most
+// 40, since longer pairs overlap. Overlapping pairs are rejected either way, saying whether
+// they contradict each other or spell out a single address.
+pub fn validate_lengths(prefix: Option<&str>, suffix: Option<&str>) -> Result<(), String> {
+    for (kind, pattern) in [("prefix", prefix), ("suffix", suffix)] {
+        if let Some(pattern) = pattern.filter(|pattern| pattern_len(pattern) > 40) {
+            return Err(format!("{} '{}' is {} characters but an address only has 40", kind, pattern, pattern_len(pattern)));
+        }
+    }
+    let (Some(prefix), Some(suffix)) = (prefix, suffix) else {
+        return Ok(());
+    };
+    let (prefix_len, suffix_len) = (pattern_len(prefix), pattern_len(suffix));
+    if prefix_len + suffix_len <= 40 {
+        return Ok(());
+    }
+    
+    let overlap = prefix_len + suffix_len - 40;
+    let overlap = format!("they overlap by {} character{}", overlap, if overlap == 1 { "" } else { "s" });
+    let needed = format!("prefix '{}' ({}) and suffix '{}' ({}) need {} characters but an address only has 40",
+                         prefix, prefix_len, suffix, suffix_len, prefix_len + suffix_len);
+    // Suffix character j sits at address position 40 - suffix_len + j
+    let prefix_chars: Vec<u8> = pattern_positions(prefix).map(|(c, _)| c).collect();
+    let suffix_chars: Vec<u8> = pattern_positions(suffix).map(|(c, _)| c).collect();
+    let contradiction = (40 - suffix_len..prefix_len).find(|&position| prefix_chars[position] != suffix_chars[position + suffix_len - 40]);
+    match contradiction {
+        Some(position) => Err(format!(
+            "{}; {} and disagree at character {} ('{}' vs '{}'), so no address can match",
+            needed,
+            overlap,
+            position + 1,
+            prefix_chars[position] as char,
+            suffix_chars[position + suffix_len - 40] as char
+        )),
+        None => {
+            let address: String = prefix_chars.iter().chain(&suffix_chars[prefix_len + suffix_len - 40..]).map(|&c| c as char).collect();
+            Err(format!("{}; {} and together spell the single address 0x{}, use --prefix with it instead", needed, overlap, address))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+    
+    // EIP-55 checksums 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed
+    const ADDRESS: &str = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+    
+    // Case-sensitive matching reads the --chain-id static, so tests that match case or set the
+    // chain hold this lock, and the chain is back to 1 when they let go of it
+    static CHECKSUM_CHAIN: Mutex<()> = Mutex::new(());
+    
+    struct ChecksumChain(#[allow(dead_code)] MutexGuard<'static, ()>);
+    
+    impl ChecksumChain {
+        fn set(chain_id: u64) -> ChecksumChain {
+            let guard = CHECKSUM_CHAIN.lock().unwrap_or_else(PoisonError::into_inner);
+            set_checksum_chain_id(chain_id);
+            ChecksumChain(guard)
+        }
+    }
+    
+    impl Drop for ChecksumChain {
+        fn drop(&mut self) {
+            set_checksum_chain_id(1);
+        }
+    }
+    
+    fn address_bytes(address: &str) -> [u8; 20] {
+        hex::decode(&address[2..]).unwrap().try_into().unwrap()
+    }
+    
+    #[test]
+    fn exclusions_reject_matching_addresses() {
+        assert!(matches_pattern(ADDRESS, Some("5a"), None, &[], false));
+        assert!(matches_pattern(ADDRESS, Some("5a"), None, &[Exclusion::Prefix("5aaf".to_string())], false));
+        assert!(!matches_pattern(ADDRESS, Some("5a"), None, &[Exclusion::Prefix("5aae".to_string())], false));
+        assert!(!matches_pattern(ADDRESS, Some("5a"), None, &[Exclusion::Suffix("AED".to_string())], false));
+        assert!(!is_excluded(ADDRESS, &[Exclusion::Prefix("5aaf".to_string()), Exclusion::Suffix("eaee".to_string())], false));
+        assert!(is_excluded(ADDRESS, &[Exclusion::Prefix("5aaf".to_string()), Exclusion::Suffix("eaed".to_string())], false));
+    }
+    
+    #[test]
+    fn case_sensitive_exclusions_follow_the_checksum() {
+        let _chain = ChecksumChain::set(1);
+        let exclude = |pattern: &str| [Exclusion::Prefix(pattern.to_string())];
+        assert!(is_excluded(ADDRESS, &exclude("5aA"), true));
+        assert!(!is_excluded(ADDRESS, &exclude("5AA"), true));
+        assert!(is_excluded(ADDRESS, &exclude("5AA"), false));
+        assert!(is_excluded(ADDRESS, &exclude("5~A~a"), true));
+    }
+    
+    #[test]
+    fn compiled_set_applies_exclusions() {
+        let set = PatternSet::new(vec![PatternEntry::new(Some("5a".to_string()), None)]);
+        let bytes = address_bytes(ADDRESS);
+        for (exclusions, expected) in [
+            (vec![], Some(0)),
+            (vec![Exclusion::Prefix("5aaf".to_string())], Some(0)),
+            (vec![Exclusion::Prefix("5aae".to_string())], None),
+            (vec![Exclusion::Suffix("beaed".to_string())], None),
+        ] {
+            assert_eq!(set.find_match(ADDRESS, 0, &exclusions, false), expected);
+            assert_eq!(set.compile(&exclusions, false).find_match(&bytes), expected);
+        }
+    }
+    
+    #[test]
+    fn common_bits_are_those_all_combinations_fix() {
+        let set = PatternSet::new(vec![
+            PatternEntry::new(Some("dead".to_string()), Some("f".to_string())),
+            PatternEntry::new(Some("de~Af".to_string()), Some("0f".to_string())),
+        ]);
+        let common = set.compile(&[], true).common_bits();
+        // 0xad and 0xaf differ in one bit
+        assert_eq!((common.mask[0], common.mask[1], common.mask[19]), (0xff, 0xfd, 0x0f));
+        assert_eq!((common.value[0], common.value[1], common.value[19]), (0xde, 0xad, 0x0f));
+        assert_eq!(common.bits(), 19);
+    }
+    
+    #[test]
+    fn fuzzy_distance_counts_mismatched_characters() {
+        let _chain = ChecksumChain::set(1);
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5aae"), None, 0, false), Some(0));
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5abe"), None, 2, false), Some(1));
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5abe"), Some("eaee"), 2, false), Some(2));
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5abe"), Some("eaee"), 1, false), None);
+        assert_eq!(matches_fuzzy(ADDRESS, Some("ffff"), Some("ffff"), usize::MAX, false), Some(8));
+        // Case-sensitive: 5aAeb...BeAed, so a wrong case is one more mismatch
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5aae"), None, 2, true), Some(1));
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5aAe"), Some("BeAed"), 0, true), Some(0));
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5aAe"), Some("beaed"), 3, true), Some(2));
+    }
+    
+    #[test]
+    fn fuzzy_distance_uses_character_lengths() {
+        let _chain = ChecksumChain::set(1);
+        // '~A' covers one character: the suffix is 'eaed' at the last four positions
+        assert_eq!(matches_fuzzy(ADDRESS, None, Some("e~Aed"), 0, true), Some(0));
+        assert_eq!(matches_fuzzy(ADDRESS, None, Some("e~Aee"), 1, true), Some(1));
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5~a~ae"), None, 0, true), Some(0));
+        assert_eq!(matches_fuzzy(ADDRESS, Some("5~a~af"), None, 1, false), Some(1));
+    }
+    
+    #[test]
+    fn fuzzy_difficulty_sums_the_allowed_mismatches() {
+        assert_eq!(fuzzy_difficulty(Some("dead"), None, 0, false), pattern_difficulty(Some("dead"), None, false));
+        // P(at most one of two characters wrong) = 1 - (15/16)^2
+        let expected = 1.0 / (1.0 - (15.0f64 / 16.0).powi(2));
+        assert!((fuzzy_difficulty(Some("d"), Some("d"), 1, false) - expected).abs() < 1e-9);
+        assert!(fuzzy_difficulty(Some("dead"), None, 4, false) - 1.0 < 1e-9);
+    }
+    
+    #[test]
+    fn fuzzy_pattern_set_match_ignores_alternatives_and_applies_exclusions() {
+        let set = PatternSet::new(vec![
+            PatternEntry::new(Some("ffff".to_string()), None),
+            PatternEntry::new(Some("5abe".to_string()), None),
+        ]);
+        assert_eq!(set.find_match(ADDRESS, 1, &[], false), Some(1));
+        assert_eq!(set.find_match(ADDRESS, 1, &[Exclusion::Prefix("5a".to_string())], false), None);
+        assert_eq!(set.find_match(ADDRESS, 0, &[], false), None);
+    }
+    
+    #[test]
+    fn palindrome_compares_mirrored_nibbles() {
+        let address = address_bytes("0xabc1200000000000000000000000000000031cba");
+        assert!(Palindrome { length: 4 }.matches(&address));
+        assert!(!Palindrome { length: 5 }.matches(&address));
+        let full = address_bytes("0x0123456789abcdef0123fedcba9876543210fedc");
+        assert!(!Palindrome { length: 40 }.matches(&full));
+        let full = address_bytes("0x0123456789abcdef01233210fedcba9876543210");
+        assert!(Palindrome { length: 40 }.matches(&full));
+        assert!(Palindrome { length: 20 }.matches(&full));
+    }
+    
+    #[test]
+    fn palindrome_difficulty_stops_at_twenty_pairs() {
+        assert_eq!(Palindrome { length: 3 }.difficulty(), 4096.0);
+        assert_eq!(Palindrome { length: 20 }.difficulty(), Palindrome { length: 40 }.difficulty());
+    }
+    
+    #[test]
+    fn partial_palindromes_mirror_the_ends() {
+        assert!(!is_partial_palindrome("deadbeefdeadbeef", 4));
+        assert!(is_partial_palindrome("deadbeefdaed", 4));
+        assert!(is_partial_palindrome("0xABC0000cba", 3));
+        assert!(!is_partial_palindrome("abc", 4));
+        assert_eq!(mirror_length("0xabc1200000000000000000000000000000031cba"), 4);
+        assert_eq!(mirror_length("0x0123456789abcdef01233210fedcba9876543210"), 40);
+        assert_eq!(mirror_length("0x1000000000000000000000000000000000000002"), 0);
+    }
+    
+    #[test]
+    fn eip_55_is_the_checksum_without_a_chain_id() {
+        for checksummed in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(to_checksum_address(&checksummed.to_lowercase()), checksummed);
+            assert_eq!(to_chain_checksum_address(&checksummed.to_lowercase(), 1), checksummed);
+        }
+    }
+    
+    #[test]
+    fn eip_1191_checksums_of_rsk_mainnet_and_testnet() {
+        for (chain_id, addresses) in [
+            (30, [
+                "0x27b1FdB04752BBc536007A920D24ACB045561c26",
+                "0x3599689E6292B81B2D85451025146515070129Bb",
+                "0x42712D45473476B98452f434E72461577d686318",
+                "0x52908400098527886E0F7030069857D2E4169ee7",
+                "0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD",
+                "0x6549F4939460DE12611948B3F82B88C3C8975323",
+                "0x66F9664f97f2B50F62d13EA064982F936de76657",
+                "0x8617E340b3D01Fa5f11f306f4090fd50E238070D",
+            ].as_slice()),
+            (31, &[
+                "0x27B1FdB04752BbC536007a920D24acB045561C26",
+                "0x3599689e6292b81b2D85451025146515070129Bb",
+                "0x42712D45473476B98452F434E72461577D686318",
+                "0x52908400098527886E0F7030069857D2e4169EE7",
+                "0x5aAeb6053F3e94c9b9A09F33669435E7EF1BEaEd",
+                "0x6549f4939460dE12611948b3f82b88C3c8975323",
+                "0xDE709F2102306220921060314715629080e2Fb77",
+            ]),
+        ] {
+            for checksummed in addresses {
+                assert_eq!(to_chain_checksum_address(&checksummed.to_lowercase(), chain_id), *checksummed);
+            }
+        }
+    }
+    
+    #[test]
+    fn case_sensitive_patterns_follow_the_chain_checksum() {
+        let bytes = address_bytes(ADDRESS);
+        let matches = |prefix: &str| {
+            let compiled = CompiledPattern::compile(Some(prefix), None, true).unwrap().matches(&bytes);
+            assert_eq!(matches_pattern(ADDRESS, Some(prefix), None, &[], true), compiled, "{}", prefix);
+            compiled
+        };
+        {
+            // 0x5aaEB6053f3e94c9b9a09f33669435E7ef1bEAeD on chain 30
+            let _chain = ChecksumChain::set(30);
+            assert!(matches("5aaEB"));
+            assert!(!matches("5aAeb"));
+        }
+        let _chain = ChecksumChain::set(1);
+        assert!(matches("5aAeb"));
+        assert!(!matches("5aaEB"));
+    }
+    
+    #[test]
+    fn pattern_lengths_stop_at_the_address() {
+        for kind in ["prefix", "suffix"] {
+            let lengths = |pattern: &str| if kind == "prefix" { validate_lengths(Some(pattern), None) } else { validate_lengths(None, Some(pattern)) };
+            assert_eq!(lengths(&"a".repeat(39)), Ok(()));
+            assert_eq!(lengths(&"a".repeat(40)), Ok(()));
+            assert_eq!(lengths(&"a".repeat(41)), Err(format!("{} '{}' is 41 characters but an address only has 40", kind, "a".repeat(41))));
+            // Escapes take no room in the address
+            assert_eq!(lengths(&"~a".repeat(40)), Ok(()));
+        }
+        
+        assert_eq!(validate_lengths(Some(&"a".repeat(20)), Some(&"b".repeat(19))), Ok(()));
+        assert_eq!(validate_lengths(Some(&"a".repeat(20)), Some(&"b".repeat(20))), Ok(()));
+        assert_eq!(
+            validate_lengths(Some(&"a".repeat(20)), Some(&"a".repeat(21))).unwrap_err(),
+            format!(
+                "prefix '{}' (20) and suffix '{}' (21) need 41 characters but an address only has 40; they overlap by 1 character \
+                 and together spell the single address 0x{}, use --prefix with it instead",
+                "a".repeat(20), "a".repeat(21), "a".repeat(40)
+            )
+        );
+        let suffix = format!("b{}", "a".repeat(20));
+        assert!(validate_lengths(Some(&"a".repeat(20)), Some(&suffix))
+            .unwrap_err()
+            .ends_with("they overlap by 1 character and disagree at character 20 ('a' vs 'b'), so no address can match"));
+        let error_msg = validate_lengths(Some(&"1".repeat(25)), Some(&"2".repeat(25))).unwrap_err();
+        assert!(error_msg.contains("need 50 characters") && error_msg.contains("overlap by 10 characters") && error_msg.contains("character 16 ('1' vs '2')"), "{}", error_msg);
+    }
+    
+    #[test]
+    fn compiled_patterns_cover_odd_lengths_and_the_whole_address() {
+        let hex = &ADDRESS[2..];
+        let bytes = address_bytes(ADDRESS);
+        // The pattern with its character `i` changed to one the address does not have there
+        let changed = |pattern: &str, i: usize| {
+            let mut pattern = pattern.as_bytes().to_vec();
+            pattern[i] = if pattern[i] == b'0' { b'1' } else { b'0' };
+            String::from_utf8(pattern).unwrap()
+        };
+        for length in 1..=40 {
+            let (prefix, suffix) = (&hex[..length], &hex[40 - length..]);
+            let compiled = |prefix: Option<&str>, suffix: Option<&str>| CompiledPattern::compile(prefix, suffix, false).unwrap().matches(&bytes);
+            assert!(compiled(Some(prefix), None), "{}", prefix);
+            assert!(compiled(None, Some(suffix)), "{}", suffix);
+            // The half byte at either end of an odd-length pattern is compared too
+            assert!(!compiled(Some(&changed(prefix, length - 1)), None), "{}", prefix);
+            assert!(!compiled(None, Some(&changed(suffix, 0))), "{}", suffix);
+            assert!(!compiled(None, Some(&changed(suffix, length - 1))), "{}", suffix);
+        }
+        
+        // A suffix starting halfway through a byte, and patterns at every offset
+        let aed = ByteMatcher::new("aed", true);
+        assert!(aed.matches(&bytes));
+        let mut other = bytes;
+        other[18] ^= 0xf0;
+        assert!(aed.matches(&other));
+        other[18] ^= 0x0f;
+        assert!(!aed.matches(&other));
+        for offset in 0..40 {
+            for length in 1..=(40 - offset).min(6) {
+                let pattern = &hex[offset..offset + length];
+                assert!(ByteMatcher::at(pattern, offset).matches(&bytes), "{} at {}", pattern, offset);
+                assert!(!ByteMatcher::at(&changed(pattern, 0), offset).matches(&bytes), "{} at {}", pattern, offset);
+            }
+        }
+        
+        // The whole checksummed address, and one letter of it in the wrong case
+        let _chain = ChecksumChain::set(1);
+        let checksummed = &to_checksum_address(ADDRESS)[2..];
+        assert!(CompiledPattern::compile(Some(checksummed), None, true).unwrap().matches(&bytes));
+        assert!(CompiledPattern::compile(None, Some(checksummed), true).unwrap().matches(&bytes));
+        let wrong_case = checksummed.replacen('A', "a", 1);
+        assert!(!CompiledPattern::compile(Some(&wrong_case), None, true).unwrap().matches(&bytes));
+    }
+    
+    #[test]
+    fn compiled_patterns_agree_with_the_string_matcher() {
+        use rand::{Rng, RngCore};
+        let _chain = ChecksumChain::set(1);
+        let mut rng = rand::thread_rng();
+        // A pattern of 1 to 8 characters at the start or end of the address: half of them copied
+        // from it, in the checksum's case or not, with a character sometimes changed
+        let pattern = |rng: &mut rand::rngs::ThreadRng, checksummed: &str, at_end: bool| -> String {
+            let length = rng.gen_range(1..=8);
+            let start = if at_end { 40 - length } else { 0 };
+            let mut chars: Vec<char> = if rng.gen_bool(0.5) {
+                checksummed[2..][start..start + length].chars().collect()
+            } else {
+                (0..length).map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap()).collect()
+            };
+            if rng.gen_bool(0.2) {
+                let i = rng.gen_range(0..length);
+                chars[i] = char::from_digit(rng.gen_range(0..16), 16).unwrap();
+            }
+            chars
+                .into_iter()
+                .map(|c| match rng.gen_range(0..4) {
+                    0 if c.is_ascii_alphabetic() => format!("~{}", c),
+                    1 => c.to_ascii_uppercase().to_string(),
+                    2 => c.to_ascii_lowercase().to_string(),
+                    _ => c.to_string(),
+                })
+                .collect()
+        };
+        
+        let mut matched = 0;
+        for _ in 0..20_000 {
+            let mut bytes = [0u8; 20];
+            rng.fill_bytes(&mut bytes);
+            let address = format!("0x{}", hex::encode(bytes));
+            let checksummed = to_checksum_address(&address);
+            let prefix = rng.gen_bool(0.7).then(|| pattern(&mut rng, &checksummed, false));
+            let suffix = (prefix.is_none() || rng.gen_bool(0.5)).then(|| pattern(&mut rng, &checksummed, true));
+            for case_sensitive in [false, true] {
+                let expected = matches_pattern(&address, prefix.as_deref(), suffix.as_deref(), &[], case_sensitive);
+                let compiled = CompiledPattern::compile(prefix.as_deref(), suffix.as_deref(), case_sensitive).unwrap();
+                assert_eq!(compiled.matches(&bytes), expected, "{} {:?} {:?} case sensitive: {}", address, prefix, suffix, case_sensitive);
+                matched += expected as usize;
+            }
+        }
+        // Enough of them match for the comparison to cover both outcomes
+        assert!(matched > 1000, "{}", matched);
+    }
+}
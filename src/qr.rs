@@ -0,0 +1,44 @@
+// QR codes for the found address, rendered in the terminal or saved as PNG
+
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use std::path::Path;
+
+// Quiet zone on each side of the code, in modules
+const QUIET_ZONE: usize = 4;
+
+/// EIP-681 style URI that wallet and hardware wallet camera scanners parse directly
+pub fn ethereum_uri(checksummed_address: &str) -> String {
+    format!("ethereum:{}", checksummed_address)
+}
+
+// Largest module size (1-3 columns per module) whose rendering fits the terminal width
+fn module_size(modules: usize) -> u32 {
+    let columns = crossterm::terminal::size().map(|(columns, _)| columns as usize).unwrap_or(80);
+    let width = modules + 2 * QUIET_ZONE;
+
+    (1..=3u32).rev().find(|&size| width * size as usize <= columns).unwrap_or(1)
+}
+
+pub fn render_terminal(content: &str) -> Result<String, String> {
+    let code = QrCode::new(content.as_bytes()).map_err(|e| format!("Failed to encode QR code: {}", e))?;
+    let size = module_size(code.width());
+
+    // Dark modules on a light background with a quiet zone scan reliably on dark terminal themes too
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .module_dimensions(size, size)
+        .build())
+}
+
+pub fn save_png(content: &str, path: &Path) -> Result<(), String> {
+    let code = QrCode::new(content.as_bytes()).map_err(|e| format!("Failed to encode QR code: {}", e))?;
+    let image = code.render::<image::Luma<u8>>().min_dimensions(256, 256).build();
+
+    image
+        .save(path)
+        .map_err(|e| format!("Failed to write QR code to {}: {}", path.display(), e))
+}
@@ -0,0 +1,161 @@
+// --verify-rpc: ask an Ethereum node whether a found address has ever sent a transaction or
+// holds a balance. A fresh key colliding with a used address is astronomically unlikely, so
+// a hit points at a broken RNG or a reused key. The check is best-effort: the wallet is
+// already printed, and a node that cannot be reached only costs a warning.
+
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// `eth_getTransactionCount` and `eth_getBalance` of `address` at the latest block. The error
+// never includes the URL, which often holds an API key.
+pub fn verify_address_unused(address: &str, rpc_url: &str) -> Result<(u64, u128), String> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    let nonce = call(&agent, rpc_url, "eth_getTransactionCount", address)?;
+    let balance = call(&agent, rpc_url, "eth_getBalance", address)?;
+    let nonce = u64::try_from(nonce).map_err(|_| format!("nonce 0x{:x} is out of range", nonce))?;
+    Ok((nonce, balance))
+}
+
+// One JSON-RPC call taking `[address, "latest"]` and returning a hex quantity
+fn call(agent: &ureq::Agent, rpc_url: &str, method: &str, address: &str) -> Result<u128, String> {
+    let request = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": [address, "latest"] });
+    let response: serde_json::Value = match agent.post(rpc_url).send_json(request) {
+        Ok(response) => response.into_json().map_err(|e| format!("{}: invalid JSON response: {}", method, e))?,
+        Err(ureq::Error::Status(code, _)) => return Err(format!("{}: HTTP status {}", method, code)),
+        Err(ureq::Error::Transport(transport)) => return Err(format!("{}: {}", method, transport.kind())),
+    };
+    if let Some(error) = response.get("error") {
+        let message = error["message"].as_str().unwrap_or("no message");
+        return Err(format!("{}: node error {}: {}", method, error["code"], message));
+    }
+    let quantity = response["result"].as_str().ok_or_else(|| format!("{}: response has no result", method))?;
+    let digits = quantity.strip_prefix("0x").ok_or_else(|| format!("{}: '{}' is not a hex quantity", method, quantity))?;
+    u128::from_str_radix(digits, 16).map_err(|_| format!("{}: '{}' is not a hex quantity", method, quantity))
+}
+
+// Prints what the node knows about a found address
+pub fn report(address: &str, rpc_url: &str) {
+    match verify_address_unused(address, rpc_url) {
+        Ok((0, 0)) => {
+            info!("🔎 RPC check: {} has no transactions and no balance", address);
+            tracing::info!(address, "rpc check passed");
+        }
+        Ok((nonce, balance)) => {
+            warning!("⚠️  WARNING: address {} may already be in use (nonce={}, balance={})", address, nonce, balance);
+            tracing::warn!(address, nonce, balance = %balance, "rpc check found a used address");
+        }
+        Err(error_msg) => {
+            warning!("⚠️  RPC check of {} failed: {}", address, error_msg);
+            tracing::error!(address, error = %error_msg, "rpc check failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // A node that answers each request in turn with a status and JSON body, and hands
+    // back the requests it got once they are all answered
+    fn mock_node(responses: Vec<(u16, String)>) -> (String, thread::JoinHandle<Vec<serde_json::Value>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v3/secret-api-key", listener.local_addr().unwrap());
+        let node = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (connection, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(connection);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut request = vec![0; content_length];
+                reader.read_exact(&mut request).unwrap();
+                requests.push(serde_json::from_slice(&request).unwrap());
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+            requests
+        });
+        (url, node)
+    }
+
+    fn result(quantity: &str) -> (u16, String) {
+        (200, serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": quantity }).to_string())
+    }
+
+    fn call_with(response: (u16, String)) -> Result<u128, String> {
+        let (url, node) = mock_node(vec![response]);
+        let result = call(&ureq::agent(), &url, "eth_getBalance", "0xabc");
+        node.join().unwrap();
+        result
+    }
+
+    #[test]
+    fn asks_for_the_nonce_and_balance_at_the_latest_block() {
+        let address = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+        let (url, node) = mock_node(vec![result("0x1b"), result("0xde0b6b3a7640000")]);
+        assert_eq!(verify_address_unused(address, &url), Ok((27, 1_000_000_000_000_000_000)));
+        let requests = node.join().unwrap();
+        assert_eq!(requests[0]["method"], "eth_getTransactionCount");
+        assert_eq!(requests[1]["method"], "eth_getBalance");
+        for request in requests {
+            assert_eq!(request["jsonrpc"], "2.0");
+            assert_eq!(request["params"], serde_json::json!([address, "latest"]));
+        }
+    }
+
+    #[test]
+    fn parses_hex_quantities() {
+        for (quantity, value) in [("0x0", 0), ("0x1", 1), ("0xFF", 255), ("0xffffffffffffffffffffffffffffffff", u128::MAX)] {
+            assert_eq!(call_with(result(quantity)), Ok(value), "{}", quantity);
+        }
+        for quantity in ["", "0x", "1b", "0xzz", "0x100000000000000000000000000000000"] {
+            assert_eq!(call_with(result(quantity)), Err(format!("eth_getBalance: '{}' is not a hex quantity", quantity)));
+        }
+    }
+
+    #[test]
+    fn nonces_must_fit_64_bits() {
+        let (url, node) = mock_node(vec![result("0x10000000000000000"), result("0x0")]);
+        assert_eq!(verify_address_unused("0xabc", &url), Err("nonce 0x10000000000000000 is out of range".to_string()));
+        node.join().unwrap();
+    }
+
+    #[test]
+    fn failed_calls_are_errors() {
+        let node_error = serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32602, "message": "invalid argument" } });
+        assert_eq!(call_with((200, node_error.to_string())), Err("eth_getBalance: node error -32602: invalid argument".to_string()));
+        assert_eq!(call_with((200, r#"{"jsonrpc":"2.0","id":1,"result":27}"#.to_string())), Err("eth_getBalance: response has no result".to_string()));
+        assert_eq!(call_with((503, "{}".to_string())), Err("eth_getBalance: HTTP status 503".to_string()));
+        assert!(call_with((200, "not json".to_string())).unwrap_err().starts_with("eth_getBalance: invalid JSON response"));
+    }
+
+    #[test]
+    fn errors_leave_out_the_url() {
+        // A port nothing listens on any more
+        let url = format!("http://{}/v3/secret-api-key", TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap());
+        let error = verify_address_unused("0xabc", &url).unwrap_err();
+        assert!(error.starts_with("eth_getTransactionCount: "), "{}", error);
+        assert!(!error.contains("secret-api-key") && !error.contains("127.0.0.1"), "{}", error);
+    }
+}
@@ -0,0 +1,731 @@
+// Salt mining for contract addresses: --create2, --create3, --clone and --safe search CREATE2
+// salts instead of keys, so no private key is involved and the pattern applies to the deployed contract.
+
+use crate::pattern::{to_checksum_address, BitMask, CompiledPattern, PatternEntry};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha3::{Digest, Keccak256};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// The proxy init code of solmate's, solady's and 0xSequence's CREATE3 libraries
+const PROXY_INITCODE: [u8; 16] = [0x67, 0x36, 0x3d, 0x3d, 0x37, 0x36, 0x3d, 0x34, 0xf0, 0x3d, 0x52, 0x60, 0x08, 0x60, 0x18, 0xf3];
+
+// EIP-1167 clone code around the 20 byte implementation address, as built by OpenZeppelin's Clones
+const CLONE_CODE_HEAD: [u8; 20] = [
+    0x3d, 0x60, 0x2d, 0x80, 0x60, 0x0a, 0x3d, 0x39, 0x81, 0xf3, 0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73,
+];
+const CLONE_CODE_HEAD_WITH_ARGS: [u8; 17] = [0x3d, 0x81, 0x60, 0x0a, 0x3d, 0x39, 0xf3, 0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+const CLONE_CODE_TAIL: [u8; 15] = [0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60, 0x2b, 0x57, 0xfd, 0x5b, 0xf3];
+// Length of the clone's runtime code without immutable args
+const CLONE_RUNTIME_LEN: usize = 0x2d;
+// EIP-170 limit on deployed code
+const MAX_CODE_SIZE: usize = 24576;
+// Safe.setup, which the proxy is initialized with
+const SAFE_SETUP: &str = "setup(address[],uint256,address,bytes,address,address,uint256,address)";
+// The canonical Safe v1.3.0 deployments, at the same address on most chains: the
+// SafeProxyFactory and the GnosisSafe singleton
+pub const SAFE_PROXY_FACTORY: [u8; 20] = [
+    0xa6, 0xb7, 0x1e, 0x26, 0xc5, 0xe0, 0x84, 0x5f, 0x74, 0xc8, 0x12, 0x10, 0x2c, 0xa7, 0x11, 0x4b, 0x6a, 0x89, 0x6a, 0xb2,
+];
+pub const SAFE_SINGLETON: [u8; 20] = [
+    0xd9, 0xdb, 0x27, 0x0c, 0x1b, 0x5e, 0x3b, 0xd1, 0x61, 0xe8, 0xc8, 0x50, 0x3c, 0x55, 0xce, 0xab, 0xee, 0x70, 0x95, 0x52,
+];
+
+// Uniswap v4 hook permissions (Hooks.sol), encoded in the lowest 14 bits of the hook's address
+const HOOK_FLAGS: [(&str, u16); 14] = [
+    ("BEFORE_INITIALIZE", 1 << 13),
+    ("AFTER_INITIALIZE", 1 << 12),
+    ("BEFORE_ADD_LIQUIDITY", 1 << 11),
+    ("AFTER_ADD_LIQUIDITY", 1 << 10),
+    ("BEFORE_REMOVE_LIQUIDITY", 1 << 9),
+    ("AFTER_REMOVE_LIQUIDITY", 1 << 8),
+    ("BEFORE_SWAP", 1 << 7),
+    ("AFTER_SWAP", 1 << 6),
+    ("BEFORE_DONATE", 1 << 5),
+    ("AFTER_DONATE", 1 << 4),
+    ("BEFORE_SWAP_RETURNS_DELTA", 1 << 3),
+    ("AFTER_SWAP_RETURNS_DELTA", 1 << 2),
+    ("AFTER_ADD_LIQUIDITY_RETURNS_DELTA", 1 << 1),
+    ("AFTER_REMOVE_LIQUIDITY_RETURNS_DELTA", 1 << 0),
+];
+const HOOK_FLAG_MASK: u16 = (1 << 14) - 1;
+
+pub fn default_proxy_initcode_hash() -> [u8; 32] {
+    Keccak256::digest(PROXY_INITCODE).into()
+}
+
+// Creation code of an EIP-1167 clone of `implementation`. With `extra_data` it is the
+// immutable-args variant (OpenZeppelin's cloneDeterministicWithImmutableArgs), whose
+// runtime code ends with the data.
+pub fn clone_initcode(implementation: &[u8; 20], extra_data: &[u8]) -> Vec<u8> {
+    let mut code = Vec::with_capacity(55 + extra_data.len());
+    if extra_data.is_empty() {
+        code.extend_from_slice(&CLONE_CODE_HEAD);
+    } else {
+        let runtime_len = u16::try_from(CLONE_RUNTIME_LEN + extra_data.len()).expect("checked by parse_extra_data");
+        code.push(0x61);
+        code.extend_from_slice(&runtime_len.to_be_bytes());
+        code.extend_from_slice(&CLONE_CODE_HEAD_WITH_ARGS);
+    }
+    code.extend_from_slice(implementation);
+    code.extend_from_slice(&CLONE_CODE_TAIL);
+    code.extend_from_slice(extra_data);
+    code
+}
+
+// clap value parser for --deployer, --factory and --implementation
+pub fn parse_address(value: &str) -> Result<[u8; 20], String> {
+    let hex = value.strip_prefix("0x").unwrap_or(value);
+    let mut address = [0u8; 20];
+    match hex::decode_to_slice(hex, &mut address) {
+        Ok(()) => Ok(address),
+        Err(_) => Err(format!("'{}' is not an address (40 hexadecimal characters, 0x optional)", value)),
+    }
+}
+
+// clap value parser for --hook-flags: a hex bit pattern, or flag names with or without the
+// _FLAG suffix of the Solidity constants
+pub fn parse_hook_flags(value: &str) -> Result<u16, String> {
+    if let Some(hex) = value.strip_prefix("0x") {
+        return match u16::from_str_radix(hex, 16) {
+            Ok(flags) if flags & !HOOK_FLAG_MASK == 0 => Ok(flags),
+            Ok(_) => Err(format!("'{}' sets bits above the 14 hook permission bits (mask 0x{:04x})", value, HOOK_FLAG_MASK)),
+            Err(e) => Err(format!("'{}' is not hex: {}", value, e)),
+        };
+    }
+    value.split(',').try_fold(0, |flags, name| {
+        let name = name.trim().to_ascii_uppercase();
+        let name = name.strip_suffix("_FLAG").unwrap_or(&name);
+        match HOOK_FLAGS.iter().find(|(flag, _)| *flag == name) {
+            Some((_, bit)) => Ok(flags | bit),
+            None => Err(format!("unknown hook flag '{}' (expected 0x-prefixed hex or names like BEFORE_SWAP,AFTER_SWAP)", name)),
+        }
+    })
+}
+
+// clap value parser for --salt-prefix: up to 24 bytes of hex, as the last 8 bytes of a salt
+// are its nonce
+pub fn parse_salt_prefix(value: &str) -> Result<Box<[u8]>, String> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    let bytes = hex::decode(digits).map_err(|_| format!("'{}' is not an even number of hex characters", value))?;
+    if bytes.is_empty() || bytes.len() > 24 {
+        return Err(format!("expected 1 to 24 bytes, got {}", bytes.len()));
+    }
+    Ok(bytes.into_boxed_slice())
+}
+
+// The names of the flags in a bit pattern, highest bit first
+pub fn hook_flag_names(flags: u16) -> Vec<&'static str> {
+    HOOK_FLAGS.iter().filter(|(_, bit)| flags & bit != 0).map(|(name, _)| *name).collect()
+}
+
+// v4 reads every one of the 14 bits: requested permissions must be set and all others clear
+pub fn has_hook_flags(address: &[u8; 20], flags: u16) -> bool {
+    u16::from_be_bytes([address[18], address[19]]) & HOOK_FLAG_MASK == flags
+}
+
+// clap value parser for --proxy-creation-code and --initializer
+pub fn parse_bytes(value: &str) -> Result<Box<[u8]>, String> {
+    let data = hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| format!("invalid hex: {}", e))?;
+    if data.is_empty() {
+        return Err("expected at least one byte of hex".to_string());
+    }
+    Ok(data.into_boxed_slice())
+}
+
+// clap value parser for --extra-data; the clone's code must stay within the EIP-170 size limit
+pub fn parse_extra_data(value: &str) -> Result<Box<[u8]>, String> {
+    let data = hex::decode(value.strip_prefix("0x").unwrap_or(value)).map_err(|e| format!("invalid hex: {}", e))?;
+    if CLONE_RUNTIME_LEN + data.len() > MAX_CODE_SIZE {
+        return Err(format!("at most {} bytes fit in a clone", MAX_CODE_SIZE - CLONE_RUNTIME_LEN));
+    }
+    Ok(data.into_boxed_slice())
+}
+
+// CREATE2: keccak256(0xff ++ deployer ++ salt ++ keccak256(init code))[12..]
+pub fn create2_address(deployer: &[u8; 20], salt: &[u8; 32], initcode_hash: &[u8; 32]) -> [u8; 20] {
+    let hash = Keccak256::new()
+        .chain_update([0xff])
+        .chain_update(deployer)
+        .chain_update(salt)
+        .chain_update(initcode_hash)
+        .finalize();
+    hash[12..].try_into().expect("20 bytes")
+}
+
+// CREATE from `deployer` at nonce 1: keccak256(rlp([deployer, 1]))[12..]
+pub fn create_address_nonce1(deployer: &[u8; 20]) -> [u8; 20] {
+    let hash = Keccak256::new().chain_update([0xd6, 0x94]).chain_update(deployer).chain_update([0x01]).finalize();
+    hash[12..].try_into().expect("20 bytes")
+}
+
+// Calldata of Safe.setup(owners, threshold, 0, "", fallback_handler, 0, 0, 0): a plain
+// Safe without a setup call or deployment payment
+pub fn safe_setup_initializer(owners: &[[u8; 20]], threshold: u64, fallback_handler: &[u8; 20]) -> Vec<u8> {
+    let word = |bytes: &[u8]| {
+        let mut word = [0u8; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+        word
+    };
+    let data_offset = 8 * 32 + 32 * (1 + owners.len());
+    let mut calldata = Keccak256::digest(SAFE_SETUP)[..4].to_vec();
+    for head in [
+        word(&(8 * 32u64).to_be_bytes()),
+        word(&threshold.to_be_bytes()),
+        [0u8; 32],
+        word(&(data_offset as u64).to_be_bytes()),
+        word(fallback_handler),
+        [0u8; 32],
+        [0u8; 32],
+        [0u8; 32],
+    ] {
+        calldata.extend_from_slice(&head);
+    }
+    calldata.extend_from_slice(&word(&(owners.len() as u64).to_be_bytes()));
+    for owner in owners {
+        calldata.extend_from_slice(&word(owner));
+    }
+    // The empty `data` bytes
+    calldata.extend_from_slice(&[0u8; 32]);
+    calldata
+}
+
+// A 256-bit big-endian number in decimal, as the factory's uint256 saltNonce is usually entered
+fn decimal(value: &[u8; 32]) -> String {
+    let mut digits = Vec::new();
+    let mut number = *value;
+    while number.iter().any(|&byte| byte != 0) {
+        let mut remainder = 0u32;
+        for byte in number.iter_mut() {
+            let current = (remainder << 8) | *byte as u32;
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).expect("ASCII digits")
+}
+
+fn checksummed(address: &[u8; 20]) -> String {
+    to_checksum_address(&crate::format_address(address))
+}
+
+pub enum Target {
+    // A plain CREATE2 deployment of init code with a known hash
+    Create2 { deployer: [u8; 20], initcode_hash: [u8; 32] },
+    // The factory CREATE2-deploys a fixed proxy from the salt, and the proxy CREATEs the
+    // contract at nonce 1, so the address does not depend on the contract's init code
+    Create3 { deployer: [u8; 20], proxy_initcode_hash: [u8; 32] },
+    // The factory CREATE2-deploys an EIP-1167 clone; the init code is hashed once up front
+    Clone { factory: [u8; 20], implementation: [u8; 20], extra_data: Vec<u8>, initcode_hash: [u8; 32] },
+    // SafeProxyFactory.createProxyWithNonce: the mined value is the saltNonce, and the CREATE2
+    // salt is keccak256(keccak256(initializer) ++ saltNonce)
+    Safe { factory: [u8; 20], singleton: [u8; 20], initializer: Vec<u8>, initializer_hash: [u8; 32], initcode_hash: [u8; 32] },
+}
+
+impl Target {
+    pub fn clone_of(factory: [u8; 20], implementation: [u8; 20], extra_data: Vec<u8>) -> Target {
+        let initcode_hash = Keccak256::digest(clone_initcode(&implementation, &extra_data)).into();
+        Target::Clone { factory, implementation, extra_data, initcode_hash }
+    }
+    
+    // The init code is the factory's proxy creation code followed by the singleton as a uint256
+    pub fn safe(factory: [u8; 20], singleton: [u8; 20], proxy_creation_code: &[u8], initializer: &[u8]) -> Target {
+        let mut singleton_word = [0u8; 32];
+        singleton_word[12..].copy_from_slice(&singleton);
+        let initcode_hash = Keccak256::new().chain_update(proxy_creation_code).chain_update(singleton_word).finalize().into();
+        let initializer_hash = Keccak256::digest(initializer).into();
+        Target::Safe { factory, singleton, initializer: initializer.to_vec(), initializer_hash, initcode_hash }
+    }
+    
+    fn create2_salt(&self, salt: &[u8; 32]) -> [u8; 32] {
+        match self {
+            Target::Safe { initializer_hash, .. } => Keccak256::new().chain_update(initializer_hash).chain_update(salt).finalize().into(),
+            _ => *salt,
+        }
+    }
+    
+    fn address(&self, salt: &[u8; 32]) -> [u8; 20] {
+        match self {
+            Target::Create2 { deployer, initcode_hash } => create2_address(deployer, salt, initcode_hash),
+            Target::Create3 { deployer, proxy_initcode_hash } => create_address_nonce1(&create2_address(deployer, salt, proxy_initcode_hash)),
+            Target::Clone { factory, initcode_hash, .. } => create2_address(factory, salt, initcode_hash),
+            Target::Safe { factory, initcode_hash, .. } => create2_address(factory, &self.create2_salt(salt), initcode_hash),
+        }
+    }
+    
+    fn banner(&self) {
+        match self {
+            Target::Create2 { deployer, initcode_hash } => {
+                info!("🔍 Mining a CREATE2 salt...");
+                info!("Deployer: {}", checksummed(deployer));
+                info!("Init code hash: 0x{}", hex::encode(initcode_hash));
+            }
+            Target::Create3 { deployer, proxy_initcode_hash } => {
+                info!("🔍 Mining a CREATE3 salt...");
+                info!("Deployer: {}", checksummed(deployer));
+                info!("Proxy init code hash: 0x{}", hex::encode(proxy_initcode_hash));
+            }
+            Target::Clone { factory, implementation, extra_data, initcode_hash } => {
+                info!("🔍 Mining an EIP-1167 clone salt...");
+                info!("Factory: {}", checksummed(factory));
+                info!("Implementation: {}", checksummed(implementation));
+                if !extra_data.is_empty() {
+                    info!("Immutable args: {} bytes", extra_data.len());
+                }
+                info!("Init code hash: 0x{}", hex::encode(initcode_hash));
+            }
+            Target::Safe { factory, singleton, initializer_hash, initcode_hash, .. } => {
+                info!("🔍 Mining a Safe saltNonce...");
+                info!("Proxy factory: {}", checksummed(factory));
+                info!("Singleton: {}", checksummed(singleton));
+                info!("Initializer hash: 0x{}", hex::encode(initializer_hash));
+                info!("Init code hash: 0x{}", hex::encode(initcode_hash));
+            }
+        }
+    }
+    
+    // The result fields of a salt, with the final address under "address"
+    fn result(&self, salt: &[u8; 32]) -> serde_json::Value {
+        let mut object = serde_json::json!({ "salt": format!("0x{}", hex::encode(salt)) });
+        match self {
+            Target::Create2 { deployer, initcode_hash } => {
+                object["deployer"] = checksummed(deployer).into();
+                object["initcode_hash"] = format!("0x{}", hex::encode(initcode_hash)).into();
+            }
+            Target::Create3 { deployer, proxy_initcode_hash } => {
+                let proxy = create2_address(deployer, salt, proxy_initcode_hash);
+                object["deployer"] = checksummed(deployer).into();
+                object["proxy_initcode_hash"] = format!("0x{}", hex::encode(proxy_initcode_hash)).into();
+                object["proxy_address"] = checksummed(&proxy).into();
+            }
+            Target::Clone { factory, implementation, extra_data, initcode_hash } => {
+                object["factory"] = checksummed(factory).into();
+                object["implementation"] = checksummed(implementation).into();
+                if !extra_data.is_empty() {
+                    object["extra_data"] = format!("0x{}", hex::encode(extra_data)).into();
+                }
+                // The full CREATE2 parameters: factory, salt and init code
+                object["initcode"] = format!("0x{}", hex::encode(clone_initcode(implementation, extra_data))).into();
+                object["initcode_hash"] = format!("0x{}", hex::encode(initcode_hash)).into();
+            }
+            Target::Safe { factory, singleton, initializer, initializer_hash, initcode_hash } => {
+                object["salt_nonce"] = decimal(salt).into();
+                object["salt"] = format!("0x{}", hex::encode(self.create2_salt(salt))).into();
+                object["factory"] = checksummed(factory).into();
+                object["singleton"] = checksummed(singleton).into();
+                // Everything createProxyWithNonce(singleton, initializer, saltNonce) needs
+                object["initializer"] = format!("0x{}", hex::encode(initializer)).into();
+                object["initializer_hash"] = format!("0x{}", hex::encode(initializer_hash)).into();
+                object["initcode_hash"] = format!("0x{}", hex::encode(initcode_hash)).into();
+            }
+        }
+        object["address"] = checksummed(&self.address(salt)).into();
+        object
+    }
+}
+
+// The salt nonces one thread checks: start, start + step, start + 2·step, ...
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceShard {
+    pub start: u64,
+    pub step: u64,
+}
+
+impl NonceShard {
+    // One shard per thread, together covering every nonce from `start` on exactly once
+    pub fn split(start: u64, threads: usize) -> Vec<NonceShard> {
+        (0..threads as u64)
+            .filter_map(|thread| Some(NonceShard { start: start.checked_add(thread)?, step: threads as u64 }))
+            .collect()
+    }
+    
+    // The shard's nonces up to and including `end`
+    pub fn nonces(self, end: u64) -> impl Iterator<Item = u64> {
+        std::iter::successors(Some(self.start).filter(|&nonce| nonce <= end), move |&nonce| {
+            nonce.checked_add(self.step).filter(|&nonce| nonce <= end)
+        })
+    }
+}
+
+pub struct SaltSearch {
+    pub target: Target,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    pub case_sensitive: bool,
+    pub hook_flags: Option<u16>,
+    pub bit_mask: Option<BitMask>,
+    pub leading_zero_bytes: usize,
+    // --nonce-start/--nonce-end: the range of the salt's low 8 bytes
+    pub nonce_start: Option<u64>,
+    pub nonce_end: Option<u64>,
+    // --salt-prefix: the salt's leading bytes, the same in every salt
+    pub salt_prefix: Vec<u8>,
+}
+
+impl SaltSearch {
+    fn matches(&self, pattern: &CompiledPattern, address: &[u8; 20]) -> bool {
+        address[..self.leading_zero_bytes].iter().all(|&byte| byte == 0)
+            && self.hook_flags.is_none_or(|flags| has_hook_flags(address, flags))
+            && self.bit_mask.is_none_or(|bit_mask| bit_mask.matches(address))
+            && pattern.matches(address)
+    }
+}
+
+// Mines until `count` salts are found or the nonce range is exhausted. The salt is a 24-byte
+// base followed by a 64-bit nonce, which the threads share out as NonceShards. The base starts
+// with the --salt-prefix bytes and is random after them, or zero with --nonce-start or
+// --nonce-end, so that the salt is the nonce itself.
+pub fn run(search: &SaltSearch, threads: usize, count: u64, json_output: bool, quiet: bool) -> Result<(), Box<dyn std::error::Error>> {
+    search.target.banner();
+    info!("Pattern: {}", PatternEntry::new(search.prefix.clone(), search.suffix.clone()));
+    let pattern = CompiledPattern::compile(search.prefix.as_deref(), search.suffix.as_deref(), search.case_sensitive)?;
+    if let Some(flags) = search.hook_flags {
+        let names = hook_flag_names(flags);
+        info!("Hook flags: 0x{:04x} ({})", flags, if names.is_empty() { "no permissions".to_string() } else { names.join(", ") });
+    }
+    if search.leading_zero_bytes > 0 {
+        info!("Leading zero bytes: {}", search.leading_zero_bytes);
+    }
+    if let Some(bit_mask) = &search.bit_mask {
+        info!("Mask: 0x{} == 0x{} ({} bits)", hex::encode(bit_mask.mask), hex::encode(bit_mask.value), bit_mask.bits());
+    }
+    let nonce_start = search.nonce_start.unwrap_or(0);
+    let nonce_end = search.nonce_end.unwrap_or(u64::MAX);
+    let mut base_salt = [0u8; 32];
+    if search.nonce_start.is_some() || search.nonce_end.is_some() {
+        info!("Nonces: {} to {}", nonce_start, nonce_end);
+    } else {
+        OsRng.fill_bytes(&mut base_salt[..24]);
+    }
+    base_salt[..search.salt_prefix.len()].copy_from_slice(&search.salt_prefix);
+    if !search.salt_prefix.is_empty() {
+        info!("Salt prefix: 0x{} ({} fixed bytes)", hex::encode(&search.salt_prefix), search.salt_prefix.len());
+    }
+    info!("Threads: {}", threads);
+    info!("Press Ctrl+C to stop\n");
+    let attempts = AtomicU64::new(0);
+    let found = Mutex::new(Vec::new());
+    let done = AtomicBool::new(false);
+    let shards = NonceShard::split(nonce_start, threads);
+    // The last thread to run out of nonces ends the search
+    let searching = AtomicUsize::new(shards.len());
+    let start_time = Instant::now();
+    
+    std::thread::scope(|scope| {
+        for shard in shards {
+            let (attempts, found, done, searching) = (&attempts, &found, &done, &searching);
+            scope.spawn(move || {
+                let mut salt = base_salt;
+                let mut checked = 0;
+                for nonce in shard.nonces(nonce_end) {
+                    salt[24..].copy_from_slice(&nonce.to_be_bytes());
+                    if search.matches(&pattern, &search.target.address(&salt)) {
+                        let mut found = found.lock().unwrap();
+                        if (found.len() as u64) < count {
+                            found.push(salt);
+                        }
+                        if found.len() as u64 >= count {
+                            done.store(true, Ordering::Relaxed);
+                        }
+                    }
+                    checked += 1;
+                    if checked == crate::KEYGEN_BATCH_SIZE as u64 {
+                        attempts.fetch_add(checked, Ordering::Relaxed);
+                        checked = 0;
+                        if done.load(Ordering::Relaxed) {
+                            break;
+                        }
+                    }
+                }
+                attempts.fetch_add(checked, Ordering::Relaxed);
+                if searching.fetch_sub(1, Ordering::Relaxed) == 1 {
+                    done.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+        
+        scope.spawn(|| {
+            let mut next_console = start_time + Duration::from_secs(5);
+            while !done.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(100));
+                if Instant::now() >= next_console {
+                    let elapsed = start_time.elapsed();
+                    let salts = attempts.load(Ordering::Relaxed);
+                    progress!("⏳ Salts: {} | Rate: {} | Elapsed: {}",
+                              salts, crate::output::rate(salts as f64 / elapsed.as_secs_f64()), crate::output::elapsed(elapsed));
+                    next_console += Duration::from_secs(5);
+                }
+            }
+        });
+    });
+    
+    let final_attempts = attempts.load(Ordering::Relaxed);
+    let elapsed = start_time.elapsed();
+    let found = found.into_inner().unwrap();
+    if found.is_empty() {
+        error!("❌ No match found in range [{}, {}] after {} salts in {:.2?}", nonce_start, nonce_end, final_attempts, elapsed);
+        std::process::exit(1);
+    }
+    if (found.len() as u64) < count {
+        warning!("⚠️  Only {} of {} salts found in range [{}, {}]", found.len(), count, nonce_start, nonce_end);
+    }
+    if !quiet {
+        success!("🎉 Found {} salt{} after {} salts in {:.2?}!", found.len(), if found.len() == 1 { "" } else { "s" }, final_attempts, elapsed);
+    }
+    
+    let prefix_len = search.prefix.as_deref().map_or(0, crate::pattern_len).max(2 * search.leading_zero_bytes);
+    let suffix_len = search.suffix.as_deref().map_or(0, crate::pattern_len);
+    let mut json_results = Vec::new();
+    for (i, salt) in found.iter().enumerate() {
+        let mut object = search.target.result(salt);
+        if let Some(flags) = search.hook_flags {
+            object["hook_flags"] = format!("0x{:04x}", flags).into();
+        }
+        if !search.salt_prefix.is_empty() {
+            object["salt_fixed_bytes"] = search.salt_prefix.len().into();
+        }
+        let field = |key: &str| object[key].as_str().unwrap_or_default().to_string();
+        if json_output && quiet {
+            println!("{}", object);
+        } else if quiet {
+            if i > 0 {
+                println!();
+            }
+            for key in ["salt_nonce", "salt", "proxy_address", "address", "initializer"] {
+                if object[key].is_string() {
+                    println!("{}={}", key, field(key));
+                }
+            }
+        } else if !json_output {
+            if found.len() > 1 {
+                println!("\n── Match {} of {} ──", i + 1, found.len());
+            }
+            // The fixed salt prefix, then the searched bytes
+            let print_salt = || {
+                println!("🧂 Salt: {}", field("salt"));
+                if !search.salt_prefix.is_empty() {
+                    let fixed = search.salt_prefix.len();
+                    println!("   0x{} [fixed] {} [random]", hex::encode(&salt[..fixed]), hex::encode(&salt[fixed..]));
+                }
+            };
+            match &search.target {
+                Target::Create2 { .. } => {
+                    print_salt();
+                    println!("🏭 Deployer: {}", field("deployer"));
+                }
+                Target::Create3 { .. } => {
+                    print_salt();
+                    println!("🏭 Deployer: {}", field("deployer"));
+                    println!("📦 Proxy: {}", field("proxy_address"));
+                }
+                Target::Clone { .. } => {
+                    print_salt();
+                    println!("🏭 Factory: {}", field("factory"));
+                    println!("🧬 Implementation: {}", field("implementation"));
+                    println!("📜 Init code: {}", field("initcode"));
+                }
+                Target::Safe { .. } => {
+                    println!("🧂 Salt nonce: {}", field("salt_nonce"));
+                    println!("🏭 Proxy factory: {}", field("factory"));
+                    println!("🧬 Singleton: {}", field("singleton"));
+                    println!("📜 Initializer: {}", field("initializer"));
+                }
+            }
+            println!("📍 Address: {}", crate::output::address(&field("address"), prefix_len, suffix_len));
+            if let Some(flags) = search.hook_flags {
+                println!("🪝 Hook flags: 0x{:04x} in the low 14 bits", flags);
+            }
+        }
+        json_results.push(object);
+    }
+    if json_output && !quiet {
+        println!("{}", serde_json::to_string_pretty(&json_results).expect("JSON values serialize"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn address(value: &str) -> [u8; 20] {
+        parse_address(value).unwrap()
+    }
+    
+    fn bytes32(value: &str) -> [u8; 32] {
+        hex::decode(value.strip_prefix("0x").unwrap_or(value)).unwrap().try_into().unwrap()
+    }
+    
+    #[test]
+    fn create3_proxy_and_final_address() {
+        // PROXY_BYTECODE_HASH of solmate's CREATE3
+        assert_eq!(default_proxy_initcode_hash(), bytes32("21c35dbe1b344a2488cf3321d6ce542f8e9f305544ff09e4993a62319a497c1f"));
+        // Nonce 1 of the usual worked example of CREATE addresses
+        assert_eq!(create_address_nonce1(&address("0x6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0")), address("0x343c43a37d37dff08ae8c4a11544c718abb4fcf8"));
+        
+        let deployer = address("0x9fbb3df7c40da2e5a0de984ffe2ccb7c47cd0abf");
+        let target = Target::Create3 { deployer, proxy_initcode_hash: default_proxy_initcode_hash() };
+        let salt = bytes32("0x000000000000000000000000000000000000000000000000000000000000002a");
+        let proxy = create2_address(&deployer, &salt, &default_proxy_initcode_hash());
+        assert_eq!(target.address(&salt), create_address_nonce1(&proxy));
+        
+        let result = target.result(&salt);
+        assert_eq!(result["proxy_address"], checksummed(&proxy));
+        assert_eq!(result["address"], checksummed(&target.address(&salt)));
+        assert_eq!(result["salt"], "0x000000000000000000000000000000000000000000000000000000000000002a");
+    }
+    
+    #[test]
+    fn clone_initcode_follows_eip_1167() {
+        // The creation code from EIP-1167, with its 0xbebe... placeholder implementation
+        let implementation = [0xbe; 20];
+        assert_eq!(
+            hex::encode(clone_initcode(&implementation, &[])),
+            "3d602d80600a3d3981f3363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf3"
+        );
+        // OpenZeppelin's immutable-args variant pushes the longer runtime length and appends the args
+        assert_eq!(
+            hex::encode(clone_initcode(&implementation, &[0x12, 0x34])),
+            "61002f3d81600a3d39f3363d3d373d3d3d363d73bebebebebebebebebebebebebebebebebebebebe5af43d82803e903d91602b57fd5bf31234"
+        );
+        
+        let factory = address("0x5fbdb2315678afecb367f032d93f642f64180aa3");
+        let target = Target::clone_of(factory, implementation, Vec::new());
+        let salt = [7; 32];
+        let initcode_hash = Keccak256::digest(clone_initcode(&implementation, &[])).into();
+        assert_eq!(target.address(&salt), create2_address(&factory, &salt, &initcode_hash));
+        assert_eq!(target.result(&salt)["initcode_hash"], format!("0x{}", hex::encode(initcode_hash)));
+    }
+    
+    #[test]
+    fn extra_data_must_fit_in_a_clone() {
+        assert_eq!(parse_extra_data("0x").unwrap().len(), 0);
+        assert_eq!(parse_extra_data(&"ab".repeat(MAX_CODE_SIZE - CLONE_RUNTIME_LEN)).unwrap().len(), MAX_CODE_SIZE - CLONE_RUNTIME_LEN);
+        assert!(parse_extra_data(&"ab".repeat(MAX_CODE_SIZE - CLONE_RUNTIME_LEN + 1)).is_err());
+        assert!(parse_extra_data("0xabc").is_err());
+    }
+    
+    #[test]
+    fn safe_initializer_is_the_abi_encoded_setup_call() {
+        // setup([owner], 1, 0, "", CompatibilityFallbackHandler v1.3.0, 0, 0, 0)
+        let owner = address("0x70997970c51812dc3a010c7d01b50e0d17dc79c8");
+        let fallback_handler = address("0xf48f2b2d2a534e402487b3ee7c18c33aec0fe5e4");
+        let initializer = safe_setup_initializer(&[owner], 1, &fallback_handler);
+        assert_eq!(hex::encode(&initializer[..4]), "b63e800d");
+        let words: Vec<String> = initializer[4..].chunks(32).map(hex::encode).collect();
+        assert_eq!(words, [
+            "0000000000000000000000000000000000000000000000000000000000000100",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000140",
+            "000000000000000000000000f48f2b2d2a534e402487b3ee7c18c33aec0fe5e4",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0000000000000000000000000000000000000000000000000000000000000001",
+            "00000000000000000000000070997970c51812dc3a010c7d01b50e0d17dc79c8",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        ]);
+        
+        // Each extra owner moves the empty `data` one word further
+        let two_owners = safe_setup_initializer(&[owner, fallback_handler], 2, &[0; 20]);
+        assert_eq!(two_owners.len(), 4 + 32 * 12);
+        assert_eq!(two_owners[4 + 3 * 32..4 + 4 * 32], bytes32(&format!("{:064x}", 0x160)));
+    }
+    
+    #[test]
+    fn safe_salt_hashes_the_initializer_hash_and_salt_nonce() {
+        let initializer = safe_setup_initializer(&[address("0x70997970c51812dc3a010c7d01b50e0d17dc79c8")], 1, &[0; 20]);
+        let target = Target::safe(SAFE_PROXY_FACTORY, SAFE_SINGLETON, &[0x60, 0x80], &initializer);
+        let salt_nonce = bytes32(&format!("{:064x}", 42));
+        let salt: [u8; 32] = Keccak256::new().chain_update(Keccak256::digest(&initializer)).chain_update(salt_nonce).finalize().into();
+        
+        let mut initcode = vec![0x60, 0x80];
+        initcode.extend_from_slice(&[0; 12]);
+        initcode.extend_from_slice(&SAFE_SINGLETON);
+        assert_eq!(target.address(&salt_nonce), create2_address(&SAFE_PROXY_FACTORY, &salt, &Keccak256::digest(&initcode).into()));
+        
+        let result = target.result(&salt_nonce);
+        assert_eq!(result["salt_nonce"], "42");
+        assert_eq!(result["salt"], format!("0x{}", hex::encode(salt)));
+        assert_eq!(result["initializer"], format!("0x{}", hex::encode(&initializer)));
+    }
+    
+    #[test]
+    fn salt_nonces_are_printed_in_decimal() {
+        assert_eq!(decimal(&[0; 32]), "0");
+        assert_eq!(decimal(&bytes32(&format!("{:064x}", 1234567890u64))), "1234567890");
+        assert_eq!(decimal(&[0xff; 32]), "115792089237316195423570985008687907853269984665640564039457584007913129639935");
+    }
+    
+    #[test]
+    fn create2_matches_the_eip_1014_examples() {
+        let long_code = hex::decode("deadbeef".repeat(11)).unwrap();
+        for (deployer, salt, initcode, expected) in [
+            ("0x0000000000000000000000000000000000000000", [0; 32], &[0x00][..], "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"),
+            ("0xdeadbeef00000000000000000000000000000000", [0; 32], &[0x00], "0xB928f69Bb1D91Cd65274e3c79d8986362984fDA3"),
+            (
+                "0xdeadbeef00000000000000000000000000000000",
+                bytes32("000000000000000000000000feed000000000000000000000000000000000000"),
+                &[0x00],
+                "0xD04116cDd17beBE565EB2422F2497E06cC1C9833",
+            ),
+            ("0x0000000000000000000000000000000000000000", [0; 32], &[0xde, 0xad, 0xbe, 0xef], "0x70f2b2914A2a4b783FaEFb75f459A580616Fcb5e"),
+            (
+                "0x00000000000000000000000000000000deadbeef",
+                bytes32("00000000000000000000000000000000000000000000000000000000cafebabe"),
+                &[0xde, 0xad, 0xbe, 0xef],
+                "0x60f3f640a8508fC6a86d45DF051962668E1e8AC7",
+            ),
+            (
+                "0x00000000000000000000000000000000deadbeef",
+                bytes32("00000000000000000000000000000000000000000000000000000000cafebabe"),
+                &long_code,
+                "0x1d8bfDC5D46DC4f61D6b6115972536eBE6A8854C",
+            ),
+            ("0x0000000000000000000000000000000000000000", [0; 32], &[], "0xE33C0C7F7df4809055C3ebA6c09CFe4BaF1BD9e0"),
+        ] {
+            let address = create2_address(&address(deployer), &salt, &Keccak256::digest(initcode).into());
+            assert_eq!(checksummed(&address), expected);
+        }
+    }
+    
+    #[test]
+    fn hook_flags_use_the_v4_permission_bits() {
+        // Hooks.sol: BEFORE_SWAP_FLAG = 1 << 7, AFTER_SWAP_FLAG = 1 << 6, BEFORE_INITIALIZE_FLAG = 1 << 13
+        assert_eq!(parse_hook_flags("BEFORE_SWAP,AFTER_SWAP"), Ok(0x00c0));
+        assert_eq!(parse_hook_flags("before_swap_flag, before_initialize"), Ok(0x2080));
+        assert_eq!(parse_hook_flags("0x3fff"), Ok(0x3fff));
+        assert!(parse_hook_flags("0x4000").is_err());
+        assert!(parse_hook_flags("BEFORE_LUNCH").is_err());
+        assert_eq!(hook_flag_names(0x2080), ["BEFORE_INITIALIZE", "BEFORE_SWAP"]);
+        
+        // Requested bits set and every other permission bit clear; the top two bits are free
+        let hook = address("0x00000000000000000000000000000000000040c0");
+        assert!(has_hook_flags(&hook, 0x00c0));
+        assert!(has_hook_flags(&address("0x000000000000000000000000000000000000c0c0"), 0x00c0));
+        assert!(!has_hook_flags(&hook, 0x0080));
+        assert!(!has_hook_flags(&address("0x00000000000000000000000000000000000000c4"), 0x00c0));
+    }
+    
+    #[test]
+    fn nonce_shards_are_disjoint_without_gaps() {
+        for (start, end, threads) in [(0, 99, 1), (0, 99, 4), (5, 1004, 3), (17, 17, 8), (10, 12, 7), (u64::MAX - 9, u64::MAX, 4), (u64::MAX - 2, u64::MAX, 8)] {
+            let shards = NonceShard::split(start, threads);
+            let mut nonces: Vec<u64> = shards.iter().flat_map(|shard| shard.nonces(end)).collect();
+            let count = nonces.len();
+            nonces.sort_unstable();
+            nonces.dedup();
+            assert_eq!(nonces.len(), count, "{}..={} on {} threads checks a nonce twice", start, end, threads);
+            assert!(nonces.iter().copied().eq(start..=end), "{}..={} on {} threads", start, end, threads);
+        }
+        assert!(NonceShard { start: 10, step: 2 }.nonces(9).next().is_none());
+    }
+}
@@ -0,0 +1,309 @@
+// Scoring mode: rate every candidate with weighted components read from a TOML
+// file and keep the best address seen so far, or with --top-k, rank candidates by
+// a single --score-by criterion and keep the best K
+//
+// leading_zero_nibbles = 2.0     # points per leading '0'
+// longest_repeat_run = 1.0       # points per character of the longest run of one character
+// trailing_zeros = 0.5           # points per trailing '0'
+//
+// [contains]                     # points when the string appears anywhere
+// ab = 3.0
+
+use crate::error::VanityError;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering as CmpOrdering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap};
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScoreConfig {
+    #[serde(default)]
+    pub leading_zero_nibbles: f64,
+    #[serde(default)]
+    pub longest_repeat_run: f64,
+    #[serde(default)]
+    pub trailing_zeros: f64,
+    #[serde(default)]
+    pub contains: BTreeMap<String, f64>,
+}
+
+// How an address scored, component by component
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Score {
+    pub total: f64,
+    pub leading_zero_nibbles: usize,
+    pub longest_repeat_run: usize,
+    pub trailing_zeros: usize,
+    pub contains: Vec<String>,
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (leading zeros {}, longest run {}, trailing zeros {}",
+               self.total, self.leading_zero_nibbles, self.longest_repeat_run, self.trailing_zeros)?;
+        for text in &self.contains {
+            write!(f, ", contains '{}'", text)?;
+        }
+        write!(f, ")")
+    }
+}
+
+pub struct Scorer {
+    pub config: ScoreConfig,
+    // The `contains` strings as nibbles, with their weights
+    contains: Vec<(Vec<u8>, f64)>,
+}
+
+fn nibbles(address: &[u8; 20]) -> [u8; 40] {
+    let mut nibbles = [0u8; 40];
+    for (i, &byte) in address.iter().enumerate() {
+        nibbles[2 * i] = byte >> 4;
+        nibbles[2 * i + 1] = byte & 0x0f;
+    }
+    nibbles
+}
+
+// A single criterion for --score-by; the prefix of PrefixMatchLength is in nibbles
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Criterion {
+    LeadingZeros,
+    MaxRepeat,
+    PalindromeScore,
+    PrefixMatchLength(Vec<u8>),
+}
+
+impl Criterion {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Criterion::LeadingZeros => "leading_zeros",
+            Criterion::MaxRepeat => "max_repeat",
+            Criterion::PalindromeScore => "palindrome_score",
+            Criterion::PrefixMatchLength(_) => "prefix_match_length",
+        }
+    }
+    
+    // Characters of the address the score stands for, at the start and the end
+    pub fn highlight(&self, score: f64) -> (usize, usize) {
+        let score = score as usize;
+        match self {
+            Criterion::LeadingZeros | Criterion::PrefixMatchLength(_) => (score, 0),
+            Criterion::PalindromeScore => (score, score),
+            Criterion::MaxRepeat => (0, 0),
+        }
+    }
+}
+
+// Leading '0's, the longest run of one character, mirrored characters at the ends
+// (as --palindrome counts them) or leading characters equal to the target prefix
+pub fn score_address(address: &[u8; 20], criterion: &Criterion) -> f64 {
+    let nibbles = nibbles(address);
+    let score = match criterion {
+        Criterion::LeadingZeros => nibbles.iter().take_while(|&&nibble| nibble == 0).count(),
+        Criterion::MaxRepeat => longest_run(&nibbles),
+        Criterion::PalindromeScore => (0..20).find(|&i| nibbles[i] != nibbles[39 - i]).unwrap_or(40),
+        Criterion::PrefixMatchLength(prefix) => prefix.iter().zip(&nibbles).take_while(|(a, b)| a == b).count(),
+    };
+    score as f64
+}
+
+fn longest_run(nibbles: &[u8; 40]) -> usize {
+    nibbles
+        .chunk_by(|a, b| a == b)
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0)
+}
+
+impl Scorer {
+    pub fn from_file(path: &Path) -> Result<Scorer, VanityError> {
+        let invalid = |message: String| VanityError::InvalidConfig { path: path.to_path_buf(), message };
+        let contents = std::fs::read_to_string(path).map_err(|source| VanityError::Io { path: path.to_path_buf(), source })?;
+        let config: ScoreConfig = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+        
+        let weights = [
+            ("leading_zero_nibbles", config.leading_zero_nibbles),
+            ("longest_repeat_run", config.longest_repeat_run),
+            ("trailing_zeros", config.trailing_zeros),
+        ];
+        for (name, weight) in weights.iter().copied().chain(config.contains.iter().map(|(text, &weight)| (text.as_str(), weight))) {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(invalid(format!("weight of '{}' must be a non-negative number, got {}", name, weight)));
+            }
+        }
+        if weights.iter().all(|(_, weight)| *weight == 0.0) && config.contains.values().all(|&weight| weight == 0.0) {
+            return Err(invalid("every weight is zero, so all addresses would score the same".to_string()));
+        }
+        
+        let mut contains = Vec::new();
+        for (text, &weight) in &config.contains {
+            if text.is_empty() || text.len() > 40 {
+                return Err(invalid(format!("contains string '{}' must be 1 to 40 characters long", text)));
+            }
+            let digits: Option<Vec<u8>> = text.chars().map(|c| c.to_digit(16).map(|nibble| nibble as u8)).collect();
+            match digits {
+                Some(digits) => contains.push((digits, weight)),
+                None => return Err(invalid(format!("contains string '{}' is not hexadecimal", text))),
+            }
+        }
+        
+        Ok(Scorer { config, contains })
+    }
+    
+    // The total alone, for the hot path; `breakdown` explains it
+    pub fn total(&self, address: &[u8; 20]) -> f64 {
+        self.breakdown_of(&nibbles(address), false).total
+    }
+    
+    pub fn breakdown(&self, address: &[u8; 20]) -> Score {
+        self.breakdown_of(&nibbles(address), true)
+    }
+    
+    fn breakdown_of(&self, nibbles: &[u8; 40], with_strings: bool) -> Score {
+        let leading_zero_nibbles = nibbles.iter().take_while(|&&nibble| nibble == 0).count();
+        let trailing_zeros = nibbles.iter().rev().take_while(|&&nibble| nibble == 0).count();
+        let longest_repeat_run = longest_run(nibbles);
+        
+        let mut total = leading_zero_nibbles as f64 * self.config.leading_zero_nibbles
+            + longest_repeat_run as f64 * self.config.longest_repeat_run
+            + trailing_zeros as f64 * self.config.trailing_zeros;
+        let mut contains = Vec::new();
+        for (digits, weight) in &self.contains {
+            if nibbles.windows(digits.len()).any(|window| window == digits.as_slice()) {
+                total += weight;
+                if with_strings {
+                    contains.push(digits.iter().map(|nibble| format!("{:x}", nibble)).collect());
+                }
+            }
+        }
+        
+        Score { total, leading_zero_nibbles, longest_repeat_run, trailing_zeros, contains }
+    }
+}
+
+// The best-scoring candidate so far, shared by all workers. Scores are non-negative,
+// so their f64 bit patterns order like the values and the common case of a candidate
+// that is no better is a single atomic load.
+pub struct BestSoFar<T> {
+    total_bits: AtomicU64,
+    best: Mutex<Option<(Score, T)>>,
+}
+
+const NO_BEST: u64 = u64::MAX;
+
+impl<T> BestSoFar<T> {
+    pub fn new() -> BestSoFar<T> {
+        BestSoFar { total_bits: AtomicU64::new(NO_BEST), best: Mutex::new(None) }
+    }
+    
+    // Keep the candidate if it beats the best; `candidate` is only built when it does
+    pub fn offer(&self, total: f64, candidate: impl FnOnce() -> (Score, T)) {
+        let current = self.total_bits.load(Ordering::Relaxed);
+        if current != NO_BEST && total <= f64::from_bits(current) {
+            return;
+        }
+        
+        let mut best = self.best.lock().unwrap();
+        if best.as_ref().is_some_and(|(score, _)| total <= score.total) {
+            return;
+        }
+        *best = Some(candidate());
+        self.total_bits.store(total.to_bits(), Ordering::Relaxed);
+    }
+    
+    pub fn score(&self) -> Option<Score> {
+        self.best.lock().unwrap().as_ref().map(|(score, _)| score.clone())
+    }
+    
+    // The best candidate so far, seen through `view` without taking it
+    pub fn peek<R>(&self, view: impl FnOnce(&Score, &T) -> R) -> Option<R> {
+        self.best.lock().unwrap().as_ref().map(|(score, item)| view(score, item))
+    }
+    
+    pub fn take(&self) -> Option<(Score, T)> {
+        self.best.lock().unwrap().take()
+    }
+}
+
+// A --top-k entry, ordered by its score alone
+pub struct Scored<T> {
+    pub score: f64,
+    pub item: T,
+}
+
+impl<T> PartialEq for Scored<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl<T> Eq for Scored<T> {}
+
+impl<T> PartialOrd for Scored<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Scored<T> {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.score.total_cmp(&other.score)
+    }
+}
+
+// The K best-scoring candidates, shared by all workers. The heap is a min-heap, so
+// its top is the candidate to beat once it is full; like BestSoFar, that score is
+// also kept in an atomic so a candidate that is no better is a single load.
+pub struct TopK<T> {
+    k: usize,
+    threshold_bits: AtomicU64,
+    heap: Mutex<BinaryHeap<Reverse<Scored<T>>>>,
+}
+
+impl<T> TopK<T> {
+    pub fn new(k: usize) -> TopK<T> {
+        TopK { k, threshold_bits: AtomicU64::new(NO_BEST), heap: Mutex::new(BinaryHeap::with_capacity(k + 1)) }
+    }
+    
+    // Keep the candidate if it beats the worst of a full heap; ties keep the earlier one
+    pub fn offer(&self, score: f64, candidate: impl FnOnce() -> T) {
+        let threshold = self.threshold_bits.load(Ordering::Relaxed);
+        if threshold != NO_BEST && score <= f64::from_bits(threshold) {
+            return;
+        }
+        
+        let mut heap = self.heap.lock().unwrap();
+        if heap.len() == self.k {
+            if heap.peek().is_some_and(|Reverse(worst)| score <= worst.score) {
+                return;
+            }
+            heap.pop();
+        }
+        heap.push(Reverse(Scored { score, item: candidate() }));
+        if heap.len() == self.k {
+            let worst = heap.peek().expect("the heap is full").0.score;
+            self.threshold_bits.store(worst.to_bits(), Ordering::Relaxed);
+        }
+    }
+    
+    pub fn best_score(&self) -> Option<f64> {
+        self.heap.lock().unwrap().iter().map(|Reverse(scored)| scored.score).max_by(f64::total_cmp)
+    }
+    
+    // The candidates so far, best first, seen through `view` without taking them
+    pub fn peek<R>(&self, mut view: impl FnMut(f64, &T) -> R) -> Vec<R> {
+        let heap = self.heap.lock().unwrap();
+        let mut entries: Vec<&Scored<T>> = heap.iter().map(|Reverse(scored)| scored).collect();
+        entries.sort_by(|a, b| b.cmp(a));
+        entries.into_iter().map(|scored| view(scored.score, &scored.item)).collect()
+    }
+    
+    // Best first
+    pub fn take(&self) -> Vec<Scored<T>> {
+        let heap = std::mem::take(&mut *self.heap.lock().unwrap());
+        heap.into_sorted_vec().into_iter().map(|Reverse(scored)| scored).collect()
+    }
+}
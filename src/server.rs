@@ -0,0 +1,384 @@
+// `serve` and `--api`: a REST API that runs prefix/suffix searches as background jobs
+//
+//   POST   /search            {"prefix":"dead","suffix":"beef","threads":4}  ->  {"job_id":"<uuid>"}
+//   GET    /search/{id}       {"status":"running"|"done"|"cancelled","attempts":N,"result":null|{...}}
+//   DELETE /search/{id}       cancels the job
+//
+//   POST   /jobs              {"prefix":"dead","include_secret":false}  ->  {"job_id":"<uuid>"}
+//   GET    /jobs/{id}         {"status":"running"|"found"|"cancelled","attempts":N,"rate":R,"address":null|"0x..",...}
+//   GET    /jobs/{id}/secret  the private key and mnemonic of a found job
+//   DELETE /jobs/{id}         cancels the job
+//
+//   GET    /healthz           {"status":"ok"}
+//
+// Both sets of routes share one registry of jobs. A job's result holds the private key, so the
+// server binds to localhost unless told otherwise, and with --api-token every endpoint but
+// /healthz needs `Authorization: Bearer <token>`. The key is handed out once: a job is dropped
+// as soon as its key or its cancellation has been read, and an ended job nobody asks about is
+// dropped JOB_TTL after it ended.
+
+use crate::keygen::generate_addresses_batch;
+use crate::pattern::{to_checksum_address, validate_lengths, validate_pattern, CompiledPattern};
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use secp256k1::{Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+// Jobs searching at the same time; more are refused until one finishes
+const MAX_RUNNING_JOBS: usize = 10;
+// Jobs held at all, running or ended and not yet read, so submitting without reading cannot
+// grow the registry without bound
+const MAX_JOBS: usize = 100;
+// How long an ended job waits to be read before it is dropped with its key
+const JOB_TTL: Duration = Duration::from_secs(600);
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobRequest {
+    // A prefix, like -p on the command line
+    pattern: Option<String>,
+    prefix: Option<String>,
+    suffix: Option<String>,
+    threads: Option<usize>,
+    #[serde(default)]
+    case_sensitive: bool,
+    // Show the key in the /jobs/{id} status instead of only through /secret
+    #[serde(default)]
+    include_secret: bool,
+}
+
+#[derive(Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum JobStatus {
+    Running,
+    Done,
+    Cancelled,
+}
+
+struct Found {
+    address: String,
+    checksum_address: String,
+    attempts: u64,
+    elapsed_secs: f64,
+}
+
+struct Secret {
+    private_key: Zeroizing<String>,
+    mnemonic: Option<Zeroizing<String>>,
+}
+
+// What a job's search thread shares with its handle
+#[derive(Default)]
+struct JobState {
+    attempts: AtomicU64,
+    // When the search stopped, found or cancelled
+    ended: OnceLock<Instant>,
+    found: OnceLock<Found>,
+    // In place before `found` is set, and taken by the first read of the key
+    secret: Mutex<Option<Secret>>,
+}
+
+struct JobHandle {
+    cancel: CancellationToken,
+    start: Instant,
+    include_secret: bool,
+    state: Arc<JobState>,
+}
+
+impl JobHandle {
+    fn status(&self) -> JobStatus {
+        if self.state.found.get().is_some() {
+            JobStatus::Done
+        } else if self.cancel.is_cancelled() {
+            JobStatus::Cancelled
+        } else {
+            JobStatus::Running
+        }
+    }
+    
+    fn expired(&self) -> bool {
+        self.state.ended.get().is_some_and(|ended| ended.elapsed() > JOB_TTL)
+    }
+    
+    // The key, and the end of the job: whoever calls this drops the job
+    fn take_secret(&self) -> Option<Secret> {
+        self.state.secret.lock().unwrap().take()
+    }
+}
+
+// The search thread may still hold the state, so the key is wiped with the handle
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        self.take_secret();
+    }
+}
+
+type JobRegistry = Arc<Mutex<HashMap<Uuid, JobHandle>>>;
+
+// The registry, less the ended jobs that waited too long to be read
+fn jobs(registry: &JobRegistry) -> MutexGuard<'_, HashMap<Uuid, JobHandle>> {
+    let mut jobs = registry.lock().unwrap();
+    jobs.retain(|_, job| !job.expired());
+    jobs
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+pub async fn serve(address: SocketAddr, token: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut app = Router::new()
+        .route("/search", post(create_job))
+        .route("/search/{id}", get(search_status).delete(cancel_job))
+        .route("/jobs", post(create_job))
+        .route("/jobs/{id}", get(job_status).delete(cancel_job))
+        .route("/jobs/{id}/secret", get(take_secret));
+    if let Some(token) = token.as_deref() {
+        app = app.route_layer(axum::middleware::from_fn_with_state(Arc::<str>::from(token), authorize));
+    }
+    let app = app
+        .route("/healthz", get(|| async { Json(serde_json::json!({ "status": "ok" })) }))
+        .with_state(JobRegistry::default());
+    
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    println!("🌐 Serving the search API on http://{}", listener.local_addr()?);
+    if token.is_none() && !address.ip().is_loopback() {
+        warning!("⚠️  Anyone who can reach this address can start searches and read their keys; use --api with --api-token");
+    }
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+// Checks the bearer token, comparing every byte so the time taken gives nothing away
+async fn authorize(State(token): State<Arc<str>>, request: Request, next: Next) -> Response {
+    let given = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    let matches = given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0;
+    if !matches {
+        return error(StatusCode::UNAUTHORIZED, "missing or wrong bearer token".to_string());
+    }
+    next.run(request).await
+}
+
+// Validates a requested search and returns the threads to run it on
+pub fn check_search(pattern: &Pattern, threads: Option<usize>) -> Result<usize, String> {
+    if pattern.prefix.is_none() && pattern.suffix.is_none() {
+        return Err("give a prefix, a suffix or both".to_string());
+    }
+    for pattern in pattern.prefix.iter().chain(&pattern.suffix) {
+        // Keep the listed problems, not the command line hints around them
+        if let Err(error_msg) = validate_pattern(pattern) {
+            let problems: Vec<&str> = error_msg.lines().filter_map(|line| line.trim().strip_prefix("• ")).collect();
+            return Err(format!("invalid pattern '{}': {}", pattern, problems.join("; ")));
+        }
+    }
+    validate_lengths(pattern.prefix, pattern.suffix)?;
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    match threads {
+        Some(0) => Err("threads must be at least 1".to_string()),
+        Some(threads) => Ok(threads.min(cores)),
+        None => Ok(cores),
+    }
+}
+
+async fn create_job(State(registry): State<JobRegistry>, Json(mut request): Json<JobRequest>) -> Response {
+    if request.pattern.is_some() && (request.prefix.is_some() || request.suffix.is_some()) {
+        return error(StatusCode::BAD_REQUEST, "give either pattern or prefix/suffix".to_string());
+    }
+    request.prefix = request.pattern.take().or(request.prefix);
+    let pattern = Pattern { prefix: request.prefix.as_deref(), suffix: request.suffix.as_deref(), case_sensitive: request.case_sensitive };
+    let threads = match check_search(&pattern, request.threads) {
+        Ok(threads) => threads,
+        Err(error_msg) => return error(StatusCode::BAD_REQUEST, error_msg),
+    };
+    
+    let mut jobs = jobs(&registry);
+    if jobs.values().filter(|job| job.status() == JobStatus::Running).count() >= MAX_RUNNING_JOBS {
+        return error(
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("{} searches are already running; cancel one or try again later", MAX_RUNNING_JOBS),
+        );
+    }
+    if jobs.len() >= MAX_JOBS {
+        return error(
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("{} jobs are waiting to be read; read their results or try again later", MAX_JOBS),
+        );
+    }
+    let job_id = Uuid::new_v4();
+    let job = JobHandle { cancel: CancellationToken::new(), start: Instant::now(), include_secret: request.include_secret, state: Arc::default() };
+    let (cancel, state) = (job.cancel.clone(), job.state.clone());
+    jobs.insert(job_id, job);
+    drop(jobs);
+    tracing::info!(job = %job_id, threads, "job started");
+    
+    tokio::task::spawn_blocking(move || run_search(job_id, request, threads, cancel, state));
+    (StatusCode::ACCEPTED, Json(serde_json::json!({ "job_id": job_id }))).into_response()
+}
+
+// GET /search/{id}: the whole result, key included, once the job is done
+async fn search_status(State(registry): State<JobRegistry>, Path(job_id): Path<Uuid>) -> Response {
+    let mut jobs = jobs(&registry);
+    let Some(job) = jobs.get(&job_id) else {
+        return error(StatusCode::NOT_FOUND, format!("no job {}", job_id));
+    };
+    let status = job.status();
+    let result = job.state.found.get().map(|found| {
+        let secret = job.take_secret();
+        serde_json::json!({
+            "address": found.address,
+            "checksum_address": found.checksum_address,
+            "private_key": secret.as_ref().map(|secret| secret.private_key.as_str()),
+            "mnemonic": secret.as_ref().and_then(|secret| secret.mnemonic.as_ref()).map(|mnemonic| mnemonic.as_str()),
+            "attempts": found.attempts,
+            "elapsed_secs": found.elapsed_secs,
+        })
+    });
+    let response = Json(serde_json::json!({
+        "status": status,
+        "attempts": job.state.attempts.load(Ordering::Relaxed),
+        "result": result,
+    }))
+    .into_response();
+    // An ended job has nothing more to report once read, so it goes, and its key with it
+    if status != JobStatus::Running {
+        jobs.remove(&job_id);
+    }
+    response
+}
+
+// GET /jobs/{id}: progress and the address; the key only for jobs created with include_secret
+async fn job_status(State(registry): State<JobRegistry>, Path(job_id): Path<Uuid>) -> Response {
+    let mut jobs = jobs(&registry);
+    let Some(job) = jobs.get(&job_id) else {
+        return error(StatusCode::NOT_FOUND, format!("no job {}", job_id));
+    };
+    let status = job.status();
+    let attempts = job.state.attempts.load(Ordering::Relaxed);
+    let elapsed = job.state.ended.get().map_or_else(|| job.start.elapsed(), |ended| ended.duration_since(job.start)).as_secs_f64();
+    let found = job.state.found.get();
+    let mut report = serde_json::json!({
+        // /jobs calls a done job found
+        "status": if status == JobStatus::Done { serde_json::json!("found") } else { serde_json::json!(status) },
+        "attempts": attempts,
+        "rate": if elapsed > 0.0 { attempts as f64 / elapsed } else { 0.0 },
+        "elapsed_secs": elapsed,
+        "address": found.map(|found| found.address.as_str()),
+        "checksum_address": found.map(|found| found.checksum_address.as_str()),
+    });
+    let shows_secret = status == JobStatus::Done && job.include_secret;
+    if shows_secret {
+        let secret = job.take_secret().expect("a found job keeps its key until it is dropped");
+        report["private_key"] = secret.private_key.as_str().into();
+        report["mnemonic"] = secret.mnemonic.as_ref().map(|mnemonic| mnemonic.as_str()).into();
+    }
+    // A found job keeps its key for /secret unless this report carried it
+    if shows_secret || status == JobStatus::Cancelled {
+        jobs.remove(&job_id);
+    }
+    Json(report).into_response()
+}
+
+async fn take_secret(State(registry): State<JobRegistry>, Path(job_id): Path<Uuid>) -> Response {
+    let mut jobs = jobs(&registry);
+    let Some(job) = jobs.get(&job_id) else {
+        return error(StatusCode::NOT_FOUND, format!("no job {}", job_id));
+    };
+    if job.status() != JobStatus::Done {
+        return error(StatusCode::CONFLICT, format!("job {} has not found an address", job_id));
+    }
+    let secret = job.take_secret().expect("a found job keeps its key until it is dropped");
+    jobs.remove(&job_id);
+    Json(serde_json::json!({
+        "private_key": secret.private_key.as_str(),
+        "mnemonic": secret.mnemonic.as_ref().map(|mnemonic| mnemonic.as_str()),
+    }))
+    .into_response()
+}
+
+async fn cancel_job(State(registry): State<JobRegistry>, Path(job_id): Path<Uuid>) -> Response {
+    let jobs = jobs(&registry);
+    match jobs.get(&job_id) {
+        Some(job) if job.status() == JobStatus::Done => error(StatusCode::CONFLICT, format!("job {} already found its address", job_id)),
+        Some(job) => {
+            job.cancel.cancel();
+            Json(serde_json::json!({ "status": JobStatus::Cancelled })).into_response()
+        }
+        None => error(StatusCode::NOT_FOUND, format!("no job {}", job_id)),
+    }
+}
+
+fn run_search(job_id: Uuid, request: JobRequest, threads: usize, cancel: CancellationToken, state: Arc<JobState>) {
+    let start = Instant::now();
+    let pattern = Pattern { prefix: request.prefix.as_deref(), suffix: request.suffix.as_deref(), case_sensitive: request.case_sensitive };
+    search(&pattern, threads, &cancel, &state.attempts, |private_key| {
+        let wallet = crate::generate_wallet_info(private_key);
+        // The secret is in place before the job reports itself done
+        let mut secret = state.secret.lock().unwrap();
+        if state.found.get().is_none() {
+            *secret = Some(Secret { private_key: wallet.private_key.clone(), mnemonic: wallet.mnemonic.clone() });
+            let _ = state.found.set(Found {
+                checksum_address: to_checksum_address(&wallet.address),
+                address: wallet.address.clone(),
+                attempts: state.attempts.load(Ordering::Relaxed),
+                elapsed_secs: start.elapsed().as_secs_f64(),
+            });
+        }
+        false
+    });
+    let _ = state.ended.set(Instant::now());
+    tracing::info!(job = %job_id, found = state.found.get().is_some(), "job ended");
+}
+
+pub struct Pattern<'a> {
+    pub prefix: Option<&'a str>,
+    pub suffix: Option<&'a str>,
+    pub case_sensitive: bool,
+}
+
+// The raw-mode loop of the command line search, shared with the gRPC service. Runs until
+// cancelled or `on_match` returns false, which cancels the other workers too.
+pub fn search(pattern: &Pattern, threads: usize, cancel: &CancellationToken, attempts: &AtomicU64, on_match: impl Fn(SecretKey) -> bool + Sync) {
+    let secp = Secp256k1::new();
+    let compiled = CompiledPattern::compile(pattern.prefix, pattern.suffix, pattern.case_sensitive).expect("check_search accepted the pattern");
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let mut rng = ChaCha20Rng::from_entropy();
+                while !cancel.is_cancelled() {
+                    let mut batch = generate_addresses_batch(&secp, &mut rng, None, crate::KEYGEN_BATCH_SIZE);
+                    attempts.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                    for (address, private_key) in &batch {
+                        if compiled.matches(address)
+                            && !cancel.is_cancelled()
+                            && !on_match(*private_key)
+                        {
+                            cancel.cancel();
+                        }
+                    }
+                    for (_, private_key) in &mut batch {
+                        private_key.non_secure_erase();
+                    }
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,236 @@
+// Shamir secret sharing of private keys over GF(256)
+//
+// Each byte of the key is the constant term of its own polynomial of degree
+// threshold - 1, whose other coefficients are drawn uniformly from 0..=255, and
+// share x (1..=N) holds every polynomial evaluated at x. The field is reduced by
+// x^8 + x^4 + x^3 + x^2 + 1, so shares printed before this module did its own
+// arithmetic (with the `sharks` crate) still reconstruct.
+//
+// Each share is hex encoded as `threshold || x || y[32]`. Carrying the
+// threshold in the share lets `reconstruct` refuse to interpolate from too few
+// shares, which would otherwise silently produce a wrong key. A `-` and the
+// first 4 bytes of the Keccak-256 hash of those bytes follow, so a mistyped
+// share is caught instead of interpolated; shares without one are still accepted.
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha3::{Digest, Keccak256};
+use zeroize::Zeroizing;
+
+fn checksum(bytes: &[u8]) -> String {
+    hex::encode(&Keccak256::digest(bytes)[..4])
+}
+
+// Product in GF(256), without branches or table lookups that depend on the (secret) operands
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        a = (a << 1) ^ (0u8.wrapping_sub(a >> 7) & 0x1d);
+        b >>= 1;
+    }
+    product
+}
+
+// a^254, the inverse of a non-zero a
+fn gf_inv(a: u8) -> u8 {
+    let mut inverse = 1;
+    let mut square = a;
+    for bit in 0..8 {
+        if 254 >> bit & 1 == 1 {
+            inverse = gf_mul(inverse, square);
+        }
+        square = gf_mul(square, square);
+    }
+    inverse
+}
+
+pub fn split_private_key(private_key: &[u8; 32], threshold: u8, shares: u8) -> Vec<String> {
+    split(private_key, threshold, shares, &mut OsRng)
+}
+
+fn split<R: RngCore>(private_key: &[u8; 32], threshold: u8, shares: u8, rng: &mut R) -> Vec<String> {
+    // The coefficients of x^1..x^(threshold - 1) of each byte's polynomial, in that order
+    let degree = threshold.saturating_sub(1) as usize;
+    let mut coefficients = Zeroizing::new(vec![0u8; 32 * degree]);
+    rng.fill_bytes(&mut coefficients);
+    
+    (1..=shares)
+        .map(|x| {
+            let mut bytes = Zeroizing::new(Vec::with_capacity(34));
+            bytes.extend_from_slice(&[threshold, x]);
+            for (i, secret) in private_key.iter().enumerate() {
+                // Horner's rule from the highest coefficient down to the secret
+                let y = coefficients[i * degree..(i + 1) * degree].iter().rev().fold(0, |y, &coefficient| gf_mul(y, x) ^ coefficient);
+                bytes.push(gf_mul(y, x) ^ secret);
+            }
+            format!("{}-{}", hex::encode(bytes.as_slice()), checksum(&bytes))
+        })
+        .collect()
+}
+
+pub fn reconstruct_private_key(encoded_shares: &[String]) -> Result<Zeroizing<[u8; 32]>, String> {
+    let mut threshold = None;
+    let mut points: Vec<(u8, Zeroizing<[u8; 32]>)> = Vec::with_capacity(encoded_shares.len());
+    
+    for (i, encoded) in encoded_shares.iter().enumerate() {
+        let (encoded, expected_checksum) = match encoded.trim().split_once('-') {
+            Some((encoded, expected_checksum)) => (encoded, Some(expected_checksum)),
+            None => (encoded.trim(), None),
+        };
+        let bytes = Zeroizing::new(
+            hex::decode(encoded.trim_start_matches("0x"))
+                .map_err(|e| format!("Share {} is not valid hex: {}", i + 1, e))?,
+        );
+        if bytes.len() != 34 {
+            return Err(format!("Share {} has {} bytes, expected 34", i + 1, bytes.len()));
+        }
+        if expected_checksum.is_some_and(|expected| !expected.eq_ignore_ascii_case(&checksum(&bytes))) {
+            return Err(format!("Share {} does not match its checksum; it was mistyped or corrupted", i + 1));
+        }
+        
+        match threshold {
+            None => threshold = Some(bytes[0]),
+            Some(t) if t != bytes[0] => {
+                return Err(format!("Share {} has threshold {} but earlier shares have {}", i + 1, bytes[0], t));
+            }
+            Some(_) => {}
+        }
+        
+        let x = bytes[1];
+        if x == 0 {
+            return Err(format!("Share {} has index 0, which no split produces", i + 1));
+        }
+        let mut y = Zeroizing::new([0u8; 32]);
+        y.copy_from_slice(&bytes[2..]);
+        match points.iter().find(|(other, _)| *other == x) {
+            // The same share given twice counts once
+            Some((_, other)) if *other == y => {}
+            Some(_) => return Err(format!("Share {} has the same index as an earlier, different share", i + 1)),
+            None => points.push((x, y)),
+        }
+    }
+    
+    let threshold = threshold.ok_or("No shares provided")?;
+    if points.len() < threshold as usize {
+        return Err(format!("Need at least {} distinct shares, got {}", threshold, points.len()));
+    }
+    
+    // Lagrange interpolation at x = 0; subtraction in GF(256) is XOR
+    let mut private_key = Zeroizing::new([0u8; 32]);
+    for (i, (x_i, y_i)) in points.iter().enumerate() {
+        let basis = points
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .fold(1, |basis, (_, (x_j, _))| gf_mul(basis, gf_mul(*x_j, gf_inv(x_j ^ x_i))));
+        for (byte, y) in private_key.iter_mut().zip(y_i.iter()) {
+            *byte ^= gf_mul(basis, *y);
+        }
+    }
+    Ok(private_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+    
+    const KEY: [u8; 32] = [
+        0xa1, 0x0d, 0xad, 0x2b, 0x75, 0x2d, 0xdf, 0xbf, 0xa2, 0x75, 0x3d, 0xd3, 0x30, 0xa3, 0x67, 0x46,
+        0x10, 0xb6, 0x2e, 0x19, 0x0b, 0x64, 0x7b, 0x05, 0x0c, 0x55, 0x2c, 0x44, 0x6c, 0xf8, 0x55, 0x5e,
+    ];
+    
+    // Every subset of `shares` with exactly `size` members
+    fn subsets(shares: &[String], size: usize) -> Vec<Vec<String>> {
+        (0u32..1 << shares.len())
+            .filter(|mask| mask.count_ones() as usize == size)
+            .map(|mask| shares.iter().enumerate().filter(|(i, _)| mask >> i & 1 == 1).map(|(_, share)| share.clone()).collect())
+            .collect()
+    }
+    
+    #[test]
+    fn field_inverse() {
+        assert_eq!(gf_mul(0x80, 2), 0x1d);
+        for a in 1..=255 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1, "{}", a);
+        }
+    }
+    
+    #[test]
+    fn every_threshold_subset_reconstructs() {
+        for (threshold, count) in [(1, 1), (1, 3), (2, 2), (2, 3), (3, 5), (4, 6), (6, 6)] {
+            let shares = split_private_key(&KEY, threshold, count);
+            assert_eq!(shares.len(), count as usize);
+            for size in threshold as usize..=count as usize {
+                for subset in subsets(&shares, size) {
+                    assert_eq!(*reconstruct_private_key(&subset).unwrap(), KEY, "{}-of-{}: {:?}", threshold, count, subset);
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn too_few_shares_are_refused() {
+        let shares = split_private_key(&KEY, 3, 5);
+        for subset in subsets(&shares, 2) {
+            assert!(reconstruct_private_key(&subset).unwrap_err().starts_with("Need at least 3 distinct shares"));
+        }
+        let repeated = [shares[0].clone(), shares[1].clone(), shares[1].clone()];
+        assert_eq!(reconstruct_private_key(&repeated).unwrap_err(), "Need at least 3 distinct shares, got 2");
+        assert!(reconstruct_private_key(&[]).is_err());
+    }
+    
+    #[test]
+    fn coefficients_may_be_zero() {
+        // An RNG of zeros gives constant polynomials, so every share carries the key itself;
+        // a dealer that skipped zero coefficients could never produce these shares
+        let shares = split(&KEY, 3, 3, &mut StepRng::new(0, 0));
+        for share in &shares {
+            assert_eq!(&hex::decode(&share[4..68]).unwrap(), &KEY);
+        }
+        assert_eq!(*reconstruct_private_key(&shares).unwrap(), KEY);
+    }
+    
+    #[test]
+    fn reconstructs_shares_split_by_sharks() {
+        let shares = [
+            "0201899011c6eb1ebd4dedcec0ff6914021f92c2332e73d5d5dbfb5f0265f7c0a354-5655c001".to_string(),
+            "0203d9b77401ca7879b473a527a7db67c8ad8b2a094083aa947a084b5e27dcb05240-c955249c".to_string(),
+        ];
+        assert_eq!(*reconstruct_private_key(&shares).unwrap(), KEY);
+    }
+    
+    #[test]
+    fn tampered_shares_fail_the_checksum() {
+        let shares = split_private_key(&KEY, 2, 3);
+        for position in [0, 1, 2, 3, 20, 67] {
+            let mut tampered = shares[0].clone().into_bytes();
+            tampered[position] = if tampered[position] == b'0' { b'1' } else { b'0' };
+            let tampered = [String::from_utf8(tampered).unwrap(), shares[1].clone()];
+            assert_eq!(
+                reconstruct_private_key(&tampered).unwrap_err(),
+                "Share 1 does not match its checksum; it was mistyped or corrupted"
+            );
+        }
+        let (share, checksum) = shares[1].split_once('-').unwrap();
+        let wrong_checksum = format!("{}-{}", share, if checksum == "00000000" { "11111111" } else { "00000000" });
+        assert!(reconstruct_private_key(&[shares[0].clone(), wrong_checksum]).unwrap_err().starts_with("Share 2 does not match"));
+        // The checksum is optional, and case-insensitive
+        let unchecked = [shares[0].split_once('-').unwrap().0.to_string(), shares[2].to_uppercase()];
+        assert_eq!(*reconstruct_private_key(&unchecked).unwrap(), KEY);
+    }
+    
+    #[test]
+    fn mismatched_shares_are_refused() {
+        let two_of_three = split_private_key(&KEY, 2, 3);
+        let three_of_three = split_private_key(&KEY, 3, 3);
+        assert_eq!(
+            reconstruct_private_key(&[two_of_three[0].clone(), three_of_three[1].clone()]).unwrap_err(),
+            "Share 2 has threshold 3 but earlier shares have 2"
+        );
+        let other_split = split_private_key(&[7; 32], 2, 3);
+        assert!(reconstruct_private_key(&[two_of_three[0].clone(), other_split[0].clone()]).unwrap_err().contains("same index"));
+        assert!(reconstruct_private_key(&["0201".to_string()]).unwrap_err().contains("expected 34"));
+    }
+}
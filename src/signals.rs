@@ -0,0 +1,92 @@
+// Signal handling: a graceful stop on SIGINT, SIGTERM and SIGHUP (console
+// events on Windows) and an on-demand status line on SIGUSR1 (Ctrl+Break)
+
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::Arc;
+
+pub const SIGHUP: i32 = 1;
+pub const SIGINT: i32 = 2;
+pub const SIGTERM: i32 = 15;
+
+pub fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        SIGHUP => "SIGHUP",
+        SIGINT => "Ctrl+C",
+        SIGTERM => "SIGTERM",
+        _ => "signal",
+    }
+}
+
+// Conventional exit code of a process stopped by `signal`
+pub fn exit_code(signal: i32) -> i32 {
+    128 + signal
+}
+
+// The first stop signal asks the search to wind down and print its summary;
+// a second one exits straight away
+pub fn stop(running: &AtomicBool, stop_signal: &AtomicI32, signal: i32) {
+    if !running.swap(false, Ordering::SeqCst) {
+        tracing::warn!(signal = signal_name(signal), "second stop signal, exiting immediately");
+        eprintln!("\nReceived {} again, exiting immediately", signal_name(signal));
+        std::process::exit(exit_code(signal));
+    }
+    tracing::info!(signal = signal_name(signal), "stop signal received");
+    stop_signal.store(signal, Ordering::SeqCst);
+    info!("\nReceived {}, shutting down...", signal_name(signal));
+}
+
+#[cfg(unix)]
+pub async fn handle_signals(running: Arc<AtomicBool>, stop_signal: Arc<AtomicI32>, status: impl Fn()) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut interrupt = signal(SignalKind::interrupt()).expect("Failed to listen for SIGINT");
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to listen for SIGTERM");
+    let mut hangup = signal(SignalKind::hangup()).expect("Failed to listen for SIGHUP");
+    let mut user_defined = signal(SignalKind::user_defined1()).expect("Failed to listen for SIGUSR1");
+
+    loop {
+        let received = tokio::select! {
+            _ = interrupt.recv() => SIGINT,
+            _ = terminate.recv() => SIGTERM,
+            _ = hangup.recv() => SIGHUP,
+            _ = user_defined.recv() => {
+                status();
+                continue;
+            }
+        };
+        stop(&running, &stop_signal, received);
+    }
+}
+
+// Closing the console window or shutting down the system is treated like SIGTERM,
+// and Ctrl+Break prints the status line like SIGUSR1
+#[cfg(windows)]
+pub async fn handle_signals(running: Arc<AtomicBool>, stop_signal: Arc<AtomicI32>, status: impl Fn()) {
+    use tokio::signal::windows;
+
+    let mut ctrl_c = windows::ctrl_c().expect("Failed to listen for Ctrl+C");
+    let mut ctrl_break = windows::ctrl_break().expect("Failed to listen for Ctrl+Break");
+    let mut ctrl_close = windows::ctrl_close().expect("Failed to listen for console close events");
+    let mut ctrl_shutdown = windows::ctrl_shutdown().expect("Failed to listen for shutdown events");
+
+    loop {
+        let received = tokio::select! {
+            _ = ctrl_c.recv() => SIGINT,
+            _ = ctrl_close.recv() => SIGTERM,
+            _ = ctrl_shutdown.recv() => SIGTERM,
+            _ = ctrl_break.recv() => {
+                status();
+                continue;
+            }
+        };
+        stop(&running, &stop_signal, received);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub async fn handle_signals(running: Arc<AtomicBool>, stop_signal: Arc<AtomicI32>, _status: impl Fn()) {
+    loop {
+        tokio::signal::ctrl_c().await.expect("Failed to listen for ctrl+c");
+        stop(&running, &stop_signal, SIGINT);
+    }
+}
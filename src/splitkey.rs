@@ -0,0 +1,221 @@
+// Split-key searches: --search-for-pubkey finds an offset k such that P + k·G has a vanity
+// address, for someone who only shares the public key P of a private key a. The owner adds
+// k to a with `combine`, so whoever searched never learns the final private key a + k.
+
+use crate::keygen::address_bytes_from_public_key;
+use crate::pattern::{BitMask, CompiledPattern, PatternEntry};
+use rand::rngs::OsRng;
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Accepts 0x04 || x || y with or without the 04 byte, or a compressed key
+pub fn parse_public_key(value: &str) -> Result<PublicKey, String> {
+    let digits = value.trim().strip_prefix("0x").unwrap_or(value.trim());
+    let bytes = hex::decode(digits).map_err(|_| "expected a public key in hex".to_string())?;
+    let bytes = if bytes.len() == 64 { [&[0x04], bytes.as_slice()].concat() } else { bytes };
+    if bytes.len() != 65 && bytes.len() != 33 {
+        return Err(format!("expected 130 hex characters (04 followed by x and y), got {}", digits.len()));
+    }
+    PublicKey::from_slice(&bytes).map_err(|_| "not a point on the secp256k1 curve".to_string())
+}
+
+// a + k modulo the curve order; fails only if the sum is zero
+pub fn combine(private_key: &SecretKey, partial_key: &SecretKey) -> Result<SecretKey, secp256k1::Error> {
+    private_key.add_tweak(&Scalar::from(*partial_key))
+}
+
+pub struct SplitKeySearch {
+    pub public_key: PublicKey,
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    pub case_sensitive: bool,
+    pub bit_mask: Option<BitMask>,
+    pub leading_zero_bytes: usize,
+}
+
+impl SplitKeySearch {
+    fn matches(&self, pattern: &CompiledPattern, address: &[u8; 20]) -> bool {
+        address[..self.leading_zero_bytes].iter().all(|&byte| byte == 0)
+            && self.bit_mask.is_none_or(|bit_mask| bit_mask.matches(address))
+            && pattern.matches(address)
+    }
+}
+
+// A random offset and P + offset·G to count up from
+fn random_start(secp: &Secp256k1<secp256k1::All>, public_key: &PublicKey) -> (SecretKey, PublicKey) {
+    loop {
+        let offset = SecretKey::new(&mut OsRng);
+        // Only fails when the offset is the negated private key
+        if let Ok(point) = public_key.combine(&PublicKey::from_secret_key(secp, &offset)) {
+            return (offset, point);
+        }
+    }
+}
+
+// Searches until `count` partial keys are found; each thread adds G to its point, one
+// point addition per candidate instead of a full scalar multiplication
+pub fn run(search: &SplitKeySearch, threads: usize, count: u64, json_output: bool, quiet: bool) -> Result<(), Box<dyn std::error::Error>> {
+    info!("🧩 Searching P + k·G for P = 0x{}", hex::encode(search.public_key.serialize_uncompressed()));
+    info!("Pattern: {}", PatternEntry::new(search.prefix.clone(), search.suffix.clone()));
+    let pattern = CompiledPattern::compile(search.prefix.as_deref(), search.suffix.as_deref(), search.case_sensitive)?;
+    if search.leading_zero_bytes > 0 {
+        info!("Leading zero bytes: {}", search.leading_zero_bytes);
+    }
+    if let Some(bit_mask) = &search.bit_mask {
+        info!("Mask: 0x{} == 0x{} ({} bits)", hex::encode(bit_mask.mask), hex::encode(bit_mask.value), bit_mask.bits());
+    }
+    info!("Threads: {}", threads);
+    info!("Press Ctrl+C to stop\n");
+    let secp = Secp256k1::new();
+    let generator = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&Scalar::ONE.to_be_bytes()).expect("one is a valid key"));
+    let attempts = AtomicU64::new(0);
+    let found = Mutex::new(Vec::new());
+    let done = AtomicBool::new(false);
+    let start_time = Instant::now();
+    
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let (mut offset, mut point) = random_start(&secp, &search.public_key);
+                while !done.load(Ordering::Relaxed) {
+                    for _ in 0..crate::KEYGEN_BATCH_SIZE {
+                        let address = address_bytes_from_public_key(&point);
+                        if search.matches(&pattern, &address) {
+                            let mut found = found.lock().unwrap();
+                            if (found.len() as u64) < count {
+                                found.push((offset, address));
+                            }
+                            if found.len() as u64 >= count {
+                                done.store(true, Ordering::Relaxed);
+                            }
+                        }
+                        match (offset.add_tweak(&Scalar::ONE), point.combine(&generator)) {
+                            (Ok(next_offset), Ok(next_point)) => (offset, point) = (next_offset, next_point),
+                            _ => (offset, point) = random_start(&secp, &search.public_key),
+                        }
+                    }
+                    attempts.fetch_add(crate::KEYGEN_BATCH_SIZE as u64, Ordering::Relaxed);
+                }
+            });
+        }
+        
+        scope.spawn(|| {
+            let mut next_console = start_time + Duration::from_secs(5);
+            while !done.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(100));
+                if Instant::now() >= next_console {
+                    let elapsed = start_time.elapsed();
+                    let points = attempts.load(Ordering::Relaxed);
+                    progress!("⏳ Points: {} | Rate: {} | Elapsed: {}",
+                              points, crate::output::rate(points as f64 / elapsed.as_secs_f64()), crate::output::elapsed(elapsed));
+                    next_console += Duration::from_secs(5);
+                }
+            }
+        });
+    });
+    
+    let final_attempts = attempts.load(Ordering::Relaxed);
+    let elapsed = start_time.elapsed();
+    let found = found.into_inner().unwrap();
+    if !quiet {
+        success!("🎉 Found {} partial key{} after {} points in {:.2?}!", found.len(), if found.len() == 1 { "" } else { "s" }, final_attempts, elapsed);
+    }
+    
+    let prefix_len = search.prefix.as_deref().map_or(0, crate::pattern_len).max(2 * search.leading_zero_bytes);
+    let suffix_len = search.suffix.as_deref().map_or(0, crate::pattern_len);
+    let mut json_results = Vec::new();
+    for (i, (offset, address)) in found.iter().enumerate() {
+        let object = serde_json::json!({
+            "address": crate::format_address(address),
+            "partial_key": format!("0x{}", hex::encode(offset.secret_bytes())),
+            "public_key": format!("0x{}", hex::encode(search.public_key.serialize_uncompressed())),
+        });
+        let field = |key: &str| object[key].as_str().unwrap_or_default().to_string();
+        if json_output && quiet {
+            println!("{}", object);
+        } else if quiet {
+            if i > 0 {
+                println!();
+            }
+            for key in ["partial_key", "address"] {
+                println!("{}={}", key, field(key));
+            }
+        } else if !json_output {
+            if found.len() > 1 {
+                println!("\n── Match {} of {} ──", i + 1, found.len());
+            }
+            println!("📍 Address: {}", crate::output::address(&field("address"), prefix_len, suffix_len));
+            println!("🧩 Partial Key: {}", field("partial_key"));
+        }
+        json_results.push(object);
+    }
+    if json_output && !quiet {
+        println!("{}", serde_json::to_string_pretty(&json_results).expect("JSON values serialize"));
+    }
+    if !quiet && !json_output {
+        println!("\nThe owner of the private key recovers the final key with:");
+        println!("  evm-vanity combine --private-key <KEY> --partial-key <PARTIAL_KEY> --address <ADDRESS>");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keygen::address_bytes_from_secret_key;
+    
+    fn secret_key(value: &str) -> SecretKey {
+        SecretKey::from_slice(&hex::decode(value).unwrap()).unwrap()
+    }
+    
+    // The address a searcher reports for partial key k: that of P + k·G
+    fn searched_address(secp: &Secp256k1<secp256k1::All>, public_key: &PublicKey, partial_key: &SecretKey) -> [u8; 20] {
+        address_bytes_from_public_key(&public_key.combine(&PublicKey::from_secret_key(secp, partial_key)).unwrap())
+    }
+    
+    #[test]
+    fn combined_key_controls_the_searched_address() {
+        let secp = Secp256k1::new();
+        for _ in 0..8 {
+            let private_key = SecretKey::new(&mut OsRng);
+            let public_key = PublicKey::from_secret_key(&secp, &private_key);
+            let (partial_key, point) = random_start(&secp, &public_key);
+            assert_eq!(address_bytes_from_public_key(&point), searched_address(&secp, &public_key, &partial_key));
+            
+            let combined = combine(&private_key, &partial_key).unwrap();
+            assert_eq!(address_bytes_from_secret_key(&secp, &combined), address_bytes_from_public_key(&point));
+        }
+    }
+    
+    #[test]
+    fn combined_key_wraps_past_the_curve_order() {
+        let secp = Secp256k1::new();
+        // n - 1 and 2 add up to n + 1, which is the key 1
+        let private_key = secret_key("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140");
+        let partial_key = secret_key("0000000000000000000000000000000000000000000000000000000000000002");
+        let combined = combine(&private_key, &partial_key).unwrap();
+        assert_eq!(combined, secret_key("0000000000000000000000000000000000000000000000000000000000000001"));
+        
+        let address = searched_address(&secp, &PublicKey::from_secret_key(&secp, &private_key), &partial_key);
+        assert_eq!(hex::encode(address), "7e5f4552091a69125d5dfcb7b8c2659029395bdf");
+        assert_eq!(address_bytes_from_secret_key(&secp, &combined), address);
+        
+        // A sum of exactly n is no key at all
+        assert!(combine(&private_key, &secret_key("0000000000000000000000000000000000000000000000000000000000000001")).is_err());
+    }
+    
+    #[test]
+    fn public_keys_parse_in_every_encoding() {
+        let secp = Secp256k1::new();
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key("0000000000000000000000000000000000000000000000000000000000000001"));
+        let uncompressed = hex::encode(public_key.serialize_uncompressed());
+        for value in [uncompressed.clone(), format!("0x{}", uncompressed), uncompressed[2..].to_string(), hex::encode(public_key.serialize())] {
+            assert_eq!(parse_public_key(&value), Ok(public_key), "{}", value);
+        }
+        assert!(parse_public_key("04abcd").unwrap_err().starts_with("expected 130 hex characters"));
+        assert_eq!(parse_public_key(&format!("04{}", "00".repeat(64))).unwrap_err(), "not a point on the secp256k1 curve");
+        assert!(parse_public_key("not hex").is_err());
+    }
+}
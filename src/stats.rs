@@ -0,0 +1,76 @@
+// CSV log of search progress for graphing hash rate over long runs
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const HEADER: &str = "unix_timestamp,attempts,rate,smoothed_rate,elapsed_secs,threads";
+
+// Time constant of the exponential moving average, in seconds
+const SMOOTHING_SECS: f64 = 60.0;
+
+pub struct StatsCsv {
+    writer: BufWriter<File>,
+    threads: usize,
+    last_attempts: u64,
+    last_time: Instant,
+    smoothed_rate: Option<f64>,
+}
+
+impl StatsCsv {
+    /// Opens `path` for appending, writing the header only if the file is new or empty
+    pub fn open(path: &Path, threads: usize, start_time: Instant) -> std::io::Result<StatsCsv> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_empty = file.metadata()?.len() == 0;
+
+        let mut writer = BufWriter::new(file);
+        if is_empty {
+            writeln!(writer, "{}", HEADER)?;
+            writer.flush()?;
+        }
+
+        Ok(StatsCsv {
+            writer,
+            threads,
+            last_attempts: 0,
+            last_time: start_time,
+            smoothed_rate: None,
+        })
+    }
+
+    pub fn record(&mut self, attempts: u64, now: Instant, start_time: Instant) -> std::io::Result<()> {
+        let dt = now.duration_since(self.last_time).as_secs_f64();
+        let rate = if dt > 0.0 {
+            attempts.saturating_sub(self.last_attempts) as f64 / dt
+        } else {
+            0.0
+        };
+
+        let smoothed = match self.smoothed_rate {
+            Some(previous) => {
+                let alpha = 1.0 - (-dt / SMOOTHING_SECS).exp();
+                previous + alpha * (rate - previous)
+            }
+            None => rate,
+        };
+        self.smoothed_rate = Some(smoothed);
+        self.last_attempts = attempts;
+        self.last_time = now;
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        writeln!(
+            self.writer,
+            "{},{},{:.1},{:.1},{:.3},{}",
+            timestamp,
+            attempts,
+            rate,
+            smoothed,
+            now.duration_since(start_time).as_secs_f64(),
+            self.threads
+        )?;
+
+        // Flush every row so a crash loses at most one interval
+        self.writer.flush()
+    }
+}
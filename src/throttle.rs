@@ -0,0 +1,142 @@
+// Background-friendly searching: lower scheduling priority and a CPU usage cap, plus the
+// token bucket that keeps --verbose from flooding stderr
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Lower the priority of the calling thread. On Linux `setpriority` applies to
+// the calling thread, elsewhere on Unix to the whole process.
+#[cfg(unix)]
+pub fn lower_thread_priority() -> Result<(), String> {
+    // Same niceness as the `nice` command uses by default
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, 10) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(windows)]
+pub fn lower_thread_priority() -> Result<(), String> {
+    use windows_sys::Win32::System::Threading::{GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_LOWEST};
+
+    if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_LOWEST) } != 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn lower_thread_priority() -> Result<(), String> {
+    Err("lowering the priority is not supported on this platform".to_string())
+}
+
+// A stretch of work longer than this was interrupted (paused or starved), so only this
+// much of it counts as compute time
+const MAX_STRETCH: Duration = Duration::from_secs(1);
+
+// Holds a worker to `target_frac` of a core: after each stretch of work it sleeps
+// compute_time * (1 - target_frac) / target_frac, so the work is that share of the time
+pub struct CpuLimiter {
+    target_frac: f64,
+    last_check: Instant,
+}
+
+impl CpuLimiter {
+    pub fn new(percent: u8) -> CpuLimiter {
+        CpuLimiter {
+            target_frac: f64::from(percent) / 100.0,
+            last_check: Instant::now(),
+        }
+    }
+
+    // Call after every few hundred units of work; sleeps off the time they took
+    pub fn throttle(&mut self, iterations_since_last: u64) {
+        if iterations_since_last == 0 {
+            return;
+        }
+        let compute_time = self.last_check.elapsed().min(MAX_STRETCH);
+        thread::sleep(compute_time.mul_f64((1.0 - self.target_frac) / self.target_frac));
+        self.last_check = Instant::now();
+    }
+}
+
+// Allows `rate` events per second on average, in bursts of up to `rate`
+pub struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: u32) -> TokenBucket {
+        TokenBucket {
+            rate: f64::from(rate),
+            tokens: f64::from(rate),
+            last_refill: Instant::now(),
+        }
+    }
+
+    // Takes a token if one is left; events without one are dropped, not delayed
+    pub fn try_take(&mut self) -> bool {
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.last_refill).as_secs_f64() * self.rate).min(self.rate);
+        self.last_refill = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // How long `throttle` sleeps after a stretch of work of `compute_time`
+    fn sleep_after(percent: u8, compute_time: Duration) -> Duration {
+        let mut limiter = CpuLimiter::new(percent);
+        limiter.last_check = Instant::now() - compute_time;
+        let start = Instant::now();
+        limiter.throttle(1000);
+        start.elapsed()
+    }
+
+    #[test]
+    fn work_is_the_target_share_of_the_time() {
+        for (percent, sleep) in [(50, 100), (25, 300), (80, 25)] {
+            let slept = sleep_after(percent, Duration::from_millis(100));
+            assert!(slept >= Duration::from_millis(sleep), "{}%: {:?}", percent, slept);
+            assert!(slept < Duration::from_millis(sleep + 250), "{}%: {:?}", percent, slept);
+        }
+    }
+
+    #[test]
+    fn interrupted_stretches_count_a_second_at_most() {
+        let slept = sleep_after(50, Duration::from_secs(30));
+        assert!(slept >= MAX_STRETCH && slept < MAX_STRETCH + Duration::from_millis(500), "{:?}", slept);
+    }
+
+    #[test]
+    fn no_work_means_no_sleep() {
+        let mut limiter = CpuLimiter::new(10);
+        limiter.last_check = Instant::now() - Duration::from_secs(1);
+        let start = Instant::now();
+        limiter.throttle(0);
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn tokens_refill_at_the_rate_up_to_a_burst() {
+        let mut bucket = TokenBucket::new(3);
+        assert_eq!((0..5).filter(|_| bucket.try_take()).count(), 3);
+        // Ten seconds refill no more than a burst
+        bucket.last_refill -= Duration::from_secs(10);
+        assert_eq!((0..5).filter(|_| bucket.try_take()).count(), 3);
+        // Half a second refills one and a half tokens
+        bucket.last_refill -= Duration::from_millis(500);
+        assert_eq!((0..5).filter(|_| bucket.try_take()).count(), 1);
+    }
+}
@@ -0,0 +1,169 @@
+// --tui: a full-screen dashboard on the alternate screen in place of the scrolling progress
+// lines. It is drawn with crossterm and closes before the results are printed, so the found
+// wallet lands on the normal screen.
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, style, terminal};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+// One rate sample every SAMPLE_SECS, an hour of them at most
+const SAMPLE_SECS: u64 = 10;
+const SAMPLES: usize = 360;
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub enum Action {
+    Quit,
+    Pause,
+    Snapshot,
+}
+
+// What the progress thread knows about the search at one redraw
+pub struct Frame<'a> {
+    pub label: &'a str,
+    pub attempts: u64,
+    pub per_thread: &'a [u64],
+    pub elapsed: Duration,
+    // Expected attempts per match; None for best-effort modes
+    pub difficulty: Option<f64>,
+    // Matches wanted; None when streaming
+    pub count: Option<u64>,
+    pub matches: u64,
+    pub paused: bool,
+    pub finds_title: &'a str,
+    pub finds: &'a [String],
+}
+
+pub struct Dashboard {
+    samples: VecDeque<f64>,
+    last_sample: (Instant, u64),
+    last_draw: (Instant, Vec<u64>),
+    pub message: Option<String>,
+}
+
+fn rate_text(rate: f64) -> String {
+    format!("{} addr/sec", crate::format_with_commas(rate as u64))
+}
+
+impl Dashboard {
+    pub fn open(threads: usize) -> std::io::Result<Dashboard> {
+        terminal::enable_raw_mode()?;
+        execute!(std::io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        let now = Instant::now();
+        Ok(Dashboard { samples: VecDeque::new(), last_sample: (now, 0), last_draw: (now, vec![0; threads]), message: None })
+    }
+    
+    // Keys pressed since the last call. Raw mode swallows Ctrl+C, so it quits like `q`.
+    pub fn actions(&self) -> Vec<Action> {
+        let mut actions = Vec::new();
+        while event::poll(Duration::ZERO).unwrap_or(false) {
+            let Ok(Event::Key(key)) = event::read() else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => actions.push(Action::Quit),
+                KeyCode::Char('q') | KeyCode::Esc => actions.push(Action::Quit),
+                KeyCode::Char('p') => actions.push(Action::Pause),
+                KeyCode::Char('s') => actions.push(Action::Snapshot),
+                _ => {}
+            }
+        }
+        actions
+    }
+    
+    // Rates averaged into at most `width` bars
+    fn graph(&self, width: usize) -> (String, f64) {
+        if self.samples.is_empty() || width == 0 {
+            return (String::new(), 0.0);
+        }
+        let group = self.samples.len().div_ceil(width);
+        let bars: Vec<f64> = self.samples.iter().copied().collect::<Vec<_>>().chunks(group).map(|chunk| chunk.iter().sum::<f64>() / chunk.len() as f64).collect();
+        let max = bars.iter().copied().fold(0.0, f64::max);
+        let line = bars
+            .iter()
+            .map(|&rate| if max > 0.0 { BARS[((rate / max) * (BARS.len() - 1) as f64).round() as usize] } else { BARS[0] })
+            .collect();
+        (line, max)
+    }
+    
+    pub fn draw(&mut self, frame: &Frame) -> std::io::Result<()> {
+        let now = Instant::now();
+        if now.duration_since(self.last_sample.0) >= Duration::from_secs(SAMPLE_SECS) {
+            let secs = now.duration_since(self.last_sample.0).as_secs_f64();
+            self.samples.push_back(frame.attempts.saturating_sub(self.last_sample.1) as f64 / secs);
+            if self.samples.len() > SAMPLES {
+                self.samples.pop_front();
+            }
+            self.last_sample = (now, frame.attempts);
+        }
+        let secs = now.duration_since(self.last_draw.0).as_secs_f64().max(1e-3);
+        let thread_rates: Vec<f64> = frame.per_thread.iter().zip(&self.last_draw.1).map(|(&now, &before)| now.saturating_sub(before) as f64 / secs).collect();
+        self.last_draw = (now, frame.per_thread.to_vec());
+        let rate: f64 = thread_rates.iter().sum();
+        // Some pseudo-terminals report no size at all
+        let columns = match terminal::size() {
+            Ok((columns, _)) if columns > 0 => columns,
+            _ => 80,
+        };
+        
+        let mut lines = vec![
+            format!("evm-vanity: {}{}", frame.label, if frame.paused { "   [PAUSED]" } else { "" }),
+            String::new(),
+            format!("Attempts  {:<24}Elapsed  {}", crate::format_with_commas(frame.attempts), crate::format_duration_estimate(frame.elapsed.as_secs_f64())),
+            match frame.count {
+                Some(count) => format!("Rate      {:<24}Matches  {} of {}", rate_text(rate), frame.matches, count),
+                None => format!("Rate      {:<24}Matches  {}", rate_text(rate), frame.matches),
+            },
+        ];
+        if let Some((difficulty, count)) = frame.difficulty.zip(frame.count) {
+            let chance = crate::odds::chance_within(frame.attempts as f64, difficulty, count);
+            lines.push(format!("Chance    {:.1}% of searches would have finished by now", chance * 100.0));
+            let remaining = difficulty * count.saturating_sub(frame.matches) as f64;
+            if rate > 0.0 {
+                lines.push(format!("ETA       {} expected at this rate", crate::format_duration_estimate(remaining / rate)));
+            }
+        }
+        lines.push(String::new());
+        let (graph, max) = self.graph(columns.saturating_sub(2) as usize);
+        lines.push(format!("Rate over the last hour (peak {})", rate_text(max)));
+        lines.push(if graph.is_empty() { format!("(first sample after {}s)", SAMPLE_SECS) } else { graph });
+        lines.push(String::new());
+        lines.push("Threads".to_string());
+        for (row, chunk) in thread_rates.chunks(4).enumerate() {
+            let cells: Vec<String> = chunk.iter().enumerate().map(|(i, &rate)| format!("#{:<3} {:<20}", row * 4 + i, rate_text(rate))).collect();
+            lines.push(cells.concat());
+        }
+        if !frame.finds.is_empty() {
+            lines.push(String::new());
+            lines.push(frame.finds_title.to_string());
+            lines.extend(frame.finds.iter().cloned());
+        }
+        lines.push(String::new());
+        lines.push("q quit   p pause/resume   s write a stats snapshot".to_string());
+        if let Some(message) = &self.message {
+            lines.push(message.clone());
+        }
+        
+        let mut stdout = std::io::stdout().lock();
+        queue!(stdout, terminal::Clear(terminal::ClearType::All))?;
+        for (row, line) in lines.iter().enumerate() {
+            let line: String = line.chars().take(columns as usize).collect();
+            queue!(stdout, cursor::MoveTo(0, row as u16), style::Print(line))?;
+        }
+        stdout.flush()
+    }
+    
+    pub fn close(self) {
+        restore();
+    }
+}
+
+// Back to the normal screen; also called before exiting with the dashboard still open
+pub fn restore() {
+    let _ = execute!(std::io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+}
@@ -0,0 +1,51 @@
+// --threads auto-tune: benchmark a few thread counts and keep the fastest.
+// The choice is cached per CPU model in ~/.evm-vanity-threads.json.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// Physical cores, logical cores and one less than logical, which leaves a core to the system
+pub fn candidates() -> Vec<usize> {
+    let logical = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut candidates = vec![num_cpus::get_physical(), logical, logical - 1];
+    candidates.retain(|&threads| threads > 0);
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+// The CPU model and logical core count, which together decide the best thread count
+pub fn cpu_key() -> String {
+    let logical = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let model = std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|cpuinfo| {
+            cpuinfo
+                .lines()
+                .find_map(|line| line.strip_prefix("model name")?.split_once(':').map(|(_, model)| model.trim().to_string()))
+        })
+        .unwrap_or_else(|| std::env::consts::ARCH.to_string());
+    format!("{} ({} logical {})", model, logical, if logical == 1 { "core" } else { "cores" })
+}
+
+pub fn cache_path() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(Path::new(&home).join(".evm-vanity-threads.json"))
+}
+
+fn read_cache(path: &Path) -> BTreeMap<String, usize> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+pub fn cached(path: &Path, cpu_key: &str) -> Option<usize> {
+    read_cache(path).get(cpu_key).copied().filter(|&threads| threads > 0)
+}
+
+pub fn store(path: &Path, cpu_key: &str, threads: usize) -> std::io::Result<()> {
+    let mut cache = read_cache(path);
+    cache.insert(cpu_key.to_string(), threads);
+    std::fs::write(path, serde_json::to_string_pretty(&cache).expect("JSON values serialize"))
+}
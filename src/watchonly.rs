@@ -0,0 +1,151 @@
+// --watch-only: searches that never keep a private key. Each key only lives for the scalar
+// multiplication that gives its public key and is wiped before the address is even hashed,
+// so a match is the address and compressed public key alone.
+
+use crate::keygen::address_bytes_from_public_key;
+use crate::pattern::{BitMask, CompiledPattern, PatternEntry};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zeroize::{Zeroize, Zeroizing};
+
+// Deliberately without private_key or mnemonic fields
+pub struct WatchOnlyWallet {
+    pub address: String,
+    pub public_key_compressed: String,
+}
+
+pub struct WatchOnlySearch {
+    pub prefix: Option<String>,
+    pub suffix: Option<String>,
+    pub case_sensitive: bool,
+    pub bit_mask: Option<BitMask>,
+    pub leading_zero_bytes: usize,
+}
+
+impl WatchOnlySearch {
+    fn matches(&self, pattern: &CompiledPattern, address: &[u8; 20]) -> bool {
+        address[..self.leading_zero_bytes].iter().all(|&byte| byte == 0)
+            && self.bit_mask.is_none_or(|bit_mask| bit_mask.matches(address))
+            && pattern.matches(address)
+    }
+}
+
+// A public key whose private key is already wiped. Keys come straight from the OS, since
+// a seeded RNG's state would be enough to regenerate them.
+fn random_public_key(secp: &Secp256k1<secp256k1::All>) -> PublicKey {
+    let mut key_bytes = Zeroizing::new([0u8; 32]);
+    loop {
+        OsRng.fill_bytes(key_bytes.as_mut());
+        // Only fails for zero or a value above the curve order
+        if let Ok(mut private_key) = SecretKey::from_slice(key_bytes.as_ref()) {
+            let public_key = PublicKey::from_secret_key(secp, &private_key);
+            private_key.non_secure_erase();
+            key_bytes.zeroize();
+            return public_key;
+        }
+    }
+}
+
+// Searches until `count` addresses are found
+pub fn run(search: &WatchOnlySearch, threads: usize, count: u64, json_output: bool, quiet: bool) -> Result<(), Box<dyn std::error::Error>> {
+    info!("👀 Watch-only search: private keys are wiped as soon as their public key is derived");
+    info!("Pattern: {}", PatternEntry::new(search.prefix.clone(), search.suffix.clone()));
+    let pattern = CompiledPattern::compile(search.prefix.as_deref(), search.suffix.as_deref(), search.case_sensitive)?;
+    if search.leading_zero_bytes > 0 {
+        info!("Leading zero bytes: {}", search.leading_zero_bytes);
+    }
+    if let Some(bit_mask) = &search.bit_mask {
+        info!("Mask: 0x{} == 0x{} ({} bits)", hex::encode(bit_mask.mask), hex::encode(bit_mask.value), bit_mask.bits());
+    }
+    info!("Threads: {}", threads);
+    info!("Press Ctrl+C to stop\n");
+    let secp = Secp256k1::new();
+    let attempts = AtomicU64::new(0);
+    let found = Mutex::new(Vec::new());
+    let done = AtomicBool::new(false);
+    let start_time = Instant::now();
+    
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    for _ in 0..crate::KEYGEN_BATCH_SIZE {
+                        let public_key = random_public_key(&secp);
+                        let address = address_bytes_from_public_key(&public_key);
+                        if search.matches(&pattern, &address) {
+                            let mut found = found.lock().unwrap();
+                            if (found.len() as u64) < count {
+                                found.push(WatchOnlyWallet {
+                                    address: crate::format_address(&address),
+                                    public_key_compressed: format!("0x{}", hex::encode(public_key.serialize())),
+                                });
+                            }
+                            if found.len() as u64 >= count {
+                                done.store(true, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                    attempts.fetch_add(crate::KEYGEN_BATCH_SIZE as u64, Ordering::Relaxed);
+                }
+            });
+        }
+        
+        scope.spawn(|| {
+            let mut next_console = start_time + Duration::from_secs(5);
+            while !done.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(100));
+                if Instant::now() >= next_console {
+                    let elapsed = start_time.elapsed();
+                    let keys = attempts.load(Ordering::Relaxed);
+                    progress!("⏳ Attempts: {} | Rate: {} | Elapsed: {}",
+                              keys, crate::output::rate(keys as f64 / elapsed.as_secs_f64()), crate::output::elapsed(elapsed));
+                    next_console += Duration::from_secs(5);
+                }
+            }
+        });
+    });
+    
+    let final_attempts = attempts.load(Ordering::Relaxed);
+    let elapsed = start_time.elapsed();
+    let found = found.into_inner().unwrap();
+    if !quiet {
+        success!("🎉 Found {} watch-only address{} after {} attempts in {:.2?}!", found.len(), if found.len() == 1 { "" } else { "es" }, final_attempts, elapsed);
+    }
+    
+    let prefix_len = search.prefix.as_deref().map_or(0, crate::pattern_len).max(2 * search.leading_zero_bytes);
+    let suffix_len = search.suffix.as_deref().map_or(0, crate::pattern_len);
+    let mut json_results = Vec::new();
+    for (i, wallet) in found.iter().enumerate() {
+        let object = serde_json::json!({
+            "address": wallet.address,
+            "public_key_compressed": wallet.public_key_compressed,
+        });
+        if json_output && quiet {
+            println!("{}", object);
+        } else if quiet {
+            if i > 0 {
+                println!();
+            }
+            println!("address={}", wallet.address);
+            println!("public_key_compressed={}", wallet.public_key_compressed);
+        } else if !json_output {
+            if found.len() > 1 {
+                println!("\n── Match {} of {} ──", i + 1, found.len());
+            }
+            println!("📍 Address: {}", crate::output::address(&wallet.address, prefix_len, suffix_len));
+            println!("🔑 Public Key (compressed): {}", wallet.public_key_compressed);
+        }
+        json_results.push(object);
+    }
+    if json_output && !quiet {
+        println!("{}", serde_json::to_string_pretty(&json_results).expect("JSON values serialize"));
+    }
+    if !quiet && !json_output {
+        warning!("\n⚠️  No private key was kept: funds sent to these addresses can never be spent.");
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+// Hex-leet spelling of words for vanity prefixes (o -> 0, s -> 5, ...)
+
+// Characters each letter may be written as; letters a-f can also stay themselves
+fn substitutes(c: char) -> Option<&'static [char]> {
+    let options: &'static [char] = match c.to_ascii_lowercase() {
+        'a' => &['a', '4'],
+        'b' => &['b', '8'],
+        'c' => &['c'],
+        'd' => &['d'],
+        'e' => &['e', '3'],
+        'f' => &['f'],
+        'g' => &['9', '6'],
+        'i' | 'l' => &['1'],
+        'o' => &['0'],
+        'q' => &['9'],
+        's' => &['5'],
+        't' => &['7'],
+        'z' => &['2'],
+        '0'..='9' => return Some(&DIGITS[c as usize - '0' as usize..][..1]),
+        _ => return None,
+    };
+    Some(options)
+}
+
+const DIGITS: [char; 10] = ['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'];
+
+// Keeps the search (and the candidate list we print) manageable
+const MAX_CANDIDATES: usize = 256;
+
+/// All hex patterns spelling `word`. Letters that stay letters keep the case
+/// they were typed in, so `--case-sensitive` applies to them as usual.
+pub fn leet_candidates(word: &str) -> Result<Vec<String>, String> {
+    if word.is_empty() {
+        return Err("--word must not be empty".to_string());
+    }
+
+    let mut unsupported: Vec<char> = word.chars().filter(|&c| substitutes(c).is_none()).collect();
+    unsupported.sort_unstable();
+    unsupported.dedup();
+    if !unsupported.is_empty() {
+        let mut error_msg = String::from("❌ These characters have no hex look-alike:\n");
+        for c in &unsupported {
+            error_msg.push_str(&format!("  • '{}'\n", c));
+        }
+        error_msg.push_str("\n💡 Supported letters: a b c d e f g i l o q s t z (and digits)");
+        return Err(error_msg);
+    }
+
+    let mut candidates = vec![String::new()];
+    for c in word.chars() {
+        let options = substitutes(c).expect("checked above");
+        candidates = candidates
+            .iter()
+            .flat_map(|candidate| {
+                options.iter().map(move |&option| {
+                    let mut next = candidate.clone();
+                    // Keep the typed case for letters that remain letters
+                    next.push(if c.is_ascii_uppercase() { option.to_ascii_uppercase() } else { option });
+                    next
+                })
+            })
+            .collect();
+
+        if candidates.len() > MAX_CANDIDATES {
+            return Err(format!(
+                "❌ '{}' has more than {} hex spellings; use a shorter word or an explicit --prefix",
+                word, MAX_CANDIDATES
+            ));
+        }
+    }
+
+    Ok(candidates)
+}
@@ -0,0 +1,190 @@
+// --api serves the REST API of `serve`, behind a bearer token, with /jobs routes that keep
+// the key back until it is asked for
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const TOKEN: &str = "test-token";
+
+// The API process, killed when the test ends whether it passed or not
+struct Api {
+    child: Child,
+    address: SocketAddr,
+}
+
+impl Api {
+    fn start() -> Api {
+        // A port that was free a moment ago
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+            .args(["--api", &address.to_string(), "--api-token", TOKEN])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start evm-vanity");
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut banner = String::new();
+        while !banner.contains("Serving the search API") {
+            if stdout.read_line(&mut banner).unwrap() == 0 {
+                panic!("evm-vanity exited before serving the API:\n{}", banner);
+            }
+        }
+        Api { child, address }
+    }
+
+    // The status code and JSON body of a request, sent with the token unless `token` is None
+    fn request(&self, method: &str, path: &str, token: Option<&str>, body: Option<&str>) -> (u16, serde_json::Value) {
+        let mut connection = TcpStream::connect(self.address).unwrap();
+        let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", method, path, self.address);
+        if let Some(token) = token {
+            request += &format!("Authorization: Bearer {}\r\n", token);
+        }
+        let body = body.unwrap_or_default();
+        if !body.is_empty() {
+            request += "Content-Type: application/json\r\n";
+        }
+        request += &format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+        connection.write_all(request.as_bytes()).unwrap();
+
+        let mut response = String::new();
+        connection.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap_or(serde_json::Value::Null))
+    }
+
+    fn get(&self, path: &str) -> (u16, serde_json::Value) {
+        self.request("GET", path, Some(TOKEN), None)
+    }
+
+    fn create(&self, body: &str) -> String {
+        let (status, response) = self.request("POST", "/jobs", Some(TOKEN), Some(body));
+        assert_eq!(status, 202, "{}", response);
+        response["job_id"].as_str().unwrap().to_string()
+    }
+
+    // The status of the job once it is no longer running
+    fn wait(&self, job_id: &str) -> serde_json::Value {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        loop {
+            let (status, report) = self.get(&format!("/jobs/{}", job_id));
+            assert_eq!(status, 200, "{}", report);
+            if report["status"] != "running" {
+                return report;
+            }
+            assert!(Instant::now() < deadline, "job {} is still running: {}", job_id, report);
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+impl Drop for Api {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn a_found_key_is_handed_out_once() {
+    let api = Api::start();
+    let job_id = api.create(r#"{"prefix":"ab","threads":1}"#);
+    let report = api.wait(&job_id);
+    assert_eq!(report["status"], "found", "{}", report);
+    let address = report["address"].as_str().unwrap();
+    assert!(address.starts_with("0xab"), "{}", report);
+    assert_eq!(report["checksum_address"].as_str().unwrap().to_lowercase(), address);
+    assert!(report["attempts"].as_u64().unwrap() > 0);
+    assert!(report.get("private_key").is_none(), "{}", report);
+
+    // The key belongs to the address
+    let (status, secret) = api.get(&format!("/jobs/{}/secret", job_id));
+    assert_eq!(status, 200, "{}", secret);
+    let private_key = secret["private_key"].as_str().unwrap();
+    let verified = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+        .args(["verify", "--private-key", private_key, "--address", address])
+        .output()
+        .unwrap();
+    assert!(verified.status.success(), "{}", String::from_utf8_lossy(&verified.stderr));
+
+    // The job went with its key
+    assert_eq!(api.get(&format!("/jobs/{}/secret", job_id)).0, 404);
+    assert_eq!(api.get(&format!("/jobs/{}", job_id)).0, 404);
+    assert_eq!(api.request("DELETE", &format!("/jobs/{}", job_id), Some(TOKEN), None).0, 404);
+}
+
+#[test]
+fn include_secret_shows_the_key_in_the_status() {
+    let api = Api::start();
+    let job_id = api.create(r#"{"pattern":"a","threads":1,"include_secret":true}"#);
+    let report = api.wait(&job_id);
+    assert_eq!(report["status"], "found", "{}", report);
+    assert!(report["private_key"].as_str().unwrap().starts_with("0x"), "{}", report);
+    assert!(report["address"].as_str().unwrap().starts_with("0xa"), "{}", report);
+    // That report was the one read of the key
+    assert_eq!(api.get(&format!("/jobs/{}", job_id)).0, 404);
+    assert_eq!(api.get(&format!("/jobs/{}/secret", job_id)).0, 404);
+}
+
+#[test]
+fn requests_need_the_token() {
+    let api = Api::start();
+    let (status, health) = api.request("GET", "/healthz", None, None);
+    assert_eq!((status, health["status"].as_str()), (200, Some("ok")));
+
+    for path in ["/jobs", "/search"] {
+        for token in [None, Some("wrong-token"), Some("test-toke")] {
+            let (status, response) = api.request("POST", path, token, Some(r#"{"prefix":"ab"}"#));
+            assert_eq!(status, 401, "{} {:?}", path, token);
+            assert_eq!(response["error"], "missing or wrong bearer token");
+        }
+    }
+}
+
+#[test]
+fn bad_jobs_are_refused() {
+    let api = Api::start();
+    for body in [r#"{"pattern":"ab","prefix":"ab"}"#, r#"{"prefix":"xyz"}"#] {
+        let (status, response) = api.request("POST", "/jobs", Some(TOKEN), Some(body));
+        assert_eq!(status, 400, "{}: {}", body, response);
+        assert!(response["error"].is_string(), "{}", response);
+    }
+    let (status, _) = api.request("POST", "/jobs", Some(TOKEN), Some(r#"{"prefix":"ab","color":"red"}"#));
+    assert_eq!(status, 422);
+
+    let unknown = "/jobs/00000000-0000-4000-8000-000000000000";
+    assert_eq!(api.get(unknown).0, 404);
+    assert_eq!(api.get(&format!("{}/secret", unknown)).0, 404);
+    assert_eq!(api.request("DELETE", unknown, Some(TOKEN), None).0, 404);
+}
+
+#[test]
+fn jobs_are_cancelled_and_limited() {
+    let api = Api::start();
+    // Ten searches that will not finish on their own fill every slot
+    let jobs: Vec<String> = (0..10).map(|_| api.create(r#"{"prefix":"ffffffffffff","threads":1}"#)).collect();
+    // The serve routes share the registry, and so its cap
+    for path in ["/jobs", "/search"] {
+        let (status, response) = api.request("POST", path, Some(TOKEN), Some(r#"{"prefix":"ab","threads":1}"#));
+        assert_eq!(status, 429, "{}", response);
+    }
+    let (status, _) = api.get(&format!("/jobs/{}/secret", jobs[0]));
+    assert_eq!(status, 409);
+
+    for job_id in &jobs {
+        let (status, response) = api.request("DELETE", &format!("/jobs/{}", job_id), Some(TOKEN), None);
+        assert_eq!((status, response["status"].as_str()), (200, Some("cancelled")));
+    }
+    let report = api.wait(&jobs[0]);
+    assert_eq!(report["status"], "cancelled");
+    assert!(report["address"].is_null());
+    // Read once, a cancelled job is gone
+    assert_eq!(api.get(&format!("/jobs/{}", jobs[0])).0, 404);
+
+    // The cancelled jobs free their slots
+    api.create(r#"{"prefix":"ab","threads":1}"#);
+}
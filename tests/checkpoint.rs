@@ -0,0 +1,110 @@
+// A --start-key scan killed mid-way and resumed from its checkpoint checks every key of
+// the workers' sub-ranges exactly once
+#![cfg(unix)]
+
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha3::{Digest, Keccak256};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// Worker i scans START_KEY + i·2^64 + offset, so its keys are 11…11 || i || offset
+const START_KEY: &str = "1111111111111111111111111111111100000000000000000000000000000000";
+
+// Runs a streaming scan until it has printed `matches` matches, then stops it with SIGTERM
+// and returns the private keys of every match it printed
+fn scan_until(args: &[&str], matches: usize) -> Vec<String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+        .args(["--start-key", START_KEY, "--prefix", "ab", "--threads", "2", "--stream", "--quiet", "--yes"])
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to start evm-vanity");
+    let mut keys = Vec::new();
+    let mut killed = false;
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        let wallet: serde_json::Value = serde_json::from_str(&line.unwrap()).unwrap();
+        keys.push(wallet["private_key"].as_str().unwrap().to_string());
+        if keys.len() == matches && !killed {
+            assert_eq!(unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) }, 0);
+            killed = true;
+        }
+    }
+    assert_eq!(child.wait().unwrap().code(), Some(143));
+    keys
+}
+
+fn offsets(checkpoint: &Path) -> Vec<u64> {
+    let checkpoint: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(checkpoint).unwrap()).unwrap();
+    checkpoint["offsets"].as_array().unwrap().iter().map(|offset| offset.as_u64().unwrap()).collect()
+}
+
+// The keys of the first `offsets[i]` keys of each worker's sub-range whose address starts with ab
+fn expected_matches(offsets: &[u64]) -> HashSet<String> {
+    let secp = Secp256k1::new();
+    let mut key = hex::decode(START_KEY).unwrap();
+    let mut matches = HashSet::new();
+    for (worker, &count) in offsets.iter().enumerate() {
+        key[16..24].copy_from_slice(&(worker as u64).to_be_bytes());
+        for offset in 0..count {
+            key[24..].copy_from_slice(&offset.to_be_bytes());
+            let public_key = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&key).unwrap());
+            let hash = Keccak256::digest(&public_key.serialize_uncompressed()[1..]);
+            if hash[12] == 0xab {
+                matches.insert(format!("0x{}", hex::encode(&key)));
+            }
+        }
+    }
+    matches
+}
+
+#[test]
+fn a_resumed_scan_checks_every_key_once() {
+    let checkpoint = std::env::temp_dir().join(format!("evm-vanity-checkpoint-{}.json", std::process::id()));
+    let path = checkpoint.to_str().unwrap();
+
+    let mut keys = scan_until(&["--checkpoint", path], 4);
+    let first_offsets = offsets(&checkpoint);
+    keys.extend(scan_until(&["--resume", path], 4));
+    let final_offsets = offsets(&checkpoint);
+    std::fs::remove_file(&checkpoint).unwrap();
+
+    // The second run carried on from the first, and between them no match was found twice or missed
+    assert!(first_offsets.iter().zip(&final_offsets).all(|(first, last)| first <= last), "{:?} then {:?}", first_offsets, final_offsets);
+    assert!(final_offsets.iter().sum::<u64>() > first_offsets.iter().sum::<u64>());
+    let found: HashSet<String> = keys.iter().cloned().collect();
+    assert_eq!(found.len(), keys.len(), "a key was reported twice: {:?}", keys);
+    assert_eq!(found, expected_matches(&final_offsets));
+}
+
+#[test]
+fn a_checkpoint_only_resumes_its_own_scan() {
+    let checkpoint = std::env::temp_dir().join(format!("evm-vanity-checkpoint-mismatch-{}.json", std::process::id()));
+    let path = checkpoint.to_str().unwrap();
+    scan_until(&["--checkpoint", path], 1);
+
+    let resume = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+            .args(["--resume", path, "--yes", "--quiet", "--max-attempts", "1"])
+            .args(args)
+            .output()
+            .expect("failed to run evm-vanity")
+    };
+    for (args, error_msg) in [
+        (["--start-key", START_KEY, "--prefix", "abc", "--threads", "2"], "was written for pattern prefix 'ab', not prefix 'abc'"),
+        (["--start-key", START_KEY, "--prefix", "ab", "--threads", "3"], "was written by 2 threads, not 3"),
+    ] {
+        let output = resume(&args);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&output.stderr).contains(error_msg), "{}", String::from_utf8_lossy(&output.stderr));
+    }
+    let other_key = format!("2{}", &START_KEY[1..]);
+    let output = resume(&["--start-key", &other_key, "--prefix", "ab", "--threads", "2", "--force"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("was written for another --start-key"));
+    // The start key itself is never written down
+    assert!(!std::fs::read_to_string(&checkpoint).unwrap().contains(START_KEY));
+    std::fs::remove_file(&checkpoint).unwrap();
+}
@@ -0,0 +1,29 @@
+// --encrypt-to writes the results only as an age file the recipient can decrypt
+
+use std::process::Command;
+
+#[test]
+fn results_file_decrypts_to_the_found_wallet() {
+    let identity = age::x25519::Identity::generate();
+    let output_file = std::env::temp_dir().join(format!("evm-vanity-encrypt-{}.json", std::process::id()));
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+        .args(["--prefix", "abc", "--seed", "42", "--threads", "1", "--i-understand-seeded-keys-are-insecure"])
+        .arg("--output-file")
+        .arg(&output_file)
+        .args(["--encrypt-to", &identity.to_public().to_string()])
+        .output()
+        .expect("failed to run evm-vanity");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let encrypted_file = output_file.with_extension("json.age");
+    let ciphertext = std::fs::read(&encrypted_file).unwrap();
+    std::fs::remove_file(&encrypted_file).unwrap();
+    assert!(!output_file.exists());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("written to {}", encrypted_file.display())), "{}", stdout);
+    assert!(!stdout.contains("0x83b75ba20d05af533fba2c6cfd05948e8f0c01e9e40226505ae67b73897d0ff7"), "{}", stdout);
+
+    let results: serde_json::Value = serde_json::from_slice(&age::decrypt(&identity, &ciphertext).unwrap()).unwrap();
+    assert_eq!(results[0]["address"], "0xabc4401fd7dd7eb047d46a0ec84d9c9ef3436a7f");
+    assert_eq!(results[0]["private_key"], "0x83b75ba20d05af533fba2c6cfd05948e8f0c01e9e40226505ae67b73897d0ff7");
+}
@@ -0,0 +1,48 @@
+// --log-file records the events of a search but never a secret, at any level
+
+use std::process::Command;
+
+// Runs a seeded search with a trace-level log, returning the found wallet and the log
+fn logged_search(args: &[&str]) -> (serde_json::Value, String) {
+    let log_file = std::env::temp_dir().join(format!("evm-vanity-log-{}-{}.log", std::process::id(), args.join("-")));
+    let output = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+        .args(["--seed", "42", "--threads", "1", "--json", "--quiet", "--log-level", "trace", "--log-file"])
+        .arg(&log_file)
+        .args(args)
+        .output()
+        .expect("failed to run evm-vanity");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let log = std::fs::read_to_string(&log_file).unwrap();
+    std::fs::remove_file(&log_file).unwrap();
+    (serde_json::from_slice(&output.stdout).unwrap(), log)
+}
+
+fn assert_no_secrets(wallet: &serde_json::Value, log: &str) {
+    let private_key = wallet["private_key"].as_str().unwrap();
+    assert!(!log.contains(private_key.trim_start_matches("0x")), "{}", log);
+    let mnemonic = wallet["mnemonic"].as_str().unwrap();
+    assert!(!log.contains(mnemonic), "{}", log);
+    // Not even a few words of it
+    let words: Vec<&str> = mnemonic.split(' ').collect();
+    for window in words.windows(3) {
+        assert!(!log.contains(&window.join(" ")), "{}", log);
+    }
+    assert!(!log.contains("seed=42"), "{}", log);
+}
+
+#[test]
+fn raw_key_searches_log_no_secrets() {
+    let (wallet, log) = logged_search(&["--prefix", "abc"]);
+    assert_eq!(wallet["private_key"], "0x83b75ba20d05af533fba2c6cfd05948e8f0c01e9e40226505ae67b73897d0ff7");
+    assert!(log.contains("match found address=0xabc4401fd7dd7eb047d46a0ec84d9c9ef3436a7f attempts=397"), "{}", log);
+    assert!(log.contains("worker started thread=0"), "{}", log);
+    assert!(log.contains("search finished"), "{}", log);
+    assert_no_secrets(&wallet, &log);
+}
+
+#[test]
+fn mnemonic_searches_log_no_secrets() {
+    let (wallet, log) = logged_search(&["--prefix", "ab", "--mode", "mnemonic"]);
+    assert!(log.contains(&format!("match found address={}", wallet["address"].as_str().unwrap())), "{}", log);
+    assert_no_secrets(&wallet, &log);
+}
@@ -0,0 +1,45 @@
+// --metrics-addr serves the Prometheus metrics of a running search
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+
+#[test]
+fn running_search_serves_its_metrics() {
+    // A port that was free a moment ago
+    let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+        .args(["--prefix", "ffffffff", "--threads", "1", "--yes"])
+        .args(["--metrics-addr", &address.to_string()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to start evm-vanity");
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut banner = String::new();
+    while !banner.contains("Serving metrics") {
+        if stdout.read_line(&mut banner).unwrap() == 0 {
+            panic!("evm-vanity exited before serving metrics:\n{}", banner);
+        }
+    }
+
+    let mut connection = TcpStream::connect(address).unwrap();
+    write!(connection, "GET /metrics HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", address).unwrap();
+    let mut response = String::new();
+    connection.read_to_string(&mut response).unwrap();
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    for name in [
+        "evm_vanity_attempts_total",
+        "evm_vanity_attempts_per_second",
+        "evm_vanity_matches_found_total",
+        "evm_vanity_elapsed_seconds",
+        "evm_vanity_threads 1",
+        "evm_vanity_thread_attempts_total{thread=\"0\"}",
+    ] {
+        assert!(response.contains(name), "no {} in\n{}", name, response);
+    }
+}
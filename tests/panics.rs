@@ -0,0 +1,46 @@
+// A worker that panics is reported and left out, and the others finish the search.
+// The panics are injected by a hook that only debug builds have
+#![cfg(debug_assertions)]
+
+use std::process::{Command, Output};
+
+fn evm_vanity_with_panicking_thread(thread: usize, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+        .args(args)
+        .env("EVM_VANITY_TEST_PANIC_THREAD", thread.to_string())
+        .output()
+        .expect("failed to run evm-vanity")
+}
+
+fn found_address(output: &Output) -> String {
+    let wallet: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    wallet["address"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn the_other_workers_find_the_wallet() {
+    let output = evm_vanity_with_panicking_thread(0, &["--prefix", "abc", "--threads", "2", "--json", "--quiet"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(found_address(&output).starts_with("0xabc"));
+    assert!(stderr.contains("Worker 0 panicked: injected panic; continuing with 1 of 2 workers"), "{}", stderr);
+    assert!(stderr.contains("1 work item was lost to worker panics"), "{}", stderr);
+}
+
+#[test]
+fn respawned_workers_are_restarted() {
+    let output = evm_vanity_with_panicking_thread(0, &["--prefix", "abc", "--threads", "2", "--respawn-workers", "--json", "--quiet"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{}", stderr);
+    assert!(found_address(&output).starts_with("0xabc"));
+    assert!(stderr.contains("Worker 0 panicked: injected panic; restarting it"), "{}", stderr);
+}
+
+#[test]
+fn a_search_without_workers_fails() {
+    let output = evm_vanity_with_panicking_thread(0, &["--prefix", "abc", "--threads", "1"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(stderr.contains("Worker 0 panicked: injected panic; no workers are left"), "{}", stderr);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Every worker panicked after 0 attempts"));
+}
@@ -0,0 +1,41 @@
+// Salt mining over a nonce range checks every nonce once and fails when none matches
+
+use std::process::{Command, Output};
+
+fn evm_vanity(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_evm-vanity")).args(args).output().expect("failed to run evm-vanity")
+}
+
+#[test]
+fn an_exhausted_nonce_range_is_an_error() {
+    for threads in ["1", "3", "8"] {
+        let output = evm_vanity(&[
+            "--create2",
+            "--deployer", "0x4e59b44847b379578588920ca78fbf26c0b4956c",
+            "--initcode-hash", "0x0000000000000000000000000000000000000000000000000000000000000000",
+            "--prefix", "ffffffff",
+            "--nonce-start", "5",
+            "--nonce-end", "1004",
+            "--threads", threads,
+        ]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(1), "{}", stderr);
+        assert!(stderr.contains("No match found in range [5, 1004] after 1000 salts"), "{}", stderr);
+    }
+}
+
+#[test]
+fn a_matching_nonce_in_range_is_found() {
+    // EIP-1014 example 0: the zero deployer, salt and init code 0x00 give 0x4D1A2e2b...
+    let output = evm_vanity(&[
+        "--create2",
+        "--deployer", "0x0000000000000000000000000000000000000000",
+        "--initcode-hash", "0xbc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a",
+        "--prefix", "4d1a",
+        "--nonce-end", "0",
+        "--json",
+        "--quiet",
+    ]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"));
+}
@@ -0,0 +1,36 @@
+// --seed makes a single-threaded search reproducible down to the attempt count
+
+use std::process::{Command, Output};
+
+fn evm_vanity(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_evm-vanity")).args(args).output().expect("failed to run evm-vanity")
+}
+
+#[test]
+fn seeded_search_finds_the_same_address_every_time() {
+    for _ in 0..2 {
+        let output = evm_vanity(&["--prefix", "abc", "--seed", "42", "--threads", "1", "--json", "--quiet"]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("SEEDED RUN (--seed 42)"));
+
+        let wallet: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(wallet["address"], "0xabc4401fd7dd7eb047d46a0ec84d9c9ef3436a7f");
+        assert_eq!(wallet["private_key"], "0x83b75ba20d05af533fba2c6cfd05948e8f0c01e9e40226505ae67b73897d0ff7");
+        assert_eq!(wallet["attempts"], 397);
+        // Luck against the 16^3 expected attempts of a three-character prefix
+        assert_eq!(wallet["expected_attempts"], 4096.0);
+        assert_eq!(wallet["luck_ratio"], 397.0 / 4096.0);
+        let faster_than = wallet["faster_than_percent"].as_f64().unwrap();
+        assert!((faster_than - (-397.0f64 / 4096.0).exp() * 100.0).abs() < 1e-9, "{}", faster_than);
+    }
+}
+
+#[test]
+fn seeded_keys_are_not_saved_without_acknowledgement() {
+    let output_file = std::env::temp_dir().join(format!("evm-vanity-seed-{}.json", std::process::id()));
+    let output_file = output_file.to_str().unwrap();
+    let output = evm_vanity(&["--prefix", "abc", "--seed", "42", "--output-file", output_file]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--i-understand-seeded-keys-are-insecure"));
+    assert!(!std::path::Path::new(output_file).exists());
+}
@@ -0,0 +1,136 @@
+// `serve` runs searches as jobs and hands each result, with its key, out once
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The server process, killed when the test ends whether it passed or not
+struct Server {
+    child: Child,
+    port: u16,
+}
+
+impl Server {
+    fn start() -> Server {
+        // A port that was free a moment ago
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+            .args(["serve", "--port", &port.to_string()])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start evm-vanity");
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut banner = String::new();
+        while !banner.contains("Serving the search API") {
+            if stdout.read_line(&mut banner).unwrap() == 0 {
+                panic!("evm-vanity exited before serving the API:\n{}", banner);
+            }
+        }
+        Server { child, port }
+    }
+
+    // The status code and JSON body of a request
+    fn request(&self, method: &str, path: &str, body: &str) -> (u16, serde_json::Value) {
+        let mut connection = TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        write!(
+            connection,
+            "{} {} HTTP/1.1\r\nHost: 127.0.0.1\r\nConnection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        connection.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap_or(serde_json::Value::Null))
+    }
+
+    fn submit(&self, body: &str) -> (u16, serde_json::Value) {
+        self.request("POST", "/search", body)
+    }
+
+    fn status(&self, job_id: &str) -> (u16, serde_json::Value) {
+        self.request("GET", &format!("/search/{}", job_id), "")
+    }
+
+    // The first status of the job that is not running, which is also the last it gives
+    fn wait(&self, job_id: &str) -> serde_json::Value {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        loop {
+            let (status, report) = self.status(job_id);
+            assert_eq!(status, 200, "{}", report);
+            if report["status"] != "running" {
+                return report;
+            }
+            assert!(Instant::now() < deadline, "job {} is still running: {}", job_id, report);
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn job_id(response: &serde_json::Value) -> String {
+    response["job_id"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn a_result_is_read_once() {
+    let server = Server::start();
+    let (status, response) = server.submit(r#"{"prefix":"ab","threads":1}"#);
+    assert_eq!(status, 202, "{}", response);
+    let job_id = job_id(&response);
+
+    let report = server.wait(&job_id);
+    assert_eq!(report["status"], "done", "{}", report);
+    let result = &report["result"];
+    assert!(result["address"].as_str().unwrap().starts_with("0xab"), "{}", report);
+    assert!(result["private_key"].as_str().unwrap().starts_with("0x"), "{}", report);
+
+    // The job went with its key
+    assert_eq!(server.status(&job_id).0, 404);
+    assert_eq!(server.request("DELETE", &format!("/search/{}", job_id), "").0, 404);
+}
+
+#[test]
+fn a_cancelled_job_is_dropped_once_read() {
+    let server = Server::start();
+    let job_id = job_id(&server.submit(r#"{"prefix":"ffffffffffff","threads":1}"#).1);
+    let (status, response) = server.request("DELETE", &format!("/search/{}", job_id), "");
+    assert_eq!((status, response["status"].as_str()), (200, Some("cancelled")));
+
+    let report = server.wait(&job_id);
+    assert_eq!(report["status"], "cancelled");
+    assert!(report["result"].is_null());
+    assert_eq!(server.status(&job_id).0, 404);
+}
+
+#[test]
+fn unread_jobs_are_capped() {
+    let server = Server::start();
+    // Quick searches nobody reads, submitted as fast as the running-job cap allows
+    let mut stored = 0;
+    while stored < 100 {
+        match server.submit(r#"{"prefix":"a","threads":1}"#) {
+            (202, _) => stored += 1,
+            (429, response) if response["error"].as_str().unwrap().contains("already running") => thread::sleep(Duration::from_millis(10)),
+            (status, response) => panic!("{} after {} jobs: {}", status, stored, response),
+        }
+    }
+    thread::sleep(Duration::from_secs(1));
+    let (status, response) = server.submit(r#"{"prefix":"a","threads":1}"#);
+    assert_eq!(status, 429, "{}", response);
+    assert!(response["error"].as_str().unwrap().contains("100 jobs are waiting to be read"), "{}", response);
+}
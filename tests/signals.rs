@@ -0,0 +1,37 @@
+// SIGTERM stops a running search like Ctrl+C: the summary is printed and the
+// process exits with the conventional code of the signal
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn sigterm_prints_the_summary_and_exits_with_143() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+        .args(["--prefix", "ffffffff", "--threads", "1", "--yes"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to start evm-vanity");
+
+    // The handlers are installed right after the banner, so wait for it to end
+    let mut stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut banner = String::new();
+    while !banner.contains("Press Ctrl+C to stop") {
+        if stdout.read_line(&mut banner).unwrap() == 0 {
+            panic!("evm-vanity exited before the search started:\n{}", banner);
+        }
+    }
+    thread::sleep(Duration::from_secs(1));
+
+    assert_eq!(unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) }, 0);
+    let mut rest = String::new();
+    stdout.read_to_string(&mut rest).unwrap();
+    let status = child.wait().unwrap();
+
+    assert_eq!(status.code(), Some(143), "{}", rest);
+    assert!(rest.contains("Received SIGTERM, shutting down..."), "{}", rest);
+    assert!(rest.contains("Search stopped by SIGTERM after"), "{}", rest);
+}
@@ -0,0 +1,31 @@
+// The split-key protocol end to end: the searcher only sees the public key, and the owner's
+// `combine` reaches the address the searcher reported
+
+use std::process::{Command, Output};
+
+// Hardhat's first account and its public key
+const PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const PUBLIC_KEY: &str = "0x048318535b54105d4a7aae60c08fc45f9687181b4fdfc625bd1a753fa7397fed753547f11ca8696646f2f3acb08e31016afac23e630c5d11f59f61fef57b0d2aa5";
+
+fn evm_vanity(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_evm-vanity")).args(args).output().expect("failed to run evm-vanity")
+}
+
+#[test]
+fn combined_key_reaches_the_searched_address() {
+    let output = evm_vanity(&["--search-for-pubkey", PUBLIC_KEY, "--prefix", "ab", "--threads", "1", "--json", "--quiet"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let found: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let address = found["address"].as_str().unwrap();
+    assert!(address.starts_with("0xab"), "{}", address);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains(&PRIVATE_KEY[2..]));
+
+    let output = evm_vanity(&["combine", "--private-key", PRIVATE_KEY, "--partial-key", found["partial_key"].as_str().unwrap(), "--address", address]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains(address));
+
+    // Someone else's key does not reach it
+    let other_key = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    let output = evm_vanity(&["combine", "--private-key", other_key, "--partial-key", found["partial_key"].as_str().unwrap(), "--address", address]);
+    assert_eq!(output.status.code(), Some(1));
+}
@@ -0,0 +1,50 @@
+// --cpu-percent holds a search to its share of a core, as the kernel accounts it
+#![cfg(target_os = "linux")]
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// User and system time of a process, in clock ticks, from /proc/<pid>/stat
+fn cpu_ticks(pid: u32) -> u64 {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+    // The fields after the parenthesized command name, which may itself hold spaces
+    let fields: Vec<&str> = stat.rsplit_once(") ").unwrap().1.split(' ').collect();
+    fields[11].parse::<u64>().unwrap() + fields[12].parse::<u64>().unwrap()
+}
+
+fn clock_ticks_per_second() -> f64 {
+    (unsafe { libc::sysconf(libc::_SC_CLK_TCK) }) as f64
+}
+
+#[test]
+fn cpu_usage_stays_near_the_target() {
+    for percent in [25, 50] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_evm-vanity"))
+            .args(["--prefix", "ffffffffffff", "--threads", "1", "--yes"])
+            .args(["--cpu-percent", &percent.to_string()])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start evm-vanity");
+
+        // The rate is measured at full speed before the banner, so the search is measured after it
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut banner = String::new();
+        while !banner.contains("Press Ctrl+C to stop") {
+            if stdout.read_line(&mut banner).unwrap() == 0 {
+                panic!("evm-vanity exited before the search started:\n{}", banner);
+            }
+        }
+        thread::sleep(Duration::from_millis(500));
+        let (start, start_ticks) = (Instant::now(), cpu_ticks(child.id()));
+        thread::sleep(Duration::from_secs(4));
+        let used = (cpu_ticks(child.id()) - start_ticks) as f64 / clock_ticks_per_second() / start.elapsed().as_secs_f64();
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let target = f64::from(percent) / 100.0;
+        assert!((used - target).abs() <= 0.1, "--cpu-percent {} used {:.0}% of a core", percent, used * 100.0);
+    }
+}
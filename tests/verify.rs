@@ -0,0 +1,71 @@
+// `verify` derives addresses of known wallets and fails loudly on a mismatch
+
+use std::process::{Command, Output};
+
+const HARDHAT_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+fn verify(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_evm-vanity")).arg("verify").args(args).output().expect("failed to run evm-vanity")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn private_keys_derive_known_addresses() {
+    // The key 1, with and without 0x, and Hardhat's first account
+    for (key, address, checksummed) in [
+        (
+            "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "0x7e5f4552091a69125d5dfcb7b8c2659029395bdf",
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf",
+        ),
+        (
+            "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80",
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+            "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266",
+        ),
+    ] {
+        let output = verify(&["--private-key", key, "--address", checksummed]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert!(stdout(&output).contains(&format!("Address: {}", address)));
+        assert!(stdout(&output).contains(&format!("Checksummed: {}", checksummed)));
+        assert!(stdout(&output).contains(&format!("Matches {}", checksummed)));
+    }
+}
+
+#[test]
+fn mnemonics_derive_the_address_at_the_path() {
+    let output = verify(&["--mnemonic", HARDHAT_MNEMONIC, "--address", "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = verify(&["--mnemonic", HARDHAT_MNEMONIC, "--derivation-path", "m/44'/60'/0'/0/1"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Address: 0x70997970c51812dc3a010c7d01b50e0d17dc79c8"));
+}
+
+#[test]
+fn a_mismatch_is_an_error() {
+    let output = verify(&["--mnemonic", HARDHAT_MNEMONIC, "--address", "0x70997970c51812dc3a010c7d01b50e0d17dc79c8"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Does not match 0x70997970c51812dc3a010c7d01b50e0d17dc79c8"));
+    assert!(!stdout(&output).contains("Does not match"));
+}
+
+#[test]
+fn out_of_range_keys_are_refused() {
+    // Zero and the curve order itself
+    for key in [
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+    ] {
+        let output = verify(&["--private-key", key]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stderr(&output).contains("must be between 1 and the secp256k1 curve order"), "{}", stderr(&output));
+    }
+}